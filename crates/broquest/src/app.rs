@@ -22,7 +22,7 @@ use crate::{
     app_database::{AppDatabase, CollectionData, UserSetting},
     app_events::AppEvent,
     collections::{CollectionManager, CollectionManagerEvent, CollectionsPanel},
-    domain::{AuthType, HttpMethod, RequestData},
+    domain::RequestData,
     history::HistoryPanel,
     requests::EditorPanel,
     result_ext::ResultExt,
@@ -136,16 +136,7 @@ impl BroquestApp {
                     // Create a new empty request
                     let request_data = RequestData {
                         name: "New Request".to_string(),
-                        method: HttpMethod::Get,
-                        url: "".to_string(),
-                        path_params: Vec::new(),
-                        query_params: Vec::new(),
-                        headers: Vec::new(),
-                        body: "".to_string(),
-                        auth: AuthType::None,
-                        pre_request_script: None,
-                        post_response_script: None,
-                        vars: Vec::new(),
+                        ..Default::default()
                     };
 
                     editor_panel_clone.update(cx, |editor_panel, cx| {
@@ -197,6 +188,31 @@ impl BroquestApp {
                     });
                 }
 
+                if let AppEvent::OpenRunnerTab {
+                    collection_path,
+                    group_name,
+                } = event
+                {
+                    editor_panel_clone.update(cx, |editor_panel, cx| {
+                        editor_panel.create_and_add_runner_tab(
+                            collection_path.to_string(),
+                            group_name.as_ref().map(|s| s.to_string()),
+                            window,
+                            cx,
+                        );
+                    });
+                }
+
+                if let AppEvent::OpenCookiesTab { collection_path } = event {
+                    editor_panel_clone.update(cx, |editor_panel, cx| {
+                        editor_panel.create_and_add_cookies_tab(
+                            collection_path.to_string(),
+                            window,
+                            cx,
+                        );
+                    });
+                }
+
                 if let AppEvent::CollectionDeleted { collection_path } = event {
                     tracing::info!("Received CollectionDeleted event for: {}", collection_path);
                 }
@@ -328,16 +344,7 @@ impl BroquestApp {
     ) {
         let request_data = RequestData {
             name: "New Request".to_string(),
-            method: HttpMethod::Get,
-            url: String::new(),
-            path_params: Vec::new(),
-            query_params: Vec::new(),
-            headers: Vec::new(),
-            body: String::new(),
-            auth: AuthType::None,
-            pre_request_script: None,
-            post_response_script: None,
-            vars: Vec::new(),
+            ..Default::default()
         };
 
         self.editor_panel.update(cx, |editor_panel, cx| {
@@ -4,6 +4,8 @@ use sqlx::{ConnectOptions, Row};
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::cookies::Cookie;
+
 /// Application database for persistance
 #[derive(Clone)]
 pub struct AppDatabase {
@@ -43,6 +45,15 @@ pub struct HistoryEntry {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// A persisted cookie and the jar it belongs to. `environment` is empty for
+/// requests sent without an environment.
+#[derive(Debug, Clone)]
+pub struct StoredCookie {
+    pub collection_path: String,
+    pub environment: String,
+    pub cookie: Cookie,
+}
+
 impl Global for AppDatabase {}
 
 impl AppDatabase {
@@ -157,6 +168,27 @@ impl AppDatabase {
         .execute(&self.pool)
         .await?;
 
+        // Cookie jars, one per collection and environment
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cookies (
+                collection_path TEXT NOT NULL,
+                environment TEXT NOT NULL,
+                domain TEXT NOT NULL,
+                path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                expires INTEGER,
+                secure INTEGER NOT NULL,
+                http_only INTEGER NOT NULL,
+                host_only INTEGER NOT NULL,
+                PRIMARY KEY (collection_path, environment, domain, path, name)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 
@@ -223,6 +255,10 @@ impl AppDatabase {
             .bind(path)
             .execute(&self.pool)
             .await?;
+        sqlx::query("DELETE FROM cookies WHERE collection_path = ?")
+            .bind(path)
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
@@ -373,4 +409,76 @@ impl AppDatabase {
             .await?;
         Ok(())
     }
+
+    // Cookie operations
+    /// Load every stored cookie that hasn't expired
+    pub async fn load_all_cookies(&self) -> Result<Vec<StoredCookie>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT collection_path, environment, domain, path, name, value, expires, secure, http_only, host_only
+            FROM cookies
+            WHERE expires IS NULL OR expires > ?
+            "#,
+        )
+        .bind(chrono::Utc::now().timestamp())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| StoredCookie {
+                collection_path: row.get("collection_path"),
+                environment: row.get("environment"),
+                cookie: Cookie {
+                    name: row.get("name"),
+                    value: row.get("value"),
+                    domain: row.get("domain"),
+                    path: row.get("path"),
+                    expires: row.get("expires"),
+                    secure: row.get("secure"),
+                    http_only: row.get("http_only"),
+                    host_only: row.get("host_only"),
+                },
+            })
+            .collect())
+    }
+
+    /// Replace the stored cookies of one collection and environment
+    pub async fn replace_cookies(
+        &self,
+        collection_path: &str,
+        environment: &str,
+        cookies: &[Cookie],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM cookies WHERE collection_path = ? AND environment = ?")
+            .bind(collection_path)
+            .bind(environment)
+            .execute(&mut *tx)
+            .await?;
+
+        for cookie in cookies {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO cookies (collection_path, environment, domain, path, name, value, expires, secure, http_only, host_only)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(collection_path)
+            .bind(environment)
+            .bind(&cookie.domain)
+            .bind(&cookie.path)
+            .bind(&cookie.name)
+            .bind(&cookie.value)
+            .bind(cookie.expires)
+            .bind(cookie.secure)
+            .bind(cookie.http_only)
+            .bind(cookie.host_only)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
 }
@@ -82,6 +82,15 @@ pub enum AppEvent {
         collection_path: SharedString,
         group_name: Option<SharedString>,
     },
+    /// Open the collection runner for a collection, or one of its groups
+    OpenRunnerTab {
+        collection_path: SharedString,
+        group_name: Option<SharedString>,
+    },
+    /// Open the cookie jar of a collection
+    OpenCookiesTab {
+        collection_path: SharedString,
+    },
     /// Request was moved (drag and drop)
     RequestMoved,
     /// A request was completed and saved to history
@@ -19,7 +19,7 @@ use gpui_component::{
 
 use super::manager::{CollectionManager, CollectionManagerEvent};
 use super::openapi::OpenAPIImporter;
-use super::types::{CollectionMeta, CollectionToml};
+use super::types::{CollectionMeta, CollectionToml, RequestScript};
 
 use crate::{
     app_database::{AppDatabase, CollectionData},
@@ -27,6 +27,7 @@ use crate::{
     environments::EnvironmentEditor,
     requests::AuthEditor,
     result_ext::ResultExt,
+    scripting::ScriptEditor,
     ui::icon::IconName,
 };
 
@@ -78,6 +79,10 @@ pub struct CollectionEditor {
     path_input: Entity<InputState>,
     /// Editable collection-level variables (`CollectionMeta.vars`).
     vars_editor: Entity<crate::requests::KeyValueEditor>,
+    /// Headers sent with every request of the collection (`CollectionMeta.headers`).
+    headers_editor: Entity<crate::requests::KeyValueEditor>,
+    /// Scripts run around every request's own (`CollectionMeta.script`).
+    script_editor: Entity<ScriptEditor>,
     /// Read-only runtime variable inspector for this collection.
     vars_view: Entity<super::VarsView>,
     /// Markdown docs editor (code editor mode).
@@ -168,6 +173,32 @@ impl CollectionEditor {
                 cx,
             )
         });
+        let collection_headers = collection_data.collection.headers.clone();
+        let headers_editor = cx.new(|cx| {
+            let mut editor = crate::requests::KeyValueEditor::new(
+                crate::requests::KeyValueConfig::new(
+                    "collection-headers",
+                    "Header name",
+                    "Header value",
+                ),
+                window,
+                cx,
+            );
+            editor.set_pairs(&collection_headers, window, cx);
+            editor
+        });
+        let collection_defaults = collection_data.collection.defaults();
+        let script_editor = cx.new(|cx| {
+            let mut editor = ScriptEditor::new(window, cx).without_event_script();
+            editor.set_scripts(
+                collection_defaults.pre_request_script.as_deref(),
+                collection_defaults.post_response_script.as_deref(),
+                None,
+                window,
+                cx,
+            );
+            editor
+        });
         let vars_view = cx.new(|cx| {
             let mut view = super::VarsView::new(cx);
             view.set_collection(
@@ -216,6 +247,8 @@ impl CollectionEditor {
             name_input,
             path_input,
             vars_editor,
+            headers_editor,
+            script_editor,
             vars_view,
             docs_input,
             docs_editing: false,
@@ -288,12 +321,24 @@ impl CollectionEditor {
             Some(docs_text)
         };
         let ignore = self.collection_data.collection.ignore.clone();
+        let proxy = self.collection_data.collection.proxy.clone();
+        let tls = self.collection_data.collection.tls.clone();
+        let http_version = self.collection_data.collection.http_version;
 
         let auth = match self.auth_editor.read(cx).get_auth(cx) {
             AuthType::None => None,
             auth => Some(auth),
         };
 
+        let script_editor = self.script_editor.read(cx);
+        let pre_request = script_editor.get_pre_request_script(cx);
+        let post_response = script_editor.get_post_response_script(cx);
+        let script = (pre_request.is_some() || post_response.is_some()).then(|| RequestScript {
+            pre_request,
+            post_response,
+            on_event: None,
+        });
+
         // Get environments from the environment editor
         let environments = self
             .environment_editor
@@ -309,6 +354,11 @@ impl CollectionEditor {
                 ignore,
                 auth,
                 vars: self.vars_editor.read(cx).get_pairs(cx),
+                headers: self.headers_editor.read(cx).get_pairs(cx),
+                script,
+                proxy,
+                tls,
+                http_version,
             },
             environments,
         }
@@ -555,6 +605,8 @@ impl CollectionEditor {
                     Tab::new().label("Environments"),
                     Tab::new().label("Vars"),
                     Tab::new().label("Auth"),
+                    Tab::new().label("Headers"),
+                    Tab::new().label("Scripts"),
                 ]),
         )
     }
@@ -571,6 +623,8 @@ impl CollectionEditor {
             }
             2 => div().size_full().child(self.render_vars_tab(cx)),
             3 => div().size_full().child(self.auth_editor.clone()),
+            4 => div().size_full().child(self.headers_editor.clone()),
+            5 => div().size_full().child(self.script_editor.clone()),
             _ => {
                 let content = self.render_collection_tab(cx);
                 div().child(content)
@@ -1,6 +1,6 @@
 use gpui::{
     App, AppContext, Context, Entity, EventEmitter, FocusHandle, Focusable, IntoElement,
-    KeyBinding, ParentElement, Render, Styled, Window, actions, div, prelude::*,
+    KeyBinding, ParentElement, Render, Styled, Window, actions, div, prelude::*, px,
 };
 use gpui_component::{
     ActiveTheme as _, StyledExt, WindowExt,
@@ -9,11 +9,18 @@ use gpui_component::{
     input::{Input, InputState},
     kbd::Kbd,
     notification::NotificationType,
+    tab::{Tab, TabBar},
     v_flex,
 };
 
 use super::manager::CollectionManager;
-use crate::{app_events::AppEvent, ui::icon::IconName};
+use crate::{
+    app_events::AppEvent,
+    domain::FolderSettings,
+    requests::{AuthEditor, KeyValueConfig, KeyValueEditor},
+    scripting::ScriptEditor,
+    ui::icon::IconName,
+};
 
 const CONTEXT: &str = "group_editor";
 
@@ -23,6 +30,12 @@ pub struct GroupEditor {
     collection_path: String,
     group_name: Option<String>, // Some for editing, None for new group
     name_input: Entity<InputState>,
+    active_tab: usize,
+    /// Folder settings shared by the group's requests (`.folder.toml`)
+    vars_editor: Entity<KeyValueEditor>,
+    headers_editor: Entity<KeyValueEditor>,
+    auth_editor: Entity<AuthEditor>,
+    script_editor: Entity<ScriptEditor>,
     focus_handle: FocusHandle,
 }
 
@@ -40,14 +53,76 @@ impl GroupEditor {
         let initial_name = group_name.clone().unwrap_or_default();
         let name_input = cx.new(|cx| InputState::new(window, cx).default_value(&initial_name));
 
+        let folder = group_name
+            .as_ref()
+            .and_then(|name| {
+                CollectionManager::global(cx)
+                    .read(cx)
+                    .get_collection_by_path(&collection_path)
+                    .and_then(|info| info.groups.get(name))
+                    .map(|group| group.folder.clone())
+            })
+            .unwrap_or_default();
+
+        let vars_editor = cx.new(|cx| {
+            let mut editor = KeyValueEditor::new(
+                KeyValueConfig::new("folder-vars", "Variable name", "Variable value"),
+                window,
+                cx,
+            );
+            editor.set_pairs(&folder.vars, window, cx);
+            editor
+        });
+        let headers_editor = cx.new(|cx| {
+            let mut editor = KeyValueEditor::new(
+                KeyValueConfig::new("folder-headers", "Header name", "Header value"),
+                window,
+                cx,
+            );
+            editor.set_pairs(&folder.headers, window, cx);
+            editor
+        });
+        let auth_editor = cx.new(|cx| {
+            let mut editor = AuthEditor::new(window, cx);
+            editor.set_auth(&folder.auth, window, cx);
+            editor
+        });
+        let script_editor = cx.new(|cx| {
+            let mut editor = ScriptEditor::new(window, cx).without_event_script();
+            editor.set_scripts(
+                folder.pre_request_script.as_deref(),
+                folder.post_response_script.as_deref(),
+                None,
+                window,
+                cx,
+            );
+            editor
+        });
+
         Self {
             collection_path,
             group_name,
             name_input,
+            active_tab: 0,
+            vars_editor,
+            headers_editor,
+            auth_editor,
+            script_editor,
             focus_handle: cx.focus_handle(),
         }
     }
 
+    fn folder_settings(&self, cx: &App) -> FolderSettings {
+        let script_editor = self.script_editor.read(cx);
+        FolderSettings {
+            vars: self.vars_editor.read(cx).get_pairs(cx),
+            headers: self.headers_editor.read(cx).get_pairs(cx),
+            auth: self.auth_editor.read(cx).get_auth(cx),
+            pre_request_script: script_editor.get_pre_request_script(cx),
+            post_response_script: script_editor.get_post_response_script(cx),
+        }
+    }
+
     fn save_group(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let group_name = self.name_input.read(cx).value().trim().to_string();
 
@@ -58,21 +133,20 @@ impl GroupEditor {
 
         let collection_path = self.collection_path.clone();
         let old_group_name = self.group_name.clone();
+        let folder = self.folder_settings(cx);
 
         let manager = CollectionManager::global(cx);
         let result = manager.update(cx, |collection_manager, cx| {
             if let Some(old_name) = &old_group_name {
                 // Renaming existing group
-                if old_name == &group_name {
-                    // Name hasn't changed, nothing to do
-                    Ok(())
-                } else {
-                    collection_manager.rename_group(&collection_path, old_name, &group_name, cx)
+                if old_name != &group_name {
+                    collection_manager.rename_group(&collection_path, old_name, &group_name, cx)?;
                 }
             } else {
                 // Creating new group
-                collection_manager.create_group(&collection_path, &group_name, cx)
+                collection_manager.create_group(&collection_path, &group_name, cx)?;
             }
+            collection_manager.update_folder(&collection_path, &group_name, &folder, cx)
         });
 
         match result {
@@ -102,6 +176,55 @@ impl GroupEditor {
         }
     }
 
+    fn render_tab_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        // The segmented trough is full-bleed, so inset it with a wrapper.
+        div().p(px(6.)).min_w_0().child(
+            TabBar::new("group-tabs")
+                .segmented()
+                .selected_index(self.active_tab)
+                .on_click(cx.listener(|this, ix: &usize, _window, cx| {
+                    this.active_tab = *ix;
+                    cx.notify();
+                }))
+                .children(vec![
+                    Tab::new().label("Group"),
+                    Tab::new().label("Vars"),
+                    Tab::new().label("Headers"),
+                    Tab::new().label("Auth"),
+                    Tab::new().label("Scripts"),
+                ]),
+        )
+    }
+
+    fn render_group_tab(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .gap_3()
+            .p_3()
+            .child(
+                div()
+                    .text_sm()
+                    .font_medium()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("Group Name"),
+            )
+            .child(div().child(Input::new(&self.name_input)))
+    }
+
+    fn render_tab_content(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .flex_1()
+            .min_h_0()
+            .size_full()
+            .child(match self.active_tab {
+                1 => div().size_full().child(self.vars_editor.clone()),
+                2 => div().size_full().child(self.headers_editor.clone()),
+                3 => div().size_full().child(self.auth_editor.clone()),
+                4 => div().size_full().child(self.script_editor.clone()),
+                _ => div().size_full().child(self.render_group_tab(cx)),
+            })
+    }
+
     /// Get the name input entity for external subscriptions
     pub fn name_input(&self) -> &Entity<InputState> {
         &self.name_input
@@ -126,21 +249,8 @@ impl Render for GroupEditor {
             }))
             .flex_1()
             .size_full()
-            .child(
-                // Content area with padding
-                v_flex()
-                    .flex_1()
-                    .gap_3()
-                    .p_3()
-                    .child(
-                        div()
-                            .text_sm()
-                            .font_medium()
-                            .text_color(cx.theme().muted_foreground)
-                            .child("Group Name"),
-                    )
-                    .child(div().child(Input::new(&self.name_input))),
-            )
+            .child(self.render_tab_bar(cx))
+            .child(self.render_tab_content(cx))
             .child(
                 h_flex()
                     .gap_2()
@@ -1,11 +1,10 @@
 use super::format::CollectionFormat;
 use super::opencollection::{self, LoadedCollection, OcEnvironment, OcItem, OpenCollectionFile};
 use super::storage::storage_for;
-use super::types::{
-    CollectionMeta, CollectionToml, EnvironmentToml, EnvironmentVariable, RequestToml,
-};
+use super::types::{CollectionToml, EnvironmentToml, EnvironmentVariable, FolderToml, RequestToml};
 use crate::app_database::{AppDatabase, CollectionData};
-use crate::domain::RequestData;
+use crate::domain::{FolderSettings, RequestData};
+use crate::environments::is_variable_reference;
 use anyhow::{Context as _, Result};
 use gpui::{App, Context, Entity, EventEmitter, Global, SharedString};
 use std::collections::HashMap;
@@ -39,6 +38,8 @@ pub struct GroupInfo {
     pub name: String,
     pub requests: HashMap<String, RequestData>, // file_path -> RequestData within group
     pub path: String,                           // Relative path from collection root
+    /// Variables, headers, auth and scripts shared by the group's requests
+    pub folder: FolderSettings,
 }
 
 #[derive(Clone, Debug)]
@@ -216,13 +217,7 @@ impl CollectionManager {
         cx: &mut Context<Self>,
     ) -> Result<()> {
         let now = chrono::Utc::now();
-        let base = opencollection::build_unbundled_root(
-            None,
-            &collection_data.collection.name,
-            &collection_data.collection.version,
-            collection_data.collection.docs.as_deref(),
-            &collection_data.collection.vars,
-        );
+        let base = opencollection::build_unbundled_root(None, &collection_data.collection);
         let info = CollectionInfo {
             data: CollectionData {
                 id: None,
@@ -276,6 +271,31 @@ impl CollectionManager {
         Ok(toml)
     }
 
+    /// Load a collection directory straight from disk, without consulting the
+    /// database or caching it in a manager. Used by the headless runner, which
+    /// runs outside GPUI.
+    pub fn load_detached(dir: &Path) -> Result<CollectionInfo> {
+        let manager = Self::new();
+        let now = chrono::Utc::now();
+        let data = CollectionData {
+            id: None,
+            name: String::new(),
+            path: dir.to_string_lossy().to_string(),
+            position: 0,
+            format: String::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        match Self::detect_collection_format(&data, dir) {
+            CollectionFormat::OpenCollection => manager.build_opencollection_info(dir, data),
+            CollectionFormat::Broquest => {
+                let mut info = manager.build_broquest_info(dir, data)?;
+                info.data.name = info.toml.collection.name.clone();
+                Ok(info)
+            }
+        }
+    }
+
     /// Read collection data as CollectionToml from a collection directory
     pub fn read_collection_toml(&self, collection_dir: &Path) -> Result<CollectionToml> {
         let collection_path = collection_dir.join("collection.toml");
@@ -290,6 +310,18 @@ impl CollectionManager {
                 format!("Failed to parse collection.toml from {:?}", collection_path)
             })?;
 
+        // Saving the collection drops a literal password (see `ProxySettings`)
+        if let Some(proxy) = &collection_toml.collection.proxy
+            && !proxy.password.is_empty()
+            && !is_variable_reference(&proxy.password)
+        {
+            tracing::warn!(
+                "The proxy password in {:?} is not kept when the collection is saved; \
+                 put it in an environment secret and use a {{{{variable}}}} instead",
+                collection_path
+            );
+        }
+
         Ok(collection_toml)
     }
 
@@ -300,16 +332,7 @@ impl CollectionManager {
         cx: &mut Context<Self>,
     ) -> Result<CollectionToml> {
         let collection_path = collection_dir.join("collection.toml");
-
-        // Read and parse collection.toml
-        let collection_content = fs::read_to_string(&collection_path).with_context(|| {
-            format!("Failed to read collection.toml from {:?}", collection_path)
-        })?;
-
-        let collection_toml: CollectionToml =
-            toml::from_str(&collection_content).with_context(|| {
-                format!("Failed to parse collection.toml from {:?}", collection_path)
-            })?;
+        let collection_toml = self.read_collection_toml(collection_dir)?;
 
         // Load all requests and groups in this collection
         let (requests, groups) = Self::load_collection_structure(collection_dir)?;
@@ -354,10 +377,13 @@ impl CollectionManager {
             let entry = entry?;
             let path = entry.path();
 
-            // Skip collection.toml and environments directory
+            // Skip collection.toml, the environments directory and hidden
+            // entries (.git, archived run reports in .runs, ...)
             if let Some(filename) = path.file_name()
                 && let Some(filename_str) = filename.to_str()
-                && (filename_str == "collection.toml" || filename_str == "environments")
+                && (filename_str == "collection.toml"
+                    || filename_str == "environments"
+                    || filename_str.starts_with('.'))
             {
                 continue;
             }
@@ -371,6 +397,7 @@ impl CollectionManager {
                     .ok_or_else(|| anyhow::anyhow!("Invalid group directory name"))?;
 
                 let group_requests = Self::load_group_requests(&path)?;
+                let folder = Self::load_folder_file(&path.join(FOLDER_FILE));
                 let group_info = GroupInfo {
                     name: group_name.to_string(),
                     requests: group_requests,
@@ -379,6 +406,7 @@ impl CollectionManager {
                         .map_err(|_| anyhow::anyhow!("Failed to get relative group path"))?
                         .to_string_lossy()
                         .to_string(),
+                    folder,
                 };
                 groups.insert(group_name.to_string(), group_info);
             }
@@ -413,6 +441,7 @@ impl CollectionManager {
             if let Some(filename) = path.file_name()
                 && let Some(filename_str) = filename.to_str()
                 && filename_str.ends_with(".toml")
+                && filename_str != FOLDER_FILE
             {
                 match Self::load_request_file(&path) {
                     Ok(request) => {
@@ -433,6 +462,24 @@ impl CollectionManager {
         Ok(requests)
     }
 
+    /// Load a group's folder settings, falling back to none when the file is
+    /// missing or unreadable
+    fn load_folder_file(path: &Path) -> FolderSettings {
+        if !path.exists() {
+            return FolderSettings::default();
+        }
+        match fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(toml::from_str::<FolderToml>(&content)?))
+        {
+            Ok(folder) => folder.into(),
+            Err(e) => {
+                tracing::error!("Failed to load folder settings from {:?}: {}", path, e);
+                FolderSettings::default()
+            }
+        }
+    }
+
     /// Save collection data to the specified path and update the in-memory cache
     pub fn save_collection(
         &mut self,
@@ -767,6 +814,22 @@ impl CollectionManager {
         Ok(())
     }
 
+    /// Replace a group's folder settings (variables, headers, auth, scripts)
+    pub fn update_folder(
+        &mut self,
+        collection_path: &str,
+        group_name: &str,
+        folder: &FolderSettings,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let info = self.collections.get_mut(collection_path).ok_or_else(|| {
+            anyhow::anyhow!("Collection with path '{}' not found", collection_path)
+        })?;
+        storage_for(info.format).save_folder(info, group_name, folder)?;
+        cx.emit(CollectionManagerEvent::CollectionsChanged);
+        Ok(())
+    }
+
     /// Add an environment to an existing collection
     pub fn add_environment_to_collection(
         &mut self,
@@ -807,6 +870,10 @@ impl CollectionManager {
 
 impl EventEmitter<CollectionManagerEvent> for CollectionManager {}
 
+/// A group directory's settings file, alongside its request files. Hidden so
+/// that it can't be mistaken for a request's `<name>.toml`.
+pub(crate) const FOLDER_FILE: &str = ".folder.toml";
+
 /// Sanitize a name for use as a filesystem path segment.
 pub(crate) fn sanitize_name(name: &str) -> String {
     name.chars()
@@ -838,15 +905,7 @@ fn assemble_opencollection_info(
     loaded: LoadedCollection,
 ) -> CollectionInfo {
     let toml = CollectionToml {
-        collection: CollectionMeta {
-            name: loaded.name.clone(),
-            version: loaded.version.clone(),
-            collection_type: "collection".to_string(),
-            docs: loaded.docs.clone(),
-            ignore: Vec::new(),
-            auth: None,
-            vars: loaded.vars.clone(),
-        },
+        collection: loaded.meta(),
         environments: loaded.environments.iter().map(|e| e.toml.clone()).collect(),
     };
 
@@ -878,6 +937,7 @@ fn assemble_opencollection_info(
                 name: group.name.clone(),
                 requests: group_requests,
                 path: sanitize_name(&group.name),
+                folder: group.folder.clone(),
             },
         );
         if let Some(source) = &group.source {
@@ -935,6 +995,11 @@ mod tests {
                 ignore: Vec::new(),
                 auth: None,
                 vars: Vec::new(),
+                headers: Vec::new(),
+                script: None,
+                proxy: None,
+                tls: None,
+                http_version: None,
             },
             environments: vec![
                 EnvironmentToml {
@@ -1022,6 +1087,101 @@ mod tests {
         let _ = fs::remove_dir_all(&temp_dir);
     }
 
+    #[gpui::test]
+    fn test_folder_file_is_not_a_request(cx: &mut gpui::TestAppContext) {
+        let temp_dir = std::env::temp_dir().join("broquest_test_folder_file");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let collection_path = temp_dir.to_string_lossy().to_string();
+
+        let manager = cx.new(|_| CollectionManager::new());
+        manager
+            .update(cx, |manager, cx| {
+                manager.save_collection(&make_collection_with_environments(), &collection_path, cx)
+            })
+            .expect("save_collection should succeed");
+        manager
+            .update(cx, |manager, cx| {
+                manager.create_group(&collection_path, "Users", cx)
+            })
+            .expect("create group");
+        let folder = FolderSettings {
+            pre_request_script: Some("bro.setVar('folder', true);".to_string()),
+            ..Default::default()
+        };
+        manager
+            .update(cx, |manager, cx| {
+                manager.update_folder(&collection_path, "Users", &folder, cx)
+            })
+            .expect("update folder");
+
+        // A request named "folder" sits next to the folder settings
+        let req = RequestData {
+            name: "folder".to_string(),
+            url: "https://example.com/folder".to_string(),
+            ..Default::default()
+        };
+        manager
+            .update(cx, |manager, cx| {
+                manager.save_request(&collection_path, &req, "folder", Some("Users"), cx)
+            })
+            .expect("save request named folder");
+        let reserved = manager.update(cx, |manager, cx| {
+            manager.save_request(&collection_path, &req, ".folder", Some("Users"), cx)
+        });
+        assert!(reserved.is_err(), "the folder file name is reserved");
+
+        let group_dir = temp_dir.join("Users");
+        let requests = CollectionManager::load_group_requests(&group_dir).expect("load requests");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests.values().next().map(|r| r.url.as_str()),
+            Some("https://example.com/folder")
+        );
+        let loaded = CollectionManager::load_folder_file(&group_dir.join(FOLDER_FILE));
+        assert_eq!(loaded.pre_request_script, folder.pre_request_script);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[gpui::test]
+    fn test_dot_named_request_round_trips_in_a_group(cx: &mut gpui::TestAppContext) {
+        let temp_dir = std::env::temp_dir().join("broquest_test_dot_named_request");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let collection_path = temp_dir.to_string_lossy().to_string();
+
+        let manager = cx.new(|_| CollectionManager::new());
+        manager
+            .update(cx, |manager, cx| {
+                manager.save_collection(&make_collection_with_environments(), &collection_path, cx)
+            })
+            .expect("save_collection should succeed");
+        manager
+            .update(cx, |manager, cx| {
+                manager.create_group(&collection_path, "Discovery", cx)
+            })
+            .expect("create group");
+
+        let req = RequestData {
+            name: ".well-known".to_string(),
+            url: "https://example.com/.well-known/openid-configuration".to_string(),
+            ..Default::default()
+        };
+        manager
+            .update(cx, |manager, cx| {
+                manager.save_request(&collection_path, &req, ".well-known", Some("Discovery"), cx)
+            })
+            .expect("save request named .well-known");
+
+        let requests = CollectionManager::load_group_requests(&temp_dir.join("Discovery"))
+            .expect("load requests");
+        assert_eq!(requests.len(), 1);
+        let loaded = requests.values().next().expect("the request");
+        assert_eq!(loaded.name, ".well-known");
+        assert_eq!(loaded.url, req.url);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
     #[gpui::test]
     fn test_save_collection_twice_preserves_environments(cx: &mut gpui::TestAppContext) {
         let temp_dir = std::env::temp_dir().join("broquest_test_save_twice");
@@ -1040,6 +1200,11 @@ mod tests {
                 ignore: Vec::new(),
                 auth: None,
                 vars: Vec::new(),
+                headers: Vec::new(),
+                script: None,
+                proxy: None,
+                tls: None,
+                http_version: None,
             },
             environments: vec![],
         };
@@ -1257,6 +1422,11 @@ items:
                 ignore: Vec::new(),
                 auth: None,
                 vars: Vec::new(),
+                headers: Vec::new(),
+                script: None,
+                proxy: None,
+                tls: None,
+                http_version: None,
             },
             environments: Vec::new(),
         };
@@ -1,5 +1,5 @@
 use super::types::{EnvironmentToml, EnvironmentVariable, ImportResult};
-use crate::domain::{AuthType, HttpMethod, KeyValuePair, RequestData};
+use crate::domain::{HttpMethod, KeyValuePair, RequestData};
 use oas3::spec::{ObjectOrReference, Operation, ParameterIn, SchemaType};
 use serde_json::json;
 use std::collections::BTreeMap;
@@ -100,16 +100,7 @@ impl OpenAPIImporter {
     }
 
     fn create_request(&self, op_info: &OperationInfo) -> RequestData {
-        let method = match op_info.method.to_uppercase().as_str() {
-            "GET" => HttpMethod::Get,
-            "POST" => HttpMethod::Post,
-            "PUT" => HttpMethod::Put,
-            "DELETE" => HttpMethod::Delete,
-            "PATCH" => HttpMethod::Patch,
-            "HEAD" => HttpMethod::Head,
-            "OPTIONS" => HttpMethod::Options,
-            _ => HttpMethod::Get,
-        };
+        let method = HttpMethod::parse(&op_info.method);
 
         // Convert path params from {param} to :param format
         let converted_path = convert_path_params_format(&op_info.path);
@@ -176,10 +167,7 @@ impl OpenAPIImporter {
             query_params,
             headers,
             body,
-            auth: AuthType::None,
-            pre_request_script: None,
-            post_response_script: None,
-            vars: Vec::new(),
+            ..Default::default()
         }
     }
 
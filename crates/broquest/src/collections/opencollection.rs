@@ -9,8 +9,8 @@
 //! internal [`RequestData`]/[`EnvironmentToml`] model.
 //!
 //! Every struct carries a `#[serde(flatten)] extra` catch-all so that fields
-//! broquest does not model (examples, settings, assertions, gRPC/websocket
-//! items, unsupported auth/body, deeper folder nesting, `extensions`, …) are
+//! broquest does not model (examples, settings, assertions, gRPC items,
+//! unsupported auth/body, deeper folder nesting, `extensions`, …) are
 //! preserved verbatim when a collection is read and written back.
 
 use anyhow::{Context, Result};
@@ -20,14 +20,36 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::collections::types::{EnvironmentToml, EnvironmentVariable};
+use crate::collections::types::{
+    CollectionMeta, EnvironmentToml, EnvironmentVariable, RequestScript,
+};
 use crate::domain::{
-    AuthType, BasicAuth, ContentType, DigestAuth, HttpMethod, KeyAuth, KeyValuePair, RequestData,
+    AuthType, BasicAuth, ContentType, DigestAuth, FolderSettings, GraphQLRequest, GrpcRequest,
+    HttpMethod, HttpVersion, KeyAuth, KeyValuePair, MessageTemplate, MultipartKind, MultipartPart,
+    RequestData, RequestKind, RequestSettings,
 };
 
 /// Key used to losslessly preserve broquest-only auth (OAuth2/JWT) that has no
 /// clean OpenCollection representation. Stored under the request's `http` block.
 const BROQUEST_AUTH_KEY: &str = "x-broquest-auth";
+/// Key for the request setting keeping Authorization across hosts on redirects,
+/// stored in the item's `settings` mapping.
+const BROQUEST_KEEP_AUTH_KEY: &str = "x-broquest-keepAuthOnRedirect";
+/// Key for the request setting asking for a stream of server-sent events,
+/// stored in the item's `settings` mapping.
+const BROQUEST_EVENT_STREAM_KEY: &str = "x-broquest-eventStream";
+/// Key for the HTTP version a request is sent with, stored in the item's
+/// `settings` mapping.
+const BROQUEST_HTTP_VERSION_KEY: &str = "x-broquest-httpVersion";
+/// Runtime script type of the script run for each server-sent event, which
+/// OpenCollection has no type for.
+const BROQUEST_EVENT_SCRIPT_TYPE: &str = "x-broquest-on-event";
+/// Key for the `.proto` files of a gRPC request after the first, which
+/// `protoFilePath` can't hold. Stored under the request's `grpc` block.
+const BROQUEST_PROTO_FILES_KEY: &str = "x-broquest-protoFiles";
+/// Key for the file name sent for a multipart part in place of the file's
+/// own, stored on the part's entry.
+const BROQUEST_FILENAME_KEY: &str = "x-broquest-filename";
 
 /// Key used to preserve broquest collection-level variables (which the
 /// OpenCollection schema does not model) on the root file's `extra` map, so
@@ -49,9 +71,10 @@ pub struct OpenCollectionFile {
     pub info: OcInfo,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub config: Option<OcConfig>,
-    /// Collection-level request defaults; preserved opaque.
+    /// Collection-level request defaults. Headers and scripts are modeled;
+    /// auth, variables and the rest are preserved as-is.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub request: Option<Value>,
+    pub request: Option<OcRequestDefaults>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub items: Vec<OcItem>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -82,12 +105,19 @@ pub struct OcItem {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub http: Option<OcHttp>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<OcWebSocket>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc: Option<OcGrpc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub graphql: Option<Value>,
     /// Folder children.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub items: Option<Vec<OcItem>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub runtime: Option<OcRuntime>,
+    /// Folder defaults for the requests under it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<OcRequestDefaults>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub settings: Option<Value>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -137,6 +167,49 @@ pub struct OcHttp {
     pub extra: Mapping,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OcWebSocket {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<OcHeader>,
+    /// A single `{type, data}` message or an array of titled variants.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    /// Typed auth object or the literal string "inherit".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Mapping,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OcGrpc {
+    #[serde(default)]
+    pub url: String,
+    /// Full method name, `package.Service/Method`
+    #[serde(default)]
+    pub method: String,
+    /// File describing the service; reflection is used without one
+    #[serde(
+        default,
+        rename = "protoFilePath",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub proto_file_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<OcHeader>,
+    /// A single `{type, data}` message or an array of titled variants, of
+    /// which the first is sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Value>,
+    /// Typed auth object or the literal string "inherit".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Mapping,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OcHeader {
     pub name: String,
@@ -177,6 +250,23 @@ pub struct OcRuntime {
     pub extra: Mapping,
 }
 
+/// Headers, auth, variables and scripts a folder (or, at the root, the
+/// collection) applies to its requests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OcRequestDefaults {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<OcHeader>,
+    /// Typed auth object or the literal string "inherit"; absent inherits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variables: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<OcScript>,
+    #[serde(flatten)]
+    pub extra: Mapping,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OcScript {
     #[serde(rename = "type")]
@@ -225,6 +315,8 @@ pub struct LoadedGroup {
     /// The folder's own config (`folder.yml` for non-bundled, or the folder
     /// item for bundled), retained for preservation.
     pub source: Option<OcItem>,
+    /// Variables, headers, auth and scripts from the folder's `request`.
+    pub folder: FolderSettings,
     pub requests: Vec<LoadedRequest>,
 }
 
@@ -250,6 +342,30 @@ pub struct LoadedCollection {
     pub groups: Vec<LoadedGroup>,
     /// broquest collection-level variables (preserved via `x-broquest-vars`).
     pub vars: Vec<KeyValuePair>,
+    /// Headers of the root `request` defaults.
+    pub headers: Vec<KeyValuePair>,
+    /// Scripts of the root `request` defaults.
+    pub script: Option<RequestScript>,
+}
+
+impl LoadedCollection {
+    /// The collection metadata broquest keeps for this collection.
+    pub fn meta(&self) -> CollectionMeta {
+        CollectionMeta {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            collection_type: "collection".to_string(),
+            docs: self.docs.clone(),
+            ignore: Vec::new(),
+            auth: None,
+            vars: self.vars.clone(),
+            headers: self.headers.clone(),
+            script: self.script.clone(),
+            proxy: None,
+            tls: None,
+            http_version: None,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -347,6 +463,32 @@ fn collection_vars(file: &OpenCollectionFile) -> Vec<KeyValuePair> {
     serde_json::from_value::<Vec<KeyValuePair>>(json).unwrap_or_default()
 }
 
+/// Headers and scripts of the root `request` defaults, which apply to every
+/// request of the collection.
+fn collection_request_defaults(
+    file: &OpenCollectionFile,
+) -> (Vec<KeyValuePair>, Option<RequestScript>) {
+    let Some(defaults) = &file.request else {
+        return (Vec::new(), None);
+    };
+    let headers = defaults
+        .headers
+        .iter()
+        .map(|h| KeyValuePair {
+            key: h.name.clone(),
+            value: h.value.clone(),
+            enabled: !h.disabled,
+        })
+        .collect();
+    let (pre_request, post_response, _) = scripts_by_type(&defaults.scripts);
+    let script = (pre_request.is_some() || post_response.is_some()).then_some(RequestScript {
+        pre_request,
+        post_response,
+        on_event: None,
+    });
+    (headers, script)
+}
+
 /// Serialize collection vars into a YAML value for `x-broquest-vars`.
 fn vars_to_yaml(vars: &[KeyValuePair]) -> Option<Value> {
     if vars.is_empty() {
@@ -367,6 +509,7 @@ fn loaded_request(item: &OcItem) -> Option<LoadedRequest> {
 fn decompose_bundled(file: OpenCollectionFile) -> LoadedCollection {
     let (name, version, docs) = collection_common(&file);
     let vars = collection_vars(&file);
+    let (headers, script) = collection_request_defaults(&file);
 
     let environments = file
         .config
@@ -379,7 +522,7 @@ fn decompose_bundled(file: OpenCollectionFile) -> LoadedCollection {
 
     for item in &file.items {
         match item_type(item) {
-            ItemKind::Http => {
+            ItemKind::Request => {
                 if let Some(loaded) = loaded_request(item) {
                     root_requests.push(loaded);
                 }
@@ -395,6 +538,7 @@ fn decompose_bundled(file: OpenCollectionFile) -> LoadedCollection {
                 groups.push(LoadedGroup {
                     name: group_name,
                     source: Some(item.clone()),
+                    folder: folder_settings(item),
                     requests,
                 });
             }
@@ -413,6 +557,8 @@ fn decompose_bundled(file: OpenCollectionFile) -> LoadedCollection {
         root_requests,
         groups,
         vars,
+        headers,
+        script,
     }
 }
 
@@ -421,6 +567,7 @@ fn decompose_bundled(file: OpenCollectionFile) -> LoadedCollection {
 fn decompose_unbundled(dir: &Path, file: OpenCollectionFile) -> Result<LoadedCollection> {
     let (name, version, docs) = collection_common(&file);
     let vars = collection_vars(&file);
+    let (headers, script) = collection_request_defaults(&file);
 
     // Environments live under environments/<name>.yml.
     let mut environments = Vec::new();
@@ -445,7 +592,8 @@ fn decompose_unbundled(dir: &Path, file: OpenCollectionFile) -> Result<LoadedCol
 
     for path in entries {
         if path.is_dir() {
-            if path.file_name().and_then(|n| n.to_str()) == Some("environments") {
+            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if dir_name == "environments" || dir_name.starts_with('.') {
                 continue;
             }
             if let Some(group) = load_unbundled_folder(&path) {
@@ -472,6 +620,8 @@ fn decompose_unbundled(dir: &Path, file: OpenCollectionFile) -> Result<LoadedCol
         root_requests,
         groups,
         vars,
+        headers,
+        script,
     })
 }
 
@@ -507,6 +657,7 @@ fn load_unbundled_folder(dir: &Path) -> Option<LoadedGroup> {
 
     Some(LoadedGroup {
         name,
+        folder: source.as_ref().map(folder_settings).unwrap_or_default(),
         source,
         requests,
     })
@@ -547,7 +698,7 @@ fn parse_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
 }
 
 enum ItemKind {
-    Http,
+    Request,
     Folder,
     Other,
 }
@@ -560,13 +711,13 @@ fn item_type(item: &OcItem) -> ItemKind {
         .or(item.script_file_type.as_deref())
         .unwrap_or("");
     match t {
-        "http" => ItemKind::Http,
+        "http" | "websocket" | "grpc" => ItemKind::Request,
         "folder" => ItemKind::Folder,
         _ => ItemKind::Other,
     }
 }
 
-/// Recursively collect HTTP requests under a folder. broquest supports a single
+/// Recursively collect requests under a folder. broquest supports a single
 /// nesting level, so requests from nested folders are flattened into the parent
 /// group (their structure is preserved in the retained source file).
 fn collect_folder_requests(folder: &OcItem, out: &mut Vec<LoadedRequest>) {
@@ -575,7 +726,7 @@ fn collect_folder_requests(folder: &OcItem, out: &mut Vec<LoadedRequest>) {
     };
     for child in children {
         match item_type(child) {
-            ItemKind::Http => {
+            ItemKind::Request => {
                 if let Some(loaded) = loaded_request(child) {
                     out.push(loaded);
                 }
@@ -591,6 +742,12 @@ fn collect_folder_requests(folder: &OcItem, out: &mut Vec<LoadedRequest>) {
 // ---------------------------------------------------------------------------
 
 fn oc_item_to_request(item: &OcItem) -> Option<RequestData> {
+    if let Some(websocket) = &item.websocket {
+        return Some(oc_websocket_to_request(item, websocket));
+    }
+    if let Some(grpc) = &item.grpc {
+        return Some(oc_grpc_to_request(item, grpc));
+    }
     let http = item.http.as_ref()?;
     let name = item
         .info
@@ -628,43 +785,301 @@ fn oc_item_to_request(item: &OcItem) -> Option<RequestData> {
         .as_ref()
         .map(|b| oc_body_to_string(b, &mut headers))
         .unwrap_or_default();
+    let body_file = http
+        .body
+        .as_ref()
+        .and_then(|b| oc_file_body(b, &mut headers));
+    let multipart = http.body.as_ref().and_then(oc_multipart_body);
 
-    let (pre_request_script, post_response_script) = match &item.runtime {
+    let (pre_request_script, post_response_script, event_script) = match &item.runtime {
         Some(rt) => scripts_from_runtime(rt),
-        None => (None, None),
-    };
-
-    // Prefer a losslessly-preserved broquest auth (OAuth2/JWT) if present.
-    let auth = if let Some(raw) = http.extra.get(Value::String(BROQUEST_AUTH_KEY.into())) {
-        serde_json::to_value(raw)
-            .ok()
-            .and_then(|j| serde_json::from_value::<AuthType>(j).ok())
-            .unwrap_or(AuthType::None)
-    } else {
-        http.auth
-            .as_ref()
-            .map(oc_auth_to_authtype)
-            .unwrap_or(AuthType::None)
+        None => (None, None, None),
     };
 
     Some(RequestData {
         name,
-        method: parse_method(&http.method),
+        kind: RequestKind::Http,
+        method: HttpMethod::parse(&http.method),
         url: http.url.clone(),
         path_params,
         query_params,
         headers,
         body,
+        body_file,
+        multipart,
         pre_request_script,
         post_response_script,
-        auth,
+        event_script,
+        auth: item_auth(http.auth.as_ref(), &http.extra),
         vars: vars_from_runtime(item.runtime.as_ref()),
+        seq: item
+            .info
+            .as_ref()
+            .and_then(|i| seq_from_value(i.seq.as_ref()?)),
+        settings: request_settings_from_oc(item.settings.as_ref()),
+        graphql: http.body.as_ref().and_then(oc_graphql_body),
+        ..Default::default()
     })
 }
 
+fn oc_websocket_to_request(item: &OcItem, websocket: &OcWebSocket) -> RequestData {
+    let (pre_request_script, post_response_script, event_script) = match &item.runtime {
+        Some(rt) => scripts_from_runtime(rt),
+        None => (None, None, None),
+    };
+
+    RequestData {
+        name: item
+            .info
+            .as_ref()
+            .map(|i| i.name.clone())
+            .unwrap_or_default(),
+        kind: RequestKind::WebSocket,
+        url: websocket.url.clone(),
+        headers: websocket
+            .headers
+            .iter()
+            .map(|h| KeyValuePair {
+                key: h.name.clone(),
+                value: h.value.clone(),
+                enabled: !h.disabled,
+            })
+            .collect(),
+        pre_request_script,
+        post_response_script,
+        event_script,
+        auth: item_auth(websocket.auth.as_ref(), &websocket.extra),
+        vars: vars_from_runtime(item.runtime.as_ref()),
+        seq: item
+            .info
+            .as_ref()
+            .and_then(|i| seq_from_value(i.seq.as_ref()?)),
+        settings: request_settings_from_oc(item.settings.as_ref()),
+        messages: websocket
+            .message
+            .as_ref()
+            .map(oc_message_to_templates)
+            .unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+fn oc_grpc_to_request(item: &OcItem, grpc: &OcGrpc) -> RequestData {
+    let (pre_request_script, post_response_script, event_script) = match &item.runtime {
+        Some(rt) => scripts_from_runtime(rt),
+        None => (None, None, None),
+    };
+    let extra_proto_files = grpc
+        .extra
+        .get(Value::from(BROQUEST_PROTO_FILES_KEY))
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(|file| file.as_str().map(str::to_string));
+
+    RequestData {
+        name: item
+            .info
+            .as_ref()
+            .map(|i| i.name.clone())
+            .unwrap_or_default(),
+        kind: RequestKind::Grpc,
+        method: HttpMethod::Post,
+        url: grpc.url.clone(),
+        headers: grpc
+            .metadata
+            .iter()
+            .map(|h| KeyValuePair {
+                key: h.name.clone(),
+                value: h.value.clone(),
+                enabled: !h.disabled,
+            })
+            .collect(),
+        body: grpc
+            .message
+            .as_ref()
+            .and_then(|message| oc_message_to_templates(message).into_iter().next())
+            .map(|template| template.body)
+            .unwrap_or_default(),
+        pre_request_script,
+        post_response_script,
+        event_script,
+        auth: item_auth(grpc.auth.as_ref(), &grpc.extra),
+        vars: vars_from_runtime(item.runtime.as_ref()),
+        seq: item
+            .info
+            .as_ref()
+            .and_then(|i| seq_from_value(i.seq.as_ref()?)),
+        settings: request_settings_from_oc(item.settings.as_ref()),
+        grpc: Some(GrpcRequest {
+            method: grpc.method.clone(),
+            proto_files: grpc
+                .proto_file_path
+                .clone()
+                .into_iter()
+                .chain(extra_proto_files)
+                .collect(),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Auth of an `http` or `websocket` block. A losslessly-preserved broquest
+/// auth (OAuth2/JWT) wins if present.
+fn item_auth(auth: Option<&Value>, extra: &Mapping) -> AuthType {
+    if let Some(raw) = extra.get(Value::String(BROQUEST_AUTH_KEY.into())) {
+        serde_json::to_value(raw)
+            .ok()
+            .and_then(|j| serde_json::from_value::<AuthType>(j).ok())
+            .unwrap_or(AuthType::None)
+    } else {
+        auth.map(oc_auth_to_authtype).unwrap_or(AuthType::None)
+    }
+}
+
+/// Template name of a single, untitled websocket `message`
+const SINGLE_MESSAGE_NAME: &str = "Message";
+
+/// Read a websocket `message`: one `{type, data}` object or an array of
+/// `{title, message: {type, data}}` variants.
+fn oc_message_to_templates(message: &Value) -> Vec<MessageTemplate> {
+    let data = |m: &Value| {
+        m.get("data")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    match message {
+        Value::Sequence(variants) => variants
+            .iter()
+            .enumerate()
+            .map(|(i, variant)| MessageTemplate {
+                name: variant
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{} {}", SINGLE_MESSAGE_NAME, i + 1)),
+                body: variant.get("message").map(data).unwrap_or_default(),
+            })
+            .collect(),
+        Value::Mapping(_) => vec![MessageTemplate {
+            name: SINGLE_MESSAGE_NAME.to_string(),
+            body: data(message),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// Write message templates as a websocket `message`, in the single form when
+/// there is one untitled template.
+fn templates_to_oc_message(templates: &[MessageTemplate]) -> Option<Value> {
+    let message = |body: &str| {
+        let kind = if serde_json::from_str::<serde_json::Value>(body).is_ok() {
+            "json"
+        } else {
+            "text"
+        };
+        let mut map = Mapping::new();
+        map.insert(Value::from("type"), Value::from(kind));
+        map.insert(Value::from("data"), Value::from(body));
+        Value::Mapping(map)
+    };
+    match templates {
+        [] => None,
+        [single] if single.name == SINGLE_MESSAGE_NAME => Some(message(&single.body)),
+        templates => Some(Value::Sequence(
+            templates
+                .iter()
+                .map(|template| {
+                    let mut map = Mapping::new();
+                    map.insert(Value::from("title"), Value::from(template.name.as_str()));
+                    map.insert(Value::from("message"), message(&template.body));
+                    Value::Mapping(map)
+                })
+                .collect(),
+        )),
+    }
+}
+
+/// Read the redirect settings of an item's `settings` mapping. Keeping the
+/// Authorization header, event streams and the HTTP version have no
+/// OpenCollection field and use broquest keys.
+fn request_settings_from_oc(settings: Option<&Value>) -> RequestSettings {
+    let get = |key: &str| settings.and_then(|s| s.get(key));
+    RequestSettings {
+        follow_redirects: get("followRedirects").and_then(Value::as_bool),
+        max_redirects: get("maxRedirects")
+            .and_then(Value::as_u64)
+            .and_then(|max| u32::try_from(max).ok()),
+        keep_auth_on_redirect: get(BROQUEST_KEEP_AUTH_KEY).and_then(Value::as_bool),
+        event_stream: get(BROQUEST_EVENT_STREAM_KEY)
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        http_version: get(BROQUEST_HTTP_VERSION_KEY)
+            .and_then(Value::as_str)
+            .and_then(HttpVersion::parse),
+    }
+}
+
+/// Write the connection settings into an item's `settings` mapping, keeping its
+/// other (unmodeled) entries.
+fn merge_settings(source: Option<Value>, settings: &RequestSettings) -> Option<Value> {
+    let mut mapping = match source {
+        Some(Value::Mapping(mapping)) => mapping,
+        Some(other) => return Some(other),
+        None => Mapping::new(),
+    };
+    let entries = [
+        (
+            "followRedirects",
+            settings.follow_redirects.map(Value::Bool),
+        ),
+        (
+            "maxRedirects",
+            settings.max_redirects.map(|max| Value::Number(max.into())),
+        ),
+        (
+            BROQUEST_KEEP_AUTH_KEY,
+            settings.keep_auth_on_redirect.map(Value::Bool),
+        ),
+        (
+            BROQUEST_EVENT_STREAM_KEY,
+            settings.event_stream.then_some(Value::Bool(true)),
+        ),
+        (
+            BROQUEST_HTTP_VERSION_KEY,
+            settings
+                .http_version
+                .map(|version| Value::from(version.as_str())),
+        ),
+    ];
+    for (key, value) in entries {
+        match value {
+            Some(value) => mapping.insert(Value::from(key), value),
+            None => mapping.remove(Value::from(key)),
+        };
+    }
+    (!mapping.is_empty()).then_some(Value::Mapping(mapping))
+}
+
+/// Read an item's `info.seq`, which Bruno writes as a number but hand-edited
+/// files sometimes quote.
+fn seq_from_value(value: &Value) -> Option<u32> {
+    value
+        .as_u64()
+        .or_else(|| value.as_f64().map(|f| f as u64))
+        .or_else(|| value.as_str()?.trim().parse().ok())
+        .and_then(|n| u32::try_from(n).ok())
+}
+
 /// Extract request-level variables from an item's `runtime.variables`.
 fn vars_from_runtime(runtime: Option<&OcRuntime>) -> Vec<KeyValuePair> {
-    let Some(vars) = runtime.and_then(|rt| rt.variables.as_ref()) else {
+    vars_from_values(runtime.and_then(|rt| rt.variables.as_ref()))
+}
+
+/// Read a list of OpenCollection `Variable` objects.
+fn vars_from_values(vars: Option<&Vec<Value>>) -> Vec<KeyValuePair> {
+    let Some(vars) = vars else {
         return Vec::new();
     };
     vars.iter()
@@ -689,8 +1104,7 @@ fn vars_from_runtime(runtime: Option<&OcRuntime>) -> Vec<KeyValuePair> {
         .collect()
 }
 
-/// Build an OpenCollection HTTP item from a broquest request.
-/// Build an OpenCollection HTTP item from a broquest request, merging the
+/// Build an OpenCollection HTTP or websocket item from a broquest request, merging the
 /// modeled fields into `source` (the item this request was parsed from) so that
 /// unmodeled fields — examples, settings, tags, param descriptions, unsupported
 /// bodies — are preserved. When the request is unchanged from its source, the
@@ -706,36 +1120,84 @@ pub fn merge_request_into_item(source: Option<&OcItem>, req: &RequestData) -> Oc
 
     let info = item.info.get_or_insert_with(|| OcItemInfo {
         name: String::new(),
-        item_type: "http".to_string(),
+        item_type: req.kind.as_str().to_string(),
         description: None,
         seq: None,
         tags: None,
         extra: Mapping::new(),
     });
     info.name = req.name.clone();
-    info.item_type = "http".to_string();
-
-    let mut http = item.http.take().unwrap_or_default();
-    http.method = req.method.as_str().to_string();
-    http.url = req.url.clone();
-    http.headers = merge_headers(&http.headers, &req.headers);
-    http.params = merge_params(&http.params, &req.query_params, &req.path_params);
-    // Only overwrite the body when the request carries one, so preserved
-    // multipart/file bodies survive when broquest leaves the body empty.
-    if !req.body.is_empty() {
-        http.body = request_to_oc_body(req);
-    }
-    http.auth = None;
-    http.extra.remove(Value::from(BROQUEST_AUTH_KEY));
-    apply_auth_to_http(&mut http, &req.auth);
-    item.http = Some(http);
+    info.item_type = req.kind.as_str().to_string();
+    if let Some(seq) = req.seq {
+        info.seq = Some(Value::from(seq));
+    }
+
+    if req.kind == RequestKind::WebSocket {
+        let mut websocket = item.websocket.take().unwrap_or_default();
+        websocket.url = req.url.clone();
+        websocket.headers = merge_headers(&websocket.headers, &req.headers);
+        websocket.message = templates_to_oc_message(&req.messages);
+        websocket.auth = None;
+        websocket.extra.remove(Value::from(BROQUEST_AUTH_KEY));
+        apply_auth(&mut websocket.auth, &mut websocket.extra, &req.auth);
+        item.websocket = Some(websocket);
+        item.http = None;
+        item.grpc = None;
+    } else if req.kind == RequestKind::Grpc {
+        let mut grpc = item.grpc.take().unwrap_or_default();
+        let request = req.grpc.clone().unwrap_or_default();
+        grpc.url = req.url.clone();
+        grpc.method = request.method;
+        let mut proto_files = request.proto_files.into_iter();
+        grpc.proto_file_path = proto_files.next();
+        let extra_proto_files: Vec<Value> = proto_files.map(Value::from).collect();
+        if extra_proto_files.is_empty() {
+            grpc.extra.remove(Value::from(BROQUEST_PROTO_FILES_KEY));
+        } else {
+            grpc.extra.insert(
+                Value::from(BROQUEST_PROTO_FILES_KEY),
+                Value::Sequence(extra_proto_files),
+            );
+        }
+        grpc.metadata = merge_headers(&grpc.metadata, &req.headers);
+        grpc.message = templates_to_oc_message(&[MessageTemplate {
+            name: SINGLE_MESSAGE_NAME.to_string(),
+            body: req.body.clone(),
+        }])
+        .filter(|_| !req.body.is_empty());
+        grpc.auth = None;
+        grpc.extra.remove(Value::from(BROQUEST_AUTH_KEY));
+        apply_auth(&mut grpc.auth, &mut grpc.extra, &req.auth);
+        item.grpc = Some(grpc);
+        item.http = None;
+        item.websocket = None;
+    } else {
+        let mut http = item.http.take().unwrap_or_default();
+        http.method = req.method.as_str().to_string();
+        http.url = req.url.clone();
+        http.headers = merge_headers(&http.headers, &req.headers);
+        http.params = merge_params(&http.params, &req.query_params, &req.path_params);
+        // Only overwrite the body when the request carries one, so bodies
+        // broquest doesn't model survive when it leaves the body empty.
+        if !req.body.is_empty() || req.body_file.is_some() || req.multipart.is_some() {
+            http.body = request_to_oc_body(req);
+        }
+        http.auth = None;
+        http.extra.remove(Value::from(BROQUEST_AUTH_KEY));
+        apply_auth(&mut http.auth, &mut http.extra, &req.auth);
+        item.http = Some(http);
+        item.websocket = None;
+        item.grpc = None;
+    }
 
     item.runtime = merge_runtime(
         item.runtime.take(),
         &req.pre_request_script,
         &req.post_response_script,
+        &req.event_script,
         &req.vars,
     );
+    item.settings = merge_settings(item.settings.take(), &req.settings);
 
     item
 }
@@ -745,9 +1207,12 @@ fn new_http_item() -> OcItem {
         info: None,
         script_file_type: None,
         http: None,
+        websocket: None,
+        grpc: None,
         graphql: None,
         items: None,
         runtime: None,
+        request: None,
         settings: None,
         examples: None,
         docs: None,
@@ -821,42 +1286,14 @@ fn merge_runtime(
     source: Option<OcRuntime>,
     pre: &Option<String>,
     post: &Option<String>,
+    event: &Option<String>,
     vars: &[KeyValuePair],
 ) -> Option<OcRuntime> {
     let mut rt = source.unwrap_or_default();
-    // Replace the before-request / after-response scripts, preserving any
-    // tests / hooks and unmodeled runtime fields (assertions, actions, …).
-    rt.scripts
-        .retain(|s| s.script_type != "before-request" && s.script_type != "after-response");
-    if let Some(code) = pre {
-        rt.scripts.push(OcScript {
-            script_type: "before-request".to_string(),
-            code: code.clone(),
-            extra: Mapping::new(),
-        });
-    }
-    if let Some(code) = post {
-        rt.scripts.push(OcScript {
-            script_type: "after-response".to_string(),
-            code: code.clone(),
-            extra: Mapping::new(),
-        });
-    }
-
-    // Request variables, preserving each source variable's unmodeled fields
-    // (description, etc.) by matching on name.
-    let named: Vec<&KeyValuePair> = vars.iter().filter(|v| !v.key.is_empty()).collect();
-    if named.is_empty() {
-        rt.variables = None;
-    } else {
-        let source_vars = rt.variables.take().unwrap_or_default();
-        rt.variables = Some(
-            named
-                .iter()
-                .map(|v| var_to_oc_value(&source_vars, v))
-                .collect(),
-        );
-    }
+    // Replace the before-request / after-response / event scripts, preserving
+    // any tests / hooks and unmodeled runtime fields (assertions, actions, …).
+    merge_scripts(&mut rt.scripts, pre, post, event);
+    rt.variables = merge_variables(rt.variables.take(), vars);
 
     if rt.scripts.is_empty() && rt.variables.is_none() && rt.extra.is_empty() {
         None
@@ -865,6 +1302,52 @@ fn merge_runtime(
     }
 }
 
+/// Replace the before-request, after-response and event scripts in `scripts`,
+/// keeping the others (tests, hooks).
+fn merge_scripts(
+    scripts: &mut Vec<OcScript>,
+    pre: &Option<String>,
+    post: &Option<String>,
+    event: &Option<String>,
+) {
+    scripts.retain(|s| {
+        !matches!(
+            s.script_type.as_str(),
+            "before-request" | "after-response" | BROQUEST_EVENT_SCRIPT_TYPE
+        )
+    });
+    for (script_type, code) in [
+        ("before-request", pre),
+        ("after-response", post),
+        (BROQUEST_EVENT_SCRIPT_TYPE, event),
+    ] {
+        if let Some(code) = code {
+            scripts.push(OcScript {
+                script_type: script_type.to_string(),
+                code: code.clone(),
+                extra: Mapping::new(),
+            });
+        }
+    }
+}
+
+/// Variables as `Variable` values, preserving each source variable's
+/// unmodeled fields (description, etc.) by matching on name. `None` when
+/// there are no named variables.
+fn merge_variables(source: Option<Vec<Value>>, vars: &[KeyValuePair]) -> Option<Vec<Value>> {
+    let named: Vec<&KeyValuePair> = vars.iter().filter(|v| !v.key.is_empty()).collect();
+    if named.is_empty() {
+        return None;
+    }
+    let source_vars = source.unwrap_or_default();
+    Some(
+        named
+            .iter()
+            .map(|v| var_to_oc_value(&source_vars, v))
+            .collect(),
+    )
+}
+
 /// Build an OpenCollection `Variable` value from a broquest var, carrying over
 /// any unmodeled fields (e.g. `description`) from the matching source variable.
 fn var_to_oc_value(source: &[Value], var: &KeyValuePair) -> Value {
@@ -888,22 +1371,6 @@ fn var_to_oc_value(source: &[Value], var: &KeyValuePair) -> Value {
     Value::Mapping(map)
 }
 
-fn parse_method(s: &str) -> HttpMethod {
-    match s.to_uppercase().as_str() {
-        "GET" => HttpMethod::Get,
-        "POST" => HttpMethod::Post,
-        "PUT" => HttpMethod::Put,
-        "DELETE" => HttpMethod::Delete,
-        "PATCH" => HttpMethod::Patch,
-        "HEAD" => HttpMethod::Head,
-        "OPTIONS" => HttpMethod::Options,
-        other => {
-            tracing::warn!("Unknown HTTP method '{}', defaulting to GET", other);
-            HttpMethod::Get
-        }
-    }
-}
-
 // ---------------------------------------------------------------------------
 // Body mapping
 // ---------------------------------------------------------------------------
@@ -918,15 +1385,8 @@ fn oc_body_to_string(body: &Value, headers: &mut Vec<KeyValuePair>) -> String {
         return String::new();
     };
 
-    // GraphQL body has `query`/`variables` and no `type`.
-    if map.get("query").is_some() || map.get("variables").is_some() {
-        let query = map.get("query").and_then(|v| v.as_str()).unwrap_or("");
-        let variables = map
-            .get("variables")
-            .and_then(|v| v.as_str())
-            .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
-            .unwrap_or(serde_json::Value::Object(serde_json::Map::new()));
-        return serde_json::json!({ "query": query, "variables": variables }).to_string();
+    if let Some(graphql) = oc_graphql_body(body) {
+        return graphql.body();
     }
 
     let body_type = map.get("type").and_then(|v| v.as_str()).unwrap_or("");
@@ -975,6 +1435,88 @@ fn oc_body_to_string(body: &Value, headers: &mut Vec<KeyValuePair>) -> String {
     }
 }
 
+/// The file of a `file` body: its selected variant, or the first. A variant's
+/// content type becomes the `Content-Type` header.
+fn oc_file_body(body: &Value, headers: &mut Vec<KeyValuePair>) -> Option<String> {
+    let map = body.as_mapping()?;
+    if map.get("type").and_then(|v| v.as_str()) != Some("file") {
+        return None;
+    }
+    let variants: Vec<&Mapping> = map
+        .get("data")?
+        .as_sequence()?
+        .iter()
+        .filter_map(|v| v.as_mapping())
+        .collect();
+    let variant = variants
+        .iter()
+        .find(|v| v.get("selected").and_then(|s| s.as_bool()) == Some(true))
+        .or(variants.first())?;
+    if let Some(content_type) = variant
+        .get("contentType")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+    {
+        ensure_content_type(headers, content_type);
+    }
+    variant
+        .get("filePath")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+/// The parts of a `multipart-form` body. A file part's value is a list of
+/// paths, of which the first is sent.
+fn oc_multipart_body(body: &Value) -> Option<Vec<MultipartPart>> {
+    let map = body.as_mapping()?;
+    if map.get("type").and_then(|v| v.as_str()) != Some("multipart-form") {
+        return None;
+    }
+    let parts = map
+        .get("data")
+        .and_then(|v| v.as_sequence())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|e| e.as_mapping())
+                .map(|entry| {
+                    let field = |key: &str| {
+                        entry
+                            .get(key)
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string()
+                    };
+                    let kind = match entry.get("type").and_then(|v| v.as_str()) {
+                        Some("file") => MultipartKind::File,
+                        _ => MultipartKind::Text,
+                    };
+                    let value = match entry.get("value") {
+                        Some(Value::Sequence(files)) => files
+                            .first()
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string(),
+                        _ => field("value"),
+                    };
+                    MultipartPart {
+                        name: field("name"),
+                        kind,
+                        value,
+                        content_type: field("contentType"),
+                        filename: field(BROQUEST_FILENAME_KEY),
+                        enabled: !entry
+                            .get("disabled")
+                            .and_then(|d| d.as_bool())
+                            .unwrap_or(false),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(parts)
+}
+
 fn ensure_content_type(headers: &mut Vec<KeyValuePair>, value: &str) {
     let has = headers
         .iter()
@@ -988,9 +1530,97 @@ fn ensure_content_type(headers: &mut Vec<KeyValuePair>, value: &str) {
     }
 }
 
+/// The query and variables of a GraphQL body, which has `query`/`variables`
+/// and no `type`
+fn oc_graphql_body(body: &Value) -> Option<GraphQLRequest> {
+    let map = body.as_mapping()?;
+    if map.get("query").is_none() && map.get("variables").is_none() {
+        return None;
+    }
+    let field = |key: &str| {
+        map.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    Some(GraphQLRequest {
+        query: field("query"),
+        variables: field("variables"),
+    })
+}
+
 /// Build an OpenCollection body from a broquest request (body type inferred
 /// from the Content-Type header, matching the native TOML behavior).
 fn request_to_oc_body(req: &RequestData) -> Option<Value> {
+    if let Some(graphql) = &req.graphql {
+        let mut map = Mapping::new();
+        map.insert(Value::from("query"), Value::from(graphql.query.clone()));
+        map.insert(
+            Value::from("variables"),
+            Value::from(graphql.variables.clone()),
+        );
+        return Some(Value::Mapping(map));
+    }
+    if let Some(file) = &req.body_file {
+        let mut variant = Mapping::new();
+        variant.insert(Value::from("filePath"), Value::from(file.clone()));
+        if let Some(content_type) = req
+            .headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case("content-type") && h.enabled)
+        {
+            variant.insert(
+                Value::from("contentType"),
+                Value::from(content_type.value.clone()),
+            );
+        }
+        variant.insert(Value::from("selected"), Value::from(true));
+        let mut map = Mapping::new();
+        map.insert(Value::from("type"), Value::from("file"));
+        map.insert(
+            Value::from("data"),
+            Value::Sequence(vec![Value::Mapping(variant)]),
+        );
+        return Some(Value::Mapping(map));
+    }
+    if let Some(parts) = &req.multipart {
+        let data = parts
+            .iter()
+            .map(|part| {
+                let mut entry = Mapping::new();
+                entry.insert(Value::from("name"), Value::from(part.name.clone()));
+                let (kind, value) = match part.kind {
+                    MultipartKind::Text => ("text", Value::from(part.value.clone())),
+                    MultipartKind::File => (
+                        "file",
+                        Value::Sequence(vec![Value::from(part.value.clone())]),
+                    ),
+                };
+                entry.insert(Value::from("type"), Value::from(kind));
+                entry.insert(Value::from("value"), value);
+                if !part.content_type.is_empty() {
+                    entry.insert(
+                        Value::from("contentType"),
+                        Value::from(part.content_type.clone()),
+                    );
+                }
+                if !part.filename.is_empty() {
+                    entry.insert(
+                        Value::from(BROQUEST_FILENAME_KEY),
+                        Value::from(part.filename.clone()),
+                    );
+                }
+                if !part.enabled {
+                    entry.insert(Value::from("disabled"), Value::from(true));
+                }
+                Value::Mapping(entry)
+            })
+            .collect();
+        let mut map = Mapping::new();
+        map.insert(Value::from("type"), Value::from("multipart-form"));
+        map.insert(Value::from("data"), Value::Sequence(data));
+        return Some(Value::Mapping(map));
+    }
     if req.body.is_empty() {
         return None;
     }
@@ -1119,26 +1749,28 @@ fn oc_auth_to_authtype(v: &Value) -> AuthType {
     }
 }
 
-fn apply_auth_to_http(http: &mut OcHttp, auth: &AuthType) {
+/// Write `auth` to the `auth` field of an `http` or `websocket` block, or
+/// under the broquest key of its `extra` fields
+fn apply_auth(slot: &mut Option<Value>, extra: &mut Mapping, auth: &AuthType) {
     match auth {
         AuthType::None => {}
         AuthType::Inherit => {
-            http.auth = Some(Value::from("inherit"));
+            *slot = Some(Value::from("inherit"));
         }
         AuthType::Basic(b) => {
-            http.auth = Some(auth_object(
+            *slot = Some(auth_object(
                 "basic",
                 &[("username", &b.username), ("password", &b.password)],
             ));
         }
         AuthType::Digest(d) => {
-            http.auth = Some(auth_object(
+            *slot = Some(auth_object(
                 "digest",
                 &[("username", &d.username), ("password", &d.password)],
             ));
         }
         AuthType::Key(k) => {
-            http.auth = Some(auth_object(
+            *slot = Some(auth_object(
                 "apikey",
                 &[
                     ("key", &k.header),
@@ -1148,14 +1780,13 @@ fn apply_auth_to_http(http: &mut OcHttp, auth: &AuthType) {
             ));
         }
         AuthType::Unsupported { raw, .. } => {
-            http.auth = Some(json_to_yaml(raw));
+            *slot = Some(json_to_yaml(raw));
         }
         AuthType::OAuth2(_) | AuthType::Jwt(_) => {
             // No clean OpenCollection representation; preserve the full broquest
             // auth losslessly under a namespaced key so it round-trips.
             if let Ok(j) = serde_json::to_value(auth) {
-                http.extra
-                    .insert(Value::from(BROQUEST_AUTH_KEY), json_to_yaml(&j));
+                extra.insert(Value::from(BROQUEST_AUTH_KEY), json_to_yaml(&j));
             }
         }
     }
@@ -1174,17 +1805,25 @@ fn auth_object(kind: &str, fields: &[(&str, &str)]) -> Value {
 // Script mapping
 // ---------------------------------------------------------------------------
 
-fn scripts_from_runtime(rt: &OcRuntime) -> (Option<String>, Option<String>) {
+/// The before-request, after-response and event scripts of an item
+fn scripts_from_runtime(rt: &OcRuntime) -> (Option<String>, Option<String>, Option<String>) {
+    scripts_by_type(&rt.scripts)
+}
+
+/// The before-request, after-response and event scripts among `scripts`
+fn scripts_by_type(scripts: &[OcScript]) -> (Option<String>, Option<String>, Option<String>) {
     let mut pre = None;
     let mut post = None;
-    for script in &rt.scripts {
+    let mut event = None;
+    for script in scripts {
         match script.script_type.as_str() {
             "before-request" => pre = Some(script.code.clone()),
             "after-response" => post = Some(script.code.clone()),
+            BROQUEST_EVENT_SCRIPT_TYPE => event = Some(script.code.clone()),
             _ => {}
         }
     }
-    (pre, post)
+    (pre, post, event)
 }
 
 // ---------------------------------------------------------------------------
@@ -1289,24 +1928,16 @@ fn default_file() -> OpenCollectionFile {
 /// preserved.
 pub fn assemble_bundled_file(
     base: Option<&OpenCollectionFile>,
-    name: &str,
-    version: &str,
-    docs: Option<&str>,
+    meta: &CollectionMeta,
     items: Vec<OcItem>,
     environments: Vec<OcEnvironment>,
-    vars: &[KeyValuePair],
 ) -> OpenCollectionFile {
     let mut file = base.cloned().unwrap_or_else(default_file);
-    file.info.name = name.to_string();
-    if file.info.version.is_none() {
-        file.info.version = Some(version.to_string());
-    }
-    apply_docs(&mut file, docs);
+    apply_collection_meta(&mut file, meta);
     file.bundled = Some(true);
     file.items = items;
     let config = file.config.get_or_insert_with(OcConfig::default);
     config.environments = environments;
-    apply_collection_vars(&mut file, vars);
     file
 }
 
@@ -1314,26 +1945,30 @@ pub fn assemble_bundled_file(
 /// (they live on disk) and no inline environments (they live in files).
 pub fn build_unbundled_root(
     base: Option<&OpenCollectionFile>,
-    name: &str,
-    version: &str,
-    docs: Option<&str>,
-    vars: &[KeyValuePair],
+    meta: &CollectionMeta,
 ) -> OpenCollectionFile {
     let mut file = base.cloned().unwrap_or_else(default_file);
-    file.info.name = name.to_string();
-    if file.info.version.is_none() {
-        file.info.version = Some(version.to_string());
-    }
-    apply_docs(&mut file, docs);
+    apply_collection_meta(&mut file, meta);
     file.bundled = Some(false);
     file.items = Vec::new();
     if let Some(cfg) = file.config.as_mut() {
         cfg.environments.clear();
     }
-    apply_collection_vars(&mut file, vars);
     file
 }
 
+/// Write the collection's name, version, docs, vars and request defaults
+/// onto the root file.
+fn apply_collection_meta(file: &mut OpenCollectionFile, meta: &CollectionMeta) {
+    file.info.name = meta.name.clone();
+    if file.info.version.is_none() {
+        file.info.version = Some(meta.version.clone());
+    }
+    apply_docs(file, meta.docs.as_deref());
+    apply_collection_vars(file, &meta.vars);
+    apply_collection_defaults(file, &meta.headers, meta.script.as_ref());
+}
+
 /// Write the collection docs onto `file.docs` as a string scalar when broquest
 /// has markdown content. When `None`, leave any pre-existing `docs` (e.g. an
 /// opaque block shape from the original file) untouched so it round-trips.
@@ -1359,17 +1994,58 @@ fn apply_collection_vars(file: &mut OpenCollectionFile, vars: &[KeyValuePair]) {
     }
 }
 
+/// Merge the collection's headers and scripts into the root `request`
+/// defaults, keeping their auth, variables and unmodeled fields. Left
+/// verbatim when unchanged.
+fn apply_collection_defaults(
+    file: &mut OpenCollectionFile,
+    headers: &[KeyValuePair],
+    script: Option<&RequestScript>,
+) {
+    let (source_headers, source_script) = collection_request_defaults(file);
+    if KeyValuePair::vec_equals(&source_headers, headers) && source_script.as_ref() == script {
+        return;
+    }
+    let mut defaults = file.request.take().unwrap_or_default();
+    defaults.headers = merge_headers(&defaults.headers, headers);
+    merge_scripts(
+        &mut defaults.scripts,
+        &script.and_then(|s| s.pre_request.clone()),
+        &script.and_then(|s| s.post_response.clone()),
+        &None,
+    );
+    file.request = if defaults.headers.is_empty()
+        && defaults.auth.is_none()
+        && defaults.variables.is_none()
+        && defaults.scripts.is_empty()
+        && defaults.extra.is_empty()
+    {
+        None
+    } else {
+        Some(defaults)
+    };
+}
+
 /// Build a folder item (bundled): folder config plus its child items.
-pub fn build_folder_item(source: Option<&OcItem>, name: &str, children: Vec<OcItem>) -> OcItem {
-    let mut item = build_folder_config(source, name);
+pub fn build_folder_item(
+    source: Option<&OcItem>,
+    name: &str,
+    folder: &FolderSettings,
+    children: Vec<OcItem>,
+) -> OcItem {
+    let mut item = build_folder_config(source, name, folder);
     item.items = Some(children);
     item
 }
 
 /// Build a folder config item (used as `folder.yml` for non-bundled: no
 /// children, since requests live in sibling files).
-pub fn build_folder_config(source: Option<&OcItem>, name: &str) -> OcItem {
+pub fn build_folder_config(source: Option<&OcItem>, name: &str, folder: &FolderSettings) -> OcItem {
     let mut item = source.cloned().unwrap_or_else(new_folder_item);
+    // Leave the source's defaults verbatim when the folder is unchanged
+    if source.map(folder_settings).as_ref() != Some(folder) {
+        item.request = merge_request_defaults(item.request.take(), folder);
+    }
     let info = item.info.get_or_insert_with(|| OcItemInfo {
         name: String::new(),
         item_type: "folder".to_string(),
@@ -1384,14 +2060,82 @@ pub fn build_folder_config(source: Option<&OcItem>, name: &str) -> OcItem {
     item
 }
 
+/// A folder item's variables, headers, auth and scripts
+fn folder_settings(item: &OcItem) -> FolderSettings {
+    let Some(defaults) = &item.request else {
+        return FolderSettings::default();
+    };
+    let (pre_request_script, post_response_script, _) = scripts_by_type(&defaults.scripts);
+    let has_auth = defaults.auth.is_some()
+        || defaults
+            .extra
+            .contains_key(Value::String(BROQUEST_AUTH_KEY.into()));
+    FolderSettings {
+        vars: vars_from_values(defaults.variables.as_ref()),
+        headers: defaults
+            .headers
+            .iter()
+            .map(|h| KeyValuePair {
+                key: h.name.clone(),
+                value: h.value.clone(),
+                enabled: !h.disabled,
+            })
+            .collect(),
+        auth: if has_auth {
+            item_auth(defaults.auth.as_ref(), &defaults.extra)
+        } else {
+            AuthType::Inherit
+        },
+        pre_request_script,
+        post_response_script,
+    }
+}
+
+/// Merge a folder's settings into its `request` defaults, keeping unmodeled
+/// fields. `None` when nothing is left.
+fn merge_request_defaults(
+    source: Option<OcRequestDefaults>,
+    folder: &FolderSettings,
+) -> Option<OcRequestDefaults> {
+    let mut defaults = source.unwrap_or_default();
+    defaults.headers = merge_headers(&defaults.headers, &folder.headers);
+    defaults.auth = None;
+    defaults.extra.remove(Value::from(BROQUEST_AUTH_KEY));
+    // An absent auth is how a folder inherits
+    if !matches!(folder.auth, AuthType::Inherit) {
+        apply_auth(&mut defaults.auth, &mut defaults.extra, &folder.auth);
+    }
+    defaults.variables = merge_variables(defaults.variables.take(), &folder.vars);
+    merge_scripts(
+        &mut defaults.scripts,
+        &folder.pre_request_script,
+        &folder.post_response_script,
+        &None,
+    );
+
+    if defaults.headers.is_empty()
+        && defaults.auth.is_none()
+        && defaults.variables.is_none()
+        && defaults.scripts.is_empty()
+        && defaults.extra.is_empty()
+    {
+        None
+    } else {
+        Some(defaults)
+    }
+}
+
 fn new_folder_item() -> OcItem {
     OcItem {
         info: None,
         script_file_type: None,
         http: None,
+        websocket: None,
+        grpc: None,
         graphql: None,
         items: None,
         runtime: None,
+        request: None,
         settings: None,
         examples: None,
         docs: None,
@@ -1527,6 +2271,7 @@ extensions:
             items.push(build_folder_item(
                 group.source.as_ref(),
                 &group.name,
+                &group.folder,
                 children,
             ));
         }
@@ -1535,15 +2280,7 @@ extensions:
             .iter()
             .map(|e| merge_env(Some(&e.source), &e.toml))
             .collect();
-        assemble_bundled_file(
-            Some(&loaded.file),
-            &loaded.name,
-            &loaded.version,
-            loaded.docs.as_deref(),
-            items,
-            environments,
-            &loaded.vars,
-        )
+        assemble_bundled_file(Some(&loaded.file), &loaded.meta(), items, environments)
     }
 
     #[test]
@@ -1702,6 +2439,221 @@ extensions:
         assert!(KeyValuePair::vec_equals(&req.vars, &back2.vars));
     }
 
+    #[test]
+    fn test_redirect_settings_roundtrip() {
+        let mut source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Login\n  type: http\nhttp:\n  method: GET\n  url: https://sso.example.com\nsettings:\n  encodeUrl: true\n  followRedirects: false\n",
+        )
+        .expect("parse");
+        let mut req = oc_item_to_request(&source).expect("request");
+        assert_eq!(req.settings.follow_redirects, Some(false));
+        assert_eq!(req.settings.max_redirects, None);
+
+        req.settings.max_redirects = Some(3);
+        req.settings.keep_auth_on_redirect = Some(true);
+        req.settings.http_version = Some(HttpVersion::Http2PriorKnowledge);
+        source = merge_request_into_item(Some(&source), &req);
+        let settings = source.settings.as_ref().expect("settings");
+        // Unmodeled settings survive
+        assert_eq!(settings.get("encodeUrl"), Some(&Value::Bool(true)));
+        assert_eq!(
+            oc_item_to_request(&source).expect("request").settings,
+            req.settings
+        );
+
+        req.settings = RequestSettings::default();
+        let cleared = merge_request_into_item(Some(&source), &req);
+        let settings = cleared.settings.expect("settings");
+        assert!(settings.get("followRedirects").is_none());
+        assert!(settings.get("maxRedirects").is_none());
+        assert!(settings.get(BROQUEST_HTTP_VERSION_KEY).is_none());
+    }
+
+    #[test]
+    fn test_event_stream_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Feed\n  type: http\nhttp:\n  method: GET\n  url: https://api.example.com/feed\nruntime:\n  scripts:\n    - type: tests\n      code: test('ok', () => {});\n",
+        )
+        .expect("parse");
+        let mut req = oc_item_to_request(&source).expect("request");
+        req.settings.event_stream = true;
+        req.event_script = Some("bro.setVar('last', event.data);".to_string());
+
+        let item = merge_request_into_item(Some(&source), &req);
+        let runtime = item.runtime.as_ref().expect("runtime");
+        // Other script types survive next to the event script
+        assert!(runtime.scripts.iter().any(|s| s.script_type == "tests"));
+
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert!(reloaded.settings.event_stream);
+        assert_eq!(reloaded.event_script, req.event_script);
+    }
+
+    #[test]
+    fn test_websocket_item_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Chat\n  type: websocket\nwebsocket:\n  url: wss://chat.example.com/ws\n  headers:\n    - name: X-Room\n      value: lobby\n  message:\n    - title: Hello\n      message:\n        type: json\n        data: '{\"say\":\"hi\"}'\n    - title: Ping\n      message:\n        type: text\n        data: ping\n  auth:\n    type: bearer\n    token: abc\n",
+        )
+        .expect("parse");
+        assert!(matches!(item_type(&source), ItemKind::Request));
+
+        let mut req = oc_item_to_request(&source).expect("request");
+        assert_eq!(req.kind, RequestKind::WebSocket);
+        assert_eq!(req.url, "wss://chat.example.com/ws");
+        assert_eq!(req.headers[0].key, "X-Room");
+        assert_eq!(
+            req.messages,
+            vec![
+                MessageTemplate {
+                    name: "Hello".to_string(),
+                    body: r#"{"say":"hi"}"#.to_string(),
+                },
+                MessageTemplate {
+                    name: "Ping".to_string(),
+                    body: "ping".to_string(),
+                },
+            ]
+        );
+
+        // Unchanged requests are written back verbatim
+        let item = merge_request_into_item(Some(&source), &req);
+        assert_eq!(
+            item_to_yaml(&item).expect("yaml"),
+            item_to_yaml(&source).expect("yaml")
+        );
+
+        req.messages.truncate(1);
+        let item = merge_request_into_item(Some(&source), &req);
+        assert!(item.http.is_none());
+        assert_eq!(
+            item.info.as_ref().map(|i| i.item_type.as_str()),
+            Some("websocket")
+        );
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert_eq!(reloaded.messages, req.messages);
+        assert_eq!(reloaded.auth, req.auth);
+    }
+
+    #[test]
+    fn test_grpc_item_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Get order\n  type: grpc\ngrpc:\n  url: grpc://localhost:50051\n  method: shop.v1.Orders/GetOrder\n  protoFilePath: protos/orders.proto\n  metadata:\n    - name: x-tenant\n      value: acme\n  message:\n    type: json\n    data: '{\"orderId\":\"1\"}'\n",
+        )
+        .expect("parse");
+        assert!(matches!(item_type(&source), ItemKind::Request));
+
+        let mut req = oc_item_to_request(&source).expect("request");
+        assert_eq!(req.kind, RequestKind::Grpc);
+        assert_eq!(req.url, "grpc://localhost:50051");
+        assert_eq!(req.headers[0].key, "x-tenant");
+        assert_eq!(req.body, r#"{"orderId":"1"}"#);
+        assert_eq!(
+            req.grpc,
+            Some(GrpcRequest {
+                method: "shop.v1.Orders/GetOrder".to_string(),
+                proto_files: vec!["protos/orders.proto".to_string()],
+            })
+        );
+
+        // Unchanged requests are written back verbatim
+        let item = merge_request_into_item(Some(&source), &req);
+        assert_eq!(
+            item_to_yaml(&item).expect("yaml"),
+            item_to_yaml(&source).expect("yaml")
+        );
+
+        if let Some(grpc) = req.grpc.as_mut() {
+            grpc.proto_files.push("protos/common.proto".to_string());
+        }
+        let item = merge_request_into_item(Some(&source), &req);
+        assert!(item.http.is_none());
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert_eq!(reloaded.grpc, req.grpc);
+        assert_eq!(reloaded.body, req.body);
+    }
+
+    #[test]
+    fn test_graphql_body_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Users\n  type: http\nhttp:\n  method: POST\n  url: https://api.example.com/graphql\n  body:\n    query: '{ users { id } }'\n    variables: '{\"first\": {{count}}}'\n",
+        )
+        .expect("parse");
+
+        let mut req = oc_item_to_request(&source).expect("request");
+        let graphql = req.graphql.clone().expect("graphql body");
+        assert_eq!(graphql.query, "{ users { id } }");
+        assert_eq!(graphql.variables, r#"{"first": {{count}}}"#);
+        assert_eq!(req.body, graphql.body());
+
+        req.graphql = Some(GraphQLRequest {
+            query: "{ users { name } }".to_string(),
+            ..graphql
+        });
+        let item = merge_request_into_item(Some(&source), &req);
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert_eq!(reloaded.graphql, req.graphql);
+    }
+
+    #[test]
+    fn test_file_body_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Upload\n  type: http\nhttp:\n  method: PUT\n  url: https://storage.example.com/logo.png\n  body:\n    type: file\n    data:\n      - filePath: old.png\n        contentType: ''\n      - filePath: fixtures/logo.png\n        contentType: image/png\n        selected: true\n",
+        )
+        .expect("parse");
+
+        let req = oc_item_to_request(&source).expect("request");
+        assert_eq!(req.body_file.as_deref(), Some("fixtures/logo.png"));
+        assert!(req.body.is_empty());
+        assert!(
+            req.headers
+                .iter()
+                .any(|h| h.key == "Content-Type" && h.value == "image/png")
+        );
+
+        let item = merge_request_into_item(Some(&source), &req);
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert_eq!(reloaded.body_file, req.body_file);
+    }
+
+    #[test]
+    fn test_custom_method_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Purge\n  type: http\nhttp:\n  method: purge\n  url: https://cdn.example.com/logo.png\n",
+        )
+        .expect("parse");
+
+        let req = oc_item_to_request(&source).expect("request");
+        assert_eq!(req.method, HttpMethod::Custom("PURGE".to_string()));
+
+        let item = merge_request_into_item(Some(&source), &req);
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert_eq!(reloaded.method, req.method);
+    }
+
+    #[test]
+    fn test_multipart_body_roundtrip() {
+        let source: OcItem = serde_yaml_ng::from_str(
+            "info:\n  name: Upload\n  type: http\nhttp:\n  method: POST\n  url: https://api.example.com/upload\n  body:\n    type: multipart-form\n    data:\n      - name: caption\n        type: text\n        value: Hello\n      - name: photo\n        type: file\n        value:\n          - fixtures/photo.jpg\n        contentType: image/jpeg\n        disabled: true\n",
+        )
+        .expect("parse");
+
+        let mut req = oc_item_to_request(&source).expect("request");
+        let parts = req.multipart.clone().expect("multipart body");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].kind, MultipartKind::Text);
+        assert_eq!(parts[0].value, "Hello");
+        assert_eq!(parts[1].kind, MultipartKind::File);
+        assert_eq!(parts[1].value, "fixtures/photo.jpg");
+        assert_eq!(parts[1].content_type, "image/jpeg");
+        assert!(!parts[1].enabled);
+        assert!(req.body.is_empty());
+
+        req.multipart.as_mut().expect("multipart body")[1].filename = "me.jpg".to_string();
+        let item = merge_request_into_item(Some(&source), &req);
+        let reloaded = oc_item_to_request(&item).expect("request");
+        assert_eq!(reloaded.multipart, req.multipart);
+    }
+
     #[test]
     fn test_collection_vars_roundtrip() {
         use crate::domain::KeyValuePair;
@@ -1743,6 +2695,120 @@ extensions:
         assert!(reloaded_none.vars.is_empty());
     }
 
+    #[test]
+    fn test_collection_request_defaults_roundtrip() {
+        let root_yml = r#"
+opencollection: "1.0.0"
+info:
+  name: Tenants
+bundled: true
+request:
+  headers:
+    - name: X-Tenant
+      value: "{{tenant}}"
+      description: Picked by the gateway
+  auth:
+    type: bearer
+    token: "{{token}}"
+  scripts:
+    - type: before-request
+      code: "bro.setVar('signed', true);"
+"#;
+        let loaded = decompose_bundled(parse_opencollection(root_yml).expect("parse"));
+        assert_eq!(loaded.headers[0].key, "X-Tenant");
+        assert_eq!(
+            loaded
+                .script
+                .as_ref()
+                .and_then(|s| s.pre_request.as_deref()),
+            Some("bro.setVar('signed', true);")
+        );
+
+        // Unchanged: the root defaults are written back verbatim
+        let unchanged = rebuild_bundled(&loaded);
+        assert_eq!(
+            serde_yaml_ng::to_string(&unchanged.request).expect("serialize"),
+            serde_yaml_ng::to_string(&loaded.file.request).expect("serialize")
+        );
+
+        // Edited: headers and scripts change, auth and descriptions survive
+        let mut edited = loaded;
+        edited.headers.push(KeyValuePair {
+            key: "Accept".to_string(),
+            value: "application/json".to_string(),
+            enabled: true,
+        });
+        edited.script = Some(RequestScript {
+            pre_request: None,
+            post_response: Some("bro.setVar('done', true);".to_string()),
+            on_event: None,
+        });
+        let yaml = to_yaml_string(&rebuild_bundled(&edited)).expect("serialize");
+        assert!(yaml.contains("Picked by the gateway"));
+        assert!(yaml.contains("type: bearer"));
+        let reloaded = decompose_bundled(parse_opencollection(&yaml).expect("reparse"));
+        assert!(KeyValuePair::vec_equals(&reloaded.headers, &edited.headers));
+        assert_eq!(reloaded.script, edited.script);
+    }
+
+    #[test]
+    fn test_folder_defaults_roundtrip() {
+        let folder_yml = r#"
+info:
+  name: Users
+  type: folder
+request:
+  headers:
+    - name: X-Service
+      value: users
+      description: Routed by the gateway
+  auth:
+    type: basic
+    username: svc
+    password: "{{usersPassword}}"
+  variables:
+    - name: baseUrl
+      value: https://users.internal.example
+  scripts:
+    - type: before-request
+      code: "bro.setVar('service', 'users');"
+    - type: tests
+      code: "test('ok', () => {});"
+"#;
+        let source: OcItem = serde_yaml_ng::from_str(folder_yml).expect("parse");
+        let folder = folder_settings(&source);
+        assert_eq!(folder.vars[0].key, "baseUrl");
+        assert_eq!(folder.headers[0].value, "users");
+        assert!(matches!(folder.auth, AuthType::Basic(_)));
+        assert_eq!(
+            folder.pre_request_script.as_deref(),
+            Some("bro.setVar('service', 'users');")
+        );
+
+        // Unchanged: the source is written back verbatim
+        let unchanged = build_folder_config(Some(&source), "Users", &folder);
+        assert_eq!(
+            item_to_yaml(&unchanged).expect("serialize"),
+            item_to_yaml(&source).expect("serialize")
+        );
+
+        // Edited: modeled fields change, unmodeled ones survive
+        let mut edited = folder.clone();
+        edited.auth = AuthType::Inherit;
+        edited.post_response_script = Some("bro.setVar('done', true);".to_string());
+        let item = build_folder_config(Some(&source), "Users", &edited);
+        let yaml = item_to_yaml(&item).expect("serialize");
+        assert!(yaml.contains("Routed by the gateway"));
+        assert!(yaml.contains("type: tests"));
+        assert!(!yaml.contains("auth"));
+        let reparsed: OcItem = serde_yaml_ng::from_str(&yaml).expect("reparse");
+        assert_eq!(folder_settings(&reparsed), edited);
+
+        // A folder without settings has no request defaults
+        let empty = build_folder_config(None, "Empty", &FolderSettings::default());
+        assert!(empty.request.is_none());
+    }
+
     #[test]
     fn test_docs_roundtrip() {
         // String-scalar docs survive a bundled read/write round-trip.
@@ -202,6 +202,28 @@ impl DraggableTreeDelegate for CollectionsTreeDelegate {
                             },
                         )),
                     )
+                    .item(
+                        PopupMenuItem::new("Run Collection").on_click(window.listener_for(
+                            &self.parent,
+                            {
+                                let collection_path = metadata.collection_path.clone();
+                                move |this, _, _, cx| {
+                                    this.open_runner_tab(&collection_path, None, cx);
+                                }
+                            },
+                        )),
+                    )
+                    .item(PopupMenuItem::new("Cookies").on_click(window.listener_for(
+                        &self.parent,
+                        {
+                            let collection_path = metadata.collection_path.clone();
+                            move |_this, _, _, cx| {
+                                cx.emit(AppEvent::OpenCookiesTab {
+                                    collection_path: collection_path.to_string().into(),
+                                });
+                            }
+                        },
+                    )))
                     .item(
                         PopupMenuItem::new("New Request").on_click(window.listener_for(
                             &self.parent,
@@ -249,6 +271,18 @@ impl DraggableTreeDelegate for CollectionsTreeDelegate {
                 TreeItemKind::Group => {
                     let group_name = metadata.name.clone();
                     menu.item(
+                        PopupMenuItem::new("Run Group").on_click(window.listener_for(
+                            &self.parent,
+                            {
+                                let collection_path = metadata.collection_path.clone();
+                                let group_name = group_name.clone();
+                                move |this, _, _, cx| {
+                                    this.open_runner_tab(&collection_path, Some(&group_name), cx);
+                                }
+                            },
+                        )),
+                    )
+                    .item(
                         PopupMenuItem::new("New Request").on_click(window.listener_for(
                             &self.parent,
                             {
@@ -542,8 +576,8 @@ impl CollectionsPanel {
                     kind: TreeItemKind::Request,
                     icon: TreeItemIcon {
                         icon: None,
-                        prefix: Some(SharedString::from(request.method.as_str())),
-                        color_fn: request.method.get_color_fn(),
+                        prefix: Some(request.kind.badge(&request.method)),
+                        color_fn: request.kind.badge_color_fn(&request.method),
                     },
                     collection_path: collection_data.path.clone(),
                     group_path: None, // Root level request
@@ -584,8 +618,8 @@ impl CollectionsPanel {
                         kind: TreeItemKind::Request,
                         icon: TreeItemIcon {
                             icon: None,
-                            prefix: Some(SharedString::from(request.method.as_str())),
-                            color_fn: request.method.get_color_fn(),
+                            prefix: Some(request.kind.badge(&request.method)),
+                            color_fn: request.kind.badge_color_fn(&request.method),
                         },
                         collection_path: collection_data.path.clone(),
                         group_path: Some(group_info.path.clone()),
@@ -717,6 +751,25 @@ impl CollectionsPanel {
         });
     }
 
+    /// Open the collection runner for a collection, or a single group in it
+    fn open_runner_tab(
+        &mut self,
+        collection_path: &str,
+        group_name: Option<&str>,
+        cx: &mut Context<Self>,
+    ) {
+        tracing::info!(
+            "Opening runner tab for collection_path: {}, group: {:?}",
+            collection_path,
+            group_name
+        );
+
+        cx.emit(AppEvent::OpenRunnerTab {
+            collection_path: collection_path.to_string().into(),
+            group_name: group_name.map(|name| name.to_string().into()),
+        });
+    }
+
     /// Create a new request tab for a collection
     fn new_request_tab(&mut self, collection_path: &str, cx: &mut Context<Self>) {
         tracing::info!(
@@ -13,10 +13,12 @@ use std::fs;
 use std::path::Path;
 
 use super::format::CollectionFormat;
-use super::manager::{CollectionInfo, CollectionManager, GroupInfo, oc_request_key, sanitize_name};
+use super::manager::{
+    CollectionInfo, CollectionManager, FOLDER_FILE, GroupInfo, oc_request_key, sanitize_name,
+};
 use super::opencollection::{self, OcEnvironment, OcItem};
-use super::types::RequestToml;
-use crate::domain::RequestData;
+use super::types::{FolderToml, RequestToml};
+use crate::domain::{FolderSettings, RequestData};
 
 /// A collection's on-disk persistence strategy. Implementations mutate the
 /// passed-in [`CollectionInfo`] (in-memory model) and persist the change to
@@ -59,6 +61,14 @@ pub(crate) trait CollectionStorage {
 
     /// Delete a group and all its requests.
     fn delete_group(&self, info: &mut CollectionInfo, group_name: &str) -> Result<()>;
+
+    /// Replace a group's variables, headers, auth and scripts.
+    fn save_folder(
+        &self,
+        info: &mut CollectionInfo,
+        group_name: &str,
+        folder: &FolderSettings,
+    ) -> Result<()>;
 }
 
 /// Return the storage strategy for a collection format.
@@ -121,7 +131,14 @@ impl CollectionStorage for BroquestStorage {
         }
 
         // Create the full file path
-        let request_file_path = target_dir.join(format!("{}.toml", request_name));
+        let file_name = format!("{}.toml", request_name);
+        if file_name == FOLDER_FILE {
+            return Err(anyhow::anyhow!(
+                "'{}' is reserved for the folder settings",
+                request_name
+            ));
+        }
+        let request_file_path = target_dir.join(file_name);
 
         // Convert RequestData to RequestToml and serialize
         let request_toml: RequestToml = request_data.clone().into();
@@ -156,6 +173,7 @@ impl CollectionStorage for BroquestStorage {
                     name: group_name.to_string(),
                     requests: new_group_requests,
                     path: group_path.to_string(),
+                    folder: FolderSettings::default(),
                 };
                 info.groups.insert(group_name.to_string(), new_group);
                 false
@@ -355,6 +373,44 @@ impl CollectionStorage for BroquestStorage {
         );
         Self::reload(info)
     }
+
+    fn save_folder(
+        &self,
+        info: &mut CollectionInfo,
+        group_name: &str,
+        folder: &FolderSettings,
+    ) -> Result<()> {
+        let group = info
+            .groups
+            .get_mut(group_name)
+            .ok_or_else(|| anyhow::anyhow!("Group '{}' not found in collection", group_name))?;
+        let folder_file_path = Path::new(&info.data.path)
+            .join(&group.path)
+            .join(FOLDER_FILE);
+
+        // A group without settings keeps its directory free of the file
+        if folder.is_default() {
+            if folder_file_path.exists() {
+                fs::remove_file(&folder_file_path).with_context(|| {
+                    format!("Failed to delete folder file {:?}", folder_file_path)
+                })?;
+            }
+        } else {
+            let toml_string = toml::to_string_pretty(&FolderToml::from(folder))
+                .with_context(|| "Failed to serialize folder settings to TOML")?;
+            fs::write(&folder_file_path, toml_string).with_context(|| {
+                format!("Failed to write folder file to {:?}", folder_file_path)
+            })?;
+        }
+        group.folder = folder.clone();
+
+        tracing::info!(
+            "Folder settings of group '{}' saved in collection '{}'",
+            group_name,
+            info.data.name
+        );
+        Ok(())
+    }
 }
 
 // -- OpenCollection YAML strategy -------------------------------------------
@@ -394,6 +450,7 @@ impl OpenCollectionStorage {
             group_items.push(opencollection::build_folder_item(
                 info.oc_groups.get(gname),
                 &group.name,
+                &group.folder,
                 children,
             ));
         }
@@ -410,12 +467,9 @@ impl OpenCollectionStorage {
 
         let file = opencollection::assemble_bundled_file(
             info.oc_source.as_ref(),
-            &info.toml.collection.name,
-            &info.toml.collection.version,
-            info.toml.collection.docs.as_deref(),
+            &info.toml.collection,
             items,
             environments,
-            &info.toml.collection.vars,
         );
         let yaml = opencollection::to_yaml_string(&file)?;
         let target = opencollection::find_opencollection_file(&dir)
@@ -434,13 +488,8 @@ impl OpenCollectionStorage {
         fs::create_dir_all(dir).ok();
 
         // 1. Root opencollection.yml (structure lives on disk, not inline).
-        let root = opencollection::build_unbundled_root(
-            info.oc_source.as_ref(),
-            &info.toml.collection.name,
-            &info.toml.collection.version,
-            info.toml.collection.docs.as_deref(),
-            &info.toml.collection.vars,
-        );
+        let root =
+            opencollection::build_unbundled_root(info.oc_source.as_ref(), &info.toml.collection);
         let target = opencollection::find_opencollection_file(dir)
             .unwrap_or_else(|| dir.join("opencollection.yml"));
         fs::write(&target, opencollection::to_yaml_string(&root)?)
@@ -482,8 +531,11 @@ impl OpenCollectionStorage {
             fs::create_dir_all(&group_dir).ok();
             desired_group_dirs.insert(group_dir_name);
 
-            let folder =
-                opencollection::build_folder_config(info.oc_groups.get(gname), &group.name);
+            let folder = opencollection::build_folder_config(
+                info.oc_groups.get(gname),
+                &group.name,
+                &group.folder,
+            );
             fs::write(
                 group_dir.join("folder.yml"),
                 opencollection::item_to_yaml(&folder)?,
@@ -547,6 +599,7 @@ impl CollectionStorage for OpenCollectionStorage {
                     name: group_name.clone(),
                     requests: HashMap::new(),
                     path: sanitize_name(&group_name),
+                    folder: FolderSettings::default(),
                 });
             group.requests.insert(key, request_data.clone());
         } else {
@@ -596,6 +649,7 @@ impl CollectionStorage for OpenCollectionStorage {
                     name: group_name.clone(),
                     requests: HashMap::new(),
                     path: sanitize_name(&group_name),
+                    folder: FolderSettings::default(),
                 });
             group.requests.insert(new_key, request_data.clone());
         } else {
@@ -617,6 +671,7 @@ impl CollectionStorage for OpenCollectionStorage {
                 name: group_name.to_string(),
                 requests: HashMap::new(),
                 path: sanitize_name(group_name),
+                folder: FolderSettings::default(),
             },
         );
         Self::persist(info)
@@ -650,6 +705,20 @@ impl CollectionStorage for OpenCollectionStorage {
         info.groups.remove(group_name);
         Self::persist(info)
     }
+
+    fn save_folder(
+        &self,
+        info: &mut CollectionInfo,
+        group_name: &str,
+        folder: &FolderSettings,
+    ) -> Result<()> {
+        let group = info
+            .groups
+            .get_mut(group_name)
+            .ok_or_else(|| anyhow::anyhow!("Group '{}' not found", group_name))?;
+        group.folder = folder.clone();
+        Self::persist(info)
+    }
 }
 
 // -- Shared OpenCollection helpers ------------------------------------------
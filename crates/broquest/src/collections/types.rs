@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 
-use crate::domain::{AuthType, ContentType, HttpMethod, KeyValuePair, RequestData};
+use crate::domain::{
+    AuthType, CollectionDefaults, ContentType, FolderSettings, GraphQLRequest, GrpcRequest,
+    HttpMethod, HttpVersion, KeyValuePair, MessageTemplate, MultipartKind, MultipartPart,
+    RequestData, RequestKind, RequestSettings,
+};
+use crate::environments::is_variable_reference;
+use crate::settings::ProxySettings;
 use serde::{Deserialize, Serialize};
 
 /// TOML structure for collection.toml files
@@ -31,13 +37,96 @@ pub struct CollectionMeta {
     /// `bru.getCollectionVar` / `bru.hasCollectionVar`. Persisted to disk.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub vars: Vec<KeyValuePair>,
+    /// Headers sent with every request that neither it nor its folder sets,
+    /// `[[collection.headers]]` in collection.toml.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<KeyValuePair>,
+    /// Scripts run before and after every request's own,
+    /// `[collection.script]` in collection.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<RequestScript>,
+    /// Proxy settings replacing the app-wide ones for this collection's
+    /// requests. Edited in collection.toml as `[collection.proxy]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxySettings>,
+    /// Extra CA certificates, client certificates and certificate checks for
+    /// this collection's requests, `[collection.tls]` in collection.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsSettings>,
+    /// HTTP version of this collection's requests, unless a request sets
+    /// its own. `http_version = "http1"` in collection.toml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<HttpVersion>,
+}
+
+impl CollectionMeta {
+    /// The headers and scripts this collection applies to its requests
+    pub fn defaults(&self) -> CollectionDefaults {
+        let (pre_request_script, post_response_script) = self
+            .script
+            .as_ref()
+            .map(|script| (script.pre_request.clone(), script.post_response.clone()))
+            .unwrap_or_default();
+        CollectionDefaults {
+            headers: self.headers.clone(),
+            pre_request_script,
+            post_response_script,
+        }
+    }
+}
+
+/// TLS options of a collection. Paths are relative to the collection
+/// directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TlsSettings {
+    /// PEM files of CA certificates trusted in addition to the system ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ca_certificates: Vec<String>,
+    /// Client certificates for mutual TLS, first matching host wins
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub client_certificates: Vec<ClientCertificate>,
+    /// Skip verifying server certificates and host names
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool,
+}
+
+/// A client certificate presented to the hosts matching `host`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct ClientCertificate {
+    /// Host name, `*.example.com` for its subdomains or `*` for every host
+    pub host: String,
+    /// PEM certificate, or a PKCS#12 archive when named `.p12` or `.pfx`
+    pub cert: String,
+    /// PEM (PKCS#8) private key, when not in the `cert` file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Password of a PKCS#12 archive: a `{{variable}}` reference resolved
+    /// from the environment's variables and secrets. A literal passphrase is
+    /// never written back.
+    #[serde(default, skip_serializing_if = "is_literal_passphrase")]
+    pub passphrase: Option<String>,
+}
+
+fn is_literal_passphrase(passphrase: &Option<String>) -> bool {
+    passphrase
+        .as_deref()
+        .is_none_or(|passphrase| !is_variable_reference(passphrase))
 }
 
 /// TOML structure for request .toml files
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RequestToml {
     pub meta: RequestMeta,
-    pub http: RequestHttp,
+    /// Method and URL of `http` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<RequestHttp>,
+    /// URL of `websocket` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket: Option<RequestWebSocket>,
+    /// URL, method and `.proto` files of `grpc` requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc: Option<RequestGrpc>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub auth: Option<AuthType>,
     pub script: Option<RequestScript>,
@@ -48,6 +137,12 @@ pub struct RequestToml {
     /// Request-level variables, resolvable via `{{name}}`.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub vars: Vec<KeyValuePair>,
+    /// Overrides of the connection settings, e.g. redirect handling.
+    #[serde(default, skip_serializing_if = "RequestSettings::is_default")]
+    pub settings: RequestSettings,
+    /// Saved messages of a WebSocket request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<MessageTemplate>,
 }
 
 /// TOML structure for request body
@@ -58,6 +153,63 @@ pub struct RequestBodyToml {
     pub form: Option<HashMap<String, String>>,
     pub graphql: Option<GraphQLBody>,
     pub xml: Option<String>,
+    /// File sent as the body, relative to the collection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Parts of a `multipart/form-data` body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multipart: Option<Vec<MultipartPartToml>>,
+}
+
+/// TOML structure for a part of a multipart body: its text `value` or a
+/// `file` relative to the collection
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MultipartPartToml {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub content_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub filename: String,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enabled: bool,
+}
+
+impl From<&MultipartPartToml> for MultipartPart {
+    fn from(part: &MultipartPartToml) -> Self {
+        let (kind, value) = match (&part.file, &part.value) {
+            (Some(file), _) => (MultipartKind::File, file.clone()),
+            (None, value) => (MultipartKind::Text, value.clone().unwrap_or_default()),
+        };
+        Self {
+            name: part.name.clone(),
+            kind,
+            value,
+            content_type: part.content_type.clone(),
+            filename: part.filename.clone(),
+            enabled: part.enabled,
+        }
+    }
+}
+
+impl From<&MultipartPart> for MultipartPartToml {
+    fn from(part: &MultipartPart) -> Self {
+        let (value, file) = match part.kind {
+            MultipartKind::Text => (Some(part.value.clone()), None),
+            MultipartKind::File => (None, Some(part.value.clone())),
+        };
+        Self {
+            name: part.name.clone(),
+            value,
+            file,
+            content_type: part.content_type.clone(),
+            filename: part.filename.clone(),
+            enabled: part.enabled,
+        }
+    }
 }
 
 /// TOML structure for GraphQL body
@@ -67,6 +219,33 @@ pub struct GraphQLBody {
     pub variables: Option<serde_json::Value>,
 }
 
+impl GraphQLBody {
+    /// Variables that aren't valid JSON, e.g. with a `{{placeholder}}` for a
+    /// whole value, are kept as the string typed
+    fn from_request(graphql: &GraphQLRequest) -> Self {
+        let variables = graphql.variables.trim();
+        Self {
+            query: Some(graphql.query.clone()),
+            variables: (!variables.is_empty()).then(|| {
+                serde_json::from_str(variables)
+                    .unwrap_or_else(|_| serde_json::Value::String(graphql.variables.clone()))
+            }),
+        }
+    }
+
+    fn to_request(&self) -> GraphQLRequest {
+        let variables = match &self.variables {
+            None => String::new(),
+            Some(serde_json::Value::String(raw)) => raw.clone(),
+            Some(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        };
+        GraphQLRequest {
+            query: self.query.clone().unwrap_or_default(),
+            variables,
+        }
+    }
+}
+
 /// TOML structure for request parameters
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RequestParams {
@@ -89,11 +268,32 @@ pub struct RequestHttp {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+pub struct RequestWebSocket {
+    pub url: String,
+    pub auth: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RequestGrpc {
+    pub url: String,
+    /// Full method name, `package.Service/Method`
+    #[serde(default)]
+    pub method: String,
+    pub auth: String,
+    /// Files describing the service; reflection is used without any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proto_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct RequestScript {
     #[serde(rename = "pre-request")]
     pub pre_request: Option<String>,
     #[serde(rename = "post-response")]
     pub post_response: Option<String>,
+    /// Runs for every server-sent event of the response
+    #[serde(rename = "on-event", default, skip_serializing_if = "Option::is_none")]
+    pub on_event: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -116,6 +316,78 @@ pub struct QueryToml {
     pub enabled: bool,
 }
 
+/// TOML structure for the `.folder.toml` of a group directory
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct FolderToml {
+    /// Absent when the folder leaves its requests to the collection's auth
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<RequestScript>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<HeaderToml>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vars: Vec<KeyValuePair>,
+}
+
+impl From<FolderToml> for FolderSettings {
+    fn from(toml: FolderToml) -> Self {
+        let (pre_request_script, post_response_script) = toml
+            .script
+            .map(|script| (script.pre_request, script.post_response))
+            .unwrap_or_default();
+        Self {
+            vars: toml.vars,
+            headers: toml
+                .headers
+                .into_iter()
+                .map(|h| KeyValuePair {
+                    key: h.key,
+                    value: h.value,
+                    enabled: h.enabled,
+                })
+                .collect(),
+            auth: toml.auth.unwrap_or(AuthType::Inherit),
+            pre_request_script,
+            post_response_script,
+        }
+    }
+}
+
+impl From<&FolderSettings> for FolderToml {
+    fn from(folder: &FolderSettings) -> Self {
+        let script = (folder.pre_request_script.is_some() || folder.post_response_script.is_some())
+            .then(|| RequestScript {
+                pre_request: folder.pre_request_script.clone(),
+                post_response: folder.post_response_script.clone(),
+                on_event: None,
+            });
+        Self {
+            auth: match &folder.auth {
+                AuthType::Inherit => None,
+                auth => Some(auth.clone()),
+            },
+            script,
+            headers: folder
+                .headers
+                .iter()
+                .filter(|h| !h.key.is_empty())
+                .map(|h| HeaderToml {
+                    key: h.key.clone(),
+                    value: h.value.clone(),
+                    enabled: h.enabled,
+                })
+                .collect(),
+            vars: folder
+                .vars
+                .iter()
+                .filter(|v| !v.key.is_empty())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
 /// TOML structure for environment files
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct EnvironmentToml {
@@ -212,17 +484,21 @@ pub fn is_true(value: &bool) -> bool {
 /// Convert TOML request to internal RequestData
 impl From<RequestToml> for RequestData {
     fn from(toml: RequestToml) -> Self {
-        let method = match toml.http.method.to_uppercase().as_str() {
-            "GET" => HttpMethod::Get,
-            "POST" => HttpMethod::Post,
-            "PUT" => HttpMethod::Put,
-            "DELETE" => HttpMethod::Delete,
-            "PATCH" => HttpMethod::Patch,
-            _ => HttpMethod::Get,
+        let kind = RequestKind::from_type(&toml.meta.request_type);
+        let grpc = toml.grpc.as_ref().map(|grpc| GrpcRequest {
+            method: grpc.method.clone(),
+            proto_files: grpc.proto_files.clone(),
+        });
+        let (method, url) = match (toml.http, toml.websocket, toml.grpc) {
+            (Some(http), _, _) => (http.method, http.url),
+            (None, Some(websocket), _) => ("GET".to_string(), websocket.url),
+            (None, None, Some(grpc)) => ("POST".to_string(), grpc.url),
+            (None, None, None) => ("GET".to_string(), String::new()),
         };
+        let method = HttpMethod::parse(&method);
 
         // Parse headers from TOML
-        let headers = toml
+        let mut headers = toml
             .headers
             .unwrap_or_default()
             .into_iter()
@@ -264,11 +540,12 @@ impl From<RequestToml> for RequestData {
         };
 
         // Extract script data
-        let (pre_request_script, post_response_script) = if let Some(script) = toml.script {
-            (script.pre_request, script.post_response)
-        } else {
-            (None, None)
-        };
+        let (pre_request_script, post_response_script, event_script) =
+            if let Some(script) = toml.script {
+                (script.pre_request, script.post_response, script.on_event)
+            } else {
+                (None, None, None)
+            };
 
         // Infer body type from Content-Type header
         let content_type = headers
@@ -278,10 +555,55 @@ impl From<RequestToml> for RequestData {
             .unwrap_or(ContentType::Json);
         let body_type = content_type.body_type();
 
+        let graphql = toml
+            .body
+            .as_ref()
+            .and_then(|b| b.graphql.as_ref())
+            .map(GraphQLBody::to_request);
+
+        let body_file = toml.body.as_ref().and_then(|b| b.file.clone());
+
+        let mut multipart = toml
+            .body
+            .as_ref()
+            .and_then(|b| b.multipart.as_ref())
+            .map(|parts| parts.iter().map(MultipartPart::from).collect::<Vec<_>>());
+
+        // Form fields used to send files as `@path` values; they're parts of
+        // a multipart body now
+        if multipart.is_none()
+            && let Some(form) = toml.body.as_ref().and_then(|b| b.form.as_ref())
+            && form.values().any(|value| value.starts_with('@'))
+        {
+            let mut fields: Vec<_> = form.iter().collect();
+            fields.sort();
+            multipart = Some(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let (kind, value) = match value.strip_prefix('@') {
+                            Some(file) => (MultipartKind::File, file.to_string()),
+                            None => (MultipartKind::Text, value.clone()),
+                        };
+                        MultipartPart {
+                            name: name.clone(),
+                            kind,
+                            value,
+                            enabled: true,
+                            ..Default::default()
+                        }
+                    })
+                    .collect(),
+            );
+            headers.retain(|h| !h.key.eq_ignore_ascii_case("content-type"));
+        }
+
         // Extract body content from [body] section based on inferred content type
-        let body = match &toml.body {
-            None => String::new(),
-            Some(body_section) => match body_type {
+        let body = match (&toml.body, &graphql) {
+            (_, Some(graphql)) => graphql.body(),
+            _ if multipart.is_some() => String::new(),
+            (None, None) => String::new(),
+            (Some(body_section), None) => match body_type {
                 "json" => body_section.json.clone().unwrap_or_default(),
                 "text" => body_section.text.clone().unwrap_or_default(),
                 "xml" => body_section.xml.clone().unwrap_or_default(),
@@ -321,16 +643,25 @@ impl From<RequestToml> for RequestData {
 
         RequestData {
             name: toml.meta.name,
+            kind,
             method,
-            url: toml.http.url,
+            url,
             path_params,
             body,
+            body_file,
+            multipart,
             headers,
             query_params,
             auth: toml.auth.unwrap_or_default(),
             pre_request_script,
             post_response_script,
+            event_script,
             vars: toml.vars,
+            seq: toml.meta.seq.trim().parse().ok(),
+            settings: toml.settings,
+            messages: toml.messages,
+            graphql,
+            grpc,
         }
     }
 }
@@ -403,29 +734,94 @@ impl From<RequestData> for RequestToml {
             auth => Some(auth.clone()),
         };
 
+        let (http, websocket, grpc) = match data.kind {
+            RequestKind::Http => (
+                Some(RequestHttp {
+                    method: data.method.as_str().to_string(),
+                    url: data.url,
+                    auth: data.auth.type_name().to_string(),
+                }),
+                None,
+                None,
+            ),
+            RequestKind::WebSocket => (
+                None,
+                Some(RequestWebSocket {
+                    url: data.url,
+                    auth: data.auth.type_name().to_string(),
+                }),
+                None,
+            ),
+            RequestKind::Grpc => {
+                let grpc = data.grpc.clone().unwrap_or_default();
+                (
+                    None,
+                    None,
+                    Some(RequestGrpc {
+                        url: data.url,
+                        method: grpc.method,
+                        auth: data.auth.type_name().to_string(),
+                        proto_files: grpc.proto_files,
+                    }),
+                )
+            }
+        };
+
         RequestToml {
             meta: RequestMeta {
                 name: data.name,
-                request_type: "http".to_string(),
-                seq: "1".to_string(),
-            },
-            http: RequestHttp {
-                method: data.method.as_str().to_string(),
-                url: data.url,
-                auth: data.auth.type_name().to_string(),
+                request_type: data.kind.as_str().to_string(),
+                seq: data.seq.unwrap_or(1).to_string(),
             },
+            http,
+            websocket,
+            grpc,
             auth,
-            script: if data.pre_request_script.is_some() || data.post_response_script.is_some() {
+            script: if data.pre_request_script.is_some()
+                || data.post_response_script.is_some()
+                || data.event_script.is_some()
+            {
                 Some(RequestScript {
                     pre_request: data.pre_request_script,
                     post_response: data.post_response_script,
+                    on_event: data.event_script,
                 })
             } else {
                 None
             },
             headers,
             query,
-            body: if data.body.is_empty() {
+            body: if let Some(graphql) = &data.graphql {
+                Some(RequestBodyToml {
+                    json: None,
+                    text: None,
+                    form: None,
+                    graphql: Some(GraphQLBody::from_request(graphql)),
+                    xml: None,
+                    file: None,
+                    multipart: None,
+                })
+            } else if let Some(parts) = &data.multipart {
+                Some(RequestBodyToml {
+                    json: None,
+                    text: None,
+                    form: None,
+                    graphql: None,
+                    xml: None,
+                    file: None,
+                    multipart: Some(parts.iter().map(MultipartPartToml::from).collect()),
+                })
+            } else if let Some(file) = &data.body_file {
+                Some(RequestBodyToml {
+                    json: None,
+                    text: None,
+                    form: None,
+                    graphql: None,
+                    xml: None,
+                    file: Some(file.clone()),
+                    multipart: None,
+                })
+            } else if data.body.is_empty() {
                 None
             } else {
                 // Create body section based on detected content type
@@ -433,6 +829,8 @@ impl From<RequestData> for RequestToml {
             },
             params,
             vars: data.vars,
+            settings: data.settings,
+            messages: data.messages,
         }
     }
 }
@@ -446,6 +844,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
             form: None,
             graphql: None,
             xml: None,
+            file: None,
+            multipart: None,
         }),
         "text" => Some(RequestBodyToml {
             json: None,
@@ -453,6 +853,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
             form: None,
             graphql: None,
             xml: None,
+            file: None,
+            multipart: None,
         }),
         "xml" => Some(RequestBodyToml {
             json: None,
@@ -460,6 +862,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
             form: None,
             graphql: None,
             xml: Some(body.to_string()),
+            file: None,
+            multipart: None,
         }),
         "form" => {
             // Try to parse URL-encoded form data into a HashMap
@@ -478,6 +882,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
                 form: Some(form_map),
                 graphql: None,
                 xml: None,
+                file: None,
+                multipart: None,
             })
         }
         "graphql" => {
@@ -496,6 +902,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
                             variables: obj.get("variables").cloned(),
                         }),
                         xml: None,
+                        file: None,
+                        multipart: None,
                     })
                 } else {
                     // If it's not an object, treat as plain text
@@ -505,6 +913,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
                         form: None,
                         graphql: None,
                         xml: None,
+                        file: None,
+                        multipart: None,
                     })
                 }
             } else {
@@ -515,6 +925,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
                     form: None,
                     graphql: None,
                     xml: None,
+                    file: None,
+                    multipart: None,
                 })
             }
         }
@@ -524,6 +936,8 @@ fn create_body_toml(body: &str, body_type: &str) -> Option<RequestBodyToml> {
             form: None,
             graphql: None,
             xml: None,
+            file: None,
+            multipart: None,
         }),
     }
 }
@@ -546,6 +960,11 @@ pub fn create_empty_collection() -> CollectionToml {
             ignore: Vec::new(),
             auth: None,
             vars: Vec::new(),
+            headers: Vec::new(),
+            script: None,
+            proxy: None,
+            tls: None,
+            http_version: None,
         },
         environments: vec![],
     }
@@ -566,6 +985,11 @@ mod tests {
                 ignore: Vec::new(),
                 auth: None,
                 vars: Vec::new(),
+                headers: Vec::new(),
+                script: None,
+                proxy: None,
+                tls: None,
+                http_version: None,
             },
             environments: vec![],
         };
@@ -599,6 +1023,11 @@ mod tests {
                 ignore: Vec::new(),
                 auth: None,
                 vars: Vec::new(),
+                headers: Vec::new(),
+                script: None,
+                proxy: None,
+                tls: None,
+                http_version: None,
             },
             environments: vec![EnvironmentToml {
                 name: "Development".to_string(),
@@ -691,6 +1120,226 @@ auth = "none"
         assert!(matches!(request_data.auth, AuthType::None));
     }
 
+    #[test]
+    fn test_request_toml_seq_roundtrip() {
+        let request = RequestData {
+            name: "Ordered".to_string(),
+            seq: Some(7),
+            ..Default::default()
+        };
+        let request_toml: RequestToml = request.into();
+        assert_eq!(request_toml.meta.seq, "7");
+        let request_data: RequestData = request_toml.into();
+        assert_eq!(request_data.seq, Some(7));
+
+        // Requests without a seq keep the historical "1"; unparsable values
+        // come back as None.
+        let unordered: RequestToml = RequestData::default().into();
+        assert_eq!(unordered.meta.seq, "1");
+        let mut garbled: RequestToml = RequestData::default().into();
+        garbled.meta.seq = "first".to_string();
+        assert_eq!(RequestData::from(garbled).seq, None);
+    }
+
+    #[test]
+    fn test_request_toml_websocket_roundtrip() {
+        let request = RequestData {
+            name: "Feed".to_string(),
+            kind: RequestKind::WebSocket,
+            url: "wss://example.com/feed".to_string(),
+            messages: vec![MessageTemplate {
+                name: "Subscribe".to_string(),
+                body: r#"{"op":"subscribe"}"#.to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("type = \"websocket\""));
+        assert!(toml_string.contains("[websocket]"));
+        assert!(!toml_string.contains("[http]"));
+        assert!(toml_string.contains("[[messages]]"));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.kind, RequestKind::WebSocket);
+        assert_eq!(request_data.url, request.url);
+        assert_eq!(request_data.messages, request.messages);
+    }
+
+    #[test]
+    fn test_request_toml_grpc_roundtrip() {
+        let request = RequestData {
+            name: "Get order".to_string(),
+            kind: RequestKind::Grpc,
+            url: "grpc://localhost:50051".to_string(),
+            body: "{\n  \"orderId\": \"{{orderId}}\"\n}".to_string(),
+            grpc: Some(GrpcRequest {
+                method: "shop.v1.Orders/GetOrder".to_string(),
+                proto_files: vec!["protos/orders.proto".to_string()],
+            }),
+            ..Default::default()
+        };
+
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("type = \"grpc\""));
+        assert!(toml_string.contains("[grpc]"));
+        assert!(!toml_string.contains("[http]"));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.kind, RequestKind::Grpc);
+        assert_eq!(request_data.url, request.url);
+        assert_eq!(request_data.body, request.body);
+        assert_eq!(request_data.grpc, request.grpc);
+    }
+
+    #[test]
+    fn test_request_toml_file_body_roundtrip() {
+        let request = RequestData {
+            name: "Upload".to_string(),
+            method: HttpMethod::Put,
+            url: "https://storage.example.com/bucket/logo.png".to_string(),
+            body_file: Some("fixtures/logo.png".to_string()),
+            ..Default::default()
+        };
+
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("file = \"fixtures/logo.png\""));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.body_file, request.body_file);
+        assert!(request_data.body.is_empty());
+    }
+
+    #[test]
+    fn test_request_toml_custom_method_roundtrip() {
+        let request = RequestData {
+            name: "Properties".to_string(),
+            method: HttpMethod::Custom("PROPFIND".to_string()),
+            url: "https://dav.example.com/files/".to_string(),
+            body: "<propfind xmlns=\"DAV:\"><allprop/></propfind>".to_string(),
+            ..Default::default()
+        };
+
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("method = \"PROPFIND\""));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.method, request.method);
+        assert_eq!(request_data.body, request.body);
+    }
+
+    #[test]
+    fn test_request_toml_multipart_roundtrip() {
+        let parts = vec![
+            MultipartPart {
+                name: "avatar".to_string(),
+                kind: MultipartKind::File,
+                value: "fixtures/avatar.png".to_string(),
+                filename: "me.png".to_string(),
+                enabled: true,
+                ..Default::default()
+            },
+            MultipartPart {
+                name: "meta".to_string(),
+                kind: MultipartKind::Text,
+                value: r#"{"public": true}"#.to_string(),
+                content_type: "application/json".to_string(),
+                enabled: false,
+                ..Default::default()
+            },
+        ];
+        let request = RequestData {
+            name: "Upload avatar".to_string(),
+            method: HttpMethod::Post,
+            url: "https://api.example.com/avatar".to_string(),
+            multipart: Some(parts),
+            ..Default::default()
+        };
+
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("[[body.multipart]]"));
+        assert!(toml_string.contains("file = \"fixtures/avatar.png\""));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.multipart, request.multipart);
+        assert!(request_data.body.is_empty());
+    }
+
+    #[test]
+    fn test_file_form_fields_become_multipart_parts() {
+        let toml_str = r#"
+[meta]
+name = "Upload"
+type = "http"
+seq = "1"
+
+[http]
+method = "POST"
+url = "https://api.example.com/upload"
+auth = "none"
+
+[[headers]]
+key = "Content-Type"
+value = "application/x-www-form-urlencoded"
+
+[body.form]
+caption = "Hello"
+file = "@/tmp/photo.jpg"
+"#;
+        let toml: RequestToml = toml::from_str(toml_str).expect("Failed to parse");
+        let request_data: RequestData = toml.into();
+
+        let parts = request_data.multipart.expect("multipart body");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name, "caption");
+        assert_eq!(parts[0].kind, MultipartKind::Text);
+        assert_eq!(parts[1].kind, MultipartKind::File);
+        assert_eq!(parts[1].value, "/tmp/photo.jpg");
+        assert!(request_data.headers.is_empty());
+        assert!(request_data.body.is_empty());
+    }
+
+    #[test]
+    fn test_request_toml_graphql_roundtrip() {
+        let graphql = GraphQLRequest {
+            query: "query User($id: ID!) { user(id: $id) { name } }".to_string(),
+            variables: "{\n  \"id\": {{userId}}\n}".to_string(),
+        };
+        let request = RequestData {
+            name: "User".to_string(),
+            method: HttpMethod::Post,
+            url: "https://api.example.com/graphql".to_string(),
+            body: graphql.body(),
+            graphql: Some(graphql.clone()),
+            ..Default::default()
+        };
+
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("[body.graphql]"));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.graphql, Some(graphql));
+        assert_eq!(request_data.body, request.body);
+    }
+
     #[test]
     fn test_request_toml_roundtrip_oauth2_auth() {
         let request = RequestData {
@@ -736,6 +1385,11 @@ auth = "none"
                 ignore: Vec::new(),
                 auth: None,
                 vars: Vec::new(),
+                headers: Vec::new(),
+                script: None,
+                proxy: None,
+                tls: None,
+                http_version: None,
             },
             environments: vec![],
         };
@@ -757,4 +1411,203 @@ auth = "none"
         let back_none: CollectionToml = toml::from_str(&toml_none).expect("deserialize");
         assert!(back_none.collection.docs.is_none());
     }
+
+    #[test]
+    fn test_collection_proxy_override() {
+        let toml_str = r#"
+[collection]
+name = "Behind proxy"
+version = "1.0.0"
+type = "collection"
+
+[collection.proxy]
+enabled = true
+https_url = "http://proxy.corp:3128"
+no_proxy = "localhost, .internal"
+"#;
+        let collection: CollectionToml = toml::from_str(toml_str).expect("deserialize");
+        let proxy = collection.collection.proxy.expect("proxy override");
+        assert!(proxy.enabled);
+        assert_eq!(proxy.https_url, "http://proxy.corp:3128");
+        assert_eq!(proxy.no_proxy, "localhost, .internal");
+        // Unset fields fall back to the defaults
+        assert!(proxy.http_url.is_empty());
+        assert!(proxy.use_env);
+
+        // Only a `{{variable}}` reference to the password is written back
+        let mut with_password = CollectionToml {
+            collection: CollectionMeta {
+                proxy: None,
+                ..collection.collection
+            },
+            environments: vec![],
+        };
+        let written = |collection: &CollectionToml| toml::to_string(collection).expect("serialize");
+        with_password.collection.proxy = Some(ProxySettings {
+            password: "{{proxyPassword}}".to_string(),
+            ..proxy.clone()
+        });
+        assert!(written(&with_password).contains("password = \"{{proxyPassword}}\""));
+        with_password.collection.proxy = Some(ProxySettings {
+            password: "hunter2".to_string(),
+            ..proxy.clone()
+        });
+        assert!(!written(&with_password).contains("hunter2"));
+
+        let without: CollectionToml = toml::from_str(
+            "[collection]\nname = \"Direct\"\nversion = \"1.0.0\"\ntype = \"collection\"\n",
+        )
+        .expect("deserialize");
+        assert!(without.collection.proxy.is_none());
+        assert!(
+            !toml::to_string(&without)
+                .expect("serialize")
+                .contains("proxy")
+        );
+    }
+
+    #[test]
+    fn test_collection_headers_and_script() {
+        let toml_str = r#"
+[collection]
+name = "Tenants"
+version = "1.0.0"
+type = "collection"
+
+[[collection.headers]]
+key = "X-Tenant"
+value = "{{tenant}}"
+enabled = true
+
+[collection.script]
+pre-request = "bro.setVar('signed', true);"
+"#;
+        let collection: CollectionToml = toml::from_str(toml_str).expect("deserialize");
+        let defaults = collection.collection.defaults();
+        assert_eq!(defaults.headers[0].key, "X-Tenant");
+        assert_eq!(
+            defaults.pre_request_script.as_deref(),
+            Some("bro.setVar('signed', true);")
+        );
+        assert!(defaults.post_response_script.is_none());
+
+        let back: CollectionToml =
+            toml::from_str(&toml::to_string(&collection).expect("serialize")).expect("reparse");
+        assert_eq!(back, collection);
+    }
+
+    #[test]
+    fn test_http_version_settings() {
+        let collection: CollectionToml = toml::from_str(
+            "[collection]\nname = \"Gateway\"\nversion = \"1.0.0\"\ntype = \"collection\"\nhttp_version = \"http1\"\n",
+        )
+        .expect("deserialize");
+        assert_eq!(collection.collection.http_version, Some(HttpVersion::Http1));
+
+        let request = RequestData {
+            name: "Cleartext".to_string(),
+            url: "http://gateway.internal:8080/health".to_string(),
+            settings: RequestSettings {
+                http_version: Some(HttpVersion::Http2PriorKnowledge),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let toml_string =
+            toml::to_string(&RequestToml::from(request.clone())).expect("Failed to serialize");
+        assert!(toml_string.contains("http_version = \"http2-prior-knowledge\""));
+
+        let deserialized: RequestToml =
+            toml::from_str(&toml_string).expect("Failed to deserialize");
+        let request_data: RequestData = deserialized.into();
+        assert_eq!(request_data.settings, request.settings);
+    }
+
+    #[test]
+    fn test_collection_tls_settings() {
+        let toml_str = r#"
+[collection]
+name = "Internal"
+version = "1.0.0"
+type = "collection"
+
+[collection.tls]
+ca_certificates = ["certs/internal-ca.pem"]
+
+[[collection.tls.client_certificates]]
+host = "*.internal.example"
+cert = "certs/client.p12"
+passphrase = "{{certPassphrase}}"
+"#;
+        let collection: CollectionToml = toml::from_str(toml_str).expect("deserialize");
+        let tls = collection.collection.tls.clone().expect("tls settings");
+        assert_eq!(tls.ca_certificates, vec!["certs/internal-ca.pem"]);
+        assert!(!tls.insecure);
+        assert_eq!(tls.client_certificates.len(), 1);
+        assert_eq!(tls.client_certificates[0].host, "*.internal.example");
+        assert_eq!(tls.client_certificates[0].key, None);
+
+        let round_trip: CollectionToml =
+            toml::from_str(&toml::to_string(&collection).expect("serialize")).expect("deserialize");
+        assert_eq!(round_trip, collection);
+
+        // A literal passphrase isn't written back
+        let mut literal = collection.clone();
+        if let Some(tls) = &mut literal.collection.tls {
+            tls.client_certificates[0].passphrase = Some("changeit".to_string());
+        }
+        assert!(
+            !toml::to_string(&literal)
+                .expect("serialize")
+                .contains("changeit")
+        );
+    }
+
+    #[test]
+    fn test_folder_toml_roundtrip() {
+        let toml_str = r#"
+[auth]
+type = "key"
+header = "Authorization"
+value = "Bearer {{usersToken}}"
+
+[script]
+pre-request = "bro.setVar('service', 'users');"
+
+[[headers]]
+key = "X-Service"
+value = "users"
+
+[[vars]]
+key = "baseUrl"
+value = "https://users.internal.example"
+enabled = true
+"#;
+        let folder: FolderSettings = toml::from_str::<FolderToml>(toml_str)
+            .expect("deserialize")
+            .into();
+        assert_eq!(folder.vars[0].key, "baseUrl");
+        assert_eq!(folder.headers[0].key, "X-Service");
+        assert!(folder.headers[0].enabled);
+        assert!(matches!(folder.auth, AuthType::Key(_)));
+        assert_eq!(
+            folder.pre_request_script.as_deref(),
+            Some("bro.setVar('service', 'users');")
+        );
+        assert_eq!(folder.post_response_script, None);
+
+        let toml_string = toml::to_string(&FolderToml::from(&folder)).expect("serialize");
+        let round_trip: FolderSettings = toml::from_str::<FolderToml>(&toml_string)
+            .expect("deserialize")
+            .into();
+        assert_eq!(round_trip, folder);
+
+        // A folder without settings inherits the collection's auth
+        let empty: FolderSettings = toml::from_str::<FolderToml>("").expect("empty").into();
+        assert!(empty.is_default());
+        assert_eq!(
+            toml::to_string(&FolderToml::from(&empty)).expect("serialize"),
+            ""
+        );
+    }
 }
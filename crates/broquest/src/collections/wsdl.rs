@@ -1,5 +1,5 @@
 use super::types::{EnvironmentToml, EnvironmentVariable, ImportResult};
-use crate::domain::{AuthType, HttpMethod, KeyValuePair, RequestData};
+use crate::domain::{HttpMethod, KeyValuePair, RequestData};
 
 /// Import from a local WSDL file and produce an ImportResult compatible with the collection system.
 #[allow(dead_code)]
@@ -172,13 +172,8 @@ fn create_soap_request(op: &wsdl::WsdlOperationInfo) -> RequestData {
         name: op.name.clone(),
         method: HttpMethod::Post,
         url: "{{baseUrl}}".to_string(),
-        path_params: vec![],
-        query_params: vec![],
         headers,
         body: op.soap_envelope.clone(),
-        auth: AuthType::None,
-        pre_request_script: None,
-        post_response_script: None,
-        vars: Vec::new(),
+        ..Default::default()
     }
 }
@@ -0,0 +1,371 @@
+use reqwest::Url;
+use std::sync::{Arc, Mutex};
+
+use crate::http::current_unix_timestamp;
+
+/// A cookie stored in a jar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// Lowercase, without a leading dot.
+    pub domain: String,
+    pub path: String,
+    /// Unix timestamp (seconds); `None` for session cookies.
+    pub expires: Option<i64>,
+    pub secure: bool,
+    pub http_only: bool,
+    /// Sent to `domain` only, not its subdomains (set without a `Domain`
+    /// attribute).
+    pub host_only: bool,
+}
+
+impl Cookie {
+    /// A session cookie for `url`'s host and every path. `None` when the URL
+    /// has no host or the name is empty.
+    pub fn new(url: &Url, name: &str, value: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: url.host_str()?.to_ascii_lowercase(),
+            path: "/".to_string(),
+            expires: None,
+            secure: false,
+            http_only: false,
+            host_only: true,
+        })
+    }
+
+    /// Parse a `Set-Cookie` header received from `url`. Returns `None` for
+    /// malformed headers and for a `Domain` that doesn't cover the host.
+    pub fn parse(set_cookie: &str, url: &Url) -> Option<Self> {
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let mut cookie = Cookie::new(url, name, value)?;
+        cookie.path = default_path(url.path());
+        let host = cookie.domain.clone();
+        let mut max_age = None;
+
+        for attribute in parts {
+            let (key, val) = match attribute.split_once('=') {
+                Some((key, val)) => (key.trim(), val.trim()),
+                None => (attribute.trim(), ""),
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "domain" => {
+                    let domain = val.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain.is_empty() {
+                        if !domain_matches(&host, &domain) {
+                            return None;
+                        }
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    }
+                }
+                "path" if val.starts_with('/') => cookie.path = val.to_string(),
+                "expires" => cookie.expires = parse_http_date(val).or(cookie.expires),
+                "max-age" => max_age = val.parse::<i64>().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                _ => {}
+            }
+        }
+
+        // Max-Age wins over Expires; zero or negative expires the cookie now.
+        if let Some(seconds) = max_age {
+            cookie.expires = Some(current_unix_timestamp() + seconds.min(i64::MAX / 2));
+        }
+
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the cookie is sent with a request to `url`
+    fn matches(&self, url: &Url, now: i64) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+
+        domain_ok
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired(now)
+    }
+
+    fn same_identity(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// The cookies of one collection and environment. Cloning shares the jar.
+#[derive(Debug, Clone, Default)]
+pub struct CookieJar {
+    data: Arc<Mutex<JarData>>,
+}
+
+#[derive(Debug, Default)]
+struct JarData {
+    cookies: Vec<Cookie>,
+    /// Set on every change, cleared by [`CookieJar::take_changed`].
+    changed: bool,
+}
+
+impl CookieJar {
+    pub fn new(cookies: Vec<Cookie>) -> Self {
+        Self {
+            data: Arc::new(Mutex::new(JarData {
+                cookies,
+                changed: false,
+            })),
+        }
+    }
+
+    /// All cookies in the jar, including expired ones not yet purged
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.data
+            .lock()
+            .map(|data| data.cookies.clone())
+            .unwrap_or_default()
+    }
+
+    /// The cookies sent with a request to `url`, most specific path first
+    pub fn cookies_for(&self, url: &str) -> Vec<Cookie> {
+        let Ok(url) = Url::parse(url) else {
+            return Vec::new();
+        };
+        let now = current_unix_timestamp();
+        let mut cookies: Vec<Cookie> = self
+            .data
+            .lock()
+            .map(|data| {
+                data.cookies
+                    .iter()
+                    .filter(|c| c.matches(&url, now))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        cookies.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+        cookies
+    }
+
+    /// The `Cookie` header value for a request to `url`
+    pub fn header_for(&self, url: &str) -> Option<String> {
+        let cookies = self.cookies_for(url);
+        if cookies.is_empty() {
+            return None;
+        }
+        Some(
+            cookies
+                .iter()
+                .map(|c| format!("{}={}", c.name, c.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    /// Store the `Set-Cookie` headers of a response from `url`
+    pub fn store_response_cookies<'a>(
+        &self,
+        url: &str,
+        set_cookies: impl IntoIterator<Item = &'a str>,
+    ) {
+        let Ok(url) = Url::parse(url) else {
+            return;
+        };
+        for header in set_cookies {
+            match Cookie::parse(header, &url) {
+                Some(cookie) => self.set(cookie),
+                None => tracing::debug!("Ignoring Set-Cookie header: {}", header),
+            }
+        }
+    }
+
+    /// Add or replace a cookie (same name, domain and path). An expired
+    /// cookie just removes the stored one.
+    pub fn set(&self, cookie: Cookie) {
+        if let Ok(mut data) = self.data.lock() {
+            data.cookies.retain(|c| !c.same_identity(&cookie));
+            if !cookie.is_expired(current_unix_timestamp()) {
+                data.cookies.push(cookie);
+            }
+            data.changed = true;
+        }
+    }
+
+    pub fn remove(&self, name: &str, domain: &str, path: &str) {
+        if let Ok(mut data) = self.data.lock() {
+            let before = data.cookies.len();
+            data.cookies
+                .retain(|c| !(c.name == name && c.domain == domain && c.path == path));
+            data.changed |= data.cookies.len() != before;
+        }
+    }
+
+    /// Remove the cookies that would be sent to `url`'s host (any path)
+    pub fn clear_for(&self, url: &str) {
+        let Some(host) = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        else {
+            return;
+        };
+        if let Ok(mut data) = self.data.lock() {
+            let before = data.cookies.len();
+            data.cookies.retain(|c| {
+                !(if c.host_only {
+                    c.domain == host
+                } else {
+                    domain_matches(&host, &c.domain)
+                })
+            });
+            data.changed |= data.cookies.len() != before;
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut data) = self.data.lock() {
+            data.changed |= !data.cookies.is_empty();
+            data.cookies.clear();
+        }
+    }
+
+    /// Whether the jar changed since the last call, so callers know to save it
+    pub fn take_changed(&self) -> bool {
+        self.data
+            .lock()
+            .map(|mut data| std::mem::take(&mut data.changed))
+            .unwrap_or(false)
+    }
+}
+
+/// RFC 6265 domain matching: `host` is `domain` or one of its subdomains.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.len() > domain.len()
+            && host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+/// RFC 6265 path matching.
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// The default cookie path: the request path up to its last `/`.
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+/// Parse an `Expires` date, accepting the dashed form many servers still send
+/// (`Wed, 21-Oct-2015 07:28:00 GMT`).
+fn parse_http_date(value: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc2822(value)
+        .or_else(|_| chrono::DateTime::parse_from_rfc2822(&value.replace('-', " ")))
+        .ok()
+        .map(|date| date.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).expect("valid url")
+    }
+
+    #[test]
+    fn parses_set_cookie_attributes() {
+        let cookie = Cookie::parse(
+            "sid=abc123; Domain=.Example.com; Path=/api; Expires=Wed, 21-Oct-2037 07:28:00 GMT; Secure; HttpOnly",
+            &url("https://login.example.com/auth/start"),
+        )
+        .expect("valid cookie");
+
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc123");
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/api");
+        assert_eq!(cookie.expires, Some(2139722880));
+        assert!(cookie.secure && cookie.http_only);
+
+        let defaulted =
+            Cookie::parse("a=1", &url("http://example.com/auth/start")).expect("valid cookie");
+        assert_eq!(defaulted.domain, "example.com");
+        assert!(defaulted.host_only);
+        assert_eq!(defaulted.path, "/auth");
+        assert_eq!(defaulted.expires, None);
+
+        // A Domain the host doesn't belong to is rejected.
+        assert!(Cookie::parse("a=1; Domain=other.com", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("no-equals", &url("http://example.com/")).is_none());
+    }
+
+    #[test]
+    fn sends_matching_cookies_only() {
+        let jar = CookieJar::default();
+        jar.store_response_cookies(
+            "https://example.com/login",
+            [
+                "sid=1; Path=/",
+                "scoped=2; Path=/api",
+                "sub=3; Domain=example.com",
+                "secure=4; Secure",
+            ],
+        );
+
+        assert_eq!(
+            jar.header_for("https://example.com/api/users").as_deref(),
+            Some("scoped=2; sid=1; sub=3; secure=4")
+        );
+        assert_eq!(
+            jar.header_for("http://www.example.com/apiv2").as_deref(),
+            Some("sub=3")
+        );
+        assert_eq!(jar.header_for("http://other.com/"), None);
+    }
+
+    #[test]
+    fn replaces_expires_and_clears() {
+        let jar = CookieJar::default();
+        jar.store_response_cookies("http://example.com/", ["sid=1", "theme=dark"]);
+        assert!(jar.take_changed());
+        assert!(!jar.take_changed());
+
+        jar.store_response_cookies("http://example.com/", ["sid=2"]);
+        assert_eq!(
+            jar.header_for("http://example.com/").as_deref(),
+            Some("sid=2; theme=dark")
+        );
+
+        jar.store_response_cookies("http://example.com/", ["sid=; Max-Age=0"]);
+        assert_eq!(
+            jar.header_for("http://example.com/").as_deref(),
+            Some("theme=dark")
+        );
+
+        jar.clear_for("http://example.com/anything");
+        assert!(jar.cookies().is_empty());
+        assert!(jar.take_changed());
+    }
+}
@@ -0,0 +1,10 @@
+//! Cookie jars: one per collection and environment, persisted in the app
+//! database and shared by the requests sent within that scope.
+
+mod jar;
+mod store;
+mod view;
+
+pub use jar::*;
+pub use store::*;
+pub use view::*;
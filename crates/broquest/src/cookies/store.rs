@@ -0,0 +1,72 @@
+use gpui::{App, Global};
+use std::collections::HashMap;
+
+use crate::app_database::{AppDatabase, StoredCookie};
+use crate::result_ext::ResultExt;
+
+use super::CookieJar;
+
+/// The cookie jars of every collection and environment, loaded from the app
+/// database at startup
+#[derive(Default)]
+pub struct CookieJars {
+    /// Keyed by collection path and environment name ("" for no environment).
+    jars: HashMap<(String, String), CookieJar>,
+}
+
+impl Global for CookieJars {}
+
+impl CookieJars {
+    pub fn new(stored: Vec<StoredCookie>) -> Self {
+        let mut cookies: HashMap<(String, String), Vec<_>> = HashMap::new();
+        for stored in stored {
+            cookies
+                .entry((stored.collection_path, stored.environment))
+                .or_default()
+                .push(stored.cookie);
+        }
+
+        Self {
+            jars: cookies
+                .into_iter()
+                .map(|(key, cookies)| (key, CookieJar::new(cookies)))
+                .collect(),
+        }
+    }
+
+    /// The jar for a collection and environment, created empty on first use
+    pub fn jar(cx: &mut App, collection_path: &str, environment: Option<&str>) -> CookieJar {
+        cx.global_mut::<Self>()
+            .jars
+            .entry((
+                collection_path.to_string(),
+                environment.unwrap_or_default().to_string(),
+            ))
+            .or_default()
+            .clone()
+    }
+
+    /// Save the jar to the app database if it changed since it was last saved
+    pub fn persist(
+        cx: &mut App,
+        collection_path: &str,
+        environment: Option<&str>,
+        jar: &CookieJar,
+    ) {
+        if !jar.take_changed() {
+            return;
+        }
+
+        let db = AppDatabase::global(cx).clone();
+        let collection_path = collection_path.to_string();
+        let environment = environment.unwrap_or_default().to_string();
+        let cookies = jar.cookies();
+        cx.spawn(async move |_| {
+            db.replace_cookies(&collection_path, &environment, &cookies)
+                .await
+                .log_err()
+                .ok();
+        })
+        .detach();
+    }
+}
@@ -0,0 +1,403 @@
+use gpui::{
+    App, AppContext, Context, Entity, FocusHandle, Focusable, IntoElement, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, Window, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme as _, IndexPath, Sizable as _, StyledExt, WindowExt,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    notification::NotificationType,
+    scroll::ScrollableElement,
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+use smol::Timer;
+use std::time::Duration;
+
+use crate::collections::CollectionManager;
+use crate::requests::EnvironmentOption;
+use crate::ui::icon::IconName;
+
+use super::{Cookie, CookieJar, CookieJars};
+
+/// A listed cookie and the input editing its value
+struct CookieRow {
+    cookie: Cookie,
+    value_input: Entity<InputState>,
+    _subscription: Subscription,
+}
+
+/// Lists the cookie jar of a collection for one environment at a time and
+/// lets the user edit values, add, delete and clear cookies.
+pub struct CookiesView {
+    collection_path: String,
+    environment_select: Entity<SelectState<Vec<EnvironmentOption>>>,
+    /// Name of the selected environment; `None` for "No environment".
+    environment: Option<String>,
+    jar: CookieJar,
+    rows: Vec<CookieRow>,
+    name_input: Entity<InputState>,
+    value_input: Entity<InputState>,
+    domain_input: Entity<InputState>,
+    path_input: Entity<InputState>,
+    /// Debounced save after value edits
+    save_task: Option<Task<()>>,
+    _subscriptions: Vec<Subscription>,
+    focus_handle: FocusHandle,
+}
+
+impl CookiesView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>, collection_path: String) -> Self {
+        let environments = CollectionManager::global(cx)
+            .read(cx)
+            .get_collection_environments(&collection_path)
+            .unwrap_or_default();
+        let mut env_options = vec![EnvironmentOption::None];
+        env_options.extend(environments.into_iter().map(EnvironmentOption::Environment));
+
+        let environment_select = cx
+            .new(|cx| SelectState::new(env_options, Some(IndexPath::default().row(0)), window, cx));
+        let subscription =
+            cx.subscribe_in(&environment_select, window, Self::on_environment_selected);
+
+        let jar = CookieJars::jar(cx, &collection_path, None);
+        let mut view = Self {
+            collection_path,
+            environment_select,
+            environment: None,
+            jar,
+            rows: Vec::new(),
+            name_input: cx.new(|cx| InputState::new(window, cx).placeholder("Name")),
+            value_input: cx.new(|cx| InputState::new(window, cx).placeholder("Value")),
+            domain_input: cx.new(|cx| InputState::new(window, cx).placeholder("example.com")),
+            path_input: cx.new(|cx| InputState::new(window, cx).default_value("/")),
+            save_task: None,
+            _subscriptions: vec![subscription],
+            focus_handle: cx.focus_handle(),
+        };
+        view.reload(window, cx);
+        view
+    }
+
+    fn on_environment_selected(
+        &mut self,
+        _: &Entity<SelectState<Vec<EnvironmentOption>>>,
+        event: &SelectEvent<Vec<EnvironmentOption>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let SelectEvent::Confirm(value) = event;
+        let environment = match value {
+            Some(EnvironmentOption::Environment(env)) => Some(env.name.clone()),
+            _ => None,
+        };
+
+        // Save pending edits to the jar being left
+        self.persist(cx);
+        self.environment = environment;
+        self.jar = CookieJars::jar(cx, &self.collection_path, self.environment.as_deref());
+        self.reload(window, cx);
+    }
+
+    /// Rebuild the rows from the jar, e.g. after requests stored new cookies
+    fn reload(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut cookies = self.jar.cookies();
+        cookies.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+
+        self.rows = cookies
+            .into_iter()
+            .map(|cookie| {
+                let value_input =
+                    cx.new(|cx| InputState::new(window, cx).default_value(cookie.value.clone()));
+                let edited = cookie.clone();
+                let subscription = cx.subscribe_in(
+                    &value_input,
+                    window,
+                    move |this: &mut Self,
+                          input_state: &Entity<InputState>,
+                          event: &InputEvent,
+                          window,
+                          cx| {
+                        if let InputEvent::Change = event
+                            && input_state.read(cx).focus_handle(cx).is_focused(window)
+                        {
+                            let value = input_state.read(cx).value().to_string();
+                            this.update_value(&edited, value, cx);
+                        }
+                    },
+                );
+                CookieRow {
+                    cookie,
+                    value_input,
+                    _subscription: subscription,
+                }
+            })
+            .collect();
+        cx.notify();
+    }
+
+    fn update_value(&mut self, cookie: &Cookie, value: String, cx: &mut Context<Self>) {
+        self.jar.set(Cookie {
+            value,
+            ..cookie.clone()
+        });
+
+        self.save_task = Some(cx.spawn(async move |this, cx| {
+            Timer::after(Duration::from_millis(500)).await;
+            this.update(cx, |view, cx| view.persist(cx)).ok();
+        }));
+    }
+
+    /// Save the jar if it changed. A pending debounced save then finds
+    /// nothing left to do.
+    fn persist(&self, cx: &mut Context<Self>) {
+        CookieJars::persist(
+            cx,
+            &self.collection_path,
+            self.environment.as_deref(),
+            &self.jar,
+        );
+    }
+
+    fn add_cookie(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.name_input.read(cx).value().trim().to_string();
+        let domain = self
+            .domain_input
+            .read(cx)
+            .value()
+            .trim()
+            .trim_start_matches('.')
+            .to_ascii_lowercase();
+        if name.is_empty() || domain.is_empty() {
+            window.push_notification(
+                (
+                    NotificationType::Error,
+                    "A cookie needs a name and a domain",
+                ),
+                cx,
+            );
+            return;
+        }
+        let path = match self.path_input.read(cx).value().trim() {
+            path if path.starts_with('/') => path.to_string(),
+            _ => "/".to_string(),
+        };
+
+        self.jar.set(Cookie {
+            name,
+            value: self.value_input.read(cx).value().to_string(),
+            domain,
+            path,
+            expires: None,
+            secure: false,
+            http_only: false,
+            host_only: false,
+        });
+        self.persist(cx);
+
+        for input in [&self.name_input, &self.value_input] {
+            input.update(cx, |input, cx| input.set_value("", window, cx));
+        }
+        self.reload(window, cx);
+    }
+
+    fn delete_cookie(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.get(index) {
+            let cookie = &row.cookie;
+            self.jar.remove(&cookie.name, &cookie.domain, &cookie.path);
+            self.persist(cx);
+            self.reload(window, cx);
+        }
+    }
+
+    fn clear_cookies(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.jar.clear();
+        self.persist(cx);
+        self.reload(window, cx);
+        window.push_notification((NotificationType::Info, "Cookies cleared"), cx);
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_3()
+            .p_3()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .min_w(px(160.))
+                    .child(Select::new(&self.environment_select)),
+            )
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!(
+                        "{} cookie{}",
+                        self.rows.len(),
+                        if self.rows.len() == 1 { "" } else { "s" }
+                    )),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("refresh-cookies")
+                    .ghost()
+                    .compact()
+                    .icon(IconName::Refresh)
+                    .label("Refresh")
+                    .on_click(cx.listener(|this, _, window, cx| this.reload(window, cx))),
+            )
+            .child(
+                Button::new("clear-cookies")
+                    .danger()
+                    .compact()
+                    .icon(IconName::Trash)
+                    .label("Clear all")
+                    .on_click(cx.listener(|this, _, window, cx| this.clear_cookies(window, cx))),
+            )
+    }
+
+    fn render_add_form(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_2()
+            .px_3()
+            .py_2()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .w(px(160.))
+                    .child(Input::new(&self.name_input).small()),
+            )
+            .child(div().flex_1().child(Input::new(&self.value_input).small()))
+            .child(
+                div()
+                    .w(px(180.))
+                    .child(Input::new(&self.domain_input).small()),
+            )
+            .child(
+                div()
+                    .w(px(100.))
+                    .child(Input::new(&self.path_input).small()),
+            )
+            .child(
+                Button::new("add-cookie")
+                    .outline()
+                    .small()
+                    .icon(IconName::Plus)
+                    .label("Add")
+                    .on_click(cx.listener(|this, _, window, cx| this.add_cookie(window, cx))),
+            )
+    }
+
+    fn render_row(
+        &self,
+        index: usize,
+        row: &CookieRow,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let cookie = &row.cookie;
+        let muted = cx.theme().muted_foreground;
+        let mono = cx.theme().mono_font_family.clone();
+        let expires = match cookie.expires {
+            Some(timestamp) => chrono::DateTime::from_timestamp(timestamp, 0)
+                .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+            None => "Session".to_string(),
+        };
+        let flags = [(cookie.secure, "Secure"), (cookie.http_only, "HttpOnly")]
+            .into_iter()
+            .filter_map(|(set, flag)| set.then_some(flag))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let domain = if cookie.host_only {
+            cookie.domain.clone()
+        } else {
+            format!(".{}", cookie.domain)
+        };
+
+        h_flex()
+            .gap_2()
+            .px_3()
+            .py_1()
+            .items_center()
+            .text_sm()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .w(px(160.))
+                    .font_family(mono.clone())
+                    .font_semibold()
+                    .text_ellipsis()
+                    .child(cookie.name.clone()),
+            )
+            .child(
+                div().flex_1().child(
+                    Input::new(&row.value_input)
+                        .small()
+                        .appearance(false)
+                        .font_family(mono),
+                ),
+            )
+            .child(div().w(px(180.)).text_ellipsis().child(domain))
+            .child(div().w(px(100.)).text_ellipsis().child(cookie.path.clone()))
+            .child(div().w(px(120.)).text_xs().text_color(muted).child(expires))
+            .child(div().w(px(100.)).text_xs().text_color(muted).child(flags))
+            .child(
+                Button::new(("delete-cookie", index))
+                    .small()
+                    .ghost()
+                    .icon(IconName::Trash)
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.delete_cookie(index, window, cx);
+                    })),
+            )
+    }
+}
+
+impl Focusable for CookiesView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for CookiesView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let body = if self.rows.is_empty() {
+            div()
+                .flex_1()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child(SharedString::from(
+                    "No cookies yet. Cookies set by responses to this collection's requests appear here.",
+                ))
+                .into_any_element()
+        } else {
+            v_flex()
+                .flex_1()
+                .overflow_y_scrollbar()
+                .children(
+                    self.rows
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, row)| self.render_row(ix, row, cx).into_any_element())
+                        .collect::<Vec<_>>(),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(self.render_toolbar(cx))
+            .child(self.render_add_form(cx))
+            .child(body)
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::KeyValuePair;
+
 /// Main auth type enum representing different authentication methods
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -28,8 +30,8 @@ impl AuthType {
             Self::Basic(_) => "Basic Auth",
             Self::Digest(_) => "Digest Auth",
             Self::Key(_) => "API Key",
-            Self::Inherit => "Inherit from Collection",
-            Self::OAuth2(_) => "OAuth2 Client Credentials",
+            Self::Inherit => "Inherit from Parent",
+            Self::OAuth2(_) => "OAuth2",
             Self::Jwt(_) => "JWT",
             Self::Unsupported { .. } => "Unsupported",
         }
@@ -105,17 +107,50 @@ pub enum OAuth2GrantType {
     #[default]
     ClientCredentials,
     AuthorizationCode,
+    Implicit,
     Password,
+    DeviceCode,
 }
 
 impl OAuth2GrantType {
+    pub fn all() -> Vec<OAuth2GrantType> {
+        vec![
+            Self::ClientCredentials,
+            Self::AuthorizationCode,
+            Self::Implicit,
+            Self::Password,
+            Self::DeviceCode,
+        ]
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::ClientCredentials => "client_credentials",
             Self::AuthorizationCode => "authorization_code",
+            Self::Implicit => "implicit",
             Self::Password => "password",
+            Self::DeviceCode => "urn:ietf:params:oauth:grant-type:device_code",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ClientCredentials => "Client Credentials",
+            Self::AuthorizationCode => "Authorization Code",
+            Self::Implicit => "Implicit",
+            Self::Password => "Password",
+            Self::DeviceCode => "Device Code",
         }
     }
+
+    /// Whether getting a token needs the user to sign in, in a browser or
+    /// on another device
+    pub fn is_interactive(&self) -> bool {
+        matches!(
+            self,
+            Self::AuthorizationCode | Self::Implicit | Self::DeviceCode
+        )
+    }
 }
 
 /// OAuth2 configuration
@@ -128,6 +163,26 @@ pub struct OAuth2Auth {
     pub scope: Option<String>,
     pub authorize_url: Option<String>,
     pub redirect_url: Option<String>,
+    /// Resource owner credentials of the password grant
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub username: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub password: String,
+    /// Send a S256 code challenge with the authorization code grant
+    #[serde(default = "default_true")]
+    pub pkce: bool,
+    /// Endpoint the device code grant asks for a user code at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_authorization_url: Option<String>,
+    /// API the token is for, as some providers (Auth0, Okta) expect it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// RFC 8707 resource indicator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    /// Additional parameters of the requests issuing the token
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_params: Vec<KeyValuePair>,
     pub access_token: Option<String>,
     pub refresh_token: Option<String>,
     #[serde(default)]
@@ -144,6 +199,13 @@ impl Default for OAuth2Auth {
             scope: None,
             authorize_url: None,
             redirect_url: None,
+            username: String::new(),
+            password: String::new(),
+            pkce: true,
+            device_authorization_url: None,
+            audience: None,
+            resource: None,
+            extra_params: Vec::new(),
             access_token: None,
             refresh_token: None,
             expires_at: None,
@@ -151,6 +213,10 @@ impl Default for OAuth2Auth {
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_token_type_field() -> String {
     "token_type".to_string()
 }
@@ -229,6 +295,31 @@ mod tests {
         assert!(matches!(auth, AuthType::None));
     }
 
+    #[test]
+    fn test_oauth2_deserialization_without_new_fields() {
+        let json = r#"{"type":"o_auth2","grant_type":"authorization_code","client_id":"app","client_secret":"","token_url":"https://id.example.com/token","scope":null,"authorize_url":"https://id.example.com/authorize","redirect_url":null,"access_token":null,"refresh_token":null}"#;
+        let auth: AuthType = serde_json::from_str(json).unwrap();
+        match auth {
+            AuthType::OAuth2(oauth) => {
+                assert_eq!(oauth.grant_type, OAuth2GrantType::AuthorizationCode);
+                assert!(oauth.pkce);
+                assert!(oauth.username.is_empty());
+                assert!(oauth.extra_params.is_empty());
+            }
+            _ => panic!("Expected OAuth2 auth"),
+        }
+    }
+
+    #[test]
+    fn test_device_code_grant_type_serialization() {
+        let json = serde_json::to_string(&OAuth2GrantType::DeviceCode).unwrap();
+        assert_eq!(json, r#""device_code""#);
+        assert_eq!(
+            OAuth2GrantType::DeviceCode.as_str(),
+            "urn:ietf:params:oauth:grant-type:device_code"
+        );
+    }
+
     #[test]
     fn test_jwt_auth_serialization() {
         let auth = AuthType::Jwt(JwtAuth {
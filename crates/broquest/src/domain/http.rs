@@ -4,7 +4,9 @@ use gpui::{App, SharedString};
 use gpui_component::{ActiveTheme, select::SelectItem};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+use super::RequestKind;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HttpMethod {
     Get,
     Post,
@@ -13,6 +15,8 @@ pub enum HttpMethod {
     Patch,
     Head,
     Options,
+    /// Any other method, e.g. PURGE or PROPFIND, in upper case
+    Custom(String),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -21,8 +25,57 @@ pub enum ContentType {
     Xml,
     Text,
     Html,
+    /// `multipart/form-data` parts, each text or a file
     Form,
     UrlEncoded,
+    /// A GraphQL operation, sent as JSON
+    GraphQL,
+    /// A file streamed from disk, typed by its extension
+    File,
+}
+
+/// HTTP version requests are sent with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HttpVersion {
+    /// HTTP/2 when the server offers it over TLS (ALPN), otherwise HTTP/1.1
+    #[default]
+    Negotiate,
+    /// HTTP/1.1 only
+    Http1,
+    /// HTTP/2 without negotiating, also over cleartext (h2c)
+    Http2PriorKnowledge,
+}
+
+impl HttpVersion {
+    pub const ALL: [HttpVersion; 3] = [
+        HttpVersion::Negotiate,
+        HttpVersion::Http1,
+        HttpVersion::Http2PriorKnowledge,
+    ];
+
+    /// Name in collection files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpVersion::Negotiate => "negotiate",
+            HttpVersion::Http1 => "http1",
+            HttpVersion::Http2PriorKnowledge => "http2-prior-knowledge",
+        }
+    }
+
+    pub fn parse(version: &str) -> Option<Self> {
+        HttpVersion::ALL
+            .into_iter()
+            .find(|known| known.as_str() == version.trim())
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HttpVersion::Negotiate => "Negotiate (HTTP/2 over TLS)",
+            HttpVersion::Http1 => "HTTP/1.1",
+            HttpVersion::Http2PriorKnowledge => "HTTP/2 prior knowledge (h2c)",
+        }
+    }
 }
 
 impl HttpMethod {
@@ -36,7 +89,16 @@ impl HttpMethod {
         HttpMethod::Options,
     ];
 
-    pub fn as_str(&self) -> &'static str {
+    /// The method named `method`, in any case
+    pub fn parse(method: &str) -> Self {
+        let method = method.trim().to_uppercase();
+        HttpMethod::ALL
+            .into_iter()
+            .find(|standard| standard.as_str() == method)
+            .unwrap_or(HttpMethod::Custom(method))
+    }
+
+    pub fn as_str(&self) -> &str {
         match self {
             HttpMethod::Get => "GET",
             HttpMethod::Post => "POST",
@@ -45,9 +107,16 @@ impl HttpMethod {
             HttpMethod::Patch => "PATCH",
             HttpMethod::Head => "HEAD",
             HttpMethod::Options => "OPTIONS",
+            HttpMethod::Custom(method) => method,
         }
     }
 
+    /// Whether a request with this method sends its body. Any method may,
+    /// e.g. a GET search with a JSON query, except HEAD.
+    pub fn allows_body(&self) -> bool {
+        *self != HttpMethod::Head
+    }
+
     /// Get color for HTTP method
     pub fn get_color(&self, cx: &App) -> gpui::Hsla {
         match self {
@@ -58,6 +127,7 @@ impl HttpMethod {
             HttpMethod::Patch => cx.theme().yellow,
             HttpMethod::Head => cx.theme().blue,
             HttpMethod::Options => cx.theme().cyan,
+            HttpMethod::Custom(_) => cx.theme().muted_foreground,
         }
     }
 
@@ -70,18 +140,21 @@ impl HttpMethod {
             HttpMethod::Patch => |cx| cx.theme().yellow,
             HttpMethod::Head => |cx| cx.theme().blue,
             HttpMethod::Options => |cx| cx.theme().cyan,
+            HttpMethod::Custom(_) => |cx| cx.theme().muted_foreground,
         }
     }
 }
 
 impl ContentType {
-    pub const ALL: [ContentType; 6] = [
+    pub const ALL: [ContentType; 8] = [
         ContentType::Json,
         ContentType::Xml,
         ContentType::Text,
         ContentType::Html,
         ContentType::Form,
         ContentType::UrlEncoded,
+        ContentType::GraphQL,
+        ContentType::File,
     ];
 
     pub fn from_header(content_type: &str) -> Self {
@@ -95,6 +168,8 @@ impl ContentType {
         } else if content_type.contains("text/plain") {
             ContentType::Text
         } else if content_type.contains("application/x-www-form-urlencoded") {
+            ContentType::UrlEncoded
+        } else if content_type.contains("multipart/form-data") {
             ContentType::Form
         } else {
             ContentType::Json // Default
@@ -107,8 +182,10 @@ impl ContentType {
             ContentType::Xml => "application/xml",
             ContentType::Text => "text/plain",
             ContentType::Html => "text/html",
-            ContentType::Form => "application/x-www-form-urlencoded",
+            ContentType::Form => "multipart/form-data",
             ContentType::UrlEncoded => "application/x-www-form-urlencoded",
+            ContentType::GraphQL => "application/json",
+            ContentType::File => "application/octet-stream",
         }
     }
 
@@ -118,8 +195,10 @@ impl ContentType {
             ContentType::Xml => "xml",
             ContentType::Text => "text",
             ContentType::Html => "html",
-            ContentType::Form => "form",
+            ContentType::Form => "multipart",
             ContentType::UrlEncoded => "form",
+            ContentType::GraphQL => "graphql",
+            ContentType::File => "file",
         }
     }
 
@@ -131,6 +210,8 @@ impl ContentType {
             ContentType::Html => "html",
             ContentType::Form => "text",
             ContentType::UrlEncoded => "text",
+            ContentType::GraphQL => "graphql",
+            ContentType::File => "text",
         }
     }
 }
@@ -146,6 +227,47 @@ impl SelectItem for ContentType {
             ContentType::Html => "HTML".into(),
             ContentType::Form => "Form Data".into(),
             ContentType::UrlEncoded => "URL Encoded".into(),
+            ContentType::GraphQL => "GraphQL".into(),
+            ContentType::File => "File".into(),
+        }
+    }
+
+    fn value(&self) -> &Self::Value {
+        self
+    }
+}
+
+impl RequestKind {
+    pub const ALL: [RequestKind; 3] =
+        [RequestKind::Http, RequestKind::WebSocket, RequestKind::Grpc];
+
+    /// Label of a request in the collection tree and tabs: its method, `WS`
+    /// for a WebSocket or `GRPC`
+    pub fn badge(&self, method: &HttpMethod) -> SharedString {
+        match self {
+            RequestKind::Http => method.as_str().to_string().into(),
+            RequestKind::WebSocket => "WS".into(),
+            RequestKind::Grpc => "GRPC".into(),
+        }
+    }
+
+    pub fn badge_color_fn(&self, method: &HttpMethod) -> fn(cx: &App) -> gpui::Hsla {
+        match self {
+            RequestKind::Http => method.get_color_fn(),
+            RequestKind::WebSocket => |cx| cx.theme().magenta,
+            RequestKind::Grpc => |cx| cx.theme().cyan,
+        }
+    }
+}
+
+impl SelectItem for RequestKind {
+    type Value = RequestKind;
+
+    fn title(&self) -> SharedString {
+        match self {
+            RequestKind::Http => "HTTP".into(),
+            RequestKind::WebSocket => "WS".into(),
+            RequestKind::Grpc => "gRPC".into(),
         }
     }
 
@@ -157,8 +279,12 @@ impl SelectItem for ContentType {
 impl SelectItem for HttpMethod {
     type Value = HttpMethod;
 
+    /// The custom method's entry is named in an input beside the select
     fn title(&self) -> SharedString {
-        self.as_str().into()
+        match self {
+            HttpMethod::Custom(_) => "CUSTOM".into(),
+            method => method.as_str().to_string().into(),
+        }
     }
 
     fn value(&self) -> &Self::Value {
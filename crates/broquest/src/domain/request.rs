@@ -1,9 +1,11 @@
 //! Core request and response data types
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
-use super::{AuthType, HttpMethod};
+use super::{AuthType, HttpMethod, HttpVersion};
 
 /// A key-value pair with an enabled flag, used for headers, query params, etc.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -48,40 +50,305 @@ impl KeyValuePair {
     }
 }
 
+/// What a request opens: a single HTTP exchange, a WebSocket connection or
+/// a gRPC call
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestKind {
+    #[default]
+    Http,
+    WebSocket,
+    Grpc,
+}
+
+impl RequestKind {
+    /// The `type` of the request in collection files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestKind::Http => "http",
+            RequestKind::WebSocket => "websocket",
+            RequestKind::Grpc => "grpc",
+        }
+    }
+
+    /// Read a collection file `type`, HTTP for anything but a WebSocket or
+    /// gRPC request
+    pub fn from_type(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("websocket") {
+            RequestKind::WebSocket
+        } else if value.eq_ignore_ascii_case("grpc") {
+            RequestKind::Grpc
+        } else {
+            RequestKind::Http
+        }
+    }
+}
+
+/// A saved message of a WebSocket request, ready to send from the composer
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MessageTemplate {
+    pub name: String,
+    pub body: String,
+}
+
+/// The method a gRPC request calls and where its descriptors come from. The
+/// request message is the JSON `body` and the metadata its `headers`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GrpcRequest {
+    /// Full method name, `package.Service/Method`
+    pub method: String,
+    /// `.proto` files describing the service, relative to the collection.
+    /// The server is asked by reflection when there are none.
+    #[serde(default)]
+    pub proto_files: Vec<String>,
+}
+
+/// A part of a `multipart/form-data` body
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MultipartPart {
+    pub name: String,
+    #[serde(default)]
+    pub kind: MultipartKind,
+    /// Text of a text part, or the path of a file part relative to the
+    /// collection
+    pub value: String,
+    /// Content-Type of the part; a file's is guessed from its extension
+    /// when empty
+    #[serde(default)]
+    pub content_type: String,
+    /// File name sent for the part in place of the file's own
+    #[serde(default)]
+    pub filename: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MultipartKind {
+    #[default]
+    Text,
+    File,
+}
+
+/// The two editors of a GraphQL request
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GraphQLRequest {
+    pub query: String,
+    /// JSON object of the operation's variables, kept as typed so
+    /// `{{placeholders}}` can stand in for whole values
+    pub variables: String,
+}
+
+impl GraphQLRequest {
+    /// The JSON body sent for the operation
+    pub fn body(&self) -> String {
+        let query = serde_json::Value::String(self.query.clone());
+        let variables = self.variables.trim();
+        let variables = if variables.is_empty() {
+            "{}"
+        } else {
+            variables
+        };
+        format!("{{\"query\":{query},\"variables\":{variables}}}")
+    }
+
+    /// Read the query and variables back from a JSON body
+    pub fn from_body(body: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(body).ok()?;
+        let query = value.get("query")?.as_str()?.to_string();
+        let variables = match value.get("variables") {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(variables) => serde_json::to_string_pretty(variables).unwrap_or_default(),
+        };
+        Some(Self { query, variables })
+    }
+}
+
 /// Request data for HTTP requests
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RequestData {
     pub name: String,
+    #[serde(default)]
+    pub kind: RequestKind,
     pub method: HttpMethod,
     pub url: String,
     pub path_params: Vec<KeyValuePair>,
     pub query_params: Vec<KeyValuePair>,
     pub headers: Vec<KeyValuePair>,
     pub body: String,
+    /// File streamed from disk as the body instead of `body`, relative to
+    /// the collection
+    #[serde(default)]
+    pub body_file: Option<String>,
+    /// Parts of a `multipart/form-data` body, sent instead of `body`
+    #[serde(default)]
+    pub multipart: Option<Vec<MultipartPart>>,
     pub pre_request_script: Option<String>,
     pub post_response_script: Option<String>,
+    /// Runs for every server-sent event of a `text/event-stream` response
+    #[serde(default)]
+    pub event_script: Option<String>,
     #[serde(default)]
     pub auth: AuthType,
     /// Request-level variables (Bruno `runtime.variables` / `bru.getRequestVar`),
     /// resolvable in this request via `{{name}}`.
     #[serde(default)]
     pub vars: Vec<KeyValuePair>,
+    /// Execution order within the request's group (`meta.seq` / OpenCollection
+    /// `info.seq`). `None` for requests that never had one persisted.
+    #[serde(default)]
+    pub seq: Option<u32>,
+    /// Overrides of the app's connection settings for this request.
+    #[serde(default)]
+    pub settings: RequestSettings,
+    /// Saved messages of a WebSocket request
+    #[serde(default)]
+    pub messages: Vec<MessageTemplate>,
+    /// Query and variables of a GraphQL request, sent as the JSON `body`
+    #[serde(default)]
+    pub graphql: Option<GraphQLRequest>,
+    /// Method and descriptor files of a gRPC request
+    #[serde(default)]
+    pub grpc: Option<GrpcRequest>,
+}
+
+/// Per-request overrides of the connection settings. `None` uses the app
+/// setting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RequestSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_redirects: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<u32>,
+    /// Keep the Authorization header when a redirect leaves the host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_auth_on_redirect: Option<bool>,
+    /// Ask for a stream of server-sent events and keep the connection open
+    /// without the request timeout
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub event_stream: bool,
+    /// HTTP version replacing the collection's for this request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<HttpVersion>,
+}
+
+impl RequestSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What a group (folder) gives the requests in it: variables, default
+/// headers, auth for requests inheriting theirs and scripts run around
+/// each request's own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolderSettings {
+    /// Resolvable via `{{name}}` between the request's and the collection's
+    /// variables, and in scripts via `bro.getFolderVar`
+    pub vars: Vec<KeyValuePair>,
+    /// Sent unless the request has an enabled header of the same name
+    pub headers: Vec<KeyValuePair>,
+    /// `Inherit` leaves requests to the collection's auth
+    pub auth: AuthType,
+    /// Runs before the request's own pre-request script
+    pub pre_request_script: Option<String>,
+    /// Runs after the request's own post-response script
+    pub post_response_script: Option<String>,
+}
+
+impl Default for FolderSettings {
+    fn default() -> Self {
+        Self {
+            vars: Vec::new(),
+            headers: Vec::new(),
+            auth: AuthType::Inherit,
+            pre_request_script: None,
+            post_response_script: None,
+        }
+    }
+}
+
+impl FolderSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Auth of a request in this folder whose auth is `Inherit`: the
+    /// folder's, or the collection's when the folder inherits too
+    pub fn inherited_auth(&self, collection_auth: Option<&AuthType>) -> AuthType {
+        match &self.auth {
+            AuthType::Inherit => collection_auth.cloned().unwrap_or_default(),
+            auth => auth.clone(),
+        }
+    }
+
+    /// Add the folder's enabled headers that `request` doesn't set itself
+    pub fn apply_headers(&self, request: &mut RequestData) {
+        add_default_headers(request, &self.headers);
+    }
+}
+
+/// What a collection gives all its requests besides auth and variables:
+/// default headers and scripts run around each request's and folder's own.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollectionDefaults {
+    /// Sent unless the request or its folder has an enabled header of the
+    /// same name
+    pub headers: Vec<KeyValuePair>,
+    /// Runs before the folder's and the request's pre-request scripts
+    pub pre_request_script: Option<String>,
+    /// Runs after the request's and the folder's post-response scripts
+    pub post_response_script: Option<String>,
+}
+
+impl CollectionDefaults {
+    /// Add the collection's enabled headers that `request` doesn't set itself
+    pub fn apply_headers(&self, request: &mut RequestData) {
+        add_default_headers(request, &self.headers);
+    }
+}
+
+/// Prepend the enabled `headers` whose names `request` has no enabled
+/// header for
+fn add_default_headers(request: &mut RequestData, headers: &[KeyValuePair]) {
+    let defaults: Vec<KeyValuePair> = headers
+        .iter()
+        .filter(|header| header.enabled && !header.key.is_empty())
+        .filter(|header| {
+            !request
+                .headers
+                .iter()
+                .any(|own| own.enabled && own.key.eq_ignore_ascii_case(&header.key))
+        })
+        .cloned()
+        .collect();
+    request.headers.splice(0..0, defaults);
 }
 
 impl Default for RequestData {
     fn default() -> Self {
         Self {
             name: "New Request".to_string(),
+            kind: RequestKind::Http,
             method: HttpMethod::Get,
             url: String::new(),
             path_params: Vec::new(),
             query_params: Vec::new(),
             headers: Vec::new(),
             body: String::new(),
+            body_file: None,
+            multipart: None,
             pre_request_script: None,
             post_response_script: None,
+            event_script: None,
             auth: AuthType::None,
             vars: Vec::new(),
+            seq: None,
+            settings: RequestSettings::default(),
+            messages: Vec::new(),
+            graphql: None,
+            grpc: None,
         }
     }
 }
@@ -97,5 +364,170 @@ pub struct ResponseData {
     pub request_headers: Vec<KeyValuePair>,
     pub body: String,
     pub body_bytes: Option<Vec<u8>>,
+    /// Where the body was saved instead of being kept in memory, for bodies
+    /// over the memory limit. `body` then only describes it.
+    pub body_file: Option<BodyFile>,
     pub url: Option<String>,
+    /// Protocol of the final response, e.g. `HTTP/2.0`
+    pub http_version: Option<String>,
+    /// Redirects followed before the final response, in order
+    pub redirects: Vec<RedirectHop>,
+    pub timings: ResponseTimings,
+    /// Server-sent events of a `text/event-stream` response, in order
+    pub events: Vec<ServerEvent>,
+    /// Status and trailers of a gRPC call
+    pub grpc: Option<GrpcStatus>,
+}
+
+/// A response body saved to a temporary file. The file is removed once the
+/// last response referring to it is dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyFile(Arc<TempFile>);
+
+#[derive(Debug, PartialEq)]
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+impl BodyFile {
+    /// Take ownership of the file at `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self(Arc::new(TempFile(path)))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0.0
+    }
+}
+
+/// How a gRPC call ended
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GrpcStatus {
+    pub code: i32,
+    pub message: String,
+    /// Metadata the server sent after the messages
+    pub trailers: Vec<KeyValuePair>,
+}
+
+impl GrpcStatus {
+    /// Name of the status code, as in `grpc-status` docs
+    pub fn name(&self) -> &'static str {
+        match self.code {
+            0 => "OK",
+            1 => "CANCELLED",
+            2 => "UNKNOWN",
+            3 => "INVALID_ARGUMENT",
+            4 => "DEADLINE_EXCEEDED",
+            5 => "NOT_FOUND",
+            6 => "ALREADY_EXISTS",
+            7 => "PERMISSION_DENIED",
+            8 => "RESOURCE_EXHAUSTED",
+            9 => "FAILED_PRECONDITION",
+            10 => "ABORTED",
+            11 => "OUT_OF_RANGE",
+            12 => "UNIMPLEMENTED",
+            13 => "INTERNAL",
+            14 => "UNAVAILABLE",
+            15 => "DATA_LOSS",
+            16 => "UNAUTHENTICATED",
+            _ => "UNKNOWN",
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.code == 0
+    }
+}
+
+/// An event of a `text/event-stream` response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerEvent {
+    /// The `id` field, or the last one sent before
+    pub id: Option<String>,
+    /// The `event` field, `message` when not given
+    pub event: String,
+    pub data: String,
+    /// Reconnection time the server asked for, in milliseconds
+    pub retry: Option<u64>,
+    /// When the event arrived, counted from the response headers
+    pub received_after: Duration,
+}
+
+/// Which way a WebSocket frame went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDirection {
+    Sent,
+    Received,
+}
+
+/// A frame of a WebSocket connection
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebSocketFrame {
+    pub direction: FrameDirection,
+    pub kind: FrameKind,
+    /// Text of text frames, a description of the others
+    pub data: String,
+    pub at: chrono::DateTime<chrono::Local>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    Text,
+    Binary,
+    Ping,
+    Pong,
+    Close,
+    /// The connection failed; `data` holds the error
+    Error,
+}
+
+/// Where the latency of a request went. The phases follow each other and add
+/// up to the latency.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResponseTimings {
+    /// Scripts and auth before the request was sent
+    pub prepare: Duration,
+    /// Following redirects to the final URL
+    pub redirect: Duration,
+    /// Host name lookup, `None` when no new connection was opened
+    pub dns: Option<Duration>,
+    /// TCP connect. `None` when an open connection was reused. Includes the
+    /// TLS handshake when it couldn't be told apart.
+    pub connect: Option<Duration>,
+    /// Estimated TLS handshake of a new HTTPS connection. `None` for plain
+    /// HTTP and when it couldn't be told from the TCP connect, e.g. through
+    /// a proxy.
+    pub tls: Option<Duration>,
+    /// From sending the request to receiving the response headers
+    pub ttfb: Duration,
+    /// Reading the response body
+    pub download: Duration,
+}
+
+impl ResponseTimings {
+    pub fn total(&self) -> Duration {
+        self.prepare
+            + self.redirect
+            + self.dns.unwrap_or_default()
+            + self.connect.unwrap_or_default()
+            + self.tls.unwrap_or_default()
+            + self.ttfb
+            + self.download
+    }
+}
+
+/// A redirect response received on the way to the final response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RedirectHop {
+    /// The URL that answered with the redirect
+    pub url: String,
+    pub status_code: u16,
+    pub status_text: Option<String>,
+    /// The absolute URL the redirect points to
+    pub location: String,
+    pub headers: Vec<KeyValuePair>,
 }
@@ -0,0 +1,240 @@
+//! Dynamic `{{$name}}` variables, generated anew each time they're resolved:
+//! ids, timestamps, random numbers and fake data, named as in Postman and
+//! Bruno collections.
+
+use chrono::{Duration, SecondsFormat, Utc};
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada",
+    "Alan",
+    "Grace",
+    "Linus",
+    "Margaret",
+    "Dennis",
+    "Barbara",
+    "Ken",
+    "Radia",
+    "Edsger",
+    "Frances",
+    "John",
+    "Katherine",
+    "Tim",
+    "Hedy",
+    "Guido",
+];
+const LAST_NAMES: &[&str] = &[
+    "Lovelace",
+    "Turing",
+    "Hopper",
+    "Torvalds",
+    "Hamilton",
+    "Ritchie",
+    "Liskov",
+    "Thompson",
+    "Perlman",
+    "Dijkstra",
+    "Allen",
+    "McCarthy",
+    "Johnson",
+    "Berners-Lee",
+    "Lamarr",
+    "van Rossum",
+];
+const CITIES: &[&str] = &[
+    "Amsterdam",
+    "Berlin",
+    "Lisbon",
+    "Nairobi",
+    "Osaka",
+    "Toronto",
+    "Santiago",
+    "Melbourne",
+    "Oslo",
+    "Seoul",
+];
+const COUNTRIES: &[&str] = &[
+    "Netherlands",
+    "Germany",
+    "Portugal",
+    "Kenya",
+    "Japan",
+    "Canada",
+    "Chile",
+    "Australia",
+    "Norway",
+    "South Korea",
+];
+const COMPANY_SUFFIXES: &[&str] = &["Inc", "LLC", "Group", "Labs", "Systems", "and Sons"];
+const JOB_TITLES: &[&str] = &[
+    "Software Engineer",
+    "Product Manager",
+    "Data Analyst",
+    "Designer",
+    "Site Reliability Engineer",
+    "Support Specialist",
+];
+const COLORS: &[&str] = &[
+    "red", "orange", "yellow", "green", "blue", "indigo", "violet", "teal", "black", "white",
+];
+const WORDS: &[&str] = &[
+    "alpha", "bridge", "cloud", "delta", "ember", "forest", "granite", "harbor", "island", "jade",
+    "kernel", "lantern", "meadow", "nebula", "orbit", "pixel", "quartz", "river", "signal",
+    "timber",
+];
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+/// The value of the dynamic variable `expression`, the placeholder without
+/// its `$`, e.g. `guid` or `timestamp +1d`. `None` for unknown names.
+///
+/// Timestamps take offsets in seconds, minutes, hours, days or weeks, e.g.
+/// `{{$isoTimestamp +1d -2h}}`.
+pub fn dynamic_value(expression: &str) -> Option<String> {
+    let mut terms = expression.split_whitespace();
+    let name = terms.next()?;
+    let offset = terms.try_fold(Duration::zero(), |total, term| {
+        Some(total + parse_offset(term)?)
+    });
+
+    let mut rng = rand::rng();
+    let mut pick = |values: &[&str]| values.choose(&mut rng).copied().unwrap_or_default();
+    let first_name = pick(FIRST_NAMES);
+    let last_name = pick(LAST_NAMES);
+
+    let value = match name {
+        "guid" | "uuid" | "randomUUID" => uuid::Uuid::new_v4().to_string(),
+        "timestamp" => (Utc::now() + offset?).timestamp().to_string(),
+        "isoTimestamp" => (Utc::now() + offset?).to_rfc3339_opts(SecondsFormat::Millis, true),
+        "randomInt" => rand::rng().random_range(0..=1000).to_string(),
+        "randomBoolean" => rand::rng().random_bool(0.5).to_string(),
+        "randomFirstName" => first_name.to_string(),
+        "randomLastName" => last_name.to_string(),
+        "randomFullName" => format!("{} {}", first_name, last_name),
+        "randomUserName" => format!(
+            "{}.{}{}",
+            first_name.to_lowercase(),
+            last_name.to_lowercase().replace(' ', ""),
+            rand::rng().random_range(1..100)
+        ),
+        "randomEmail" | "randomExampleEmail" => format!(
+            "{}.{}@{}",
+            first_name.to_lowercase(),
+            last_name.to_lowercase().replace(' ', ""),
+            pick(DOMAINS)
+        ),
+        "randomPhoneNumber" => {
+            let mut rng = rand::rng();
+            format!(
+                "{}-{}-{}",
+                rng.random_range(200..1000),
+                rng.random_range(200..1000),
+                rng.random_range(1000..10000)
+            )
+        }
+        "randomCity" => pick(CITIES).to_string(),
+        "randomCountry" => pick(COUNTRIES).to_string(),
+        "randomCompanyName" => format!("{} {}", last_name, pick(COMPANY_SUFFIXES)),
+        "randomJobTitle" => pick(JOB_TITLES).to_string(),
+        "randomColor" => pick(COLORS).to_string(),
+        "randomHexColor" => format!("#{:06x}", rand::rng().random_range(0..0x100_0000)),
+        "randomWord" => pick(WORDS).to_string(),
+        "randomWords" => (0..3).map(|_| pick(WORDS)).collect::<Vec<_>>().join(" "),
+        "randomAlphaNumeric" => {
+            let mut rng = rand::rng();
+            (rng.sample(rand::distr::Alphanumeric) as char)
+                .to_ascii_lowercase()
+                .to_string()
+        }
+        "randomIP" => {
+            let mut rng = rand::rng();
+            format!(
+                "{}.{}.{}.{}",
+                rng.random_range(1..255),
+                rng.random_range(0..256),
+                rng.random_range(0..256),
+                rng.random_range(1..255)
+            )
+        }
+        "randomUrl" => format!("https://{}.{}", pick(WORDS), pick(DOMAINS)),
+        "randomPrice" => format!("{:.2}", rand::rng().random_range(1.0..1000.0)),
+        "randomDateFuture" => (Utc::now() + Duration::days(rand::rng().random_range(1..=365)))
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+        "randomDatePast" => (Utc::now() - Duration::days(rand::rng().random_range(1..=365)))
+            .to_rfc3339_opts(SecondsFormat::Secs, true),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// An offset like `+1d`, `-2h` or `30m`
+fn parse_offset(term: &str) -> Option<Duration> {
+    let (sign, term) = match term.strip_prefix('-') {
+        Some(term) => (-1, term),
+        None => (1, term.strip_prefix('+').unwrap_or(term)),
+    };
+    let unit_at = term.find(|c: char| !c.is_ascii_digit())?;
+    let amount: i64 = term[..unit_at].parse().ok()?;
+    let amount = sign * amount;
+    match &term[unit_at..] {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_unique() {
+        let first = dynamic_value("guid").expect("guid");
+        assert!(uuid::Uuid::parse_str(&first).is_ok());
+        assert_ne!(first, dynamic_value("uuid").expect("uuid"));
+    }
+
+    #[test]
+    fn test_timestamp_offsets() {
+        let now = Utc::now().timestamp();
+        let timestamp: i64 = dynamic_value("timestamp")
+            .expect("timestamp")
+            .parse()
+            .unwrap();
+        assert!((timestamp - now).abs() <= 1);
+
+        let tomorrow: i64 = dynamic_value("timestamp +1d")
+            .expect("offset")
+            .parse()
+            .unwrap();
+        assert!((tomorrow - now - 86_400).abs() <= 1);
+        let earlier: i64 = dynamic_value("timestamp -2h +30m")
+            .expect("offsets")
+            .parse()
+            .unwrap();
+        assert!((earlier - now + 5_400).abs() <= 1);
+
+        let iso = dynamic_value("isoTimestamp").expect("isoTimestamp");
+        assert!(chrono::DateTime::parse_from_rfc3339(&iso).is_ok());
+
+        // A malformed offset doesn't resolve rather than being ignored
+        assert_eq!(dynamic_value("timestamp +1fortnight"), None);
+    }
+
+    #[test]
+    fn test_fake_data() {
+        let int: u32 = dynamic_value("randomInt")
+            .expect("randomInt")
+            .parse()
+            .unwrap();
+        assert!(int <= 1000);
+        let email = dynamic_value("randomEmail").expect("randomEmail");
+        assert!(email.contains('@'));
+        let name = dynamic_value("randomFullName").expect("randomFullName");
+        assert!(name.contains(' '));
+        assert_eq!(dynamic_value("notAVariable"), None);
+    }
+}
@@ -1,5 +1,6 @@
 //! Environment handling module
 
+mod dynamic;
 mod editor;
 mod resolver;
 
@@ -1,5 +1,9 @@
+use super::dynamic::dynamic_value;
 use crate::collections::{EnvironmentToml, EnvironmentVariable};
-use crate::domain::{BasicAuth, DigestAuth, JwtAuth, KeyAuth, OAuth2Auth, RequestData};
+use crate::domain::{
+    AuthType, BasicAuth, DigestAuth, JwtAuth, KeyAuth, KeyValuePair, OAuth2Auth, RequestData,
+};
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 /// Environment variable resolver for HTTP requests
@@ -17,8 +21,12 @@ impl EnvironmentResolver {
     ///
     /// Variables are in the format `{{variable_name}}`. Resolution precedence
     /// (highest to lowest) mirrors Bruno: **runtime > collection > environment >
-    /// secret**. Each placeholder is scanned in a single pass and resolved
+    /// secret**; data-driven runs layer their iteration row on top (see
+    /// [`Self::resolve_request_data`]). Each placeholder is scanned in a single pass and resolved
     /// against the highest-precedence bucket that contains its name.
+    ///
+    /// Dynamic `{{$name}}` variables are generated anew for each placeholder
+    /// (see [`dynamic_value`]). Unknown names keep their placeholder.
     pub fn resolve_string(
         &self,
         input: &str,
@@ -28,9 +36,8 @@ impl EnvironmentResolver {
         secrets: &HashMap<String, String>,
     ) -> String {
         resolve_placeholders(input, |name| {
-            if name.starts_with('$') {
-                // Dynamic {{$...}} variables are left untouched.
-                return None;
+            if let Some(expression) = name.strip_prefix('$') {
+                return dynamic_value(expression);
             }
             runtime_vars
                 .get(name)
@@ -87,15 +94,30 @@ impl EnvironmentResolver {
         Ok((variables, secrets))
     }
 
-    /// Resolve all variables in a request data
+    /// Resolve all variables in a request data.
+    ///
+    /// `iteration_data` holds the current row of a data-driven run and takes
+    /// precedence over every other tier.
     pub fn resolve_request_data(
         &self,
         mut request_data: RequestData,
+        iteration_data: &HashMap<String, String>,
         runtime_vars: &HashMap<String, String>,
         collection_vars: &HashMap<String, String>,
         variables: &HashMap<String, String>,
         secrets: &HashMap<String, String>,
     ) -> RequestData {
+        // Layer the iteration row over the runtime tier, the highest of the rest.
+        let layered_runtime;
+        let runtime_vars = if iteration_data.is_empty() {
+            runtime_vars
+        } else {
+            let mut layered = runtime_vars.clone();
+            layered.extend(iteration_data.clone());
+            layered_runtime = layered;
+            &layered_runtime
+        };
+
         // Resolve URL
         request_data.url = self.resolve_string(
             &request_data.url,
@@ -173,6 +195,22 @@ impl EnvironmentResolver {
             variables,
             secrets,
         );
+        request_data.body_file = request_data.body_file.map(|file| {
+            self.resolve_string(&file, runtime_vars, collection_vars, variables, secrets)
+        });
+        for part in request_data.multipart.iter_mut().flatten() {
+            if part.enabled {
+                for field in [&mut part.name, &mut part.value, &mut part.filename] {
+                    *field = self.resolve_string(
+                        field,
+                        runtime_vars,
+                        collection_vars,
+                        variables,
+                        secrets,
+                    );
+                }
+            }
+        }
 
         // Resolve auth
         request_data.auth = self.resolve_auth(
@@ -189,14 +227,12 @@ impl EnvironmentResolver {
     /// Resolve variables in auth configuration
     pub fn resolve_auth(
         &self,
-        auth: &crate::domain::AuthType,
+        auth: &AuthType,
         runtime_vars: &HashMap<String, String>,
         collection_vars: &HashMap<String, String>,
         variables: &HashMap<String, String>,
         secrets: &HashMap<String, String>,
-    ) -> crate::domain::AuthType {
-        use crate::domain::AuthType;
-
+    ) -> AuthType {
         match auth {
             AuthType::None | AuthType::Inherit | AuthType::Unsupported { .. } => auth.clone(),
             AuthType::Basic(basic) => AuthType::Basic(BasicAuth {
@@ -279,6 +315,45 @@ impl EnvironmentResolver {
                 redirect_url: oauth.redirect_url.as_ref().map(|s| {
                     self.resolve_string(s, runtime_vars, collection_vars, variables, secrets)
                 }),
+                username: self.resolve_string(
+                    &oauth.username,
+                    runtime_vars,
+                    collection_vars,
+                    variables,
+                    secrets,
+                ),
+                password: self.resolve_string(
+                    &oauth.password,
+                    runtime_vars,
+                    collection_vars,
+                    variables,
+                    secrets,
+                ),
+                pkce: oauth.pkce,
+                device_authorization_url: oauth.device_authorization_url.as_ref().map(|s| {
+                    self.resolve_string(s, runtime_vars, collection_vars, variables, secrets)
+                }),
+                audience: oauth.audience.as_ref().map(|s| {
+                    self.resolve_string(s, runtime_vars, collection_vars, variables, secrets)
+                }),
+                resource: oauth.resource.as_ref().map(|s| {
+                    self.resolve_string(s, runtime_vars, collection_vars, variables, secrets)
+                }),
+                extra_params: oauth
+                    .extra_params
+                    .iter()
+                    .map(|p| KeyValuePair {
+                        key: p.key.clone(),
+                        value: self.resolve_string(
+                            &p.value,
+                            runtime_vars,
+                            collection_vars,
+                            variables,
+                            secrets,
+                        ),
+                        enabled: p.enabled,
+                    })
+                    .collect(),
                 access_token: oauth.access_token.as_ref().map(|s| {
                     self.resolve_string(s, runtime_vars, collection_vars, variables, secrets)
                 }),
@@ -322,6 +397,58 @@ impl EnvironmentResolver {
             }),
         }
     }
+
+    /// Report where each `{{name}}` placeholder of `request_data` resolves
+    /// from, following the precedence of [`Self::resolve_string`] with the
+    /// request's and then its folder's variables between the runtime and
+    /// collection tiers.
+    /// Placeholders nothing resolves are reported without a source.
+    pub fn inspect_request(
+        &self,
+        request_data: &RequestData,
+        tiers: &VariableTiers,
+    ) -> VariableReport {
+        let enabled_pairs = |pairs: &[KeyValuePair]| {
+            pairs
+                .iter()
+                .filter(|pair| pair.enabled)
+                .flat_map(|pair| [pair.key.clone(), pair.value.clone()])
+                .collect::<Vec<_>>()
+        };
+
+        let mut url = vec![request_data.url.clone()];
+        url.extend(enabled_pairs(&request_data.query_params));
+        url.extend(enabled_pairs(&request_data.path_params));
+
+        let mut other = vec![request_data.body.clone()];
+        other.extend(request_data.body_file.clone());
+        for part in request_data.multipart.iter().flatten() {
+            if part.enabled {
+                other.extend([part.name.clone(), part.value.clone(), part.filename.clone()]);
+            }
+        }
+        other.extend(
+            auth_templates(&request_data.auth)
+                .into_iter()
+                .map(String::from),
+        );
+
+        let inspect = |templates: &[String]| {
+            let mut statuses: Vec<PlaceholderStatus> = Vec::new();
+            for name in templates.iter().flat_map(|t| placeholder_names(t)) {
+                if !statuses.iter().any(|status| status.name == name) {
+                    statuses.push(tiers.status(name));
+                }
+            }
+            statuses
+        };
+
+        VariableReport {
+            url: inspect(&url),
+            headers: inspect(&enabled_pairs(&request_data.headers)),
+            other: inspect(&other),
+        }
+    }
 }
 
 impl Default for EnvironmentResolver {
@@ -330,6 +457,172 @@ impl Default for EnvironmentResolver {
     }
 }
 
+/// The tier a `{{name}}` placeholder takes its value from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableSource {
+    Runtime,
+    Request,
+    /// The variables of the request's group
+    Folder,
+    Collection,
+    Environment,
+    Secret,
+    /// A generated `{{$name}}` variable
+    Dynamic,
+}
+
+impl VariableSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            VariableSource::Runtime => "runtime",
+            VariableSource::Request => "request",
+            VariableSource::Folder => "folder",
+            VariableSource::Collection => "collection",
+            VariableSource::Environment => "environment",
+            VariableSource::Secret => "secret",
+            VariableSource::Dynamic => "dynamic",
+        }
+    }
+}
+
+/// The variables a request is resolved against, tier by tier
+#[derive(Debug, Clone, Default)]
+pub struct VariableTiers {
+    pub runtime: HashMap<String, String>,
+    pub request: HashMap<String, String>,
+    pub folder: HashMap<String, String>,
+    pub collection: HashMap<String, String>,
+    pub environment: HashMap<String, String>,
+    /// Only the names are needed; the values are never reported
+    pub secrets: HashMap<String, String>,
+}
+
+impl VariableTiers {
+    fn status(&self, name: String) -> PlaceholderStatus {
+        let (source, value) = if let Some(expression) = name.strip_prefix('$') {
+            let source = dynamic_value(expression).map(|_| VariableSource::Dynamic);
+            (source, None)
+        } else if let Some(value) = self.runtime.get(&name) {
+            (Some(VariableSource::Runtime), Some(value.clone()))
+        } else if let Some(value) = self.request.get(&name) {
+            (Some(VariableSource::Request), Some(value.clone()))
+        } else if let Some(value) = self.folder.get(&name) {
+            (Some(VariableSource::Folder), Some(value.clone()))
+        } else if let Some(value) = self.collection.get(&name) {
+            (Some(VariableSource::Collection), Some(value.clone()))
+        } else if let Some(value) = self.environment.get(&name) {
+            (Some(VariableSource::Environment), Some(value.clone()))
+        } else if self.secrets.contains_key(&name) {
+            (Some(VariableSource::Secret), None)
+        } else {
+            (None, None)
+        };
+        PlaceholderStatus {
+            name,
+            source,
+            value,
+        }
+    }
+}
+
+/// A `{{name}}` placeholder and where it resolves from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaceholderStatus {
+    pub name: String,
+    /// `None` if nothing resolves it and it's sent verbatim
+    pub source: Option<VariableSource>,
+    /// The value it resolves to, hidden for secrets and dynamic variables
+    pub value: Option<String>,
+}
+
+/// The placeholders of a request, grouped by where they appear
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VariableReport {
+    /// URL, query and path parameters
+    pub url: Vec<PlaceholderStatus>,
+    pub headers: Vec<PlaceholderStatus>,
+    /// Body and auth
+    pub other: Vec<PlaceholderStatus>,
+}
+
+impl VariableReport {
+    /// Names of the placeholders nothing resolves, each once
+    pub fn unresolved(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for status in self.url.iter().chain(&self.headers).chain(&self.other) {
+            if status.source.is_none() && !names.contains(&status.name.as_str()) {
+                names.push(&status.name);
+            }
+        }
+        names
+    }
+}
+
+/// Whether `value` is a single `{{ name }}` reference, the only form in which
+/// collection files keep passwords
+pub fn is_variable_reference(value: &str) -> bool {
+    let value = value.trim();
+    value.starts_with("{{") && value.ends_with("}}") && placeholder_names(value).len() == 1
+}
+
+/// Names of the `{{ name }}` placeholders in `input`, in order
+pub fn placeholder_names(input: &str) -> Vec<String> {
+    let names = RefCell::new(Vec::new());
+    resolve_placeholders(input, |name| {
+        names.borrow_mut().push(name.to_string());
+        None
+    });
+    names.into_inner()
+}
+
+/// The templated fields of an auth configuration, as resolved by
+/// [`EnvironmentResolver::resolve_auth`]
+fn auth_templates(auth: &AuthType) -> Vec<&str> {
+    match auth {
+        AuthType::None | AuthType::Inherit | AuthType::Unsupported { .. } => Vec::new(),
+        AuthType::Basic(basic) => vec![&basic.username, &basic.password],
+        AuthType::Digest(digest) => vec![&digest.username, &digest.password],
+        AuthType::Key(key) => vec![&key.header, &key.value],
+        AuthType::OAuth2(oauth) => {
+            let mut fields = vec![
+                oauth.client_id.as_str(),
+                &oauth.client_secret,
+                &oauth.token_url,
+                &oauth.username,
+                &oauth.password,
+            ];
+            fields.extend(
+                [
+                    &oauth.scope,
+                    &oauth.authorize_url,
+                    &oauth.redirect_url,
+                    &oauth.device_authorization_url,
+                    &oauth.audience,
+                    &oauth.resource,
+                    &oauth.access_token,
+                    &oauth.refresh_token,
+                ]
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+            );
+            fields.extend(
+                oauth
+                    .extra_params
+                    .iter()
+                    .filter(|p| p.enabled)
+                    .map(|p| p.value.as_str()),
+            );
+            fields
+        }
+        AuthType::Jwt(jwt) => {
+            let mut fields = vec![jwt.login_url.as_str(), &jwt.username, &jwt.password];
+            fields.extend(jwt.access_token.as_deref());
+            fields
+        }
+    }
+}
+
 /// Scan `input` for `{{ name }}` placeholders and replace each with the value
 /// returned by `resolve(name)`, preserving the original placeholder text when
 /// the resolver returns `None`. Whitespace inside the braces is trimmed. This
@@ -418,10 +711,17 @@ mod tests {
         let s = resolver.resolve_string("a-{{missing}}-b", &empty, &empty, &empty, &empty);
         assert_eq!(s, "a-{{missing}}-b");
 
-        // Dynamic {{$...}} vars are left untouched even if they share a name.
+        // Dynamic {{$...}} vars are generated, even if a variable shares the
+        // name, and unknown ones keep their placeholder too.
         let env = map(&[("guid", "should-not-win")]);
         let s = resolver.resolve_string("{{$guid}}", &empty, &empty, &env, &empty);
-        assert_eq!(s, "{{$guid}}");
+        assert_eq!(s.len(), 36);
+        assert_ne!(
+            s,
+            resolver.resolve_string("{{$guid}}", &empty, &empty, &env, &empty)
+        );
+        let s = resolver.resolve_string("{{ $notDynamic }}", &empty, &empty, &empty, &empty);
+        assert_eq!(s, "{{ $notDynamic }}");
     }
 
     #[test]
@@ -441,4 +741,150 @@ mod tests {
         );
         assert_eq!(s, "1/2/3/12");
     }
+
+    #[test]
+    fn test_resolve_request_data_iteration_data_wins() {
+        let resolver = EnvironmentResolver::new();
+        let iteration = map(&[("user", "row-user")]);
+        let runtime = map(&[("user", "runtime-user"), ("host", "api.example.com")]);
+        let empty = HashMap::new();
+        let request = RequestData {
+            url: "https://{{host}}/users/{{user}}".to_string(),
+            body: "{{user}}".to_string(),
+            ..Default::default()
+        };
+
+        let resolved =
+            resolver.resolve_request_data(request, &iteration, &runtime, &empty, &empty, &empty);
+        assert_eq!(resolved.url, "https://api.example.com/users/row-user");
+        assert_eq!(resolved.body, "row-user");
+    }
+
+    #[test]
+    fn test_resolve_request_data_dynamic_variables() {
+        let resolver = EnvironmentResolver::new();
+        let empty = HashMap::new();
+        let request = RequestData {
+            url: "https://api.example.com/orders?since={{$timestamp -1d}}".to_string(),
+            headers: vec![KeyValuePair {
+                key: "X-Request-Id".to_string(),
+                value: "{{$guid}}".to_string(),
+                enabled: true,
+            }],
+            body: r#"{"email": "{{$randomEmail}}"}"#.to_string(),
+            auth: AuthType::Basic(BasicAuth {
+                username: "{{$randomUserName}}".to_string(),
+                password: "secret".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        let resolved =
+            resolver.resolve_request_data(request, &empty, &empty, &empty, &empty, &empty);
+        assert!(!resolved.url.contains("{{"));
+        assert_eq!(resolved.headers[0].value.len(), 36);
+        assert!(resolved.body.contains('@'));
+        let AuthType::Basic(basic) = resolved.auth else {
+            panic!("basic auth");
+        };
+        assert!(!basic.username.contains("{{"));
+    }
+
+    #[test]
+    fn test_placeholder_names() {
+        assert_eq!(
+            placeholder_names("{{host}}/users/{{ id }}?t={{$timestamp +1d}}&open={{"),
+            vec!["host", "id", "$timestamp +1d"]
+        );
+        assert!(placeholder_names("no placeholders").is_empty());
+    }
+
+    #[test]
+    fn test_inspect_request_reports_sources() {
+        let resolver = EnvironmentResolver::new();
+        let tiers = VariableTiers {
+            runtime: map(&[("token", "from-login")]),
+            request: map(&[("id", "42"), ("token", "request-token")]),
+            folder: map(&[("version", "v3"), ("id", "9")]),
+            collection: map(&[("host", "api.example.com"), ("id", "7")]),
+            environment: map(&[("host", "staging.example.com"), ("version", "v2")]),
+            secrets: map(&[("password", "")]),
+        };
+        let request = RequestData {
+            url: "https://{{host}}/{{version}}/tenants/{{tenantId}}/users/{{id}}".to_string(),
+            headers: vec![
+                KeyValuePair {
+                    key: "Authorization".to_string(),
+                    value: "Bearer {{token}}".to_string(),
+                    enabled: true,
+                },
+                KeyValuePair {
+                    key: "X-Disabled".to_string(),
+                    value: "{{disabled}}".to_string(),
+                    enabled: false,
+                },
+                KeyValuePair {
+                    key: "X-Request-Id".to_string(),
+                    value: "{{$guid}}".to_string(),
+                    enabled: true,
+                },
+            ],
+            body: "{{tenantId}} {{$notDynamic}}".to_string(),
+            auth: AuthType::Basic(BasicAuth {
+                username: "admin".to_string(),
+                password: "{{password}}".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        let report = resolver.inspect_request(&request, &tiers);
+        let sources = |statuses: &[PlaceholderStatus]| {
+            statuses
+                .iter()
+                .map(|status| (status.name.clone(), status.source, status.value.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sources(&report.url),
+            vec![
+                (
+                    "host".to_string(),
+                    Some(VariableSource::Collection),
+                    Some("api.example.com".to_string())
+                ),
+                (
+                    "version".to_string(),
+                    Some(VariableSource::Folder),
+                    Some("v3".to_string())
+                ),
+                ("tenantId".to_string(), None, None),
+                (
+                    "id".to_string(),
+                    Some(VariableSource::Request),
+                    Some("42".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            sources(&report.headers),
+            vec![
+                (
+                    "token".to_string(),
+                    Some(VariableSource::Runtime),
+                    Some("from-login".to_string())
+                ),
+                ("$guid".to_string(), Some(VariableSource::Dynamic), None),
+            ]
+        );
+        // Secret values are never reported
+        assert_eq!(
+            sources(&report.other),
+            vec![
+                ("tenantId".to_string(), None, None),
+                ("$notDynamic".to_string(), None, None),
+                ("password".to_string(), Some(VariableSource::Secret), None),
+            ]
+        );
+        assert_eq!(report.unresolved(), vec!["tenantId", "$notDynamic"]);
+    }
 }
@@ -0,0 +1,162 @@
+use anyhow::Result;
+use gpui::{Context, Task, Window};
+use gpui_component::{RopeExt, input::CompletionProvider};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionItemTag, CompletionResponse};
+use ropey::Rope;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::Schema;
+use super::query::{CursorContext, cursor_context};
+use crate::scripting::completion_item;
+
+const KEYWORDS: [(&str, &str); 4] = [
+    ("query", "Read data"),
+    ("mutation", "Change data"),
+    ("subscription", "Receive data as it changes"),
+    ("fragment", "Fields to reuse across selections"),
+];
+
+/// Completion provider of the GraphQL query editor, suggesting the fields
+/// and arguments of the fetched schema
+#[derive(Default)]
+pub struct GraphQLCompletionProvider {
+    schema: RefCell<Option<Arc<Schema>>>,
+}
+
+impl GraphQLCompletionProvider {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Self::default())
+    }
+
+    pub fn set_schema(&self, schema: Option<Arc<Schema>>) {
+        *self.schema.borrow_mut() = schema;
+    }
+
+    fn items(
+        schema: &Schema,
+        context: CursorContext,
+        start: lsp_types::Position,
+        end: lsp_types::Position,
+    ) -> Vec<CompletionItem> {
+        match context {
+            CursorContext::Definition => KEYWORDS
+                .iter()
+                .map(|(keyword, documentation)| {
+                    completion_item(
+                        keyword,
+                        CompletionItemKind::KEYWORD,
+                        "keyword",
+                        Some(documentation.to_string()),
+                        start,
+                        end,
+                    )
+                })
+                .collect(),
+            CursorContext::Selection(type_name) => {
+                let Some(ty) = schema.get(&type_name) else {
+                    return Vec::new();
+                };
+                ty.fields
+                    .iter()
+                    .map(|field| {
+                        let mut item = completion_item(
+                            &field.name,
+                            CompletionItemKind::FIELD,
+                            field.ty.to_string(),
+                            field.description.clone(),
+                            start,
+                            end,
+                        );
+                        if field.deprecated {
+                            item.tags = Some(vec![CompletionItemTag::DEPRECATED]);
+                        }
+                        item
+                    })
+                    .chain(std::iter::once(completion_item(
+                        "__typename",
+                        CompletionItemKind::FIELD,
+                        "String!",
+                        Some(format!("Name of the object type, `{type_name}`")),
+                        start,
+                        end,
+                    )))
+                    .collect()
+            }
+            CursorContext::Arguments { type_name, field } => schema
+                .field(&type_name, &field)
+                .map(|field| {
+                    field
+                        .args
+                        .iter()
+                        .map(|arg| {
+                            completion_item(
+                                &arg.name,
+                                CompletionItemKind::VARIABLE,
+                                arg.ty.to_string(),
+                                arg.description.clone(),
+                                start,
+                                end,
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            CursorContext::Unknown => Vec::new(),
+        }
+    }
+}
+
+impl CompletionProvider for GraphQLCompletionProvider {
+    fn completions(
+        &self,
+        rope: &Rope,
+        offset: usize,
+        _: lsp_types::CompletionContext,
+        _: &mut Window,
+        _cx: &mut Context<gpui_component::input::InputState>,
+    ) -> Task<Result<CompletionResponse>> {
+        let Some(schema) = self.schema.borrow().clone() else {
+            return Task::ready(Ok(CompletionResponse::Array(Vec::new())));
+        };
+
+        // Scan backwards to the start of the name being typed
+        let mut start = offset;
+        while start > 0 {
+            match rope.char_at(start - 1) {
+                Some(c) if c.is_ascii_alphanumeric() || c == '_' => start -= 1,
+                _ => break,
+            }
+        }
+        let prefix = rope.slice(start..offset).to_string().to_lowercase();
+        let context = cursor_context(&schema, &rope.slice(0..start).to_string());
+
+        let completions = Self::items(
+            &schema,
+            context,
+            rope.offset_to_position(start),
+            rope.offset_to_position(offset),
+        )
+        .into_iter()
+        .filter(|item| item.label.to_lowercase().starts_with(&prefix))
+        .collect();
+
+        Task::ready(Ok(CompletionResponse::Array(completions)))
+    }
+
+    fn is_completion_trigger(
+        &self,
+        _offset: usize,
+        new_text: &str,
+        _: &mut Context<gpui_component::input::InputState>,
+    ) -> bool {
+        // Suggest while a name is typed, and as a selection set or arguments
+        // open
+        matches!(new_text, "{" | "(")
+            || (!new_text.is_empty()
+                && new_text
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_'))
+    }
+}
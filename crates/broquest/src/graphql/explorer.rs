@@ -0,0 +1,307 @@
+//! Side panel browsing the types of a fetched GraphQL schema
+
+use gpui::{
+    AppContext, Context, Entity, InteractiveElement, IntoElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement, Styled, Window, div, prelude::FluentBuilder, px,
+};
+use gpui_component::{
+    ActiveTheme, Sizable, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    scroll::ScrollableElement,
+    v_flex,
+};
+use std::sync::Arc;
+
+use super::{Schema, SchemaType, TypeKind, TypeRef};
+use crate::ui::icon::IconName;
+
+/// A row of the explorer: a field, argument or member with the type it
+/// leads to
+struct Row {
+    label: String,
+    detail: String,
+    description: Option<String>,
+    /// Type opened by clicking the row
+    target: Option<String>,
+    deprecated: bool,
+}
+
+pub struct SchemaExplorer {
+    schema: Option<Arc<Schema>>,
+    /// Types opened from the root, the last one shown
+    path: Vec<String>,
+    search_input: Entity<InputState>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl SchemaExplorer {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search_input = cx.new(|cx| InputState::new(window, cx).placeholder("Search"));
+        let subscriptions =
+            vec![
+                cx.subscribe(&search_input, |_this, _input, event: &InputEvent, cx| {
+                    if let InputEvent::Change = event {
+                        cx.notify();
+                    }
+                }),
+            ];
+
+        Self {
+            schema: None,
+            path: Vec::new(),
+            search_input,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn set_schema(&mut self, schema: Option<Arc<Schema>>, cx: &mut Context<Self>) {
+        let same = match (&self.schema, &schema) {
+            (Some(current), Some(schema)) => Arc::ptr_eq(current, schema),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same {
+            self.schema = schema;
+            self.path.clear();
+            cx.notify();
+        }
+    }
+
+    fn open(&mut self, type_name: String, window: &mut Window, cx: &mut Context<Self>) {
+        self.path.push(type_name);
+        self.clear_search(window, cx);
+    }
+
+    fn back(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.path.pop();
+        self.clear_search(window, cx);
+    }
+
+    fn clear_search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.search_input.update(cx, |input, cx| {
+            input.set_value("", window, cx);
+        });
+        cx.notify();
+    }
+
+    /// Root types, then every type of the schema
+    fn root_rows(schema: &Schema) -> Vec<Row> {
+        let roots = schema.root_types();
+        roots
+            .iter()
+            .map(|(operation, type_name)| Row {
+                label: operation.to_string(),
+                detail: type_name.to_string(),
+                description: None,
+                target: Some(type_name.to_string()),
+                deprecated: false,
+            })
+            .chain(
+                schema
+                    .types()
+                    .filter(|ty| !roots.iter().any(|(_, name)| *name == ty.name))
+                    .map(|ty| Row {
+                        label: ty.name.clone(),
+                        detail: kind_label(ty.kind).to_string(),
+                        description: ty.description.clone(),
+                        target: Some(ty.name.clone()),
+                        deprecated: false,
+                    }),
+            )
+            .collect()
+    }
+
+    fn type_rows(schema: &Schema, ty: &SchemaType) -> Vec<Row> {
+        let target = |type_ref: &TypeRef| {
+            let named = schema.get(type_ref.name())?;
+            (named.kind != TypeKind::Scalar).then(|| named.name.clone())
+        };
+
+        let fields = ty.fields.iter().map(|field| {
+            let args = field
+                .args
+                .iter()
+                .map(|arg| format!("{}: {}", arg.name, arg.ty))
+                .collect::<Vec<_>>();
+            Row {
+                label: if args.is_empty() {
+                    field.name.clone()
+                } else {
+                    format!("{}({})", field.name, args.join(", "))
+                },
+                detail: field.ty.to_string(),
+                description: field.description.clone(),
+                target: target(&field.ty),
+                deprecated: field.deprecated,
+            }
+        });
+        let input_fields = ty.input_fields.iter().map(|field| Row {
+            label: field.name.clone(),
+            detail: field.ty.to_string(),
+            description: field.description.clone(),
+            target: target(&field.ty),
+            deprecated: false,
+        });
+        let enum_values = ty.enum_values.iter().map(|value| Row {
+            label: value.clone(),
+            detail: String::new(),
+            description: None,
+            target: None,
+            deprecated: false,
+        });
+        let possible_types = ty.possible_types.iter().map(|name| Row {
+            label: name.clone(),
+            detail: "object".to_string(),
+            description: None,
+            target: Some(name.clone()),
+            deprecated: false,
+        });
+
+        fields
+            .chain(input_fields)
+            .chain(enum_values)
+            .chain(possible_types)
+            .collect()
+    }
+
+    fn render_row(&self, index: usize, row: Row, cx: &mut Context<Self>) -> impl IntoElement {
+        let target = row.target.clone();
+        v_flex()
+            .id(("schema-row", index))
+            .px_3()
+            .py_1()
+            .gap_0p5()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .when(target.is_some(), |this| {
+                this.cursor_pointer()
+                    .hover(|this| this.bg(cx.theme().accent))
+            })
+            .when_some(target, |this, target| {
+                this.on_click(cx.listener(move |this, _, window, cx| {
+                    this.open(target.clone(), window, cx);
+                }))
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(
+                        div()
+                            .font_family(cx.theme().mono_font_family.clone())
+                            .text_sm()
+                            .when(row.deprecated, |this| {
+                                this.line_through().text_color(cx.theme().muted_foreground)
+                            })
+                            .child(row.label),
+                    )
+                    .child(
+                        div()
+                            .font_family(cx.theme().mono_font_family.clone())
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(row.detail),
+                    ),
+            )
+            .when_some(row.description, |this, description| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(description),
+                )
+            })
+    }
+}
+
+fn kind_label(kind: TypeKind) -> &'static str {
+    match kind {
+        TypeKind::Scalar => "scalar",
+        TypeKind::Object => "object",
+        TypeKind::Interface => "interface",
+        TypeKind::Union => "union",
+        TypeKind::Enum => "enum",
+        TypeKind::InputObject => "input",
+        TypeKind::List => "list",
+        TypeKind::NonNull => "non-null",
+    }
+}
+
+impl Render for SchemaExplorer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let search = self.search_input.read(cx).value().to_lowercase();
+        let current = self.path.last().cloned();
+
+        let (title, description, rows) = match &self.schema {
+            None => (SharedString::from("Schema"), None, Vec::new()),
+            Some(schema) => match current.as_deref().and_then(|name| schema.get(name)) {
+                Some(ty) => (
+                    SharedString::from(ty.name.clone()),
+                    ty.description.clone(),
+                    Self::type_rows(schema, ty),
+                ),
+                None => (SharedString::from("Schema"), None, Self::root_rows(schema)),
+            },
+        };
+        let rows: Vec<Row> = rows
+            .into_iter()
+            .filter(|row| search.is_empty() || row.label.to_lowercase().contains(&search))
+            .collect();
+
+        v_flex()
+            .w(px(300.))
+            .h_full()
+            .border_l_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_2()
+                    .gap_2()
+                    .items_center()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .when(!self.path.is_empty(), |this| {
+                        this.child(
+                            Button::new("schema-back")
+                                .small()
+                                .ghost()
+                                .icon(IconName::ChevronLeft)
+                                .on_click(cx.listener(|this, _, window, cx| this.back(window, cx))),
+                        )
+                    })
+                    .child(div().text_sm().font_semibold().truncate().child(title)),
+            )
+            .when(self.schema.is_none(), |this| {
+                this.child(
+                    div()
+                        .p_3()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Fetch the schema to explore its types and complete queries."),
+                )
+            })
+            .when(self.schema.is_some(), |this| {
+                this.child(div().p_2().child(Input::new(&self.search_input).small()))
+                    .when_some(description, |this, description| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .pb_2()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(description),
+                        )
+                    })
+                    .child(
+                        v_flex().flex_1().overflow_y_scrollbar().children(
+                            rows.into_iter()
+                                .enumerate()
+                                .map(|(index, row)| self.render_row(index, row, cx)),
+                        ),
+                    )
+            })
+    }
+}
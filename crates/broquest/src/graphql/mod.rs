@@ -0,0 +1,12 @@
+//! GraphQL requests: the schema fetched by introspection, and the query
+//! editor's completion, validation and schema explorer built on it.
+
+mod completion;
+mod explorer;
+mod query;
+mod schema;
+
+pub use completion::GraphQLCompletionProvider;
+pub use explorer::SchemaExplorer;
+pub use query::validate;
+pub use schema::*;
@@ -0,0 +1,410 @@
+//! Reading a GraphQL document just far enough to know the type each
+//! selection set selects from, for completion and validation
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::{Schema, TypeKind};
+
+/// Where the cursor is in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorContext {
+    /// Between definitions, where an operation or fragment starts
+    Definition,
+    /// In a selection set of the type
+    Selection(String),
+    /// In the arguments of a field
+    Arguments { type_name: String, field: String },
+    /// Nowhere the schema can help, e.g. after `...` or in a selection set
+    /// of an unknown type
+    Unknown,
+}
+
+/// A field selected from a type that doesn't have it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// Zero-based line and column of the field name
+    pub line: u32,
+    pub column: u32,
+    pub len: u32,
+    pub message: String,
+}
+
+/// What the schema knows at the end of `source`, the text before the cursor
+pub fn cursor_context(schema: &Schema, source: &str) -> CursorContext {
+    let tokens = tokenize(source);
+    let mut walker = Walker::new(schema);
+    walker.walk(&tokens);
+
+    match tokens.last().map(|t| (&t.kind, t.text)) {
+        Some((TokenKind::Spread, _)) | Some((TokenKind::Punct('@'), _)) => {
+            return CursorContext::Unknown;
+        }
+        Some((TokenKind::Name, "on")) if tokens.len() > 1 => {
+            if tokens[tokens.len() - 2].kind == TokenKind::Spread {
+                return CursorContext::Unknown;
+            }
+        }
+        _ => {}
+    }
+    walker.context()
+}
+
+/// Fields the schema doesn't have, in the order they appear
+pub fn validate(schema: &Schema, source: &str) -> Vec<UnknownField> {
+    let tokens = tokenize(source);
+    let mut walker = Walker::new(schema);
+    walker.walk(&tokens);
+
+    walker
+        .unknown
+        .into_iter()
+        .map(|(token, type_name)| {
+            let before = &source[..token.offset];
+            let line = before.matches('\n').count() as u32;
+            let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+            UnknownField {
+                line,
+                column: before[line_start..].chars().count() as u32,
+                len: token.text.chars().count() as u32,
+                message: format!(
+                    "Cannot query field \"{}\" on type \"{}\"",
+                    token.text, type_name
+                ),
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Name,
+    Variable,
+    Spread,
+    Punct(char),
+    /// Strings, numbers and anything else no selection depends on
+    Value,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    /// Byte offset in the document
+    offset: usize,
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let kind = match c {
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => continue,
+            '#' => {
+                eat_while(&mut chars, |c| c != '\n');
+                continue;
+            }
+            '"' => {
+                let block = source[start..].starts_with("\"\"\"");
+                let close = if block {
+                    source[start + 3..]
+                        .find("\"\"\"")
+                        .map(|i| start + 3 + i + 3)
+                } else {
+                    string_end(&source[start + 1..]).map(|i| start + 1 + i)
+                }
+                .unwrap_or(source.len());
+                while chars.peek().is_some_and(|&(i, _)| i < close) {
+                    chars.next();
+                }
+                end = close;
+                TokenKind::Value
+            }
+            '.' if source[start..].starts_with("...") => {
+                chars.next();
+                chars.next();
+                end = start + 3;
+                TokenKind::Spread
+            }
+            '$' => {
+                end = eat_while(&mut chars, is_name_char).unwrap_or(end);
+                TokenKind::Variable
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                end = eat_while(&mut chars, is_name_char).unwrap_or(end);
+                TokenKind::Name
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                end = eat_while(&mut chars, |c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-')
+                })
+                .unwrap_or(end);
+                TokenKind::Value
+            }
+            '{' | '}' | '(' | ')' | '[' | ']' | ':' | '@' | '!' | '=' | '|' | '&' => {
+                TokenKind::Punct(c)
+            }
+            _ => TokenKind::Value,
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &source[start..end],
+            offset: start,
+        });
+    }
+
+    tokens
+}
+
+/// Consume the characters matching `predicate`, returning where they end
+fn eat_while(chars: &mut Peekable<CharIndices<'_>>, predicate: fn(char) -> bool) -> Option<usize> {
+    let mut end = None;
+    while let Some(&(i, c)) = chars.peek() {
+        if !predicate(c) {
+            break;
+        }
+        end = Some(i + c.len_utf8());
+        chars.next();
+    }
+    end
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Byte length of a string's contents up to and including the closing quote,
+/// or to the end of the line when unclosed
+fn string_end(rest: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '\n' => return Some(i),
+            '\\' if !escaped => {
+                escaped = true;
+                continue;
+            }
+            '"' if !escaped => return Some(i + 1),
+            _ => {}
+        }
+        escaped = false;
+    }
+    None
+}
+
+struct Walker<'s, 'a> {
+    schema: &'s Schema,
+    /// Type of each open selection set, `None` where unknown
+    selections: Vec<Option<String>>,
+    /// Type the next selection set selects from: an operation's root type or
+    /// a type condition
+    next_type: Option<String>,
+    /// (type, field) selected last in the innermost selection set
+    last_field: Option<(String, String)>,
+    /// Depth of open parentheses, and the field whose arguments they hold
+    parens: usize,
+    arguments_of: Option<(String, String)>,
+    unknown: Vec<(Token<'a>, String)>,
+}
+
+impl<'s, 'a> Walker<'s, 'a> {
+    fn new(schema: &'s Schema) -> Self {
+        Self {
+            schema,
+            selections: Vec::new(),
+            next_type: None,
+            last_field: None,
+            parens: 0,
+            arguments_of: None,
+            unknown: Vec::new(),
+        }
+    }
+
+    fn walk(&mut self, tokens: &[Token<'a>]) {
+        for (i, token) in tokens.iter().enumerate() {
+            // Arguments, variable definitions and their values select nothing
+            if self.parens > 0 {
+                match token.kind {
+                    TokenKind::Punct('(') => self.parens += 1,
+                    TokenKind::Punct(')') => self.parens -= 1,
+                    _ => {}
+                }
+                continue;
+            }
+
+            let kind_at = |back: usize| i.checked_sub(back).map(|j| tokens[j].kind);
+            let text_at = |back: usize| i.checked_sub(back).map(|j| tokens[j].text);
+
+            match token.kind {
+                TokenKind::Punct('{') => {
+                    let selected = match self.next_type.take() {
+                        Some(type_name) => Some(type_name),
+                        None if self.selections.is_empty() => self.schema.query_type.clone(),
+                        None => self
+                            .last_field
+                            .as_ref()
+                            .and_then(|(type_name, field)| self.schema.field(type_name, field))
+                            .map(|field| field.ty.name().to_string()),
+                    };
+                    self.selections.push(selected);
+                    self.last_field = None;
+                }
+                TokenKind::Punct('}') => {
+                    self.selections.pop();
+                    self.last_field = None;
+                    self.next_type = None;
+                }
+                TokenKind::Punct('(') => {
+                    self.parens = 1;
+                    // Arguments of a directive aren't the field's
+                    self.arguments_of = if kind_at(2) == Some(TokenKind::Punct('@')) {
+                        None
+                    } else {
+                        self.last_field.clone()
+                    };
+                }
+                TokenKind::Name => {
+                    let after_spread = kind_at(1) == Some(TokenKind::Spread);
+                    let directive = kind_at(1) == Some(TokenKind::Punct('@'));
+                    let type_condition = text_at(1) == Some("on")
+                        && (self.selections.is_empty() || kind_at(2) == Some(TokenKind::Spread));
+
+                    if type_condition {
+                        self.next_type = Some(token.text.to_string());
+                    } else if directive || after_spread {
+                        // Directive, fragment spread or the `on` of an inline
+                        // fragment
+                    } else if self.selections.is_empty() {
+                        if let Some(root) = self.schema.operation_type(token.text) {
+                            self.next_type = Some(root.to_string());
+                        }
+                    } else if tokens.get(i + 1).map(|t| t.kind) != Some(TokenKind::Punct(':')) {
+                        self.select(*token);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn select(&mut self, token: Token<'a>) {
+        self.last_field = None;
+        let Some(Some(type_name)) = self.selections.last() else {
+            return;
+        };
+        if token.text == "__typename" {
+            return;
+        }
+        let Some(ty) = self.schema.get(type_name) else {
+            return;
+        };
+        if !matches!(
+            ty.kind,
+            TypeKind::Object | TypeKind::Interface | TypeKind::Union
+        ) {
+            return;
+        }
+
+        // Every query can also select the introspection fields
+        let is_root_introspection = matches!(token.text, "__schema" | "__type")
+            && self.schema.query_type.as_deref() == Some(type_name.as_str());
+        if ty.fields.iter().any(|field| field.name == token.text) {
+            self.last_field = Some((type_name.clone(), token.text.to_string()));
+        } else if !is_root_introspection {
+            self.unknown.push((token, type_name.clone()));
+        }
+    }
+
+    fn context(&self) -> CursorContext {
+        if self.parens > 0 {
+            return match &self.arguments_of {
+                Some((type_name, field)) => CursorContext::Arguments {
+                    type_name: type_name.clone(),
+                    field: field.clone(),
+                },
+                None => CursorContext::Unknown,
+            };
+        }
+        match self.selections.last() {
+            None => CursorContext::Definition,
+            Some(Some(type_name)) => CursorContext::Selection(type_name.clone()),
+            Some(None) => CursorContext::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::schema::tests::schema;
+    use super::*;
+
+    #[test]
+    fn test_cursor_context() {
+        let schema = schema();
+        let selection = |name: &str| CursorContext::Selection(name.to_string());
+
+        assert_eq!(cursor_context(&schema, ""), CursorContext::Definition);
+        assert_eq!(cursor_context(&schema, "{ "), selection("Query"));
+        assert_eq!(
+            cursor_context(&schema, "query Users { users { "),
+            selection("User")
+        );
+        assert_eq!(
+            cursor_context(&schema, "mutation { "),
+            selection("Mutation")
+        );
+        // Aliases, arguments and strings don't change the type
+        assert_eq!(
+            cursor_context(
+                &schema,
+                "query($id: ID!) { me: user(id: $id, note: \"{ (\") { friends { id } "
+            ),
+            selection("User")
+        );
+        assert_eq!(
+            cursor_context(&schema, "{ user("),
+            CursorContext::Arguments {
+                type_name: "Query".to_string(),
+                field: "user".to_string()
+            }
+        );
+        assert_eq!(
+            cursor_context(&schema, "fragment F on User { name } { users { ... on "),
+            CursorContext::Unknown
+        );
+        assert_eq!(
+            cursor_context(&schema, "{ users { ... on User { "),
+            selection("User")
+        );
+        assert_eq!(
+            cursor_context(&schema, "{ users { name } } "),
+            CursorContext::Definition
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_fields() {
+        let schema = schema();
+        assert!(
+            validate(
+                &schema,
+                "# users\nquery { users { id name __typename ...F } __schema { x } }"
+            )
+            .is_empty()
+        );
+
+        let errors = validate(&schema, "{\n  users {\n    id\n    email\n  }\n}");
+        assert_eq!(
+            errors,
+            vec![UnknownField {
+                line: 3,
+                column: 4,
+                len: 5,
+                message: "Cannot query field \"email\" on type \"User\"".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! GraphQL schema read from an introspection response, and the schemas
+//! fetched so far
+
+use anyhow::{Result, bail};
+use gpui::{App, Global};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+/// Query sent to fetch a schema by introspection
+pub const INTROSPECTION_QUERY: &str = r#"query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  fields(includeDeprecated: true) {
+    name
+    description
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+  }
+  inputFields { ...InputValue }
+  enumValues(includeDeprecated: true) { name }
+  possibleTypes { name }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type { ...TypeRef }
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType { kind name }
+            }
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    List,
+    NonNull,
+}
+
+/// Type of a field or argument, with its list and non-null wrappers
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl TypeRef {
+    /// Name of the type inside the wrappers
+    pub fn name(&self) -> &str {
+        match self {
+            TypeRef::Named(name) => name,
+            TypeRef::List(inner) | TypeRef::NonNull(inner) => inner.name(),
+        }
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeRef::Named(name) => write!(f, "{name}"),
+            TypeRef::List(inner) => write!(f, "[{inner}]"),
+            TypeRef::NonNull(inner) => write!(f, "{inner}!"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub description: Option<String>,
+    pub args: Vec<InputValue>,
+    pub ty: TypeRef,
+    pub deprecated: bool,
+}
+
+/// An argument of a field, or a field of an input object
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputValue {
+    pub name: String,
+    pub description: Option<String>,
+    pub ty: TypeRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaType {
+    pub name: String,
+    pub kind: TypeKind,
+    pub description: Option<String>,
+    pub fields: Vec<Field>,
+    pub input_fields: Vec<InputValue>,
+    pub enum_values: Vec<String>,
+    /// Object types of a union or interface
+    pub possible_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub query_type: Option<String>,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    types: BTreeMap<String, SchemaType>,
+}
+
+impl Schema {
+    /// Read the schema from the JSON response to [`INTROSPECTION_QUERY`]
+    pub fn from_introspection(body: &str) -> Result<Self> {
+        let response: RawResponse = serde_json::from_str(body)?;
+        let Some(data) = response.data else {
+            let messages: Vec<_> = response.errors.into_iter().map(|e| e.message).collect();
+            if messages.is_empty() {
+                bail!("The response has no schema");
+            }
+            bail!("{}", messages.join("; "));
+        };
+        let schema = data.schema;
+
+        let types = schema
+            .types
+            .into_iter()
+            .filter_map(|raw| {
+                let name = raw.name?;
+                let ty = SchemaType {
+                    name: name.clone(),
+                    kind: raw.kind,
+                    description: raw.description,
+                    fields: raw
+                        .fields
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|field| Field {
+                            name: field.name,
+                            description: field.description,
+                            args: field
+                                .args
+                                .into_iter()
+                                .map(RawInputValue::into_input)
+                                .collect(),
+                            ty: field.ty.into_type_ref(),
+                            deprecated: field.is_deprecated,
+                        })
+                        .collect(),
+                    input_fields: raw
+                        .input_fields
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(RawInputValue::into_input)
+                        .collect(),
+                    enum_values: raw
+                        .enum_values
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|value| value.name)
+                        .collect(),
+                    possible_types: raw
+                        .possible_types
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|ty| ty.name)
+                        .collect(),
+                };
+                Some((name, ty))
+            })
+            .collect();
+
+        Ok(Self {
+            query_type: schema.query_type.map(|t| t.name),
+            mutation_type: schema.mutation_type.map(|t| t.name),
+            subscription_type: schema.subscription_type.map(|t| t.name),
+            types,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SchemaType> {
+        self.types.get(name)
+    }
+
+    /// Field of a type by name
+    pub fn field(&self, type_name: &str, field_name: &str) -> Option<&Field> {
+        self.get(type_name)?
+            .fields
+            .iter()
+            .find(|field| field.name == field_name)
+    }
+
+    /// Types declared by the schema, leaving out the `__` introspection types
+    pub fn types(&self) -> impl Iterator<Item = &SchemaType> {
+        self.types.values().filter(|ty| !ty.name.starts_with("__"))
+    }
+
+    /// Root types as (operation, type name)
+    pub fn root_types(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("query", &self.query_type),
+            ("mutation", &self.mutation_type),
+            ("subscription", &self.subscription_type),
+        ]
+        .into_iter()
+        .filter_map(|(operation, name)| Some((operation, name.as_deref()?)))
+        .collect()
+    }
+
+    /// Type the operation keyword selects from
+    pub fn operation_type(&self, operation: &str) -> Option<&str> {
+        match operation {
+            "query" => self.query_type.as_deref(),
+            "mutation" => self.mutation_type.as_deref(),
+            "subscription" => self.subscription_type.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Schemas fetched by introspection this session, per collection and
+/// endpoint URL
+#[derive(Default)]
+pub struct SchemaCache {
+    schemas: HashMap<(String, String), Arc<Schema>>,
+}
+
+impl Global for SchemaCache {}
+
+impl SchemaCache {
+    pub fn get(cx: &App, collection_path: Option<&str>, url: &str) -> Option<Arc<Schema>> {
+        cx.try_global::<Self>()?
+            .schemas
+            .get(&Self::key(collection_path, url))
+            .cloned()
+    }
+
+    pub fn insert(cx: &mut App, collection_path: Option<&str>, url: &str, schema: Arc<Schema>) {
+        cx.default_global::<Self>()
+            .schemas
+            .insert(Self::key(collection_path, url), schema);
+    }
+
+    fn key(collection_path: Option<&str>, url: &str) -> (String, String) {
+        (
+            collection_path.unwrap_or_default().to_string(),
+            url.trim().to_string(),
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct RawResponse {
+    data: Option<RawData>,
+    #[serde(default)]
+    errors: Vec<RawError>,
+}
+
+#[derive(Deserialize)]
+struct RawError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RawData {
+    #[serde(rename = "__schema")]
+    schema: RawSchema,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSchema {
+    query_type: Option<RawName>,
+    mutation_type: Option<RawName>,
+    subscription_type: Option<RawName>,
+    types: Vec<RawType>,
+}
+
+#[derive(Deserialize)]
+struct RawName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawType {
+    kind: TypeKind,
+    name: Option<String>,
+    description: Option<String>,
+    fields: Option<Vec<RawField>>,
+    input_fields: Option<Vec<RawInputValue>>,
+    enum_values: Option<Vec<RawName>>,
+    possible_types: Option<Vec<RawName>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawField {
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    args: Vec<RawInputValue>,
+    #[serde(rename = "type")]
+    ty: RawTypeRef,
+    #[serde(default)]
+    is_deprecated: bool,
+}
+
+#[derive(Deserialize)]
+struct RawInputValue {
+    name: String,
+    description: Option<String>,
+    #[serde(rename = "type")]
+    ty: RawTypeRef,
+}
+
+impl RawInputValue {
+    fn into_input(self) -> InputValue {
+        InputValue {
+            name: self.name,
+            description: self.description,
+            ty: self.ty.into_type_ref(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTypeRef {
+    kind: TypeKind,
+    name: Option<String>,
+    of_type: Option<Box<RawTypeRef>>,
+}
+
+impl RawTypeRef {
+    fn into_type_ref(self) -> TypeRef {
+        match (self.kind, self.of_type) {
+            (TypeKind::NonNull, Some(inner)) => TypeRef::NonNull(Box::new(inner.into_type_ref())),
+            (TypeKind::List, Some(inner)) => TypeRef::List(Box::new(inner.into_type_ref())),
+            (_, _) => TypeRef::Named(self.name.unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// Introspection response of a small schema, shared with the query tests
+    pub(in crate::graphql) const INTROSPECTION: &str = r#"{
+      "data": {
+        "__schema": {
+          "queryType": { "name": "Query" },
+          "mutationType": { "name": "Mutation" },
+          "subscriptionType": null,
+          "types": [
+            {
+              "kind": "OBJECT",
+              "name": "Query",
+              "description": null,
+              "fields": [
+                {
+                  "name": "user",
+                  "description": "A user by id",
+                  "args": [
+                    {
+                      "name": "id",
+                      "description": null,
+                      "type": { "kind": "NON_NULL", "name": null, "ofType": { "kind": "SCALAR", "name": "ID", "ofType": null } }
+                    }
+                  ],
+                  "type": { "kind": "OBJECT", "name": "User", "ofType": null },
+                  "isDeprecated": false
+                },
+                {
+                  "name": "users",
+                  "description": null,
+                  "args": [],
+                  "type": {
+                    "kind": "NON_NULL", "name": null,
+                    "ofType": { "kind": "LIST", "name": null, "ofType": { "kind": "OBJECT", "name": "User", "ofType": null } }
+                  },
+                  "isDeprecated": false
+                }
+              ],
+              "inputFields": null,
+              "enumValues": null,
+              "possibleTypes": null
+            },
+            {
+              "kind": "OBJECT",
+              "name": "Mutation",
+              "description": null,
+              "fields": [
+                {
+                  "name": "deleteUser",
+                  "description": null,
+                  "args": [],
+                  "type": { "kind": "SCALAR", "name": "Boolean", "ofType": null },
+                  "isDeprecated": false
+                }
+              ],
+              "inputFields": null,
+              "enumValues": null,
+              "possibleTypes": null
+            },
+            {
+              "kind": "OBJECT",
+              "name": "User",
+              "description": "Someone with an account",
+              "fields": [
+                {
+                  "name": "id",
+                  "description": null,
+                  "args": [],
+                  "type": { "kind": "NON_NULL", "name": null, "ofType": { "kind": "SCALAR", "name": "ID", "ofType": null } },
+                  "isDeprecated": false
+                },
+                {
+                  "name": "name",
+                  "description": null,
+                  "args": [],
+                  "type": { "kind": "SCALAR", "name": "String", "ofType": null },
+                  "isDeprecated": false
+                },
+                {
+                  "name": "friends",
+                  "description": null,
+                  "args": [],
+                  "type": { "kind": "LIST", "name": null, "ofType": { "kind": "OBJECT", "name": "User", "ofType": null } },
+                  "isDeprecated": true
+                }
+              ],
+              "inputFields": null,
+              "enumValues": null,
+              "possibleTypes": null
+            },
+            { "kind": "SCALAR", "name": "ID", "description": null, "fields": null, "inputFields": null, "enumValues": null, "possibleTypes": null },
+            { "kind": "SCALAR", "name": "String", "description": null, "fields": null, "inputFields": null, "enumValues": null, "possibleTypes": null },
+            { "kind": "SCALAR", "name": "Boolean", "description": null, "fields": null, "inputFields": null, "enumValues": null, "possibleTypes": null },
+            { "kind": "OBJECT", "name": "__Schema", "description": null, "fields": [], "inputFields": null, "enumValues": null, "possibleTypes": null }
+          ]
+        }
+      }
+    }"#;
+
+    pub(in crate::graphql) fn schema() -> Schema {
+        Schema::from_introspection(INTROSPECTION).expect("valid introspection")
+    }
+
+    #[test]
+    fn test_from_introspection() {
+        let schema = schema();
+        assert_eq!(schema.query_type.as_deref(), Some("Query"));
+        assert_eq!(
+            schema.root_types(),
+            vec![("query", "Query"), ("mutation", "Mutation")]
+        );
+
+        let users = schema.field("Query", "users").expect("users field");
+        assert_eq!(users.ty.to_string(), "[User]!");
+        assert_eq!(users.ty.name(), "User");
+
+        let user = schema.field("Query", "user").expect("user field");
+        assert_eq!(user.args[0].ty.to_string(), "ID!");
+        assert!(schema.field("User", "friends").unwrap().deprecated);
+
+        assert!(schema.types().all(|ty| !ty.name.starts_with("__")));
+    }
+
+    #[test]
+    fn test_from_introspection_errors() {
+        let error = Schema::from_introspection(
+            r#"{"data": null, "errors": [{"message": "Introspection is disabled"}]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "Introspection is disabled");
+    }
+}
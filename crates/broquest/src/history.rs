@@ -98,11 +98,7 @@ impl HistoryPanel {
     }
 
     fn render_entry(&self, entry: &HistoryEntry, cx: &mut Context<Self>) -> impl IntoElement {
-        let method = HttpMethod::ALL
-            .iter()
-            .find(|m| m.as_str() == entry.method)
-            .copied()
-            .unwrap_or(HttpMethod::Get);
+        let method = HttpMethod::parse(&entry.method);
         let method_color = method.get_color(cx);
 
         let status_color = entry
@@ -4,12 +4,14 @@
 //! that the correct authentication headers are sent with requests.
 
 use crate::domain::{
-    AuthType, BasicAuth, DigestAuth, HttpMethod, JwtAuth, KeyAuth, OAuth2Auth, OAuth2GrantType,
-    RequestData,
+    AuthType, BasicAuth, DigestAuth, HttpMethod, JwtAuth, KeyAuth, KeyValuePair, OAuth2Auth,
+    OAuth2GrantType, RequestData,
 };
-use crate::http::HttpClientService;
+use crate::http::oauth2::OAuth2Prompt;
+use crate::http::{HttpClientService, RequestScope};
 use serde_json::json;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use wiremock::matchers::{body_string_contains, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -38,7 +40,14 @@ async fn test_basic_auth_sends_correct_header() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -67,7 +76,14 @@ async fn test_api_key_auth_sends_custom_header() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -97,7 +113,14 @@ async fn test_oauth2_sends_bearer_token() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -126,7 +149,14 @@ async fn test_oauth2_no_token_sends_no_auth_header() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 
@@ -159,7 +189,14 @@ async fn test_none_auth_sends_no_auth_header() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 
@@ -192,7 +229,14 @@ async fn test_inherit_auth_sends_no_auth_header() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 
@@ -263,11 +307,18 @@ async fn test_digest_auth_challenge_response() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 
-    let (response_data, _) = result.unwrap();
+    let (response_data, _, _) = result.unwrap();
     assert_eq!(response_data.status_code, Some(200));
     assert_eq!(response_data.body, "OK");
 }
@@ -296,11 +347,18 @@ async fn test_digest_auth_no_challenge_returns_initial_response() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 
-    let (response_data, _) = result.unwrap();
+    let (response_data, _, _) = result.unwrap();
     assert_eq!(response_data.status_code, Some(200));
     assert_eq!(response_data.body, "Public content");
 }
@@ -332,7 +390,14 @@ async fn test_auth_with_environment_variables() {
     variables.insert("api_key".to_string(), "resolved-api-key".to_string());
 
     let result = client
-        .send_request(request_data, Some(variables), None, None, None)
+        .send_request(
+            request_data,
+            Some(variables),
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -364,7 +429,14 @@ async fn test_auth_with_secret_variables() {
     secrets.insert("password".to_string(), "secret-token".to_string());
 
     let result = client
-        .send_request(request_data, None, Some(secrets), None, None)
+        .send_request(
+            request_data,
+            None,
+            Some(secrets),
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -394,7 +466,14 @@ async fn test_post_request_with_auth() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -514,6 +593,454 @@ async fn test_oauth2_refresh_token_stored() {
     assert_eq!(oauth.refresh_token, Some("refresh-token-123".to_string()));
 }
 
+#[tokio::test]
+async fn test_oauth2_password_grant_sends_credentials() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("grant_type=password"))
+        .and(body_string_contains("username=alice"))
+        .and(body_string_contains("password=wonderland"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "password-token",
+            "token_type": "Bearer",
+            "expires_in": 3600
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        grant_type: OAuth2GrantType::Password,
+        client_id: "test-client".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        username: "alice".to_string(),
+        password: "wonderland".to_string(),
+        ..Default::default()
+    };
+
+    client
+        .ensure_oauth2_token(&mut oauth, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(oauth.access_token, Some("password-token".to_string()));
+}
+
+#[tokio::test]
+async fn test_oauth2_reuses_token_fetched_earlier() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "cached-token",
+            "expires_in": 3600
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let oauth = OAuth2Auth {
+        client_id: "test-client".to_string(),
+        client_secret: "test-secret".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        ..Default::default()
+    };
+
+    for _ in 0..2 {
+        let mut oauth = oauth.clone();
+        client
+            .ensure_oauth2_token(&mut oauth, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(oauth.access_token, Some("cached-token".to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_oauth2_refreshes_expired_token() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("grant_type=refresh_token"))
+        .and(body_string_contains("refresh_token=refresh-123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "refreshed-token",
+            "expires_in": 3600
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("grant_type=client_credentials"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        client_id: "test-client".to_string(),
+        client_secret: "test-secret".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        access_token: Some("expired-token".to_string()),
+        refresh_token: Some("refresh-123".to_string()),
+        expires_at: Some(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64
+                - 60,
+        ),
+        ..Default::default()
+    };
+
+    client
+        .ensure_oauth2_token(&mut oauth, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(oauth.access_token, Some("refreshed-token".to_string()));
+    // The server didn't issue a new refresh token, so the old one is kept
+    assert_eq!(oauth.refresh_token, Some("refresh-123".to_string()));
+}
+
+#[tokio::test]
+async fn test_oauth2_sends_audience_and_extra_params() {
+    let mock_server = MockServer::start().await;
+
+    let guard = Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains(
+            "audience=https%3A%2F%2Fapi.example.com",
+        ))
+        .and(body_string_contains("tenant=acme"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "audience-token"
+        })))
+        .mount_as_scoped(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        client_id: "test-client".to_string(),
+        client_secret: "test-secret".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        audience: Some("https://api.example.com".to_string()),
+        extra_params: vec![
+            KeyValuePair {
+                key: "tenant".to_string(),
+                value: "acme".to_string(),
+                enabled: true,
+            },
+            KeyValuePair {
+                key: "disabled".to_string(),
+                value: "yes".to_string(),
+                enabled: false,
+            },
+        ],
+        ..Default::default()
+    };
+
+    client
+        .ensure_oauth2_token(&mut oauth, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(oauth.access_token, Some("audience-token".to_string()));
+
+    let received = guard.received_requests().await;
+    let body = String::from_utf8_lossy(&received[0].body);
+    assert!(!body.contains("disabled"));
+}
+
+/// Act as the user's browser: sign in at the authorization `url` and get
+/// redirected back with `response` and the state that was sent
+fn redirect_back(url: &str, response: &'static str) {
+    let url = url::Url::parse(url).expect("valid authorization URL");
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let redirect_uri = params["redirect_uri"].clone();
+    let state = params["state"].clone();
+    tokio::spawn(async move {
+        reqwest::get(format!("{}/?{}&state={}", redirect_uri, response, state))
+            .await
+            .unwrap();
+    });
+}
+
+#[tokio::test]
+async fn test_oauth2_authorization_code_with_pkce() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("grant_type=authorization_code"))
+        .and(body_string_contains("code=auth-code"))
+        .and(body_string_contains("code_verifier="))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "code-token",
+            "refresh_token": "code-refresh",
+            "expires_in": 3600
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        grant_type: OAuth2GrantType::AuthorizationCode,
+        client_id: "public-client".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        authorize_url: Some(format!("{}/oauth/authorize", mock_server.uri())),
+        ..Default::default()
+    };
+
+    let opened = Arc::new(Mutex::new(Vec::new()));
+    let prompts = opened.clone();
+    client
+        .ensure_oauth2_token(&mut oauth, move |prompt| {
+            if let OAuth2Prompt::OpenUrl(url) = &prompt {
+                redirect_back(url, "code=auth-code");
+            }
+            prompts.lock().unwrap().push(prompt);
+        })
+        .await
+        .unwrap();
+    assert_eq!(oauth.access_token, Some("code-token".to_string()));
+    assert_eq!(oauth.refresh_token, Some("code-refresh".to_string()));
+
+    let opened = opened.lock().unwrap();
+    let [OAuth2Prompt::OpenUrl(url)] = opened.as_slice() else {
+        panic!("expected the authorization page to open, got {:?}", opened);
+    };
+    assert!(url.contains("response_type=code"));
+    assert!(url.contains("code_challenge_method=S256"));
+}
+
+#[tokio::test]
+async fn test_oauth2_implicit_takes_the_token_from_the_fragment() {
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        grant_type: OAuth2GrantType::Implicit,
+        client_id: "spa-client".to_string(),
+        authorize_url: Some("https://id.example.com/authorize".to_string()),
+        ..Default::default()
+    };
+
+    let opened = Arc::new(Mutex::new(Vec::new()));
+    let prompts = opened.clone();
+    client
+        .ensure_oauth2_token(&mut oauth, move |prompt| {
+            if let OAuth2Prompt::OpenUrl(url) = &prompt {
+                // The browser lands on the page with the token in the
+                // fragment, which it doesn't send, and the page sends it on
+                let url = url::Url::parse(url).expect("valid authorization URL");
+                let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+                let redirect_uri = params["redirect_uri"].clone();
+                let state = params["state"].clone();
+                tokio::spawn(async move {
+                    let page = reqwest::get(&redirect_uri)
+                        .await
+                        .unwrap()
+                        .text()
+                        .await
+                        .unwrap();
+                    assert!(page.contains("location.hash"));
+                    reqwest::get(format!(
+                        "{}/?access_token=implicit-token&token_type=Bearer&expires_in=3600&state={}",
+                        redirect_uri, state
+                    ))
+                    .await
+                    .unwrap();
+                });
+            }
+            prompts.lock().unwrap().push(prompt);
+        })
+        .await
+        .unwrap();
+    assert_eq!(oauth.access_token, Some("implicit-token".to_string()));
+    assert_eq!(oauth.refresh_token, None);
+    assert!(oauth.expires_at.is_some());
+
+    let opened = opened.lock().unwrap();
+    let [OAuth2Prompt::OpenUrl(url)] = opened.as_slice() else {
+        panic!("expected the authorization page to open, got {:?}", opened);
+    };
+    assert!(url.contains("response_type=token"));
+    assert!(!url.contains("code_challenge"));
+}
+
+#[tokio::test]
+async fn test_oauth2_device_code_polls_until_authorized() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/device"))
+        .and(body_string_contains("client_id=tv-client"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "device_code": "device-123",
+            "user_code": "WDJB-MJHT",
+            "verification_uri": "https://id.example.com/device",
+            "expires_in": 600,
+            "interval": 0
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("device_code=device-123"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "error": "authorization_pending"
+        })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .and(body_string_contains("device_code=device-123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "device-token",
+            "expires_in": 3600
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        grant_type: OAuth2GrantType::DeviceCode,
+        client_id: "tv-client".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        device_authorization_url: Some(format!("{}/oauth/device", mock_server.uri())),
+        ..Default::default()
+    };
+
+    let shown = Arc::new(Mutex::new(Vec::new()));
+    let prompts = shown.clone();
+    client
+        .ensure_oauth2_token(&mut oauth, move |prompt| {
+            prompts.lock().unwrap().push(prompt);
+        })
+        .await
+        .unwrap();
+    assert_eq!(oauth.access_token, Some("device-token".to_string()));
+    let shown = shown.lock().unwrap();
+    let [
+        OAuth2Prompt::DeviceCode {
+            user_code,
+            verification_uri,
+            verification_uri_complete,
+            ..
+        },
+        OAuth2Prompt::Done,
+    ] = shown.as_slice()
+    else {
+        panic!(
+            "expected the device code, then the end of the wait, got {:?}",
+            shown
+        );
+    };
+    assert_eq!(user_code, "WDJB-MJHT");
+    assert_eq!(verification_uri, "https://id.example.com/device");
+    assert_eq!(*verification_uri_complete, None);
+}
+
+#[tokio::test]
+async fn test_oauth2_device_code_wait_can_be_cancelled() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/device"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "device_code": "device-123",
+            "user_code": "WDJB-MJHT",
+            "verification_uri": "https://id.example.com/device",
+            "interval": 0
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "error": "authorization_pending"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        grant_type: OAuth2GrantType::DeviceCode,
+        client_id: "tv-client".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        device_authorization_url: Some(format!("{}/oauth/device", mock_server.uri())),
+        ..Default::default()
+    };
+
+    // The user closes the dialog showing the code
+    let err = client
+        .ensure_oauth2_token(&mut oauth, |prompt| {
+            if let OAuth2Prompt::DeviceCode { cancel, .. } = prompt {
+                cancel.cancel();
+            }
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.summary, "OAuth2 sign-in cancelled");
+    assert_eq!(oauth.access_token, None);
+}
+
+#[tokio::test]
+async fn test_oauth2_device_code_reports_denial() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/oauth/device"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "device_code": "device-123",
+            "user_code": "WDJB-MJHT",
+            "verification_url": "https://id.example.com/device",
+            "interval": 0
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/oauth/token"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "error": "access_denied",
+            "error_description": "The user denied the request"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+    let mut oauth = OAuth2Auth {
+        grant_type: OAuth2GrantType::DeviceCode,
+        client_id: "tv-client".to_string(),
+        token_url: format!("{}/oauth/token", mock_server.uri()),
+        device_authorization_url: Some(format!("{}/oauth/device", mock_server.uri())),
+        ..Default::default()
+    };
+
+    let err = client
+        .ensure_oauth2_token(&mut oauth, |_| {})
+        .await
+        .unwrap_err();
+    assert_eq!(err.details, "The user denied the request");
+}
+
 #[tokio::test]
 async fn test_jwt_fetches_token_from_login_endpoint() {
     let mock_server = MockServer::start().await;
@@ -624,7 +1151,14 @@ async fn test_jwt_sends_bearer_token() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -654,7 +1188,14 @@ async fn test_jwt_sends_custom_token_type() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -682,7 +1223,14 @@ async fn test_jwt_no_token_sends_no_auth_header() {
     };
 
     let result = client
-        .send_request(request_data, None, None, None, None)
+        .send_request(
+            request_data,
+            None,
+            None,
+            None,
+            None,
+            RequestScope::default(),
+        )
         .await;
     assert!(result.is_ok());
 
@@ -0,0 +1,282 @@
+//! Reading response bodies chunk by chunk: reporting progress while they
+//! download and writing bodies over the memory limit to a temporary file.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::HttpError;
+use crate::domain::ServerEvent;
+
+/// Bytes received of the response body being downloaded, and the events of
+/// an event stream. Cloned handles share them, so the UI can watch a download
+/// running elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseProgress {
+    inner: Arc<ProgressInner>,
+}
+
+#[derive(Debug, Default)]
+struct ProgressInner {
+    received: AtomicU64,
+    /// Content-Length of the body, 0 when unknown
+    expected: AtomicU64,
+    started: Mutex<Option<Instant>>,
+    events: Mutex<Vec<ServerEvent>>,
+}
+
+impl ResponseProgress {
+    pub(super) fn start(&self, expected: Option<u64>) {
+        self.inner.received.store(0, Ordering::Relaxed);
+        self.inner
+            .expected
+            .store(expected.unwrap_or_default(), Ordering::Relaxed);
+        *self.inner.started.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        self.inner
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    pub(super) fn add(&self, bytes: usize) {
+        self.inner
+            .received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn push_event(&self, event: ServerEvent) {
+        self.inner
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    }
+
+    /// Server-sent events received so far
+    pub fn events(&self) -> Vec<ServerEvent> {
+        self.inner
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Number of server-sent events received so far
+    pub fn event_count(&self) -> usize {
+        self.inner
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// Bytes of the body received so far
+    pub fn received(&self) -> u64 {
+        self.inner.received.load(Ordering::Relaxed)
+    }
+
+    /// Size of the body, when the response announced it
+    pub fn expected(&self) -> Option<u64> {
+        Some(self.inner.expected.load(Ordering::Relaxed)).filter(|expected| *expected > 0)
+    }
+
+    /// Average transfer rate in bytes per second since the body started
+    pub fn rate(&self) -> f64 {
+        let started = *self.inner.started.lock().unwrap_or_else(|e| e.into_inner());
+        match started.map(|started| started.elapsed().as_secs_f64()) {
+            Some(elapsed) if elapsed > 0.0 => self.received() as f64 / elapsed,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A downloaded response body
+pub(super) enum ResponseBody {
+    Memory(Vec<u8>),
+    /// Written to a temporary file for being over the memory limit
+    File {
+        path: PathBuf,
+        size: u64,
+    },
+}
+
+/// A temporary file removed again unless the download completes, so
+/// cancelled and failed downloads don't leave files behind
+struct SpillFile {
+    path: PathBuf,
+    writer: std::io::BufWriter<std::fs::File>,
+    size: u64,
+    keep: bool,
+}
+
+impl SpillFile {
+    fn create() -> std::io::Result<Self> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!(
+            "broquest-response-{}-{}",
+            std::process::id(),
+            timestamp
+        ));
+        let file = std::fs::File::create(&path)?;
+        Ok(Self {
+            path,
+            writer: std::io::BufWriter::new(file),
+            size: 0,
+            keep: false,
+        })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn finish(mut self) -> std::io::Result<(PathBuf, u64)> {
+        self.writer.flush()?;
+        self.keep = true;
+        Ok((self.path.clone(), self.size))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub(super) fn read_error(error: reqwest::Error) -> HttpError {
+    HttpError::new(
+        "Failed to read response body",
+        format!("Failed to read response body: {}", error),
+    )
+}
+
+fn spill_error(error: std::io::Error) -> HttpError {
+    HttpError::new(
+        "Failed to save response body",
+        format!(
+            "Failed to write the response body to a temporary file: {}",
+            error
+        ),
+    )
+}
+
+/// Read the body of `response`, keeping up to `memory_limit` bytes in memory
+/// and writing larger bodies to a temporary file
+pub(super) async fn read_body(
+    mut response: reqwest::Response,
+    memory_limit: u64,
+    progress: Option<&ResponseProgress>,
+) -> Result<ResponseBody, HttpError> {
+    if let Some(progress) = progress {
+        progress.start(response.content_length());
+    }
+
+    let mut buffer = Vec::new();
+    let mut spill: Option<SpillFile> = None;
+    loop {
+        let chunk = async_compat::Compat::new(response.chunk())
+            .await
+            .map_err(read_error)?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        if let Some(progress) = progress {
+            progress.add(chunk.len());
+        }
+
+        match &mut spill {
+            Some(file) => file.write(&chunk).map_err(spill_error)?,
+            None if (buffer.len() + chunk.len()) as u64 > memory_limit => {
+                let mut file = SpillFile::create().map_err(spill_error)?;
+                file.write(&buffer).map_err(spill_error)?;
+                file.write(&chunk).map_err(spill_error)?;
+                buffer = Vec::new();
+                spill = Some(file);
+            }
+            None => buffer.extend_from_slice(&chunk),
+        }
+    }
+
+    match spill {
+        Some(file) => {
+            let (path, size) = file.finish().map_err(spill_error)?;
+            Ok(ResponseBody::File { path, size })
+        }
+        None => Ok(ResponseBody::Memory(buffer)),
+    }
+}
+
+/// Decode a text body in the charset of its Content-Type, UTF-8 by default
+pub(super) fn decode_text(bytes: &[u8], content_type: &str) -> String {
+    let encoding = content_type
+        .split(';')
+        .filter_map(|param| param.trim().split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, charset)| {
+            encoding_rs::Encoding::for_label(charset.trim().trim_matches('"').as_bytes())
+        })
+        .unwrap_or(encoding_rs::UTF_8);
+    encoding.decode(bytes).0.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// The server is returned to keep it running while the body is read
+    async fn get(body: Vec<u8>) -> (MockServer, reqwest::Response) {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+            .mount(&mock_server)
+            .await;
+        let response = reqwest::get(mock_server.uri()).await.expect("response");
+        (mock_server, response)
+    }
+
+    #[tokio::test]
+    async fn test_large_bodies_spill_to_disk() {
+        let body = vec![b'x'; 64 * 1024];
+        let progress = ResponseProgress::default();
+
+        let (_server, response) = get(body.clone()).await;
+        let ResponseBody::File { path, size } = read_body(response, 1024, Some(&progress))
+            .await
+            .expect("body")
+        else {
+            panic!("expected the body on disk");
+        };
+        assert_eq!(size, body.len() as u64);
+        assert_eq!(std::fs::read(&path).expect("spilled body"), body);
+        assert_eq!(progress.received(), body.len() as u64);
+        assert_eq!(progress.expected(), Some(body.len() as u64));
+        std::fs::remove_file(path).ok();
+
+        let (_server, response) = get(body.clone()).await;
+        assert!(matches!(
+            read_body(response, body.len() as u64, None).await,
+            Ok(ResponseBody::Memory(bytes)) if bytes == body
+        ));
+    }
+
+    #[test]
+    fn test_decode_text_uses_charset() {
+        assert_eq!(
+            decode_text(b"caf\xe9", "text/plain; charset=ISO-8859-1"),
+            "café"
+        );
+        assert_eq!(decode_text("café".as_bytes(), "application/json"), "café");
+    }
+}
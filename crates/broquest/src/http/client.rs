@@ -1,17 +1,42 @@
 use anyhow::{Context as _, Result};
 use gpui::Global;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use crate::collections::TlsSettings;
+use crate::cookies::CookieJar;
 use crate::domain::{
-    AuthType, HttpMethod, JwtAuth, KeyValuePair, OAuth2Auth, OAuth2GrantType, RequestData,
-    ResponseData,
+    AuthType, BodyFile, HttpMethod, HttpVersion, JwtAuth, KeyValuePair, OAuth2Auth,
+    OAuth2GrantType, RedirectHop, RequestData, RequestKind, RequestSettings, ResponseData,
+    ResponseTimings,
 };
-use crate::environments::EnvironmentResolver;
-use crate::scripting::{ScriptExecutionService, VariableStore};
+use crate::environments::{EnvironmentResolver, placeholder_names};
+use crate::scripting::{
+    ScriptExecutionService, ScriptFailure, ScriptRequestHandler, TestResult, VariableStore,
+};
+use crate::settings::{ProxySettings, RedirectSettings};
 
+use super::body::{self, ResponseBody, ResponseProgress};
+use super::grpc::{self, GrpcServices};
 use super::jwt;
-use super::oauth2::{self, calculate_expires_at, is_oauth_token_expired};
+use super::oauth2::{
+    self, OAuth2Cancel, OAuth2Prompt, OAuth2TokenCache, Pkce, apply_token, is_oauth_token_expired,
+};
+use super::script_requests::{RequestScope, ScriptRequests};
+use super::sse;
+use super::timings::{self, TimingLayer, TimingResolver};
+use super::tls::{self, ClientSet};
+use super::upload;
+use super::websocket::{self, MessageVariables, WebSocketConnection};
+
+/// Response bodies kept in memory at most before they go to a temporary file
+const DEFAULT_RESPONSE_MEMORY_LIMIT: u64 = 32 * 1024 * 1024;
+
+/// Replaces the request timeout of event streams, which are open until the
+/// server closes them or the request is cancelled
+const EVENT_STREAM_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
 
 /// Flatten a JSON value to its broquest string representation for `{{}}`
 /// template resolution: strings are used verbatim; other JSON values are
@@ -30,6 +55,8 @@ fn value_to_string(v: &serde_json::Value) -> String {
 pub struct HttpError {
     pub summary: String,
     pub details: String,
+    /// Tests scripts recorded before one of them failed
+    pub tests: Vec<TestResult>,
 }
 
 impl std::error::Error for HttpError {}
@@ -44,10 +71,33 @@ impl HttpError {
         Self {
             summary: summary.into(),
             details: details.into(),
+            tests: Vec::new(),
         }
     }
 
-    fn from_reqwest_error(e: &reqwest::Error) -> Self {
+    /// The error of a script that threw, with the tests recorded before it
+    /// by it and by the scripts that ran earlier
+    fn from_script_failure(
+        kind: &str,
+        failure: ScriptFailure,
+        mut earlier_tests: Vec<TestResult>,
+    ) -> Self {
+        tracing::error!(
+            "Failed to execute {} script: {}",
+            kind.to_lowercase(),
+            failure
+        );
+        earlier_tests.extend(failure.tests);
+        Self {
+            tests: earlier_tests,
+            ..Self::new(
+                format!("{} script execution failed", kind),
+                format!("{} script execution failed: {}", kind, failure),
+            )
+        }
+    }
+
+    pub(super) fn from_reqwest_error(e: &reqwest::Error) -> Self {
         if e.is_timeout() {
             Self::new("Request timed out", format!("Request timed out - {}", e))
         } else if e.is_connect() {
@@ -67,12 +117,47 @@ impl HttpError {
     }
 }
 
+/// A request ready to send: variables resolved, pre-request script run and
+/// auth tokens fetched
+struct PreparedRequest<'a> {
+    request_data: RequestData,
+    variable_store: VariableStore,
+    script_requests: ScriptRequests<'a>,
+    /// The variable tiers the request was resolved with
+    message_variables: MessageVariables,
+}
+
+/// Collection overrides a set of clients is cached for: the effective proxy,
+/// the TLS options, the directory their paths are relative to and the HTTP
+/// version
+type CollectionClientKey = (ProxySettings, Option<TlsSettings>, PathBuf, HttpVersion);
+
 /// Global HTTP client service for sending API requests
 #[derive(Clone)]
 #[allow(dead_code)]
 pub struct HttpClientService {
-    client: reqwest::Client,
+    clients: ClientSet,
+    /// Clients of the app-wide settings, shared by collections without
+    /// overrides
+    app_clients: ClientSet,
     timeout: Duration,
+    proxy: ProxySettings,
+    redirects: RedirectSettings,
+    /// Response bodies over this many bytes are saved to a temporary file
+    response_memory_limit: u64,
+    /// Clients for collections overriding the proxy or TLS options, shared by
+    /// all clones
+    collection_clients: Arc<Mutex<HashMap<CollectionClientKey, ClientSet>>>,
+    /// OAuth2 tokens fetched during the session, shared by all clones
+    oauth2_tokens: OAuth2TokenCache,
+    /// Proxy of the collection, replacing the app-wide one
+    collection_proxy: Option<ProxySettings>,
+    /// TLS options of the collection, for gRPC channels
+    tls: Option<TlsSettings>,
+    /// HTTP version `clients` send with
+    http_version: HttpVersion,
+    /// Directory the collection's files are relative to
+    collection_dir: PathBuf,
     environment_resolver: EnvironmentResolver,
     script_execution_service: ScriptExecutionService,
 }
@@ -81,35 +166,116 @@ impl Global for HttpClientService {}
 
 impl HttpClientService {
     pub fn new(timeout_seconds: u32) -> Result<Self> {
+        Self::with_proxy(timeout_seconds, ProxySettings::default())
+    }
+
+    pub fn with_proxy(timeout_seconds: u32, proxy: ProxySettings) -> Result<Self> {
         let timeout_duration = Duration::from_secs(timeout_seconds as u64);
-        let client = Self::build_client(timeout_duration)?;
+        let client = Self::build_client(timeout_duration, &proxy)?;
         let script_execution_service =
             ScriptExecutionService::new().context("Failed to create script execution service")?;
 
         Ok(Self {
-            client,
+            clients: ClientSet::new(client.clone()),
+            app_clients: ClientSet::new(client),
             timeout: timeout_duration,
+            proxy,
+            redirects: RedirectSettings::default(),
+            response_memory_limit: DEFAULT_RESPONSE_MEMORY_LIMIT,
+            collection_clients: Default::default(),
+            oauth2_tokens: OAuth2TokenCache::default(),
+            collection_proxy: None,
+            tls: None,
+            http_version: HttpVersion::default(),
+            collection_dir: PathBuf::new(),
             environment_resolver: EnvironmentResolver::new(),
             script_execution_service,
         })
     }
 
-    /// Build a reqwest client with the shared timeout and user-agent.
-    fn build_client(timeout: Duration) -> Result<reqwest::Client> {
-        reqwest::Client::builder()
-            .timeout(timeout)
-            .user_agent(format!("broquest/{}", env!("CARGO_PKG_VERSION")))
+    /// Build a reqwest client with the shared timeout, user-agent and proxy.
+    fn build_client(timeout: Duration, proxy: &ProxySettings) -> Result<reqwest::Client> {
+        Self::client_builder(timeout, proxy, HttpVersion::default())?
             .build()
             .context("Failed to create HTTP client")
     }
 
+    fn client_builder(
+        timeout: Duration,
+        proxy: &ProxySettings,
+        http_version: HttpVersion,
+    ) -> Result<reqwest::ClientBuilder> {
+        // The TCP connect can't be told from the TLS handshake through a proxy
+        let proxied = proxy.enabled
+            || (proxy.use_env
+                && ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"]
+                    .iter()
+                    .any(|var| {
+                        std::env::var_os(var).is_some()
+                            || std::env::var_os(var.to_lowercase()).is_some()
+                    }));
+        // Redirects are followed by `execute_request` to record each hop
+        let builder = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(format!("broquest/{}", env!("CARGO_PKG_VERSION")))
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(TimingResolver))
+            .connector_layer(TimingLayer { proxied });
+        let builder = match http_version {
+            HttpVersion::Negotiate => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+        Self::apply_proxy(builder, proxy)
+    }
+
+    /// Route the client through the configured proxies. reqwest picks up the
+    /// proxy environment variables on its own unless told not to.
+    fn apply_proxy(
+        mut builder: reqwest::ClientBuilder,
+        settings: &ProxySettings,
+    ) -> Result<reqwest::ClientBuilder> {
+        if !settings.use_env || settings.enabled {
+            builder = builder.no_proxy();
+        }
+        if !settings.enabled {
+            return Ok(builder);
+        }
+
+        let no_proxy = reqwest::NoProxy::from_string(&settings.no_proxy);
+        let configure = |proxy: reqwest::Result<reqwest::Proxy>, url: &str| {
+            let mut proxy = proxy.with_context(|| format!("Invalid proxy URL '{}'", url))?;
+            if !settings.username.is_empty() {
+                proxy = proxy.basic_auth(&settings.username, &settings.password);
+            }
+            anyhow::Ok(proxy.no_proxy(no_proxy.clone()))
+        };
+
+        let http_url = settings.http_url.trim();
+        if !http_url.is_empty() {
+            builder = builder.proxy(configure(reqwest::Proxy::http(http_url), http_url)?);
+        }
+        let https_url = settings.https_url.trim();
+        if !https_url.is_empty() {
+            builder = builder.proxy(configure(reqwest::Proxy::https(https_url), https_url)?);
+        }
+        // Added last so the HTTP(S) proxies take precedence for their schemes
+        let socks_url = settings.socks_url.trim();
+        if !socks_url.is_empty() {
+            builder = builder.proxy(configure(reqwest::Proxy::all(socks_url), socks_url)?);
+        }
+        Ok(builder)
+    }
+
     pub fn set_timeout(&mut self, seconds: u32) {
         let timeout_duration = Duration::from_secs(seconds as u64);
         // Keep the existing client if rebuilding fails rather than crashing.
-        match Self::build_client(timeout_duration) {
+        match Self::build_client(timeout_duration, &self.proxy) {
             Ok(client) => {
-                self.client = client;
+                self.clients = ClientSet::new(client.clone());
+                self.app_clients = ClientSet::new(client);
                 self.timeout = timeout_duration;
+                self.collection_clients = Default::default();
             }
             Err(e) => {
                 tracing::error!("Failed to rebuild HTTP client on timeout change: {}", e);
@@ -117,6 +283,166 @@ impl HttpClientService {
         }
     }
 
+    /// Apply new proxy settings. An invalid proxy URL keeps the previous
+    /// client and is returned so the caller can report it.
+    pub fn set_proxy(&mut self, proxy: ProxySettings) -> Result<()> {
+        let client = Self::build_client(self.timeout, &proxy)?;
+        self.clients = ClientSet::new(client.clone());
+        self.app_clients = ClientSet::new(client);
+        self.proxy = proxy;
+        self.collection_clients = Default::default();
+        Ok(())
+    }
+
+    pub fn set_redirects(&mut self, redirects: RedirectSettings) {
+        self.redirects = redirects;
+    }
+
+    pub fn set_response_memory_limit_mb(&mut self, limit_mb: u32) {
+        self.response_memory_limit = u64::from(limit_mb) * 1024 * 1024;
+    }
+
+    /// The service to send a collection's requests with: this one, or a copy
+    /// using the collection's proxy, TLS options and HTTP version. TLS file
+    /// paths are relative to `collection_dir`.
+    pub fn for_collection(
+        &self,
+        proxy: Option<&ProxySettings>,
+        tls: Option<&TlsSettings>,
+        http_version: Option<HttpVersion>,
+        collection_dir: &Path,
+    ) -> Result<Self, HttpError> {
+        self.with_clients(proxy, tls, http_version.unwrap_or_default(), collection_dir)
+    }
+
+    /// This service sending with `http_version`, for a request choosing its own
+    fn with_http_version(&self, http_version: HttpVersion) -> Result<Self, HttpError> {
+        self.with_clients(
+            self.collection_proxy.as_ref(),
+            self.tls.as_ref(),
+            http_version,
+            &self.collection_dir,
+        )
+    }
+
+    /// This service with the `{{variable}}` references of the collection
+    /// proxy's password and of the client certificate passphrases resolved
+    /// from the environment, or `None` when there are none
+    fn with_connection_secrets(
+        &self,
+        variables: Option<&HashMap<String, String>>,
+        secrets: Option<&HashMap<String, String>>,
+    ) -> Result<Option<Self>, HttpError> {
+        let has_reference = |value: &str| !placeholder_names(value).is_empty();
+        let proxy_reference = self
+            .collection_proxy
+            .as_ref()
+            .is_some_and(|proxy| has_reference(&proxy.password));
+        let tls_reference = self.tls.as_ref().is_some_and(|tls| {
+            tls.client_certificates
+                .iter()
+                .any(|certificate| certificate.passphrase.as_deref().is_some_and(has_reference))
+        });
+        if !proxy_reference && !tls_reference {
+            return Ok(None);
+        }
+
+        let empty = HashMap::new();
+        let resolve = |template: &str, what: &str| {
+            let value = self.environment_resolver.resolve_string(
+                template,
+                &empty,
+                &empty,
+                variables.unwrap_or(&empty),
+                secrets.unwrap_or(&empty),
+            );
+            if has_reference(&value) {
+                return Err(HttpError::new(
+                    format!("{} not set", what),
+                    format!("{} isn't defined in the environment", template),
+                ));
+            }
+            Ok(value)
+        };
+        let proxy = match &self.collection_proxy {
+            Some(proxy) => Some(ProxySettings {
+                password: resolve(&proxy.password, "Proxy password")?,
+                ..proxy.clone()
+            }),
+            None => None,
+        };
+        let tls = match &self.tls {
+            Some(tls) => {
+                let mut tls = tls.clone();
+                for certificate in &mut tls.client_certificates {
+                    if let Some(passphrase) = &certificate.passphrase {
+                        certificate.passphrase =
+                            Some(resolve(passphrase, "Client certificate passphrase")?);
+                    }
+                }
+                Some(tls)
+            }
+            None => None,
+        };
+        self.with_clients(
+            proxy.as_ref(),
+            tls.as_ref(),
+            self.http_version,
+            &self.collection_dir,
+        )
+        .map(Some)
+    }
+
+    fn with_clients(
+        &self,
+        proxy: Option<&ProxySettings>,
+        tls: Option<&TlsSettings>,
+        http_version: HttpVersion,
+        collection_dir: &Path,
+    ) -> Result<Self, HttpError> {
+        let effective_proxy = proxy.unwrap_or(&self.proxy);
+        let clients = if *effective_proxy == self.proxy
+            && tls.is_none()
+            && http_version == HttpVersion::default()
+        {
+            self.app_clients.clone()
+        } else {
+            let mut cache = self.collection_clients.lock().map_err(|_| {
+                HttpError::new("Connection error", "Collection client cache is poisoned")
+            })?;
+            let key = (
+                effective_proxy.clone(),
+                tls.cloned(),
+                collection_dir.to_path_buf(),
+                http_version,
+            );
+            match cache.get(&key) {
+                Some(clients) => clients.clone(),
+                None => {
+                    let clients = ClientSet::build(
+                        || Self::client_builder(self.timeout, effective_proxy, http_version),
+                        tls,
+                        collection_dir,
+                    )
+                    .map_err(|e| {
+                        HttpError::new("Invalid collection connection settings", format!("{:#}", e))
+                    })?;
+                    cache.insert(key, clients.clone());
+                    clients
+                }
+            }
+        };
+
+        Ok(Self {
+            clients,
+            collection_proxy: proxy.cloned(),
+            tls: tls.cloned(),
+            http_version,
+            collection_dir: collection_dir.to_path_buf(),
+            ..self.clone()
+        })
+    }
+
     /// Get the global HTTP client instance
     pub fn global(cx: &gpui::App) -> &Self {
         cx.global::<Self>()
@@ -126,65 +452,102 @@ impl HttpClientService {
         cx.global_mut::<Self>()
     }
 
-    /// Ensure OAuth2 has a valid access token, fetching if necessary
+    /// Ensure OAuth2 has a valid access token, fetching if necessary. The
+    /// interactive grants tell the user what to do through `prompt`.
     pub async fn ensure_oauth2_token(
         &self,
         oauth: &mut OAuth2Auth,
-        open_url: impl Fn(&str) + Send + 'static,
+        prompt: impl Fn(OAuth2Prompt) + Send + 'static,
     ) -> Result<(), HttpError> {
-        if !is_oauth_token_expired(oauth) {
+        if self.reuse_oauth2_token(oauth).await {
             return Ok(());
         }
 
-        match oauth.grant_type {
+        let client = self.clients.for_url(&oauth.token_url);
+        let token = match oauth.grant_type {
             OAuth2GrantType::ClientCredentials => {
-                let token = oauth2::fetch_client_credentials_token(&self.client, oauth).await?;
-                oauth.access_token = Some(token.access_token);
-                oauth.refresh_token = token.refresh_token;
-                if let Some(expires_in) = token.expires_in {
-                    oauth.expires_at = Some(calculate_expires_at(expires_in));
-                }
+                oauth2::fetch_client_credentials_token(client, oauth).await?
             }
+            OAuth2GrantType::Password => oauth2::fetch_password_token(client, oauth).await?,
             OAuth2GrantType::AuthorizationCode => {
-                let authorize_url = oauth
-                    .authorize_url
-                    .as_ref()
-                    .ok_or_else(|| {
-                        HttpError::new(
-                            "OAuth2 authorization URL missing",
-                            "Authorization URL is required for authorization code flow",
-                        )
-                    })?
-                    .clone();
-
-                let (addr, callback_future) = oauth2::spawn_callback_server(None).await?;
-                let redirect_uri = format!("http://{}", addr);
-
-                let auth_url =
-                    oauth2::build_authorization_url_with_url(&authorize_url, oauth, &redirect_uri);
+                let pkce = oauth.pkce.then(Pkce::generate);
+                let (result, redirect_uri) =
+                    oauth2::authorize_in_browser(oauth, pkce.as_ref(), &prompt).await?;
+                oauth2::exchange_auth_code(
+                    client,
+                    oauth,
+                    &result.code,
+                    &redirect_uri,
+                    pkce.as_ref().map(|pkce| pkce.verifier.as_str()),
+                )
+                .await?
+            }
+            OAuth2GrantType::Implicit => oauth2::authorize_implicit(oauth, &prompt).await?,
+            OAuth2GrantType::DeviceCode => {
+                let device = oauth2::request_device_authorization(
+                    self.clients.for_url(
+                        oauth
+                            .device_authorization_url
+                            .as_deref()
+                            .unwrap_or_default(),
+                    ),
+                    oauth,
+                )
+                .await?;
+                let cancel = OAuth2Cancel::new();
+                prompt(OAuth2Prompt::DeviceCode {
+                    user_code: device.user_code.clone(),
+                    verification_uri: device.verification_uri.clone(),
+                    verification_uri_complete: device.verification_uri_complete.clone(),
+                    cancel: cancel.clone(),
+                });
+                let token =
+                    smol::future::or(oauth2::poll_device_token(client, oauth, &device), async {
+                        cancel.cancelled().await;
+                        Err(HttpError::new(
+                            "OAuth2 sign-in cancelled",
+                            "Stopped waiting for the device code to be entered",
+                        ))
+                    })
+                    .await;
+                prompt(OAuth2Prompt::Done);
+                token?
+            }
+        };
+        apply_token(oauth, token);
+        self.oauth2_tokens.store(oauth);
 
-                open_url(&auth_url);
+        Ok(())
+    }
 
-                let result = callback_future.await?;
+    /// Give `oauth` a valid token without the user: its own, the one fetched
+    /// earlier for the same configuration, or one refreshed with their
+    /// refresh token. Returns whether it has one.
+    async fn reuse_oauth2_token(&self, oauth: &mut OAuth2Auth) -> bool {
+        if !is_oauth_token_expired(oauth) {
+            return true;
+        }
+        if self.oauth2_tokens.load(oauth) && !is_oauth_token_expired(oauth) {
+            return true;
+        }
+        let Some(refresh_token) = oauth.refresh_token.clone() else {
+            return false;
+        };
 
-                let token =
-                    oauth2::exchange_auth_code(&self.client, oauth, &result.code, &redirect_uri)
-                        .await?;
-                oauth.access_token = Some(token.access_token);
-                oauth.refresh_token = token.refresh_token;
-                if let Some(expires_in) = token.expires_in {
-                    oauth.expires_at = Some(calculate_expires_at(expires_in));
-                }
+        let client = self.clients.for_url(&oauth.token_url);
+        match oauth2::refresh_access_token(client, oauth, &refresh_token).await {
+            Ok(token) => {
+                apply_token(oauth, token);
+                self.oauth2_tokens.store(oauth);
+                true
             }
-            OAuth2GrantType::Password => {
-                return Err(HttpError::new(
-                    "Password grant not supported",
-                    "Use client credentials or authorization code flow",
-                ));
+            Err(e) => {
+                tracing::warn!("Failed to refresh OAuth2 token, authorizing again: {}", e);
+                oauth.access_token = None;
+                oauth.refresh_token = None;
+                false
             }
         }
-
-        Ok(())
     }
 
     /// Ensure JWT has a valid access token, fetching if necessary
@@ -193,7 +556,7 @@ impl HttpClientService {
             return Ok(());
         }
 
-        let token = jwt::fetch_jwt_token(&self.client, jwt).await?;
+        let token = jwt::fetch_jwt_token(self.clients.for_url(&jwt.login_url), jwt).await?;
         jwt.access_token = Some(token.access_token);
         jwt.token_type = token.token_type;
         jwt.expires_at = token.expires_at;
@@ -201,21 +564,42 @@ impl HttpClientService {
         Ok(())
     }
 
-    /// Ensure all auth tokens are valid, fetching/refreshing if necessary
+    /// Ensure all auth tokens are valid, fetching/refreshing if necessary.
+    /// Grants that need the user only run when there is someone to show
+    /// `prompts` to; otherwise a token fetched earlier is reused if there is
+    /// one.
     pub async fn ensure_auth_tokens(
         &self,
         auth: &mut crate::domain::AuthType,
+        prompts: Option<smol::channel::Sender<OAuth2Prompt>>,
     ) -> Result<(), HttpError> {
         use crate::domain::AuthType;
 
         match auth {
+            // Only fetch when token_url is configured, which the implicit
+            // grant gets its token without
             AuthType::OAuth2(oauth)
-                // Only auto-fetch for client credentials flow when token_url is configured
-                if matches!(oauth.grant_type, OAuth2GrantType::ClientCredentials)
-                    && !oauth.token_url.is_empty()
-                => {
-                    self.ensure_oauth2_token(oauth, |_| {}).await?;
+                if !oauth.token_url.is_empty()
+                    || oauth.grant_type == OAuth2GrantType::Implicit =>
+            {
+                match prompts {
+                    Some(prompts) if oauth.grant_type.is_interactive() => {
+                        self.ensure_oauth2_token(oauth, move |prompt| {
+                            let _ = prompts.try_send(prompt);
+                        })
+                        .await?;
+                    }
+                    None if oauth.grant_type.is_interactive() => {
+                        if !self.reuse_oauth2_token(oauth).await {
+                            tracing::warn!(
+                                "OAuth2 {} grant needs signing in, sending without a token",
+                                oauth.grant_type.name()
+                            );
+                        }
+                    }
+                    _ => self.ensure_oauth2_token(oauth, |_| {}).await?,
                 }
+            }
             AuthType::Jwt(jwt)
                 // Only try to fetch if login_url is configured
                 if !jwt.login_url.is_empty() => {
@@ -227,6 +611,10 @@ impl HttpClientService {
         Ok(())
     }
 
+    /// Send a request within a [`RequestScope`]: the data row of a
+    /// data-driven run and the saved requests scripts can run with
+    /// `bro.runRequest`. Requests outside of any run pass
+    /// `RequestScope::default()`.
     pub async fn send_request(
         &self,
         request_data: RequestData,
@@ -234,30 +622,173 @@ impl HttpClientService {
         secrets: Option<HashMap<String, String>>,
         runtime_vars: Option<HashMap<String, serde_json::Value>>,
         collection_vars: Option<HashMap<String, String>>,
-    ) -> std::result::Result<(ResponseData, VariableStore), HttpError> {
+        scope: RequestScope,
+    ) -> std::result::Result<(ResponseData, VariableStore, Vec<TestResult>), HttpError> {
         self.send_request_internal(
             request_data,
             variables,
             secrets,
             runtime_vars,
             collection_vars,
+            scope,
         )
         .await
     }
 
-    async fn send_request_internal(
+    pub(super) async fn send_request_internal(
         &self,
         mut request_data: RequestData,
         variables: Option<HashMap<String, String>>,
         secrets: Option<HashMap<String, String>>,
         runtime_vars: Option<HashMap<String, serde_json::Value>>,
         collection_vars: Option<HashMap<String, String>>,
-    ) -> std::result::Result<(ResponseData, VariableStore), HttpError> {
+        scope: RequestScope,
+    ) -> std::result::Result<(ResponseData, VariableStore, Vec<TestResult>), HttpError> {
+        // Connection passwords are resolved before anything is sent
+        if let Some(service) = self.with_connection_secrets(variables.as_ref(), secrets.as_ref())? {
+            return Box::pin(service.send_request_internal(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            ))
+            .await;
+        }
+
+        // A request choosing its own HTTP version is sent with clients for it
+        if let Some(http_version) = request_data.settings.http_version
+            && http_version != self.http_version
+            && request_data.kind == RequestKind::Http
+        {
+            let service = self.with_http_version(http_version)?;
+            return Box::pin(service.send_request_internal(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            ))
+            .await;
+        }
+
         let start_time = std::time::Instant::now();
+        let PreparedRequest {
+            request_data,
+            variable_store,
+            script_requests,
+            ..
+        } = self
+            .prepare_request(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            )
+            .await?;
+
+        if request_data.kind == RequestKind::Grpc {
+            return self
+                .send_grpc(request_data, start_time, variable_store, &script_requests)
+                .await;
+        }
+
+        // A body file that can't be read, or a custom method that isn't
+        // valid, fails before anything is sent
+        check_method(&request_data.method)?;
+        if let Some(file) = &request_data.body_file {
+            upload::file_size(&upload::resolve_path(file, &self.collection_dir))?;
+        }
+        if let Some(parts) = &request_data.multipart {
+            upload::check_multipart(parts, &self.collection_dir)?;
+        }
+
+        // For digest auth, use challenge-response flow
+        if matches!(&request_data.auth, AuthType::Digest(_)) {
+            return self
+                .send_with_digest_auth(request_data, start_time, variable_store, &script_requests)
+                .await;
+        }
+
+        // Standard request flow for non-digest auth
+        let cookie_jar = script_requests.cookie_jar();
+        let (request_builder, request_headers) =
+            self.build_request_builder(&request_data, None, cookie_jar);
+        let response = self
+            .execute_request(request_builder, cookie_jar, &request_data.settings)
+            .await?
+            .with_progress(script_requests.progress());
+
+        self.process_response(
+            response,
+            request_data,
+            request_headers,
+            start_time,
+            variable_store,
+            &script_requests,
+        )
+        .await
+    }
 
+    /// Resolve the variables of a request, run its pre-request script and
+    /// fetch the auth tokens it needs
+    async fn prepare_request(
+        &self,
+        mut request_data: RequestData,
+        variables: Option<HashMap<String, String>>,
+        secrets: Option<HashMap<String, String>>,
+        runtime_vars: Option<HashMap<String, serde_json::Value>>,
+        collection_vars: Option<HashMap<String, String>>,
+        scope: RequestScope,
+    ) -> std::result::Result<PreparedRequest<'_>, HttpError> {
         // Create variable store for this request
         let variable_store = VariableStore::new();
 
+        // Flatten the data row for {{}} resolution before handing it to the
+        // store for bro.iterationData.
+        let iteration_str: HashMap<String, String> = scope
+            .iteration_data
+            .iter()
+            .map(|(k, v)| (k.clone(), value_to_string(v)))
+            .collect();
+        variable_store.set_iteration_data(scope.iteration_data.clone());
+
+        // The folder's and then the collection's headers are sent unless the
+        // request sets its own, and their pre-request scripts run before the
+        // request's
+        scope.folder.apply_headers(&mut request_data);
+        scope.collection.apply_headers(&mut request_data);
+        let folder_vars_map: HashMap<String, String> = scope
+            .folder
+            .vars
+            .iter()
+            .filter(|v| v.enabled && !v.key.is_empty())
+            .map(|v| (v.key.clone(), v.value.clone()))
+            .collect();
+        let pre_request_scripts: Vec<String> = [
+            scope.collection.pre_request_script.clone(),
+            scope.folder.pre_request_script.clone(),
+            request_data.pre_request_script.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        // Requests made by this request's scripts (bro.sendRequest/runRequest)
+        // go through this client with the same variables.
+        let script_requests = ScriptRequests::new(
+            self,
+            variables.clone(),
+            secrets.clone(),
+            collection_vars.clone(),
+            variable_store.clone(),
+            scope,
+        );
+
         // Seed the read-only collection variable bucket (bru.getCollectionVar).
         if let Some(cv) = &collection_vars {
             let map = cv
@@ -286,9 +817,21 @@ impl HttpClientService {
             .unwrap_or_default();
         let mut collection_str: HashMap<String, String> = collection_vars.unwrap_or_default();
 
+        // Folder variables: seed the store for bru.getFolderVar and fold into
+        // the resolution map (folder overrides collection).
+        if !folder_vars_map.is_empty() {
+            variable_store.set_folder_vars(
+                folder_vars_map
+                    .iter()
+                    .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                    .collect(),
+            );
+            collection_str.extend(folder_vars_map);
+        }
+
         // Request-level variables: seed the store for bru.getRequestVar and fold
-        // into the resolution map (request overrides collection) so {{name}}
-        // resolves. Precedence: runtime > request > collection > env.
+        // into the resolution map (request overrides folder and collection) so
+        // {{name}} resolves. Precedence: runtime > request > folder > collection > env.
         let request_vars_map: HashMap<String, String> = request_data
             .vars
             .iter()
@@ -308,7 +851,7 @@ impl HttpClientService {
         }
 
         // Initialize variable store with environment data if provided
-        if let (Some(variables), Some(secrets)) = (variables, secrets) {
+        if let (Some(variables), Some(secrets)) = (&variables, &secrets) {
             tracing::info!(
                 "Loaded {} variables and {} secrets for request",
                 variables.len(),
@@ -316,32 +859,33 @@ impl HttpClientService {
             );
 
             // Initialize variable store with environment data
-            variable_store.initialize_with_env(&variables, &secrets);
+            variable_store.initialize_with_env(variables, secrets);
 
             // Resolve variables in request data using EnvironmentResolver.
-            // Precedence: runtime > collection > env > secret.
+            // Precedence: iteration data > runtime > collection > env > secret.
             request_data = self.environment_resolver.resolve_request_data(
                 request_data,
+                &iteration_str,
                 &runtime_str,
                 &collection_str,
-                &variables,
-                &secrets,
+                variables,
+                secrets,
             );
 
             tracing::info!("URL after environment substitution: {}", request_data.url);
         } else {
-            // No environment selected, but runtime/collection vars may still apply.
-            if !runtime_str.is_empty() || !collection_str.is_empty() {
-                let empty = HashMap::new();
-                request_data = self.environment_resolver.resolve_request_data(
-                    request_data,
-                    &runtime_str,
-                    &collection_str,
-                    &empty,
-                    &empty,
-                );
-                tracing::info!("URL after variable substitution: {}", request_data.url);
-            }
+            // No environment selected, but other vars and dynamic {{$...}}
+            // variables still apply.
+            let empty = HashMap::new();
+            request_data = self.environment_resolver.resolve_request_data(
+                request_data,
+                &iteration_str,
+                &runtime_str,
+                &collection_str,
+                &empty,
+                &empty,
+            );
+            tracing::info!("URL after variable substitution: {}", request_data.url);
         }
 
         tracing::info!(
@@ -350,14 +894,20 @@ impl HttpClientService {
             request_data.url
         );
 
-        // Execute pre-request script if present
-        if let Some(pre_request_script) = request_data.pre_request_script.clone() {
+        // Execute the collection's, the folder's and the request's
+        // pre-request scripts
+        for pre_request_script in pre_request_scripts {
             tracing::info!("Executing pre-request script");
-            if let Err(e) = self.script_execution_service.execute_pre_request_script(
-                &pre_request_script,
-                &mut request_data,
-                &variable_store,
-            ) {
+            if let Err(e) = self
+                .script_execution_service
+                .execute_pre_request_script(
+                    &pre_request_script,
+                    &mut request_data,
+                    &variable_store,
+                    &script_requests,
+                )
+                .await
+            {
                 tracing::error!("Failed to execute pre-request script: {}", e);
                 return Err(HttpError::new(
                     "Pre-request script execution failed",
@@ -367,27 +917,221 @@ impl HttpClientService {
         }
 
         // Ensure auth tokens are valid (fetch/refresh if needed)
-        self.ensure_auth_tokens(&mut request_data.auth).await?;
+        self.ensure_auth_tokens(&mut request_data.auth, script_requests.oauth2_prompts())
+            .await?;
 
-        // For digest auth, use challenge-response flow
-        if matches!(&request_data.auth, AuthType::Digest(_)) {
-            return self
-                .send_with_digest_auth(request_data, start_time, variable_store)
-                .await;
+        // Keep the tiers for resolving what is sent after the request, e.g.
+        // WebSocket messages, with the vars the script has set
+        let mut runtime: HashMap<String, String> = variable_store
+            .get_all_vars()
+            .iter()
+            .map(|(k, v)| (k.clone(), value_to_string(v)))
+            .collect();
+        runtime.extend(iteration_str);
+        let message_variables = MessageVariables {
+            runtime,
+            collection: collection_str,
+            variables: variables.unwrap_or_default(),
+            secrets: secrets.unwrap_or_default(),
+        };
+
+        Ok(PreparedRequest {
+            request_data,
+            variable_store,
+            script_requests,
+            message_variables,
+        })
+    }
+
+    /// Open the WebSocket connection of a `websocket` request. Variables,
+    /// the pre-request script and auth apply to the opening handshake as to
+    /// an HTTP request.
+    pub async fn connect_websocket(
+        &self,
+        request_data: RequestData,
+        variables: Option<HashMap<String, String>>,
+        secrets: Option<HashMap<String, String>>,
+        runtime_vars: Option<HashMap<String, serde_json::Value>>,
+        collection_vars: Option<HashMap<String, String>>,
+        scope: RequestScope,
+    ) -> std::result::Result<WebSocketConnection, HttpError> {
+        if let Some(service) = self.with_connection_secrets(variables.as_ref(), secrets.as_ref())? {
+            return Box::pin(service.connect_websocket(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            ))
+            .await;
         }
 
-        // Standard request flow for non-digest auth
-        let (request_builder, request_headers) = self.build_request_builder(&request_data, None);
-        let response = self.execute_request(request_builder).await?;
+        let PreparedRequest {
+            mut request_data,
+            script_requests,
+            message_variables,
+            ..
+        } = self
+            .prepare_request(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            )
+            .await?;
+
+        tracing::info!("Opening WebSocket connection to {}", request_data.url);
+        let url = request_data.url.clone();
+        request_data.method = HttpMethod::Get;
+        request_data.url = websocket::handshake_url(&request_data.url);
+        request_data.body.clear();
+        let (request_builder, request_headers) =
+            self.build_request_builder(&request_data, None, script_requests.cookie_jar());
+        websocket::connect(request_builder, request_headers, url, message_variables).await
+    }
 
-        self.process_response(
-            response,
+    /// Load the services a `grpc` request can call, from its `.proto` files
+    /// or by server reflection. Variables, the pre-request script and auth
+    /// apply to the reflection requests as to the call.
+    pub async fn grpc_services(
+        &self,
+        request_data: RequestData,
+        variables: Option<HashMap<String, String>>,
+        secrets: Option<HashMap<String, String>>,
+        runtime_vars: Option<HashMap<String, serde_json::Value>>,
+        collection_vars: Option<HashMap<String, String>>,
+        scope: RequestScope,
+    ) -> std::result::Result<GrpcServices, HttpError> {
+        if let Some(service) = self.with_connection_secrets(variables.as_ref(), secrets.as_ref())? {
+            return Box::pin(service.grpc_services(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            ))
+            .await;
+        }
+
+        let PreparedRequest {
             request_data,
-            request_headers,
-            start_time,
-            variable_store,
+            script_requests,
+            ..
+        } = self
+            .prepare_request(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            )
+            .await?;
+        let (_, metadata) =
+            self.build_request_builder(&request_data, None, script_requests.cookie_jar());
+        let grpc = request_data.grpc.unwrap_or_default();
+        if !grpc.proto_files.is_empty() {
+            return GrpcServices::from_files(&grpc.proto_files, &self.collection_dir);
+        }
+        let channel = self.grpc_channel(&request_data.url).await?;
+        grpc::reflect(channel, &metadata).await
+    }
+
+    /// Call the method of a `grpc` request. Its headers and auth are sent as
+    /// metadata; the response messages become the JSON body.
+    async fn send_grpc(
+        &self,
+        request_data: RequestData,
+        start_time: Instant,
+        variable_store: VariableStore,
+        script_requests: &ScriptRequests<'_>,
+    ) -> std::result::Result<(ResponseData, VariableStore, Vec<TestResult>), HttpError> {
+        let (_, metadata) =
+            self.build_request_builder(&request_data, None, script_requests.cookie_jar());
+        let grpc = request_data.grpc.clone().unwrap_or_default();
+        if grpc.method.is_empty() {
+            return Err(HttpError::new(
+                "No gRPC method",
+                "Load the services and select the method to call",
+            ));
+        }
+
+        let mut timings = ResponseTimings::default();
+        let connect_start = Instant::now();
+        let channel = self.grpc_channel(&request_data.url).await?;
+        // tonic doesn't tell the TLS handshake from the connect
+        timings.connect = Some(connect_start.elapsed());
+        let services = if grpc.proto_files.is_empty() {
+            grpc::reflect(channel.clone(), &metadata).await?
+        } else {
+            GrpcServices::from_files(&grpc.proto_files, &self.collection_dir)?
+        };
+
+        tracing::info!("Calling {} on {}", grpc.method, request_data.url);
+        let call_start = Instant::now();
+        let call = grpc::call(
+            channel,
+            &services,
+            &grpc.method,
+            &request_data.body,
+            &metadata,
         )
-        .await
+        .await?;
+        timings.ttfb = call.ttfb;
+        timings.download = call_start.elapsed().saturating_sub(call.ttfb);
+        let latency = start_time.elapsed();
+        timings.prepare = latency.saturating_sub(timings.total());
+
+        let response_data = ResponseData {
+            status_text: Some(call.status.name().to_string()),
+            latency: Some(latency),
+            size: Some(call.size),
+            headers: call.headers,
+            request_headers: metadata,
+            body: call.body,
+            url: Some(request_data.url.clone()),
+            timings,
+            grpc: Some(call.status),
+            ..Default::default()
+        };
+
+        let mut test_results = Vec::new();
+        self.run_post_response_script(
+            &request_data,
+            &response_data,
+            &variable_store,
+            script_requests,
+            &mut test_results,
+        )
+        .await?;
+        Ok((response_data, variable_store, test_results))
+    }
+
+    /// Open a channel to the server of a gRPC URL, over TLS with the
+    /// collection's certificates for `grpcs://`
+    async fn grpc_channel(
+        &self,
+        url: &str,
+    ) -> std::result::Result<tonic::transport::Channel, HttpError> {
+        let (endpoint, use_tls) = grpc::endpoint_url(url)?;
+        let tls = if use_tls {
+            let host = reqwest::Url::parse(&endpoint)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+                .unwrap_or_default();
+            let config = tls::grpc_tls_config(self.tls.as_ref(), &self.collection_dir, &host)
+                .map_err(|e| {
+                    HttpError::new("Invalid collection connection settings", format!("{:#}", e))
+                })?;
+            Some(config)
+        } else {
+            None
+        };
+        grpc::connect(&endpoint, tls, self.timeout).await
     }
 
     /// Send request with digest authentication (RFC 2617 challenge-response flow)
@@ -396,19 +1140,24 @@ impl HttpClientService {
         request_data: RequestData,
         start_time: std::time::Instant,
         variable_store: VariableStore,
-    ) -> std::result::Result<(ResponseData, VariableStore), HttpError> {
+        script_requests: &ScriptRequests<'_>,
+    ) -> std::result::Result<(ResponseData, VariableStore, Vec<TestResult>), HttpError> {
         let (username, password) = match &request_data.auth {
             AuthType::Digest(d) => (d.username.clone(), d.password.clone()),
             _ => unreachable!("send_with_digest_auth called with non-digest auth"),
         };
 
         // Send initial request without auth to get the challenge
-        let (request_builder, initial_headers) = self.build_request_builder(&request_data, None);
-        let response = self.execute_request(request_builder).await?;
+        let cookie_jar = script_requests.cookie_jar();
+        let (request_builder, initial_headers) =
+            self.build_request_builder(&request_data, None, cookie_jar);
+        let response = self
+            .execute_request(request_builder, cookie_jar, &request_data.settings)
+            .await?;
 
         // Check for 401 with Digest challenge
-        if response.status() == 401
-            && let Some(www_authenticate) = response.headers().get("www-authenticate")
+        if response.response.status() == 401
+            && let Some(www_authenticate) = response.response.headers().get("www-authenticate")
         {
             let www_auth_str = www_authenticate.to_str().unwrap_or("");
             if www_auth_str.starts_with("Digest") {
@@ -451,15 +1200,21 @@ impl HttpClientService {
                 tracing::info!("Retrying request with Digest authentication");
 
                 // Retry with the computed auth header
-                let (request_builder, mut request_headers) =
-                    self.build_request_builder(&request_data, Some(auth_header.clone()));
+                let (request_builder, mut request_headers) = self.build_request_builder(
+                    &request_data,
+                    Some(auth_header.clone()),
+                    cookie_jar,
+                );
                 request_headers.push(KeyValuePair {
                     key: "Authorization".to_string(),
                     value: auth_header,
                     enabled: true,
                 });
 
-                let response = self.execute_request(request_builder).await?;
+                let response = self
+                    .execute_request(request_builder, cookie_jar, &request_data.settings)
+                    .await?
+                    .with_progress(script_requests.progress());
 
                 return self
                     .process_response(
@@ -468,6 +1223,7 @@ impl HttpClientService {
                         request_headers,
                         start_time,
                         variable_store,
+                        script_requests,
                     )
                     .await;
             }
@@ -475,36 +1231,85 @@ impl HttpClientService {
 
         // Return initial response (either success or non-digest 401)
         self.process_response(
-            response,
+            response.with_progress(script_requests.progress()),
             request_data,
             initial_headers,
             start_time,
             variable_store,
+            script_requests,
         )
         .await
     }
 
     /// Build a request builder with optional extra auth header (for digest retry)
-    fn build_request_builder(
+    /// and the cookies `cookie_jar` holds for the URL
+    pub(super) fn build_request_builder(
         &self,
         request_data: &RequestData,
         extra_auth_header: Option<String>,
+        cookie_jar: Option<&CookieJar>,
     ) -> (reqwest::RequestBuilder, Vec<KeyValuePair>) {
         let url = Self::apply_query_parameters(&request_data.url, &request_data.query_params);
         let mut request = self
-            .client
-            .request(map_http_method(request_data.method), &url);
+            .clients
+            .for_url(&url)
+            .request(map_http_method(&request_data.method), &url);
 
         let mut request_headers: Vec<KeyValuePair> = Vec::new();
+        let jar_cookies = cookie_jar.and_then(|jar| jar.header_for(&url));
+        let is_cookie_header = |header: &KeyValuePair| {
+            jar_cookies.is_some() && header.key.eq_ignore_ascii_case("cookie")
+        };
+
+        // A multipart body's Content-Type carries the boundary of its parts,
+        // so it's set with the body
+        let is_multipart_type = |header: &KeyValuePair| {
+            request_data.multipart.is_some() && header.key.eq_ignore_ascii_case("content-type")
+        };
 
-        // Add headers
+        // Add headers (a Cookie header is merged with the jar's cookies below)
         for header in &request_data.headers {
-            if header.enabled {
+            if header.enabled && !is_cookie_header(header) && !is_multipart_type(header) {
                 request = request.header(&header.key, &header.value);
                 request_headers.push(header.clone());
             }
         }
 
+        // Send the jar's cookies in a single Cookie header, after any the
+        // request sets itself
+        if let Some(jar_cookies) = &jar_cookies {
+            let value = request_data
+                .headers
+                .iter()
+                .filter(|&h| h.enabled && is_cookie_header(h))
+                .map(|h| h.value.as_str())
+                .chain(std::iter::once(jar_cookies.as_str()))
+                .collect::<Vec<_>>()
+                .join("; ");
+            request = request.header("Cookie", &value);
+            request_headers.push(KeyValuePair {
+                key: "Cookie".to_string(),
+                value,
+                enabled: true,
+            });
+        }
+
+        // An event stream stays open as long as the server sends events
+        if request_data.settings.event_stream {
+            if !request_headers
+                .iter()
+                .any(|h| h.key.eq_ignore_ascii_case("accept"))
+            {
+                request = request.header("Accept", "text/event-stream");
+                request_headers.push(KeyValuePair {
+                    key: "Accept".to_string(),
+                    value: "text/event-stream".to_string(),
+                    enabled: true,
+                });
+            }
+            request = request.timeout(EVENT_STREAM_TIMEOUT);
+        }
+
         // Add extra auth header if provided (for digest retry)
         if let Some(auth_header) = extra_auth_header {
             request = request.header("Authorization", &auth_header);
@@ -515,148 +1320,270 @@ impl HttpClientService {
             request_headers.extend(auth_headers);
         }
 
-        // Add body for POST, PUT, PATCH requests
-        if matches!(
-            request_data.method,
-            HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch
-        ) && !request_data.body.is_empty()
+        // Any method but HEAD sends the body it has
+        if request_data.method.allows_body()
+            && (!request_data.body.is_empty()
+                || request_data.body_file.is_some()
+                || request_data.multipart.is_some())
         {
-            request = self.add_request_body(request, request_data);
+            request = self.add_request_body(request, request_data, &mut request_headers);
         }
 
         (request, request_headers)
     }
 
-    /// Add body to request, handling form data and file uploads
+    /// Add body to request, streaming a body file or the files of a
+    /// multipart body. Headers added for the body are recorded in
+    /// `request_headers`.
     fn add_request_body(
         &self,
         mut request: reqwest::RequestBuilder,
         request_data: &RequestData,
+        request_headers: &mut Vec<KeyValuePair>,
     ) -> reqwest::RequestBuilder {
-        // Check if this is form data with file uploads
-        if request_data.headers.iter().any(|h| {
-            h.key.to_lowercase() == "content-type" && h.value == "application/x-www-form-urlencoded"
-        }) {
-            // Check if body contains file references (paths starting with @)
-            if request_data.body.contains('@') {
-                let mut form = reqwest::multipart::Form::new();
-
-                for pair in request_data.body.split('&') {
-                    if let Some(eq_pos) = pair.find('=') {
-                        let key = urlencoding::decode(&pair[..eq_pos]).unwrap_or_default();
-                        let value = urlencoding::decode(&pair[eq_pos + 1..]).unwrap_or_default();
-
-                        if let Some(value_str) = value.strip_prefix('@') {
-                            match std::fs::read(value_str) {
-                                Ok(file_contents) => {
-                                    let file_name = std::path::Path::new(value_str)
-                                        .file_name()
-                                        .and_then(|n| n.to_str())
-                                        .unwrap_or("file");
-                                    let part = reqwest::multipart::Part::bytes(file_contents)
-                                        .file_name(file_name.to_string());
-                                    form = form.part(key.to_string(), part);
-                                }
-                                Err(e) => {
-                                    tracing::error!("Failed to read file '{}': {}", value_str, e);
-                                    form = form.text(key.to_string(), value.to_string());
-                                }
-                            }
-                        } else {
-                            form = form.text(key.to_string(), value.to_string());
-                        }
-                    }
-                }
+        // A multipart body is sent with the boundary reqwest picks
+        if let Some(parts) = &request_data.multipart {
+            let form = upload::multipart_form(parts, &self.collection_dir);
+            request_headers.push(KeyValuePair {
+                key: "Content-Type".to_string(),
+                value: format!("multipart/form-data; boundary={}", form.boundary()),
+                enabled: true,
+            });
+            return request.multipart(form);
+        }
 
-                request = request.multipart(form);
-            } else {
-                request = request.body(request_data.body.clone());
+        // A body file is streamed, typed by its extension unless the request
+        // sets a Content-Type itself
+        if let Some(file) = &request_data.body_file {
+            let path = upload::resolve_path(file, &self.collection_dir);
+            let has_content_type = request_headers
+                .iter()
+                .any(|h| h.key.eq_ignore_ascii_case("content-type"));
+            let mut add_header = |request: reqwest::RequestBuilder, key: &str, value: String| {
+                request_headers.push(KeyValuePair {
+                    key: key.to_string(),
+                    value: value.clone(),
+                    enabled: true,
+                });
+                request.header(key, value)
+            };
+            if !has_content_type {
+                request = add_header(request, "Content-Type", upload::file_content_type(&path));
             }
-        } else {
-            request = request.body(request_data.body.clone());
+            // Storage APIs want the length up front rather than a chunked body
+            if let Ok(size) = upload::file_size(&path) {
+                request = add_header(request, "Content-Length", size.to_string());
+            }
+            return request.body(upload::file_body(path));
         }
 
-        request
+        request.body(request_data.body.clone())
     }
 
-    /// Execute a request and return the response
-    async fn execute_request(
+    /// Execute a request, following redirects as `settings` and the app
+    /// settings allow, and store the cookies each response sets in
+    /// `cookie_jar`
+    pub(super) async fn execute_request(
         &self,
         request: reqwest::RequestBuilder,
-    ) -> std::result::Result<reqwest::Response, HttpError> {
-        async_compat::Compat::new(request.send())
-            .await
-            .map_err(|e| HttpError::from_reqwest_error(&e))
+        cookie_jar: Option<&CookieJar>,
+        settings: &RequestSettings,
+    ) -> std::result::Result<ExecutedResponse, HttpError> {
+        let policy = self.redirects.with_overrides(settings);
+        let (mut client, request) = request.build_split();
+        let mut request = request.map_err(|e| HttpError::from_reqwest_error(&e))?;
+        let mut redirects = Vec::new();
+        let first_sent = Instant::now();
+
+        loop {
+            let url = request.url().clone();
+            let method = request.method().clone();
+            let headers = request.headers().clone();
+            let had_body = request.body().is_some();
+            let body = request.body().and_then(reqwest::Body::try_clone);
+
+            let sent = Instant::now();
+            let (response, connect_timings) =
+                timings::recorded(async_compat::Compat::new(client.execute(request))).await;
+            let response = response.map_err(|e| HttpError::from_reqwest_error(&e))?;
+            let elapsed = sent.elapsed();
+
+            if let Some(jar) = cookie_jar {
+                jar.store_response_cookies(
+                    url.as_str(),
+                    response
+                        .headers()
+                        .get_all(reqwest::header::SET_COOKIE)
+                        .iter()
+                        .filter_map(|value| value.to_str().ok()),
+                );
+            }
+
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|location| url.join(location).ok());
+            let Some(location) = location.filter(|_| {
+                response.status().is_redirection()
+                    && policy.follow
+                    && redirects.len() < policy.max_redirects as usize
+            }) else {
+                return Ok(ExecutedResponse {
+                    response,
+                    redirects,
+                    timings: hop_timings(sent - first_sent, elapsed, connect_timings),
+                    progress: None,
+                });
+            };
+
+            // 303, and 301/302 after a POST, continue as a GET without a body.
+            let status = response.status().as_u16();
+            let (method, body) = if (status == 303 && method != reqwest::Method::HEAD)
+                || (matches!(status, 301 | 302) && method == reqwest::Method::POST)
+            {
+                (reqwest::Method::GET, None)
+            } else if had_body && body.is_none() {
+                // A streamed body (e.g. multipart files) can't be sent again
+                return Ok(ExecutedResponse {
+                    response,
+                    redirects,
+                    timings: hop_timings(sent - first_sent, elapsed, connect_timings),
+                    progress: None,
+                });
+            } else {
+                (method, body)
+            };
+
+            redirects.push(RedirectHop {
+                url: url.to_string(),
+                status_code: status,
+                status_text: response.status().canonical_reason().map(str::to_string),
+                location: location.to_string(),
+                headers: header_pairs(response.headers()),
+            });
+
+            let mut next = reqwest::Request::new(method, location);
+            *next.headers_mut() = redirect_headers(
+                headers,
+                &url,
+                next.url(),
+                body.is_none(),
+                policy.keep_auth_across_hosts,
+                cookie_jar,
+            );
+            *next.body_mut() = body;
+            // Another host may need another client certificate
+            client = self.clients.for_url(next.url().as_str()).clone();
+            request = next;
+        }
     }
 
     /// Process response into ResponseData and execute post-response scripts
-    async fn process_response(
+    pub(super) async fn process_response(
         &self,
-        response: reqwest::Response,
+        response: ExecutedResponse,
         request_data: RequestData,
         request_headers: Vec<KeyValuePair>,
         start_time: std::time::Instant,
         variable_store: VariableStore,
-    ) -> std::result::Result<(ResponseData, VariableStore), HttpError> {
+        script_requests: &ScriptRequests<'_>,
+    ) -> std::result::Result<(ResponseData, VariableStore, Vec<TestResult>), HttpError> {
+        let ExecutedResponse {
+            response,
+            redirects,
+            mut timings,
+            progress,
+        } = response;
         let status = response.status();
         let status_code = status.as_u16();
         let status_text = status.canonical_reason().map(|s| s.to_string());
+        let http_version = format!("{:?}", response.version());
 
         // Get response headers
-        let response_headers = response
-            .headers()
-            .iter()
-            .filter_map(|(name, value)| {
-                value.to_str().ok().map(|v| KeyValuePair {
-                    key: name.to_string(),
-                    value: v.to_string(),
-                    enabled: true,
-                })
-            })
-            .collect::<Vec<_>>();
+        let response_headers = header_pairs(response.headers());
 
         // Detect response format to determine how to read the body
         let content_type_str = response
             .headers()
             .get("content-type")
             .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-        let response_format = ResponseFormat::from_content_type(content_type_str);
+            .unwrap_or("")
+            .to_string();
+        let response_format = ResponseFormat::from_content_type(&content_type_str);
         let is_binary_response = matches!(
             response_format,
             ResponseFormat::Image(_) | ResponseFormat::Pdf | ResponseFormat::Binary
         );
 
-        // Read body as bytes for binary responses, text for everything else
-        let (response_body, body_bytes) = if is_binary_response {
-            let bytes = async_compat::Compat::new(response.bytes())
-                .await
-                .map_err(|e| {
-                    HttpError::new(
-                        "Failed to read response body",
-                        format!("Failed to read response body: {}", e),
-                    )
-                })?;
-            let body_vec = bytes.to_vec();
-            let placeholder = format!("[Binary data: {} bytes]", body_vec.len());
-            (placeholder, Some(body_vec))
+        // Keep bytes for binary responses, text for everything else. Bodies
+        // over the memory limit stay on disk.
+        let download_start = Instant::now();
+        let mut test_results = Vec::new();
+        let mut events = Vec::new();
+        let body = if sse::is_event_stream(&content_type_str) {
+            // Event streams are read until the server ends them, running the
+            // event script on each event as it arrives
+            let stream = sse::read_event_stream(
+                response,
+                self.response_memory_limit,
+                progress.as_ref(),
+                async |event| {
+                    let Some(event_script) = &request_data.event_script else {
+                        return Ok(());
+                    };
+                    let results = self
+                        .script_execution_service
+                        .execute_event_script(
+                            event_script,
+                            &request_data,
+                            event,
+                            &variable_store,
+                            script_requests,
+                        )
+                        .await
+                        .map_err(|failure| {
+                            HttpError::from_script_failure(
+                                "Event",
+                                failure,
+                                std::mem::take(&mut test_results),
+                            )
+                        })?;
+                    test_results.extend(results);
+                    Ok(())
+                },
+            )
+            .await?;
+            events = stream.events;
+            ResponseBody::Memory(stream.body)
         } else {
-            let text = async_compat::Compat::new(response.text())
-                .await
-                .map_err(|e| {
-                    HttpError::new(
-                        "Failed to read response body",
-                        format!("Failed to read response body: {}", e),
-                    )
-                })?;
-            (text, None)
+            body::read_body(response, self.response_memory_limit, progress.as_ref()).await?
+        };
+        let (response_body, body_bytes, body_file, response_size) = match body {
+            ResponseBody::File { path, size } => {
+                let placeholder = format!(
+                    "[Response body of {} bytes saved to {}]",
+                    size,
+                    path.display()
+                );
+                (placeholder, None, Some(BodyFile::new(path)), size as usize)
+            }
+            ResponseBody::Memory(bytes) if is_binary_response => {
+                let placeholder = format!("[Binary data: {} bytes]", bytes.len());
+                let size = bytes.len();
+                (placeholder, Some(bytes), None, size)
+            }
+            ResponseBody::Memory(bytes) => {
+                let text = body::decode_text(&bytes, &content_type_str);
+                let size = text.len();
+                (text, None, None, size)
+            }
         };
 
         let latency = start_time.elapsed();
-        let response_size = body_bytes
-            .as_ref()
-            .map(|b| b.len())
-            .unwrap_or(response_body.len());
+        timings.download = download_start.elapsed();
+        // Whatever came before sending: scripts, auth, digest challenges
+        timings.prepare = latency.saturating_sub(timings.total());
 
         let response_data = ResponseData {
             status_code: Some(status_code),
@@ -667,25 +1594,23 @@ impl HttpClientService {
             request_headers,
             body: response_body,
             body_bytes,
+            body_file,
             url: Some(request_data.url.clone()),
+            http_version: Some(http_version),
+            redirects,
+            timings,
+            events,
+            grpc: None,
         };
 
-        // Execute post-response script if present
-        if let Some(post_response_script) = &request_data.post_response_script {
-            tracing::info!("Executing post-response script");
-            if let Err(e) = self.script_execution_service.execute_post_response_script(
-                post_response_script,
-                &request_data,
-                &response_data,
-                &variable_store,
-            ) {
-                tracing::error!("Failed to execute post-response script: {}", e);
-                return Err(HttpError::new(
-                    "Post-response script execution failed",
-                    format!("Post-response script execution failed: {}", e),
-                ));
-            }
-        }
+        self.run_post_response_script(
+            &request_data,
+            &response_data,
+            &variable_store,
+            script_requests,
+            &mut test_results,
+        )
+        .await?;
 
         tracing::info!(
             "Request completed: {} {} ({} bytes, {}ms)",
@@ -704,8 +1629,49 @@ impl HttpClientService {
             );
         }
 
-        Ok((response_data, variable_store))
+        Ok((response_data, variable_store, test_results))
     }
+
+    /// Run the post-response script of a request, then its folder's and its
+    /// collection's, adding the tests they record to `test_results`. If one
+    /// throws, the error carries every test recorded so far.
+    async fn run_post_response_script(
+        &self,
+        request_data: &RequestData,
+        response_data: &ResponseData,
+        variable_store: &VariableStore,
+        script_requests: &ScriptRequests<'_>,
+        test_results: &mut Vec<TestResult>,
+    ) -> std::result::Result<(), HttpError> {
+        for post_response_script in request_data
+            .post_response_script
+            .as_deref()
+            .into_iter()
+            .chain(script_requests.inherited_post_response_scripts())
+        {
+            tracing::info!("Executing post-response script");
+            let results = self
+                .script_execution_service
+                .execute_post_response_script(
+                    post_response_script,
+                    request_data,
+                    response_data,
+                    variable_store,
+                    script_requests,
+                )
+                .await
+                .map_err(|failure| {
+                    HttpError::from_script_failure(
+                        "Post-response",
+                        failure,
+                        std::mem::take(test_results),
+                    )
+                })?;
+            test_results.extend(results);
+        }
+        Ok(())
+    }
+
     /// Apply query parameters to a URL, handling URL encoding
     fn apply_query_parameters(url: &str, params: &[KeyValuePair]) -> String {
         let mut result = url.to_string();
@@ -815,7 +1781,108 @@ impl HttpClientService {
     }
 }
 
-fn map_http_method(method: HttpMethod) -> reqwest::Method {
+/// A response and the redirects followed to get it
+pub(super) struct ExecutedResponse {
+    response: reqwest::Response,
+    redirects: Vec<RedirectHop>,
+    /// Timings up to the response headers
+    timings: ResponseTimings,
+    /// Reports the download of the body
+    progress: Option<ResponseProgress>,
+}
+
+impl ExecutedResponse {
+    fn with_progress(mut self, progress: Option<ResponseProgress>) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+/// Timings of the final request of an exchange, sent `redirect` after the
+/// first one and answered `elapsed` after it was sent
+fn hop_timings(
+    redirect: Duration,
+    elapsed: Duration,
+    connect_timings: timings::ConnectTimings,
+) -> ResponseTimings {
+    let timings::ConnectTimings {
+        dns, connect, tls, ..
+    } = connect_timings;
+    let connecting = [dns, connect, tls].into_iter().flatten().sum();
+    ResponseTimings {
+        redirect,
+        dns,
+        connect,
+        tls,
+        ttfb: elapsed.saturating_sub(connecting),
+        ..Default::default()
+    }
+}
+
+pub(super) fn header_pairs(headers: &reqwest::header::HeaderMap) -> Vec<KeyValuePair> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            value.to_str().ok().map(|v| KeyValuePair {
+                key: name.to_string(),
+                value: v.to_string(),
+                enabled: true,
+            })
+        })
+        .collect()
+}
+
+/// The headers to send to a redirect's `to` URL, given those sent to `from`.
+/// Credentials don't follow the request to another host (unless
+/// `keep_auth` allows Authorization) and cookies come from the jar for the
+/// new URL.
+fn redirect_headers(
+    mut headers: reqwest::header::HeaderMap,
+    from: &reqwest::Url,
+    to: &reqwest::Url,
+    body_dropped: bool,
+    keep_auth: bool,
+    cookie_jar: Option<&CookieJar>,
+) -> reqwest::header::HeaderMap {
+    use reqwest::header;
+
+    if body_dropped {
+        for name in [
+            header::CONTENT_TYPE,
+            header::CONTENT_LENGTH,
+            header::CONTENT_ENCODING,
+            header::TRANSFER_ENCODING,
+        ] {
+            headers.remove(name);
+        }
+    }
+
+    let same_host = from.host_str() == to.host_str()
+        && from.port_or_known_default() == to.port_or_known_default();
+    if !same_host {
+        headers.remove(header::PROXY_AUTHORIZATION);
+        headers.remove(header::COOKIE);
+        if !keep_auth {
+            headers.remove(header::AUTHORIZATION);
+        }
+    }
+
+    if let Some(jar) = cookie_jar {
+        headers.remove(header::COOKIE);
+        if let Some(value) = jar
+            .header_for(to.as_str())
+            .and_then(|cookies| header::HeaderValue::from_str(&cookies).ok())
+        {
+            headers.insert(header::COOKIE, value);
+        }
+    }
+
+    headers
+}
+
+/// The method sent for `method`. A custom method that isn't a valid token
+/// is refused by [`check_method`] before anything is sent.
+fn map_http_method(method: &HttpMethod) -> reqwest::Method {
     match method {
         HttpMethod::Get => reqwest::Method::GET,
         HttpMethod::Post => reqwest::Method::POST,
@@ -824,6 +1891,22 @@ fn map_http_method(method: HttpMethod) -> reqwest::Method {
         HttpMethod::Patch => reqwest::Method::PATCH,
         HttpMethod::Head => reqwest::Method::HEAD,
         HttpMethod::Options => reqwest::Method::OPTIONS,
+        HttpMethod::Custom(method) => {
+            reqwest::Method::from_bytes(method.as_bytes()).unwrap_or_default()
+        }
+    }
+}
+
+/// Refuse a custom method that isn't a valid HTTP token, e.g. one with a space
+pub(super) fn check_method(method: &HttpMethod) -> std::result::Result<(), HttpError> {
+    match method {
+        HttpMethod::Custom(name) if reqwest::Method::from_bytes(name.as_bytes()).is_err() => {
+            Err(HttpError::new(
+                "Invalid method",
+                format!("'{}' is not a valid HTTP method", name),
+            ))
+        }
+        _ => Ok(()),
     }
 }
 
@@ -869,6 +1952,9 @@ impl ResponseFormat {
 
         if content_type.contains("json") {
             ResponseFormat::Json
+        } else if content_type.starts_with("application/grpc") {
+            // gRPC messages are shown decoded as JSON
+            ResponseFormat::Json
         } else if content_type.contains("xml") && !content_type.contains("image/svg+xml") {
             ResponseFormat::Xml
         } else if content_type.contains("html") {
@@ -1100,4 +2186,671 @@ mod tests {
             "<root>\n  <item>value</item>\n</root>"
         );
     }
+
+    #[tokio::test]
+    async fn test_requests_go_through_configured_proxy() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // The mock server stands in for the proxy: it receives the request
+        // for a host that doesn't resolve.
+        let proxy_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .and(header("Proxy-Authorization", "Basic dXNlcjpwYXNz"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("proxied"))
+            .mount(&proxy_server)
+            .await;
+
+        // The password is a reference resolved from the environment's secrets
+        let proxy = ProxySettings {
+            enabled: true,
+            http_url: proxy_server.uri(),
+            username: "user".to_string(),
+            password: "{{proxyPassword}}".to_string(),
+            ..Default::default()
+        };
+        let client = HttpClientService::new(30)
+            .expect("failed to create HTTP client")
+            .for_collection(Some(&proxy), None, None, Path::new("."))
+            .expect("valid proxy");
+
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: "http://example.invalid/ping".to_string(),
+            ..Default::default()
+        };
+        let secrets = HashMap::from([("proxyPassword".to_string(), "pass".to_string())]);
+        let (response, _, _) = client
+            .send_request(
+                request_data.clone(),
+                None,
+                Some(secrets),
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("request through proxy");
+        assert_eq!(response.status_code, Some(200));
+        assert_eq!(response.body, "proxied");
+
+        let error = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect_err("unresolved proxy password");
+        assert_eq!(error.summary, "Proxy password not set");
+
+        let invalid = ProxySettings {
+            enabled: true,
+            socks_url: "not a url".to_string(),
+            ..Default::default()
+        };
+        assert!(
+            client
+                .for_collection(Some(&invalid), None, None, Path::new("."))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_passphrase_is_resolved_on_send() {
+        let tls = TlsSettings {
+            client_certificates: vec![crate::collections::ClientCertificate {
+                host: "*".to_string(),
+                cert: "certs/missing.p12".to_string(),
+                key: None,
+                passphrase: Some("{{certPassphrase}}".to_string()),
+            }],
+            ..Default::default()
+        };
+        // The certificate is loaded once the passphrase is known
+        let client = HttpClientService::new(30)
+            .expect("failed to create HTTP client")
+            .for_collection(None, Some(&tls), None, Path::new("/nonexistent-collection"))
+            .expect("certificate not loaded yet");
+
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: "http://example.invalid/ping".to_string(),
+            ..Default::default()
+        };
+        let error = client
+            .send_request(
+                request_data.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect_err("unresolved passphrase");
+        assert_eq!(error.summary, "Client certificate passphrase not set");
+
+        let secrets = HashMap::from([("certPassphrase".to_string(), "changeit".to_string())]);
+        let error = client
+            .send_request(
+                request_data,
+                None,
+                Some(secrets),
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect_err("missing certificate file");
+        assert_eq!(error.summary, "Invalid collection connection settings");
+        assert!(error.details.contains("missing.p12"));
+    }
+
+    #[tokio::test]
+    async fn test_saved_response_body_is_removed_with_the_response() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/large"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("x".repeat(4096)))
+            .mount(&mock_server)
+            .await;
+
+        let mut client = HttpClientService::new(30).expect("failed to create HTTP client");
+        client.response_memory_limit = 1024;
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: format!("{}/large", mock_server.uri()),
+            post_response_script: Some("bro.setVar('bodyFile', res.bodyFile);".to_string()),
+            ..Default::default()
+        };
+        let (response, store, _) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("request should succeed");
+
+        let file = response.body_file.clone().expect("body saved to a file");
+        let path = file.path().to_path_buf();
+        assert_eq!(
+            store.get_var("bodyFile"),
+            Some(serde_json::json!(path.to_string_lossy()))
+        );
+        assert_eq!(std::fs::metadata(&path).expect("file").len(), 4096);
+
+        drop(response);
+        assert!(path.exists(), "a clone of the response keeps the file");
+        drop(file);
+        assert!(!path.exists(), "the file goes with the last response");
+    }
+
+    #[tokio::test]
+    async fn test_body_file_is_streamed_from_the_collection() {
+        use wiremock::matchers::{body_bytes, header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let collection_dir =
+            std::env::temp_dir().join(format!("broquest-upload-{}", std::process::id()));
+        std::fs::create_dir_all(collection_dir.join("fixtures")).expect("create collection");
+        let contents: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+        std::fs::write(collection_dir.join("fixtures/logo.png"), &contents).expect("write file");
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/bucket/logo.png"))
+            .and(header("Content-Type", "image/png"))
+            .and(header("Content-Length", "100000"))
+            .and(body_bytes(contents))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+
+        let client = HttpClientService::new(30)
+            .expect("failed to create HTTP client")
+            .for_collection(None, None, None, &collection_dir)
+            .expect("client for collection");
+        let request_data = RequestData {
+            method: HttpMethod::Put,
+            url: format!("{}/bucket/logo.png", server.uri()),
+            body_file: Some("fixtures/logo.png".to_string()),
+            ..Default::default()
+        };
+        let (response, _, _) = client
+            .send_request(
+                request_data.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("upload");
+        assert_eq!(response.status_code, Some(201));
+
+        // A missing file fails before anything is sent
+        let missing = RequestData {
+            body_file: Some("fixtures/missing.png".to_string()),
+            ..request_data
+        };
+        let error = client
+            .send_request(missing, None, None, None, None, RequestScope::default())
+            .await
+            .expect_err("missing file");
+        assert_eq!(error.summary, "Body file not found");
+
+        std::fs::remove_dir_all(&collection_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_get_body_and_custom_method_are_sent() {
+        use wiremock::matchers::{body_string, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(body_string(r#"{"q":"rust"}"#))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        Mock::given(method("PURGE"))
+            .and(path("/cache/logo.png"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let search = RequestData {
+            method: HttpMethod::Get,
+            url: format!("{}/search", server.uri()),
+            body: r#"{"q":"rust"}"#.to_string(),
+            ..Default::default()
+        };
+        let (response, _, _) = client
+            .send_request(search, None, None, None, None, RequestScope::default())
+            .await
+            .expect("GET with a body");
+        assert_eq!(response.status_code, Some(200));
+
+        let purge = RequestData {
+            method: HttpMethod::parse("purge"),
+            url: format!("{}/cache/logo.png", server.uri()),
+            ..Default::default()
+        };
+        let (response, _, _) = client
+            .send_request(
+                purge.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("custom method");
+        assert_eq!(response.status_code, Some(204));
+
+        // A method that isn't a valid token fails before anything is sent
+        let invalid = RequestData {
+            method: HttpMethod::parse("NOT VALID"),
+            ..purge
+        };
+        let error = client
+            .send_request(invalid, None, None, None, None, RequestScope::default())
+            .await
+            .expect_err("invalid method");
+        assert_eq!(error.summary, "Invalid method");
+    }
+
+    #[tokio::test]
+    async fn test_http_version_is_chosen_and_reported() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/health"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: format!("{}/health", server.uri()),
+            ..Default::default()
+        };
+        let (response, _, _) = client
+            .send_request(
+                request_data.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("HTTP/1.1 over cleartext");
+        assert_eq!(response.http_version.as_deref(), Some("HTTP/1.1"));
+
+        // h2c: HTTP/2 over cleartext, with prior knowledge
+        let h2c = RequestData {
+            settings: RequestSettings {
+                http_version: Some(HttpVersion::Http2PriorKnowledge),
+                ..Default::default()
+            },
+            ..request_data.clone()
+        };
+        let (response, _, _) = client
+            .send_request(h2c, None, None, None, None, RequestScope::default())
+            .await
+            .expect("h2c");
+        assert_eq!(response.http_version.as_deref(), Some("HTTP/2.0"));
+
+        // A collection's version applies unless the request sets its own
+        let collection_client = client
+            .for_collection(
+                None,
+                None,
+                Some(HttpVersion::Http2PriorKnowledge),
+                Path::new("."),
+            )
+            .expect("client for collection");
+        let (response, _, _) = collection_client
+            .send_request(
+                request_data.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("collection version");
+        assert_eq!(response.http_version.as_deref(), Some("HTTP/2.0"));
+        let http1 = RequestData {
+            settings: RequestSettings {
+                http_version: Some(HttpVersion::Http1),
+                ..Default::default()
+            },
+            ..request_data
+        };
+        let (response, _, _) = collection_client
+            .send_request(http1, None, None, None, None, RequestScope::default())
+            .await
+            .expect("request version");
+        assert_eq!(response.http_version.as_deref(), Some("HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_parts_are_sent() {
+        use crate::domain::{MultipartKind, MultipartPart};
+        use wiremock::matchers::{body_string_contains, header_regex, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let collection_dir =
+            std::env::temp_dir().join(format!("broquest-multipart-{}", std::process::id()));
+        std::fs::create_dir_all(collection_dir.join("fixtures")).expect("create collection");
+        std::fs::write(collection_dir.join("fixtures/notes.txt"), "file contents")
+            .expect("write file");
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/upload"))
+            .and(header_regex(
+                "Content-Type",
+                "^multipart/form-data; boundary=",
+            ))
+            .and(body_string_contains(
+                "name=\"caption\"\r\nContent-Type: text/markdown\r\n\r\n# Hello",
+            ))
+            .and(body_string_contains(
+                "name=\"notes\"; filename=\"renamed.txt\"\r\nContent-Type: text/plain",
+            ))
+            .and(body_string_contains("file contents"))
+            .respond_with(ResponseTemplate::new(201))
+            .mount(&server)
+            .await;
+
+        let client = HttpClientService::new(30)
+            .expect("failed to create HTTP client")
+            .for_collection(None, None, None, &collection_dir)
+            .expect("client for collection");
+        let request_data = RequestData {
+            method: HttpMethod::Post,
+            url: format!("{}/upload", server.uri()),
+            // Replaced by the Content-Type with the parts' boundary
+            headers: vec![KeyValuePair {
+                key: "Content-Type".to_string(),
+                value: "multipart/form-data".to_string(),
+                enabled: true,
+            }],
+            multipart: Some(vec![
+                MultipartPart {
+                    name: "caption".to_string(),
+                    value: "# Hello".to_string(),
+                    content_type: "text/markdown".to_string(),
+                    enabled: true,
+                    ..Default::default()
+                },
+                MultipartPart {
+                    name: "notes".to_string(),
+                    kind: MultipartKind::File,
+                    value: "fixtures/notes.txt".to_string(),
+                    filename: "renamed.txt".to_string(),
+                    enabled: true,
+                    ..Default::default()
+                },
+                MultipartPart {
+                    name: "draft".to_string(),
+                    kind: MultipartKind::File,
+                    value: "fixtures/missing.txt".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+        let (response, _, _) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("upload");
+        assert_eq!(response.status_code, Some(201));
+
+        std::fs::remove_dir_all(&collection_dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_recorded_and_credentials_stay_on_host() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let sso = MockServer::start().await;
+        let app = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/login"))
+            .respond_with(
+                ResponseTemplate::new(302).insert_header("Location", format!("{}/home", app.uri())),
+            )
+            .mount(&sso)
+            .await;
+        // Another port is another host: the Authorization header is dropped
+        Mock::given(method("GET"))
+            .and(path("/home"))
+            .and(|request: &wiremock::Request| !request.headers.contains_key("authorization"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("home"))
+            .mount(&app)
+            .await;
+
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let request_data = RequestData {
+            method: HttpMethod::Post,
+            url: format!("{}/login", sso.uri()),
+            headers: vec![KeyValuePair {
+                key: "Authorization".to_string(),
+                value: "Bearer secret".to_string(),
+                enabled: true,
+            }],
+            body: "user=a".to_string(),
+            ..Default::default()
+        };
+
+        let (response, _, _) = client
+            .send_request(
+                request_data.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("redirected request");
+        assert_eq!(response.status_code, Some(200));
+        assert_eq!(response.redirects.len(), 1);
+        let hop = &response.redirects[0];
+        assert_eq!(hop.status_code, 302);
+        assert_eq!(hop.url, format!("{}/login", sso.uri()));
+        assert_eq!(hop.location, format!("{}/home", app.uri()));
+
+        // A request can opt out of following redirects
+        let not_followed = RequestData {
+            settings: RequestSettings {
+                follow_redirects: Some(false),
+                ..Default::default()
+            },
+            ..request_data
+        };
+        let (response, _, _) = client
+            .send_request(
+                not_followed,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("redirect response");
+        assert_eq!(response.status_code, Some(302));
+        assert!(response.redirects.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timings_cover_new_and_reused_connections() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(wiremock::matchers::any())
+            .respond_with(ResponseTemplate::new(200).set_body_string("OK"))
+            .mount(&mock_server)
+            .await;
+        let port = mock_server.address().port();
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let request_data = RequestData {
+            url: format!("http://localhost:{}/", port),
+            ..Default::default()
+        };
+
+        let (first, _, _) = client
+            .send_request(
+                request_data.clone(),
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("first request");
+        assert!(first.timings.dns.is_some());
+        assert!(first.timings.connect.is_some());
+        assert_eq!(first.timings.tls, None, "plain HTTP has no handshake");
+        assert_eq!(first.timings.total(), first.latency.expect("latency"));
+
+        // The second request goes over the pooled connection
+        let (second, _, _) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("second request");
+        assert_eq!(second.timings.dns, None);
+        assert_eq!(second.timings.connect, None);
+        assert_eq!(second.timings.tls, None);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_runs_event_script() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(wiremock::matchers::header("accept", "text/event-stream"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "id: 1\ndata: first\n\nevent: done\ndata: second\n\n",
+                "text/event-stream",
+            ))
+            .mount(&mock_server)
+            .await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let request_data = RequestData {
+            url: mock_server.uri(),
+            event_script: Some(
+                "test('has data', function () { expect(event.data).to.not.equal(''); });"
+                    .to_string(),
+            ),
+            settings: RequestSettings {
+                event_stream: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (response, _, test_results) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("event stream");
+        assert_eq!(response.events.len(), 2);
+        assert_eq!(response.events[1].event, "done");
+        assert_eq!(response.events[1].id.as_deref(), Some("1"));
+        assert_eq!(test_results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failing_post_response_script_keeps_earlier_tests() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(wiremock::matchers::header("accept", "text/event-stream"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("data: only\n\n", "text/event-stream"),
+            )
+            .mount(&mock_server)
+            .await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let request_data = RequestData {
+            url: mock_server.uri(),
+            event_script: Some(
+                "test('event', function () { expect(event.data).to.equal('only'); });"
+                    .to_string(),
+            ),
+            post_response_script: Some(
+                "test('response', function () { expect(1).to.equal(1); }); throw new Error('boom');"
+                    .to_string(),
+            ),
+            settings: RequestSettings {
+                event_stream: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let error = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect_err("the post-response script throws");
+        assert_eq!(error.summary, "Post-response script execution failed");
+        let names: Vec<_> = error.tests.iter().map(|test| test.name.as_str()).collect();
+        assert_eq!(names, vec!["event", "response"]);
+    }
 }
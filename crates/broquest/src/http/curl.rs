@@ -1,4 +1,6 @@
-use crate::domain::{HttpMethod, KeyValuePair, RequestData};
+use crate::domain::{
+    HttpMethod, HttpVersion, KeyValuePair, MultipartKind, MultipartPart, RequestData,
+};
 
 /// Parsed result from a cURL command
 pub struct ParsedCurl {
@@ -6,8 +8,13 @@ pub struct ParsedCurl {
     pub url: String,
     pub headers: Vec<KeyValuePair>,
     pub body: Option<String>,
+    /// File sent as the body by `--data-binary @file` or `--upload-file`
+    pub body_file: Option<String>,
     pub basic_auth: Option<(String, String)>,
-    pub is_multipart: bool,
+    /// Parts given by `-F`
+    pub multipart: Option<Vec<MultipartPart>>,
+    /// Given by `--http1.1`, `--http2` or `--http2-prior-knowledge`
+    pub http_version: Option<HttpVersion>,
 }
 
 /// Attempt to parse a string as a cURL command.
@@ -27,9 +34,11 @@ pub fn parse_curl(input: &str) -> Option<ParsedCurl> {
     let mut url: Option<String> = None;
     let mut headers: Vec<KeyValuePair> = Vec::new();
     let mut body: Option<String> = None;
+    let mut body_file: Option<String> = None;
     let mut basic_auth: Option<(String, String)> = None;
     let mut is_data_get = false;
-    let mut is_multipart = false;
+    let mut multipart: Option<Vec<MultipartPart>> = None;
+    let mut http_version: Option<HttpVersion> = None;
     let mut tokens = tokens.into_iter().peekable();
 
     // Skip "curl" itself
@@ -39,7 +48,7 @@ pub fn parse_curl(input: &str) -> Option<ParsedCurl> {
         match token.as_str() {
             "-X" | "--request" => {
                 if let Some(value) = tokens.next() {
-                    method = Some(parse_http_method(&value));
+                    method = Some(HttpMethod::parse(&value));
                 }
             }
             "-H" | "--header" => {
@@ -53,30 +62,38 @@ pub fn parse_curl(input: &str) -> Option<ParsedCurl> {
                     });
                 }
             }
-            "-d" | "--data" | "--data-raw" | "--data-binary" => {
+            "--data-binary" => {
                 if let Some(value) = tokens.next() {
-                    body = Some(value);
+                    match value.strip_prefix('@') {
+                        Some(file) => body_file = Some(file.to_string()),
+                        None => body = Some(value),
+                    }
                     if method.is_none() {
                         method = Some(HttpMethod::Post);
                     }
                 }
             }
-            "-F" | "--form" => {
+            "-T" | "--upload-file" => {
+                if let Some(value) = tokens.next() {
+                    body_file = Some(value);
+                    if method.is_none() {
+                        method = Some(HttpMethod::Put);
+                    }
+                }
+            }
+            "-d" | "--data" | "--data-raw" => {
                 if let Some(value) = tokens.next() {
                     body = Some(value);
-                    is_multipart = true;
                     if method.is_none() {
                         method = Some(HttpMethod::Post);
                     }
-                    if !headers
-                        .iter()
-                        .any(|h| h.key.to_lowercase() == "content-type")
-                    {
-                        headers.push(KeyValuePair {
-                            key: "Content-Type".to_string(),
-                            value: "multipart/form-data".to_string(),
-                            enabled: true,
-                        });
+                }
+            }
+            "-F" | "--form" => {
+                if let Some(part) = tokens.next().as_deref().and_then(parse_form_part) {
+                    multipart.get_or_insert_default().push(part);
+                    if method.is_none() {
+                        method = Some(HttpMethod::Post);
                     }
                 }
             }
@@ -97,6 +114,9 @@ pub fn parse_curl(input: &str) -> Option<ParsedCurl> {
             "-G" | "--get" => {
                 is_data_get = true;
             }
+            "--http1.1" => http_version = Some(HttpVersion::Http1),
+            "--http2" => http_version = Some(HttpVersion::Negotiate),
+            "--http2-prior-knowledge" => http_version = Some(HttpVersion::Http2PriorKnowledge),
             "-k" | "--insecure" | "--compressed" | "-s" | "--silent" | "-S" | "--show-error"
             | "-v" | "--verbose" | "-L" | "--location" | "-i" | "--include" | "-I" | "--head" => {}
             _ => {
@@ -121,8 +141,10 @@ pub fn parse_curl(input: &str) -> Option<ParsedCurl> {
         url,
         headers,
         body,
+        body_file,
         basic_auth,
-        is_multipart,
+        multipart,
+        http_version,
     })
 }
 
@@ -131,6 +153,9 @@ pub fn to_curl(request_data: &RequestData) -> String {
     let mut parts = Vec::new();
     parts.push("curl".to_string());
     parts.push(format!("-X {}", request_data.method.as_str()));
+    if let Some(http_version) = request_data.settings.http_version {
+        parts.push(curl_version_flag(http_version).to_string());
+    }
 
     // Add headers (only enabled ones)
     for header in &request_data.headers {
@@ -144,12 +169,20 @@ pub fn to_curl(request_data: &RequestData) -> String {
     }
 
     // Add body
-    if !request_data.body.is_empty()
-        && matches!(
-            request_data.method,
-            HttpMethod::Post | HttpMethod::Put | HttpMethod::Patch
-        )
-    {
+    let has_body = request_data.method.allows_body();
+    if let Some(form_parts) = request_data.multipart.as_ref().filter(|_| has_body) {
+        for part in form_parts
+            .iter()
+            .filter(|part| part.enabled && !part.name.is_empty())
+        {
+            parts.push(format!(
+                "-F '{}'",
+                escape_single_quotes(&form_argument(part))
+            ));
+        }
+    } else if let Some(file) = request_data.body_file.as_ref().filter(|_| has_body) {
+        parts.push(format!("--data-binary '@{}'", escape_single_quotes(file)));
+    } else if !request_data.body.is_empty() && has_body {
         parts.push(format!("-d '{}'", escape_single_quotes(&request_data.body)));
     }
 
@@ -159,16 +192,52 @@ pub fn to_curl(request_data: &RequestData) -> String {
     parts.join(" \\\n  ")
 }
 
-fn parse_http_method(s: &str) -> HttpMethod {
-    match s.to_uppercase().as_str() {
-        "GET" => HttpMethod::Get,
-        "POST" => HttpMethod::Post,
-        "PUT" => HttpMethod::Put,
-        "DELETE" => HttpMethod::Delete,
-        "PATCH" => HttpMethod::Patch,
-        "HEAD" => HttpMethod::Head,
-        "OPTIONS" => HttpMethod::Options,
-        _ => HttpMethod::Get,
+fn curl_version_flag(http_version: HttpVersion) -> &'static str {
+    match http_version {
+        HttpVersion::Negotiate => "--http2",
+        HttpVersion::Http1 => "--http1.1",
+        HttpVersion::Http2PriorKnowledge => "--http2-prior-knowledge",
+    }
+}
+
+/// A part given by `-F name=value` or `-F name=@file;type=...;filename=...`
+fn parse_form_part(argument: &str) -> Option<MultipartPart> {
+    let (name, value) = argument.split_once('=')?;
+    let mut part = MultipartPart {
+        name: name.to_string(),
+        value: value.to_string(),
+        enabled: true,
+        ..Default::default()
+    };
+    if let Some(file) = value.strip_prefix('@') {
+        let mut attributes = file.split(';');
+        part.kind = MultipartKind::File;
+        part.value = attributes.next().unwrap_or_default().to_string();
+        for attribute in attributes {
+            match attribute.split_once('=') {
+                Some(("type", content_type)) => part.content_type = content_type.to_string(),
+                Some(("filename", filename)) => part.filename = filename.to_string(),
+                _ => {}
+            }
+        }
+    }
+    Some(part)
+}
+
+/// The `-F` argument of a part
+fn form_argument(part: &MultipartPart) -> String {
+    match part.kind {
+        MultipartKind::Text => format!("{}={}", part.name, part.value),
+        MultipartKind::File => {
+            let mut argument = format!("{}=@{}", part.name, part.value);
+            if !part.content_type.is_empty() {
+                argument.push_str(&format!(";type={}", part.content_type));
+            }
+            if !part.filename.is_empty() {
+                argument.push_str(&format!(";filename={}", part.filename));
+            }
+            argument
+        }
     }
 }
 
@@ -335,6 +404,55 @@ mod tests {
         assert_eq!(result.url, "https://example.com");
     }
 
+    #[test]
+    fn test_parse_body_file() {
+        let result =
+            parse_curl("curl --data-binary @archive.zip https://example.com/upload").unwrap();
+        assert_eq!(result.method, HttpMethod::Post);
+        assert_eq!(result.body_file.as_deref(), Some("archive.zip"));
+        assert!(result.body.is_none());
+
+        let result = parse_curl("curl -T logo.png https://storage.example.com/logo.png").unwrap();
+        assert_eq!(result.method, HttpMethod::Put);
+        assert_eq!(result.body_file.as_deref(), Some("logo.png"));
+
+        let request = RequestData {
+            method: HttpMethod::Put,
+            url: "https://storage.example.com/logo.png".to_string(),
+            body_file: Some("fixtures/logo.png".to_string()),
+            ..Default::default()
+        };
+        assert!(to_curl(&request).contains("--data-binary '@fixtures/logo.png'"));
+    }
+
+    #[test]
+    fn test_parse_and_write_form_parts() {
+        let result = parse_curl(
+            "curl -F 'caption=Hello' -F 'photo=@/tmp/me.jpg;type=image/jpeg;filename=avatar.jpg' https://example.com/upload",
+        )
+        .unwrap();
+        assert_eq!(result.method, HttpMethod::Post);
+        assert!(result.body.is_none());
+        let parts = result.multipart.expect("multipart body");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].kind, MultipartKind::Text);
+        assert_eq!(parts[0].value, "Hello");
+        assert_eq!(parts[1].kind, MultipartKind::File);
+        assert_eq!(parts[1].value, "/tmp/me.jpg");
+        assert_eq!(parts[1].content_type, "image/jpeg");
+        assert_eq!(parts[1].filename, "avatar.jpg");
+
+        let request = RequestData {
+            method: HttpMethod::Post,
+            url: "https://example.com/upload".to_string(),
+            multipart: Some(parts),
+            ..Default::default()
+        };
+        let curl = to_curl(&request);
+        assert!(curl.contains("-F 'caption=Hello'"));
+        assert!(curl.contains("-F 'photo=@/tmp/me.jpg;type=image/jpeg;filename=avatar.jpg'"));
+    }
+
     #[test]
     fn test_not_curl_returns_none() {
         assert!(parse_curl("https://example.com").is_none());
@@ -388,6 +506,36 @@ mod tests {
         assert!(!curl.contains("X-Disabled"));
     }
 
+    #[test]
+    fn test_custom_method_and_get_body() {
+        let result = parse_curl("curl -X purge https://cdn.example.com/logo.png").unwrap();
+        assert_eq!(result.method, HttpMethod::Custom("PURGE".to_string()));
+
+        let request = RequestData {
+            method: HttpMethod::Get,
+            url: "https://search.example.com/_search".to_string(),
+            body: r#"{"query": {"match_all": {}}}"#.to_string(),
+            ..Default::default()
+        };
+        assert!(to_curl(&request).contains("-d '{\"query\": {\"match_all\": {}}}'"));
+    }
+
+    #[test]
+    fn test_http_version_flags() {
+        let result = parse_curl("curl --http2-prior-knowledge http://gateway:8080/health").unwrap();
+        assert_eq!(result.http_version, Some(HttpVersion::Http2PriorKnowledge));
+        let result = parse_curl("curl --http1.1 https://example.com").unwrap();
+        assert_eq!(result.http_version, Some(HttpVersion::Http1));
+
+        let mut request = RequestData {
+            url: "https://example.com".to_string(),
+            ..Default::default()
+        };
+        assert!(!to_curl(&request).contains("--http"));
+        request.settings.http_version = Some(HttpVersion::Http1);
+        assert!(to_curl(&request).contains("--http1.1"));
+    }
+
     #[test]
     fn test_roundtrip() {
         let original = "curl -X PUT https://api.example.com/items/1 -H 'Content-Type: application/json' -d '{\"name\": \"test\"}'";
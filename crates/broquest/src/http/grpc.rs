@@ -0,0 +1,760 @@
+//! gRPC calls. Messages are described at runtime, by `.proto` files of the
+//! collection or by the server's reflection service, and written as JSON.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr as _;
+use std::time::{Duration, Instant};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use prost::Message as _;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, MethodDescriptor,
+    SerializeOptions,
+};
+use prost_types::FileDescriptorProto;
+use serde_json::{Value, json};
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder, ProstCodec};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{KeyAndValueRef, MetadataKey, MetadataMap, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::{ServerReflectionRequest, ServerReflectionResponse};
+
+use super::HttpError;
+use crate::domain::{GrpcStatus, KeyValuePair};
+
+/// Services of a gRPC endpoint and the messages they exchange
+#[derive(Debug, Clone)]
+pub struct GrpcServices {
+    pool: DescriptorPool,
+}
+
+impl GrpcServices {
+    /// Compile `.proto` files. Imports are looked up next to each file and in
+    /// the collection directory, which relative paths start from.
+    pub(super) fn from_files(files: &[String], collection_dir: &Path) -> Result<Self, HttpError> {
+        let files: Vec<PathBuf> = files.iter().map(|file| collection_dir.join(file)).collect();
+        let mut includes: Vec<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+        includes.push(collection_dir.to_path_buf());
+        includes.dedup();
+
+        let file_set = protox::compile(&files, &includes)
+            .map_err(|e| HttpError::new("Invalid .proto files", e.to_string()))?;
+        let pool = DescriptorPool::from_file_descriptor_set(file_set)
+            .map_err(|e| HttpError::new("Invalid .proto files", e.to_string()))?;
+        Ok(Self { pool })
+    }
+
+    /// Full names of the methods that can be called, `package.Service/Method`
+    pub fn methods(&self) -> Vec<String> {
+        self.pool
+            .services()
+            .filter(|service| !service.full_name().starts_with("grpc.reflection."))
+            .flat_map(|service| {
+                service
+                    .methods()
+                    .map(|method| format!("{}/{}", service.full_name(), method.name()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn method(&self, name: &str) -> Option<MethodDescriptor> {
+        let (service, method) = name.trim_start_matches('/').split_once('/')?;
+        self.pool
+            .get_service_by_name(service)?
+            .methods()
+            .find(|m| m.name() == method)
+    }
+
+    /// A JSON request message for `method` with every field set to a sample
+    /// value
+    pub fn sample_request(&self, method: &str) -> Option<String> {
+        let method = self.method(method)?;
+        let sample = sample_message(&method.input(), &mut Vec::new());
+        serde_json::to_string_pretty(&sample).ok()
+    }
+}
+
+/// Sample value of a message: its fields with sample values, the first one of
+/// each `oneof`. Messages already being expanded are left empty so recursive
+/// types end.
+fn sample_message(message: &MessageDescriptor, expanding: &mut Vec<String>) -> Value {
+    if let Some(value) = well_known_sample(message.full_name()) {
+        return value;
+    }
+    let mut fields = serde_json::Map::new();
+    if expanding.iter().any(|name| name == message.full_name()) {
+        return Value::Object(fields);
+    }
+    expanding.push(message.full_name().to_string());
+
+    for field in message.fields() {
+        if let Some(oneof) = field.containing_oneof()
+            && !oneof.is_synthetic()
+            && oneof
+                .fields()
+                .next()
+                .is_some_and(|first| first.number() != field.number())
+        {
+            continue;
+        }
+
+        let value = if field.is_map() {
+            let Kind::Message(entry) = field.kind() else {
+                continue;
+            };
+            let key = match entry.map_entry_key_field().kind() {
+                Kind::String => "key".to_string(),
+                Kind::Bool => "false".to_string(),
+                _ => "0".to_string(),
+            };
+            let mut map = serde_json::Map::new();
+            map.insert(key, sample_value(&entry.map_entry_value_field(), expanding));
+            Value::Object(map)
+        } else if field.is_list() {
+            Value::Array(vec![sample_value(&field, expanding)])
+        } else {
+            sample_value(&field, expanding)
+        };
+        fields.insert(field.json_name().to_string(), value);
+    }
+
+    expanding.pop();
+    Value::Object(fields)
+}
+
+fn sample_value(field: &FieldDescriptor, expanding: &mut Vec<String>) -> Value {
+    match field.kind() {
+        Kind::Double | Kind::Float => json!(0.0),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 | Kind::Uint32 | Kind::Fixed32 => json!(0),
+        // 64-bit integers are strings in the JSON mapping
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 | Kind::Uint64 | Kind::Fixed64 => json!("0"),
+        Kind::Bool => json!(false),
+        Kind::String | Kind::Bytes => json!(""),
+        Kind::Enum(enum_type) => enum_type
+            .values()
+            .next()
+            .map(|value| json!(value.name()))
+            .unwrap_or(Value::Null),
+        Kind::Message(message) => sample_message(&message, expanding),
+    }
+}
+
+/// Well-known types have a JSON form of their own
+fn well_known_sample(full_name: &str) -> Option<Value> {
+    let value = match full_name {
+        "google.protobuf.Timestamp" => json!("1970-01-01T00:00:00Z"),
+        "google.protobuf.Duration" => json!("0s"),
+        "google.protobuf.Empty" | "google.protobuf.Struct" => json!({}),
+        "google.protobuf.Value" => Value::Null,
+        "google.protobuf.ListValue" => json!([]),
+        "google.protobuf.FieldMask" | "google.protobuf.StringValue" => json!(""),
+        "google.protobuf.BytesValue" => json!(""),
+        "google.protobuf.BoolValue" => json!(false),
+        "google.protobuf.Int32Value" | "google.protobuf.UInt32Value" => json!(0),
+        "google.protobuf.Int64Value" | "google.protobuf.UInt64Value" => json!("0"),
+        "google.protobuf.DoubleValue" | "google.protobuf.FloatValue" => json!(0.0),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Encodes request messages and decodes response messages known only by
+/// their descriptors
+#[derive(Clone)]
+struct DynamicCodec {
+    response: MessageDescriptor,
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = Self;
+    type Decoder = Self;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = tonic::Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|e| tonic::Status::internal(e.to_string()))
+    }
+}
+
+impl Decoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = tonic::Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.response.clone(), src)
+            .map(Some)
+            .map_err(|e| tonic::Status::internal(e.to_string()))
+    }
+}
+
+/// Address of the server of a request URL: `grpc://` and `http://` are plain
+/// text, `grpcs://` and `https://` use TLS. A path is ignored.
+pub(super) fn endpoint_url(url: &str) -> Result<(String, bool), HttpError> {
+    let url = url.trim();
+    let (tls, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => match scheme.to_ascii_lowercase().as_str() {
+            "grpc" | "http" => (false, rest),
+            "grpcs" | "https" => (true, rest),
+            _ => {
+                return Err(HttpError::new(
+                    "Invalid gRPC URL",
+                    format!("Unsupported scheme '{}', use grpc:// or grpcs://", scheme),
+                ));
+            }
+        },
+        None => (false, url),
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    if authority.is_empty() {
+        return Err(HttpError::new("Invalid gRPC URL", "The URL has no host"));
+    }
+    let scheme = if tls { "https" } else { "http" };
+    Ok((format!("{}://{}", scheme, authority), tls))
+}
+
+/// Open a channel to the server of `url`
+pub(super) async fn connect(
+    url: &str,
+    tls: Option<ClientTlsConfig>,
+    timeout: Duration,
+) -> Result<Channel, HttpError> {
+    let endpoint = Endpoint::from_shared(url.to_string())
+        .map_err(|e| HttpError::new("Invalid gRPC URL", e.to_string()))?
+        .connect_timeout(timeout);
+    let endpoint = match tls {
+        Some(tls) => endpoint
+            .tls_config(tls)
+            .map_err(|e| HttpError::new("Invalid TLS settings", e.to_string()))?,
+        None => endpoint,
+    };
+    endpoint.connect().await.map_err(|e| {
+        let details = match std::error::Error::source(&e) {
+            Some(source) => format!("Connection failed - {}: {}", e, source),
+            None => format!("Connection failed - {}", e),
+        };
+        HttpError::new("Request failed: couldn't connect", details)
+    })
+}
+
+/// Ask the server's reflection service for its services and the files
+/// describing them
+pub(super) async fn reflect(
+    channel: Channel,
+    metadata: &[KeyValuePair],
+) -> Result<GrpcServices, HttpError> {
+    let mut headers = MetadataMap::new();
+    insert_metadata(&mut headers, metadata)?;
+    let mut client = tonic::client::Grpc::new(channel);
+
+    // Servers that predate v1 only speak v1alpha, whose messages are the same
+    let list_services = MessageRequest::ListServices(String::new());
+    let mut path = REFLECTION_V1;
+    let mut listed = reflection_call(&mut client, path, &headers, list_services.clone()).await;
+    if matches!(&listed, Err(status) if status.code() == tonic::Code::Unimplemented) {
+        path = REFLECTION_V1ALPHA;
+        listed = reflection_call(&mut client, path, &headers, list_services).await;
+    }
+    let services = match reflection_response(listed)? {
+        MessageResponse::ListServicesResponse(list) => list.service,
+        _ => return Err(unexpected_reflection_response()),
+    };
+
+    let mut pending: Vec<MessageRequest> = services
+        .into_iter()
+        .filter(|service| !service.name.starts_with("grpc.reflection."))
+        .map(|service| MessageRequest::FileContainingSymbol(service.name))
+        .collect();
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    while let Some(request) = pending.pop() {
+        let response = reflection_call(&mut client, path, &headers, request).await;
+        let MessageResponse::FileDescriptorResponse(response) = reflection_response(response)?
+        else {
+            return Err(unexpected_reflection_response());
+        };
+        for bytes in response.file_descriptor_proto {
+            let file = FileDescriptorProto::decode(bytes.as_slice())
+                .map_err(|e| HttpError::new("Server reflection failed", e.to_string()))?;
+            if files.contains_key(file.name()) {
+                continue;
+            }
+            // Files the server sends are followed by the ones they import
+            for dependency in &file.dependency {
+                if !files.contains_key(dependency) {
+                    pending.push(MessageRequest::FileByFilename(dependency.clone()));
+                }
+            }
+            files.insert(file.name().to_string(), file);
+        }
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files.into_values())
+        .map_err(|e| HttpError::new("Server reflection failed", e.to_string()))?;
+    Ok(GrpcServices { pool })
+}
+
+const REFLECTION_V1: &str = "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo";
+const REFLECTION_V1ALPHA: &str = "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo";
+
+/// Send one request to the reflection service at `path` and read its answer
+async fn reflection_call(
+    client: &mut tonic::client::Grpc<Channel>,
+    path: &'static str,
+    metadata: &MetadataMap,
+    request: MessageRequest,
+) -> Result<Option<MessageResponse>, tonic::Status> {
+    let message = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(request),
+    };
+    let mut request = tonic::Request::new(futures_util::stream::iter([message]));
+    *request.metadata_mut() = metadata.clone();
+
+    client
+        .ready()
+        .await
+        .map_err(|e| tonic::Status::unavailable(e.to_string()))?;
+    let codec = ProstCodec::<ServerReflectionRequest, ServerReflectionResponse>::default();
+    let response = client
+        .streaming(request, PathAndQuery::from_static(path), codec)
+        .await?
+        .into_inner()
+        .message()
+        .await?;
+    Ok(response.and_then(|response| response.message_response))
+}
+
+fn reflection_response(
+    response: Result<Option<MessageResponse>, tonic::Status>,
+) -> Result<MessageResponse, HttpError> {
+    let response = response
+        .map_err(|status| {
+            HttpError::new(
+                "Server reflection failed",
+                format!(
+                    "The server doesn't answer reflection requests ({}): {}",
+                    status.code(),
+                    status.message()
+                ),
+            )
+        })?
+        .ok_or_else(unexpected_reflection_response)?;
+
+    match response {
+        MessageResponse::ErrorResponse(error) => Err(HttpError::new(
+            "Server reflection failed",
+            error.error_message,
+        )),
+        response => Ok(response),
+    }
+}
+
+fn unexpected_reflection_response() -> HttpError {
+    HttpError::new(
+        "Server reflection failed",
+        "The server sent an unexpected reflection response",
+    )
+}
+
+/// Add request metadata. Values of `-bin` keys are base64.
+fn insert_metadata(map: &mut MetadataMap, metadata: &[KeyValuePair]) -> Result<(), HttpError> {
+    let invalid = |pair: &KeyValuePair| {
+        HttpError::new(
+            "Invalid metadata",
+            format!("'{}' isn't valid gRPC metadata", pair.key),
+        )
+    };
+    for pair in metadata
+        .iter()
+        .filter(|pair| pair.enabled && !pair.key.is_empty())
+    {
+        let key = pair.key.to_ascii_lowercase();
+        if key.ends_with("-bin") {
+            let key = MetadataKey::from_bytes(key.as_bytes()).map_err(|_| invalid(pair))?;
+            let value = STANDARD
+                .decode(pair.value.trim())
+                .map_err(|_| invalid(pair))?;
+            map.append_bin(key, MetadataValue::from_bytes(&value));
+        } else {
+            let key = MetadataKey::from_bytes(key.as_bytes()).map_err(|_| invalid(pair))?;
+            let value = MetadataValue::try_from(pair.value.as_str()).map_err(|_| invalid(pair))?;
+            map.append(key, value);
+        }
+    }
+    Ok(())
+}
+
+fn metadata_pairs(map: &MetadataMap) -> Vec<KeyValuePair> {
+    map.iter()
+        .map(|entry| {
+            let (key, value) = match entry {
+                KeyAndValueRef::Ascii(key, value) => (
+                    key.to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                ),
+                KeyAndValueRef::Binary(key, value) => (
+                    key.to_string(),
+                    STANDARD.encode(value.to_bytes().unwrap_or_default()),
+                ),
+            };
+            KeyValuePair {
+                key,
+                value,
+                enabled: true,
+            }
+        })
+        .collect()
+}
+
+/// What came back from a call
+pub(super) struct GrpcCall {
+    /// The response message as JSON, or an array of them for a
+    /// server-streaming method
+    pub(super) body: String,
+    /// Bytes of the response messages
+    pub(super) size: usize,
+    /// Response metadata sent before the messages
+    pub(super) headers: Vec<KeyValuePair>,
+    pub(super) status: GrpcStatus,
+    /// Until the response metadata arrived
+    pub(super) ttfb: Duration,
+}
+
+/// Call `method` with a JSON request message, reading response messages
+/// until the server ends the call
+pub(super) async fn call(
+    channel: Channel,
+    services: &GrpcServices,
+    method: &str,
+    message: &str,
+    metadata: &[KeyValuePair],
+) -> Result<GrpcCall, HttpError> {
+    let descriptor = services.method(method).ok_or_else(|| {
+        HttpError::new(
+            "Unknown gRPC method",
+            format!("The service has no method '{}'", method),
+        )
+    })?;
+    if descriptor.is_client_streaming() {
+        return Err(HttpError::new(
+            "Unsupported gRPC method",
+            format!(
+                "'{}' streams requests, only unary and server-streaming methods can be called",
+                method
+            ),
+        ));
+    }
+
+    let message = if message.trim().is_empty() {
+        "{}"
+    } else {
+        message
+    };
+    let mut deserializer = serde_json::Deserializer::from_str(message);
+    let request_message = DynamicMessage::deserialize(descriptor.input(), &mut deserializer)
+        .and_then(|request| deserializer.end().map(|_| request))
+        .map_err(|e| HttpError::new("Invalid request message", e.to_string()))?;
+
+    let mut request = tonic::Request::new(request_message);
+    insert_metadata(request.metadata_mut(), metadata)?;
+    let path = PathAndQuery::from_str(&format!(
+        "/{}/{}",
+        descriptor.parent_service().full_name(),
+        descriptor.name()
+    ))
+    .map_err(|e| HttpError::new("Unknown gRPC method", e.to_string()))?;
+
+    let mut grpc = tonic::client::Grpc::new(channel);
+    grpc.ready()
+        .await
+        .map_err(|e| HttpError::new("Request failed: couldn't connect", e.to_string()))?;
+
+    // A unary call is read like a stream of one message, so its trailers
+    // stay apart from the response metadata
+    let start = Instant::now();
+    let codec = DynamicCodec {
+        response: descriptor.output(),
+    };
+    let mut messages = Vec::new();
+    let (headers, status, ttfb) = match grpc.server_streaming(request, path, codec).await {
+        Ok(response) => {
+            let ttfb = start.elapsed();
+            let headers = metadata_pairs(response.metadata());
+            let mut stream = response.into_inner();
+            let status = loop {
+                match stream.message().await {
+                    Ok(Some(message)) => messages.push(message),
+                    Ok(None) => {
+                        let trailers = match stream.trailers().await {
+                            Ok(trailers) => trailers.as_ref().map(metadata_pairs),
+                            Err(status) => break status_of(&status),
+                        };
+                        break GrpcStatus {
+                            code: 0,
+                            message: String::new(),
+                            trailers: trailers.unwrap_or_default(),
+                        };
+                    }
+                    Err(status) => break status_of(&status),
+                }
+            };
+            (headers, status, ttfb)
+        }
+        // The server answered with trailers only
+        Err(status) => (Vec::new(), status_of(&status), start.elapsed()),
+    };
+
+    let size = messages.iter().map(|message| message.encoded_len()).sum();
+    let options = SerializeOptions::new().skip_default_fields(false);
+    let to_json = |message: &DynamicMessage| {
+        message
+            .serialize_with_options(serde_json::value::Serializer, &options)
+            .unwrap_or_default()
+    };
+    let body = if descriptor.is_server_streaming() {
+        Value::Array(messages.iter().map(to_json).collect())
+    } else {
+        messages.first().map(to_json).unwrap_or(Value::Null)
+    };
+    let body = match body {
+        Value::Null => String::new(),
+        body => serde_json::to_string_pretty(&body).unwrap_or_default(),
+    };
+
+    Ok(GrpcCall {
+        body,
+        size,
+        headers,
+        status,
+        ttfb,
+    })
+}
+
+fn status_of(status: &tonic::Status) -> GrpcStatus {
+    GrpcStatus {
+        code: status.code() as i32,
+        message: status.message().to_string(),
+        trailers: metadata_pairs(status.metadata()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTO: &str = r#"
+syntax = "proto3";
+package shop.v1;
+
+import "google/protobuf/timestamp.proto";
+
+service Orders {
+  rpc GetOrder(GetOrderRequest) returns (Order);
+  rpc WatchOrders(WatchOrdersRequest) returns (stream Order);
+}
+
+enum Status {
+  STATUS_UNSPECIFIED = 0;
+  STATUS_SHIPPED = 1;
+}
+
+message GetOrderRequest {
+  string order_id = 1;
+  int64 customer_id = 2;
+  repeated string fields = 3;
+  map<string, int32> limits = 4;
+  oneof lookup {
+    string email = 5;
+    string phone = 6;
+  }
+}
+
+message WatchOrdersRequest {
+  Status status = 1;
+  google.protobuf.Timestamp since = 2;
+  Category category = 3;
+}
+
+message Category {
+  string name = 1;
+  Category parent = 2;
+}
+
+message Order {
+  string id = 1;
+}
+"#;
+
+    fn services() -> GrpcServices {
+        let dir = std::env::temp_dir().join(format!("broquest-grpc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        std::fs::write(dir.join("orders.proto"), PROTO).expect("write proto");
+        GrpcServices::from_files(&["orders.proto".to_string()], &dir).expect("compile")
+    }
+
+    #[test]
+    fn test_methods_from_proto_files() {
+        let services = services();
+        assert_eq!(
+            services.methods(),
+            vec![
+                "shop.v1.Orders/GetOrder".to_string(),
+                "shop.v1.Orders/WatchOrders".to_string()
+            ]
+        );
+        assert!(services.method("/shop.v1.Orders/GetOrder").is_some());
+        assert!(services.method("shop.v1.Orders/Missing").is_none());
+    }
+
+    #[test]
+    fn test_sample_requests() {
+        let services = services();
+        let sample: Value = serde_json::from_str(
+            &services
+                .sample_request("shop.v1.Orders/GetOrder")
+                .expect("sample"),
+        )
+        .expect("json");
+        assert_eq!(
+            sample,
+            json!({
+                "orderId": "",
+                "customerId": "0",
+                "fields": [""],
+                "limits": {"key": 0},
+                "email": "",
+            })
+        );
+
+        // Enums use their first value, well-known types their JSON form and
+        // recursive messages stop where they repeat
+        let sample: Value = serde_json::from_str(
+            &services
+                .sample_request("shop.v1.Orders/WatchOrders")
+                .expect("sample"),
+        )
+        .expect("json");
+        assert_eq!(
+            sample,
+            json!({
+                "status": "STATUS_UNSPECIFIED",
+                "since": "1970-01-01T00:00:00Z",
+                "category": {"name": "", "parent": {}},
+            })
+        );
+    }
+
+    #[test]
+    fn test_endpoint_url() {
+        assert_eq!(
+            endpoint_url("grpc://localhost:50051").unwrap(),
+            ("http://localhost:50051".to_string(), false)
+        );
+        assert_eq!(
+            endpoint_url("grpcs://api.example.com/ignored/path").unwrap(),
+            ("https://api.example.com".to_string(), true)
+        );
+        assert_eq!(
+            endpoint_url("localhost:50051").unwrap(),
+            ("http://localhost:50051".to_string(), false)
+        );
+        assert!(endpoint_url("ftp://example.com").is_err());
+        assert!(endpoint_url("grpc://").is_err());
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut map = MetadataMap::new();
+        insert_metadata(
+            &mut map,
+            &[
+                KeyValuePair {
+                    key: "X-Tenant".to_string(),
+                    value: "acme".to_string(),
+                    enabled: true,
+                },
+                KeyValuePair {
+                    key: "trace-bin".to_string(),
+                    value: STANDARD.encode([1, 2, 3]),
+                    enabled: true,
+                },
+                KeyValuePair {
+                    key: "x-disabled".to_string(),
+                    value: "no".to_string(),
+                    enabled: false,
+                },
+            ],
+        )
+        .expect("metadata");
+        let pairs = metadata_pairs(&map);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].key, "x-tenant");
+        assert_eq!(pairs[0].value, "acme");
+        assert_eq!(pairs[1].value, STANDARD.encode([1, 2, 3]));
+
+        let invalid = [KeyValuePair {
+            key: "bad key".to_string(),
+            value: String::new(),
+            enabled: true,
+        }];
+        assert!(insert_metadata(&mut MetadataMap::new(), &invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reflection_falls_back_to_v1alpha() {
+        services();
+        let dir = std::env::temp_dir().join(format!("broquest-grpc-{}", std::process::id()));
+        let file_set = protox::compile([dir.join("orders.proto")], [&dir]).expect("compile");
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(&file_set.encode_to_vec())
+            .build_v1alpha()
+            .expect("reflection service");
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let url = format!("http://{}", listener.local_addr().expect("address"));
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(reflection)
+                .serve_with_incoming(incoming),
+        );
+
+        let channel = connect(&url, None, Duration::from_secs(5))
+            .await
+            .expect("connect");
+        let services = reflect(channel, &[]).await.expect("reflect");
+        assert_eq!(
+            services.methods(),
+            vec![
+                "shop.v1.Orders/GetOrder".to_string(),
+                "shop.v1.Orders/WatchOrders".to_string()
+            ]
+        );
+    }
+}
@@ -1,15 +1,28 @@
 //! HTTP client service module
 
+mod body;
 mod client;
 pub mod curl;
+mod grpc;
 pub mod jwt;
 pub mod oauth2;
+mod script_requests;
+mod sse;
+mod timings;
+mod tls;
+mod upload;
+mod websocket;
 
 #[cfg(test)]
 mod auth_tests;
 
+pub use body::ResponseProgress;
 pub use client::*;
+pub use grpc::GrpcServices;
+pub use script_requests::{RequestScope, SavedRequests};
 use std::time::{SystemTime, UNIX_EPOCH};
+pub use upload::file_content_type;
+pub use websocket::{WebSocketConnection, WebSocketSession};
 
 /// Get the current Unix timestamp in seconds
 pub fn current_unix_timestamp() -> i64 {
@@ -1,15 +1,49 @@
 //! OAuth2 token acquisition logic
 //!
-//! Supports Client Credentials and Authorization Code grant types.
+//! Supports the Client Credentials, Authorization Code (with PKCE), Implicit,
+//! Password and Device Code grant types, and refreshing tokens.
 
-use crate::domain::OAuth2Auth;
+use crate::domain::{OAuth2Auth, OAuth2GrantType};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::Rng;
+use sha2::{Digest, Sha256};
 use smol::io::{AsyncReadExt, AsyncWriteExt};
-use smol::net::TcpListener;
+use smol::net::{TcpListener, TcpStream};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use super::{HttpError, current_unix_timestamp, is_token_expired};
 
+/// How long to wait for the user to sign in in the browser
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long a device code is valid when the server doesn't say
+const DEFAULT_DEVICE_CODE_LIFETIME: u64 = 600;
+
+/// Seconds between polls for a device code token when the server doesn't say
+const DEFAULT_DEVICE_POLL_INTERVAL: u64 = 5;
+
+const SUCCESS_PAGE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n\
+    <html><body><h1>Authorization successful!</h1>\
+    <p>You can close this window now.</p></body></html>";
+
+/// Browsers keep the token of the implicit grant in the fragment of the
+/// redirect URL, which they don't send. This page sends it back as a query.
+const FRAGMENT_PAGE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n\
+    <html><body><h1>Completing authorization...</h1><script>\
+    if (location.hash.length > 1) {\
+      location.replace(location.pathname + '?' + location.hash.substring(1));\
+    } else {\
+      document.body.innerHTML = '<h1>Authorization failed</h1><p>You can close this window and try again.</p>';\
+    }\
+    </script></body></html>";
+
+const FAILURE_PAGE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n\
+    <html><body><h1>Authorization failed</h1>\
+    <p>You can close this window and try again.</p></body></html>";
+
 /// OAuth2 token response from authorization server
 #[derive(Debug, Clone, serde::Deserialize)]
 #[allow(dead_code)]
@@ -25,31 +59,236 @@ pub struct TokenResponse {
     pub scope: Option<String>,
 }
 
+/// Error response of a token endpoint (RFC 6749 section 5.2)
+#[derive(Debug, Default, serde::Deserialize)]
+struct TokenErrorResponse {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
 /// Result of authorization code callback
-#[allow(dead_code)]
 pub struct AuthCodeResult {
     pub code: String,
     pub state: Option<String>,
 }
 
-/// Fetch a new access token using client credentials grant
-pub async fn fetch_client_credentials_token(
+/// A step of an interactive grant the user has to take
+#[derive(Debug, Clone)]
+pub enum OAuth2Prompt {
+    /// Sign in on the authorization page at this URL
+    OpenUrl(String),
+    /// Enter `user_code` at `verification_uri`, or open
+    /// `verification_uri_complete` which has it filled in. `cancel` stops
+    /// waiting for the code to be entered.
+    DeviceCode {
+        user_code: String,
+        verification_uri: String,
+        verification_uri_complete: Option<String>,
+        cancel: OAuth2Cancel,
+    },
+    /// The grant no longer waits for the user: it got a token, failed or was
+    /// cancelled
+    Done,
+}
+
+/// Lets the user give up on a grant that waits for them
+#[derive(Debug, Clone)]
+pub struct OAuth2Cancel {
+    sender: smol::channel::Sender<()>,
+    receiver: smol::channel::Receiver<()>,
+}
+
+impl OAuth2Cancel {
+    pub fn new() -> Self {
+        let (sender, receiver) = smol::channel::bounded(1);
+        Self { sender, receiver }
+    }
+
+    pub fn cancel(&self) {
+        self.sender.close();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Resolves once [`Self::cancel`] is called
+    pub async fn cancelled(&self) {
+        let _ = self.receiver.recv().await;
+    }
+}
+
+impl Default for OAuth2Cancel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Device authorization response (RFC 8628 section 3.2)
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    /// Google calls it `verification_url`
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    #[serde(default)]
+    pub interval: Option<u64>,
+}
+
+/// PKCE code verifier of an authorization code request and its S256
+/// challenge (RFC 7636)
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn generate() -> Self {
+        let verifier = random_string(64);
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+/// Tokens fetched during the session, keyed by the OAuth2 configuration
+/// they were fetched for and shared by all clones
+#[derive(Debug, Clone, Default)]
+pub struct OAuth2TokenCache(Arc<Mutex<HashMap<String, CachedToken>>>);
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<i64>,
+}
+
+impl OAuth2TokenCache {
+    /// Give `oauth` the token fetched for the same configuration, if any
+    pub fn load(&self, oauth: &mut OAuth2Auth) -> bool {
+        let Ok(tokens) = self.0.lock() else {
+            return false;
+        };
+        let Some(token) = tokens.get(&cache_key(oauth)) else {
+            return false;
+        };
+        oauth.access_token = Some(token.access_token.clone());
+        oauth.refresh_token = token.refresh_token.clone();
+        oauth.expires_at = token.expires_at;
+        true
+    }
+
+    /// Keep the token of `oauth` for the next requests with its configuration
+    pub fn store(&self, oauth: &OAuth2Auth) {
+        let Some(access_token) = oauth.access_token.clone() else {
+            return;
+        };
+        if let Ok(mut tokens) = self.0.lock() {
+            tokens.insert(
+                cache_key(oauth),
+                CachedToken {
+                    access_token,
+                    refresh_token: oauth.refresh_token.clone(),
+                    expires_at: oauth.expires_at,
+                },
+            );
+        }
+    }
+}
+
+fn cache_key(oauth: &OAuth2Auth) -> String {
+    [
+        oauth.grant_type.as_str(),
+        oauth.token_url.as_str(),
+        oauth.authorize_url.as_deref().unwrap_or_default(),
+        oauth
+            .device_authorization_url
+            .as_deref()
+            .unwrap_or_default(),
+        oauth.client_id.as_str(),
+        oauth.scope.as_deref().unwrap_or_default(),
+        oauth.audience.as_deref().unwrap_or_default(),
+        oauth.resource.as_deref().unwrap_or_default(),
+        oauth.username.as_str(),
+    ]
+    .join("\n")
+}
+
+/// Store a token response on `oauth`, keeping its refresh token unless the
+/// server issued a new one
+pub fn apply_token(oauth: &mut OAuth2Auth, token: TokenResponse) {
+    oauth.access_token = Some(token.access_token);
+    if token.refresh_token.is_some() {
+        oauth.refresh_token = token.refresh_token;
+    }
+    oauth.expires_at = token.expires_in.map(calculate_expires_at);
+}
+
+fn param(name: &str, value: impl Into<String>) -> (String, String) {
+    (name.to_string(), value.into())
+}
+
+/// The client id and, for confidential clients, the secret
+fn client_params(oauth: &OAuth2Auth) -> Vec<(String, String)> {
+    let mut params = vec![param("client_id", &oauth.client_id)];
+    if !oauth.client_secret.is_empty() {
+        params.push(param("client_secret", &oauth.client_secret));
+    }
+    params
+}
+
+fn scope_param(oauth: &OAuth2Auth) -> Option<(String, String)> {
+    oauth
+        .scope
+        .as_ref()
+        .filter(|scope| !scope.is_empty())
+        .map(|scope| param("scope", scope))
+}
+
+/// The audience and resource the token is for
+fn target_params(oauth: &OAuth2Auth) -> Vec<(String, String)> {
+    [("audience", &oauth.audience), ("resource", &oauth.resource)]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            value
+                .as_ref()
+                .filter(|value| !value.is_empty())
+                .map(|value| param(name, value))
+        })
+        .collect()
+}
+
+fn extra_params(oauth: &OAuth2Auth) -> Vec<(String, String)> {
+    oauth
+        .extra_params
+        .iter()
+        .filter(|p| p.enabled && !p.key.is_empty())
+        .map(|p| (p.key.clone(), p.value.clone()))
+        .collect()
+}
+
+/// Post a token request with the target and extra params of `oauth`
+async fn request_token(
     client: &reqwest::Client,
     oauth: &OAuth2Auth,
+    mut params: Vec<(String, String)>,
+    failure: &str,
 ) -> Result<TokenResponse, HttpError> {
-    let mut params = vec![
-        ("grant_type", "client_credentials".to_string()),
-        ("client_id", oauth.client_id.clone()),
-        ("client_secret", oauth.client_secret.clone()),
-    ];
-
-    if let Some(scope) = &oauth.scope {
-        params.push(("scope", scope.clone()));
-    }
+    params.extend(target_params(oauth));
+    params.extend(extra_params(oauth));
 
     let response = async_compat::Compat::new(client.post(&oauth.token_url).form(&params).send())
         .await
-        .map_err(|e| HttpError::new("OAuth2 token request failed", e.to_string()))?;
+        .map_err(|e| HttpError::new(failure, e.to_string()))?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -57,7 +296,7 @@ pub async fn fetch_client_credentials_token(
             .await
             .unwrap_or_default();
         return Err(HttpError::new(
-            "OAuth2 token request failed",
+            failure,
             format!("Status {}: {}", status, body),
         ));
     }
@@ -67,14 +306,57 @@ pub async fn fetch_client_credentials_token(
         .map_err(|e| HttpError::new("Failed to parse token response", e.to_string()))
 }
 
-/// Spawn a local HTTP server to receive OAuth2 callback
-/// Returns the server address and a future that resolves when callback is received
+/// Fetch a new access token using client credentials grant
+pub async fn fetch_client_credentials_token(
+    client: &reqwest::Client,
+    oauth: &OAuth2Auth,
+) -> Result<TokenResponse, HttpError> {
+    let mut params = vec![param("grant_type", "client_credentials")];
+    params.extend(client_params(oauth));
+    params.extend(scope_param(oauth));
+    request_token(client, oauth, params, "OAuth2 token request failed").await
+}
+
+/// Fetch a new access token using the resource owner password grant
+pub async fn fetch_password_token(
+    client: &reqwest::Client,
+    oauth: &OAuth2Auth,
+) -> Result<TokenResponse, HttpError> {
+    let mut params = vec![
+        param("grant_type", "password"),
+        param("username", &oauth.username),
+        param("password", &oauth.password),
+    ];
+    params.extend(client_params(oauth));
+    params.extend(scope_param(oauth));
+    request_token(client, oauth, params, "OAuth2 token request failed").await
+}
+
+/// Get a new access token with a refresh token
+pub async fn refresh_access_token(
+    client: &reqwest::Client,
+    oauth: &OAuth2Auth,
+    refresh_token: &str,
+) -> Result<TokenResponse, HttpError> {
+    let mut params = vec![
+        param("grant_type", "refresh_token"),
+        param("refresh_token", refresh_token),
+    ];
+    params.extend(client_params(oauth));
+    params.extend(scope_param(oauth));
+    request_token(client, oauth, params, "OAuth2 token refresh failed").await
+}
+
+/// Spawn a local HTTP server to receive the OAuth2 redirect, which must
+/// carry the `expected` parameter. Returns the server address and a future
+/// that resolves with the redirect's parameters.
 pub async fn spawn_callback_server(
     port: Option<u16>,
+    expected: &'static str,
 ) -> Result<
     (
         SocketAddr,
-        impl std::future::Future<Output = Result<AuthCodeResult, HttpError>>,
+        impl std::future::Future<Output = Result<HashMap<String, String>, HttpError>>,
     ),
     HttpError,
 > {
@@ -87,64 +369,57 @@ pub async fn spawn_callback_server(
         .map_err(|e| HttpError::new("Failed to get server address", e.to_string()))?;
 
     let future = async move {
-        let (mut stream, _) = listener
-            .accept()
-            .await
-            .map_err(|e| HttpError::new("Failed to accept connection", e.to_string()))?;
+        loop {
+            let (mut stream, _) = listener
+                .accept()
+                .await
+                .map_err(|e| HttpError::new("Failed to accept connection", e.to_string()))?;
 
-        let mut buffer = vec![0u8; 4096];
-        let n = stream
-            .read(&mut buffer)
-            .await
-            .map_err(|e| HttpError::new("Failed to read request", e.to_string()))?;
+            let mut buffer = vec![0u8; 4096];
+            let n = stream
+                .read(&mut buffer)
+                .await
+                .map_err(|e| HttpError::new("Failed to read request", e.to_string()))?;
 
-        let request = String::from_utf8_lossy(&buffer[..n]);
-        let path = extract_path(&request)?;
+            let request = String::from_utf8_lossy(&buffer[..n]);
+            let path = extract_path(&request)?;
+            let mut params = parse_query(path.split_once('?').map(|(_, q)| q).unwrap_or(""));
 
-        let query = path.split('?').nth(1).unwrap_or("");
-        let params: HashMap<&str, &str> = query
-            .split('&')
-            .filter_map(|p| {
-                let mut parts = p.splitn(2, '=');
-                Some((parts.next()?, parts.next().unwrap_or("")))
-            })
-            .collect();
+            // A redirect without a query keeps its parameters in the fragment
+            if params.is_empty() {
+                respond(&mut stream, FRAGMENT_PAGE).await;
+                continue;
+            }
 
-        if let Some(error) = params.get("error") {
-            return Err(HttpError::new(
-                "OAuth2 authorization failed",
-                params
-                    .get("error_description")
-                    .map(|d| d.to_string())
-                    .unwrap_or_else(|| error.to_string()),
-            ));
-        }
+            if let Some(error) = params.remove("error") {
+                respond(&mut stream, FAILURE_PAGE).await;
+                return Err(HttpError::new(
+                    "OAuth2 authorization failed",
+                    params.remove("error_description").unwrap_or(error),
+                ));
+            }
 
-        let code = params
-            .get("code")
-            .ok_or_else(|| {
-                HttpError::new(
-                    "OAuth2 callback missing code",
-                    "No authorization code in callback",
-                )
-            })?
-            .to_string();
-
-        let state = params.get("state").map(|s| s.to_string());
-
-        let response = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n\
-            <html><body><h1>Authorization successful!</h1>\
-            <p>You can close this window now.</p></body></html>";
-        if let Err(e) = stream.write_all(response.as_bytes()).await {
-            tracing::error!("Failed to write OAuth callback response: {}", e);
+            if !params.contains_key(expected) {
+                respond(&mut stream, FAILURE_PAGE).await;
+                return Err(HttpError::new(
+                    "OAuth2 callback incomplete",
+                    format!("No {} in the callback", expected),
+                ));
+            }
+            respond(&mut stream, SUCCESS_PAGE).await;
+            return Ok(params);
         }
-
-        Ok(AuthCodeResult { code, state })
     };
 
     Ok((addr, future))
 }
 
+async fn respond(stream: &mut TcpStream, page: &str) {
+    if let Err(e) = stream.write_all(page.as_bytes()).await {
+        tracing::error!("Failed to write OAuth callback response: {}", e);
+    }
+}
+
 fn extract_path(request: &str) -> Result<String, HttpError> {
     let first_line = request
         .lines()
@@ -157,24 +432,167 @@ fn extract_path(request: &str) -> Result<String, HttpError> {
     Ok(path.to_string())
 }
 
+fn parse_query(query: &str) -> HashMap<String, String> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Let the user sign in in the browser and wait for the authorization
+/// server to redirect to a local callback server with a code. Returns the
+/// code and the redirect URI it was received at.
+pub async fn authorize_in_browser(
+    oauth: &OAuth2Auth,
+    pkce: Option<&Pkce>,
+    prompt: &impl Fn(OAuth2Prompt),
+) -> Result<(AuthCodeResult, String), HttpError> {
+    let (mut params, redirect_uri) = authorize(
+        oauth,
+        "code",
+        pkce.map(|pkce| pkce.challenge.as_str()),
+        prompt,
+    )
+    .await?;
+    let code = params.remove("code").unwrap_or_default();
+    Ok((
+        AuthCodeResult {
+            code,
+            state: params.remove("state"),
+        },
+        redirect_uri,
+    ))
+}
+
+/// Let the user sign in in the browser and take the token the
+/// authorization server redirects back with (implicit grant)
+pub async fn authorize_implicit(
+    oauth: &OAuth2Auth,
+    prompt: &impl Fn(OAuth2Prompt),
+) -> Result<TokenResponse, HttpError> {
+    let (mut params, _) = authorize(oauth, "token", None, prompt).await?;
+    Ok(TokenResponse {
+        access_token: params.remove("access_token").unwrap_or_default(),
+        token_type: params.remove("token_type").unwrap_or_default(),
+        expires_in: params
+            .remove("expires_in")
+            .and_then(|expires_in| expires_in.parse().ok()),
+        refresh_token: None,
+        scope: params.remove("scope"),
+    })
+}
+
+/// Open the authorization page asking for `response_type` and wait for the
+/// redirect to a local callback server. Returns the redirect's parameters
+/// and the redirect URI they were received at.
+async fn authorize(
+    oauth: &OAuth2Auth,
+    response_type: &str,
+    code_challenge: Option<&str>,
+    prompt: &impl Fn(OAuth2Prompt),
+) -> Result<(HashMap<String, String>, String), HttpError> {
+    let authorize_url = oauth
+        .authorize_url
+        .as_ref()
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| {
+            HttpError::new(
+                "OAuth2 authorization URL missing",
+                format!(
+                    "Authorization URL is required for {} flow",
+                    oauth.grant_type.name().to_lowercase()
+                ),
+            )
+        })?;
+
+    // A loopback redirect URL registered with the provider fixes the port
+    let loopback_redirect = oauth.redirect_url.as_ref().and_then(|redirect_url| {
+        let url = url::Url::parse(redirect_url).ok()?;
+        if !matches!(url.host_str(), Some("localhost" | "127.0.0.1")) {
+            return None;
+        }
+        Some((redirect_url.clone(), url.port()?))
+    });
+    let expected = match response_type {
+        "token" => "access_token",
+        _ => "code",
+    };
+    let (addr, callback_future) =
+        spawn_callback_server(loopback_redirect.as_ref().map(|(_, port)| *port), expected).await?;
+    let redirect_uri = loopback_redirect
+        .map(|(redirect_url, _)| redirect_url)
+        .unwrap_or_else(|| format!("http://{}", addr));
+
+    let state = random_string(32);
+    let auth_url = build_authorization_url_with_url(
+        authorize_url,
+        oauth,
+        response_type,
+        &redirect_uri,
+        &state,
+        code_challenge,
+    );
+    prompt(OAuth2Prompt::OpenUrl(auth_url));
+
+    let params = smol::future::or(callback_future, async {
+        smol::Timer::after(AUTHORIZATION_TIMEOUT).await;
+        Err(HttpError::new(
+            "OAuth2 authorization timed out",
+            "Sign-in wasn't completed in the browser within 5 minutes",
+        ))
+    })
+    .await?;
+
+    if params.get("state").map(String::as_str) != Some(state.as_str()) {
+        return Err(HttpError::new(
+            "OAuth2 authorization failed",
+            "The state returned by the authorization server doesn't match the one sent",
+        ));
+    }
+    Ok((params, redirect_uri))
+}
+
 /// Exchange authorization code for access token
 pub async fn exchange_auth_code(
     client: &reqwest::Client,
     oauth: &OAuth2Auth,
     code: &str,
     redirect_uri: &str,
+    code_verifier: Option<&str>,
 ) -> Result<TokenResponse, HttpError> {
-    let params = vec![
-        ("grant_type", "authorization_code".to_string()),
-        ("code", code.to_string()),
-        ("redirect_uri", redirect_uri.to_string()),
-        ("client_id", oauth.client_id.clone()),
-        ("client_secret", oauth.client_secret.clone()),
+    let mut params = vec![
+        param("grant_type", "authorization_code"),
+        param("code", code),
+        param("redirect_uri", redirect_uri),
     ];
+    params.extend(client_params(oauth));
+    if let Some(code_verifier) = code_verifier {
+        params.push(param("code_verifier", code_verifier));
+    }
+    request_token(client, oauth, params, "OAuth2 token exchange failed").await
+}
 
-    let response = async_compat::Compat::new(client.post(&oauth.token_url).form(&params).send())
+/// Ask the device authorization endpoint for a user code
+pub async fn request_device_authorization(
+    client: &reqwest::Client,
+    oauth: &OAuth2Auth,
+) -> Result<DeviceAuthorization, HttpError> {
+    let url = oauth
+        .device_authorization_url
+        .as_ref()
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| {
+            HttpError::new(
+                "OAuth2 device authorization URL missing",
+                "Device authorization URL is required for device code flow",
+            )
+        })?;
+    let mut params = client_params(oauth);
+    params.extend(scope_param(oauth));
+    params.extend(target_params(oauth));
+
+    let response = async_compat::Compat::new(client.post(url).form(&params).send())
         .await
-        .map_err(|e| HttpError::new("OAuth2 token exchange failed", e.to_string()))?;
+        .map_err(|e| HttpError::new("OAuth2 device authorization failed", e.to_string()))?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -182,35 +600,119 @@ pub async fn exchange_auth_code(
             .await
             .unwrap_or_default();
         return Err(HttpError::new(
-            "OAuth2 token exchange failed",
+            "OAuth2 device authorization failed",
             format!("Status {}: {}", status, body),
         ));
     }
 
-    async_compat::Compat::new(response.json::<TokenResponse>())
+    async_compat::Compat::new(response.json::<DeviceAuthorization>())
         .await
-        .map_err(|e| HttpError::new("Failed to parse token response", e.to_string()))
+        .map_err(|e| HttpError::new("Failed to parse device authorization", e.to_string()))
+}
+
+/// Poll the token endpoint until the user has entered the code of `device`
+pub async fn poll_device_token(
+    client: &reqwest::Client,
+    oauth: &OAuth2Auth,
+    device: &DeviceAuthorization,
+) -> Result<TokenResponse, HttpError> {
+    let lifetime = device.expires_in.unwrap_or(DEFAULT_DEVICE_CODE_LIFETIME);
+    let deadline = Instant::now() + Duration::from_secs(lifetime);
+    let mut interval = device.interval.unwrap_or(DEFAULT_DEVICE_POLL_INTERVAL);
+    let mut params = vec![
+        param("grant_type", OAuth2GrantType::DeviceCode.as_str()),
+        param("device_code", &device.device_code),
+    ];
+    params.extend(client_params(oauth));
+    params.extend(extra_params(oauth));
+
+    loop {
+        smol::Timer::after(Duration::from_secs(interval)).await;
+        if Instant::now() >= deadline {
+            return Err(HttpError::new(
+                "OAuth2 device code expired",
+                "The code wasn't entered before it expired",
+            ));
+        }
+
+        let response =
+            async_compat::Compat::new(client.post(&oauth.token_url).form(&params).send())
+                .await
+                .map_err(|e| HttpError::new("OAuth2 token request failed", e.to_string()))?;
+        if response.status().is_success() {
+            return async_compat::Compat::new(response.json::<TokenResponse>())
+                .await
+                .map_err(|e| HttpError::new("Failed to parse token response", e.to_string()));
+        }
+
+        let status = response.status();
+        let body = async_compat::Compat::new(response.text())
+            .await
+            .unwrap_or_default();
+        let error: TokenErrorResponse = serde_json::from_str(&body).unwrap_or_default();
+        match error.error.as_str() {
+            "authorization_pending" => {}
+            "slow_down" => interval += 5,
+            _ => {
+                return Err(HttpError::new(
+                    "OAuth2 device authorization failed",
+                    error
+                        .error_description
+                        .unwrap_or_else(|| format!("Status {}: {}", status, body)),
+                ));
+            }
+        }
+    }
 }
 
 /// Build the authorization URL with a provided authorize_url
 pub fn build_authorization_url_with_url(
     authorize_url: &str,
     oauth: &OAuth2Auth,
+    response_type: &str,
     redirect_uri: &str,
+    state: &str,
+    code_challenge: Option<&str>,
 ) -> String {
-    let mut url = authorize_url.to_string();
-    url.push_str("?response_type=code");
-    url.push_str("&client_id=");
-    url.push_str(&urlencoding::encode(&oauth.client_id));
-    url.push_str("&redirect_uri=");
-    url.push_str(&urlencoding::encode(redirect_uri));
-
-    if let Some(scope) = &oauth.scope {
-        url.push_str("&scope=");
-        url.push_str(&urlencoding::encode(scope));
+    let mut params = vec![
+        param("response_type", response_type),
+        param("client_id", &oauth.client_id),
+        param("redirect_uri", redirect_uri),
+        param("state", state),
+    ];
+    params.extend(scope_param(oauth));
+    params.extend(target_params(oauth));
+    if let Some(code_challenge) = code_challenge {
+        params.push(param("code_challenge", code_challenge));
+        params.push(param("code_challenge_method", "S256"));
     }
 
-    url
+    let query = params
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{}={}",
+                urlencoding::encode(name),
+                urlencoding::encode(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    let separator = if authorize_url.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    format!("{}{}{}", authorize_url, separator, query)
+}
+
+/// Random string of characters allowed in a PKCE verifier
+fn random_string(len: usize) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~";
+    let mut rng = rand::rng();
+    (0..len)
+        .map(|_| CHARS[rng.random_range(0..CHARS.len())] as char)
+        .collect()
 }
 
 /// Calculate expiration timestamp from expires_in seconds
@@ -222,3 +724,54 @@ pub fn calculate_expires_at(expires_in: u64) -> i64 {
 pub fn is_oauth_token_expired(oauth: &OAuth2Auth) -> bool {
     is_token_expired(oauth.expires_at, oauth.access_token.is_some())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pkce_challenge_is_s256_of_verifier() {
+        let pkce = Pkce::generate();
+        assert_eq!(pkce.verifier.len(), 64);
+        assert_eq!(
+            pkce.challenge,
+            URL_SAFE_NO_PAD.encode(Sha256::digest(pkce.verifier.as_bytes()))
+        );
+        assert!(!pkce.challenge.contains('='));
+    }
+
+    #[test]
+    fn authorization_url_carries_pkce_and_target() {
+        let oauth = OAuth2Auth {
+            client_id: "public app".to_string(),
+            scope: Some("openid profile".to_string()),
+            audience: Some("https://api.example.com".to_string()),
+            ..Default::default()
+        };
+        let url = build_authorization_url_with_url(
+            "https://id.example.com/authorize?tenant=a",
+            &oauth,
+            "code",
+            "http://127.0.0.1:8080",
+            "xyz",
+            Some("challenge"),
+        );
+        let url = url::Url::parse(&url).expect("valid URL");
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(params["tenant"], "a");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], "public app");
+        assert_eq!(params["scope"], "openid profile");
+        assert_eq!(params["audience"], "https://api.example.com");
+        assert_eq!(params["state"], "xyz");
+        assert_eq!(params["code_challenge"], "challenge");
+        assert_eq!(params["code_challenge_method"], "S256");
+    }
+
+    #[test]
+    fn query_values_are_decoded() {
+        let params = parse_query("code=a%2Bb&state=xyz&scope=read+write");
+        assert_eq!(params["code"], "a+b");
+        assert_eq!(params["scope"], "read write");
+    }
+}
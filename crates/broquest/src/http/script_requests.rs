@@ -0,0 +1,614 @@
+use std::collections::HashMap;
+
+use crate::collections::CollectionInfo;
+use crate::cookies::CookieJar;
+use crate::domain::{
+    AuthType, CollectionDefaults, FolderSettings, HttpMethod, KeyValuePair, RequestData,
+};
+use crate::scripting::{
+    ScriptRequest, ScriptRequestFuture, ScriptRequestHandler, ScriptResponse, VariableStore,
+};
+
+use super::client::check_method;
+use super::oauth2::OAuth2Prompt;
+use super::{HttpClientService, ResponseProgress};
+
+/// How deep `bro.runRequest` calls may nest, so saved requests that run each
+/// other can't recurse forever.
+const MAX_RUN_DEPTH: usize = 5;
+
+/// Saved requests of a collection that scripts can run with
+/// `bro.runRequest`, keyed `group/name` (or just `name` for requests at the
+/// collection root).
+#[derive(Debug, Clone, Default)]
+pub struct SavedRequests {
+    requests: HashMap<String, RequestData>,
+    /// Collection auth, used by saved requests whose auth is `Inherit`.
+    collection_auth: Option<AuthType>,
+    /// Settings of each group, keyed by group name.
+    folders: HashMap<String, FolderSettings>,
+}
+
+impl SavedRequests {
+    pub fn from_collection(info: &CollectionInfo) -> Self {
+        let mut requests = HashMap::new();
+        let mut folders = HashMap::new();
+        for request in info.requests.values() {
+            requests.insert(request.name.clone(), request.clone());
+        }
+        for group in info.groups.values() {
+            for request in group.requests.values() {
+                requests.insert(format!("{}/{}", group.name, request.name), request.clone());
+            }
+            folders.insert(group.name.clone(), group.folder.clone());
+        }
+
+        Self {
+            requests,
+            collection_auth: info.toml.collection.auth.clone(),
+            folders,
+        }
+    }
+
+    /// A saved request with the settings of its group, if it's in one
+    fn get(&self, name: &str) -> Option<(RequestData, FolderSettings)> {
+        let name = name.trim_matches('/');
+        let mut request = self.requests.get(name)?.clone();
+        let folder = name
+            .split_once('/')
+            .and_then(|(group, _)| self.folders.get(group))
+            .cloned()
+            .unwrap_or_default();
+        if matches!(request.auth, AuthType::Inherit) {
+            request.auth = folder.inherited_auth(self.collection_auth.as_ref());
+        }
+        Some((request, folder))
+    }
+}
+
+/// What a request is sent with besides its variable tiers: the current row
+/// of a data-driven run, the saved requests its scripts can run, the
+/// settings of its folder and collection, the cookie jar of its collection and environment,
+/// where to report the download of its body and where to ask the user to
+/// sign in for OAuth2.
+#[derive(Debug, Clone, Default)]
+pub struct RequestScope {
+    pub(super) iteration_data: HashMap<String, serde_json::Value>,
+    pub(super) folder: FolderSettings,
+    pub(super) collection: CollectionDefaults,
+    saved_requests: SavedRequests,
+    cookie_jar: Option<CookieJar>,
+    progress: Option<ResponseProgress>,
+    /// Where OAuth2 grants that need the user ask them to sign in
+    oauth2_prompts: Option<smol::channel::Sender<OAuth2Prompt>>,
+    /// How many `bro.runRequest` calls deep this request is.
+    depth: usize,
+}
+
+impl RequestScope {
+    pub fn new(saved_requests: SavedRequests) -> Self {
+        Self {
+            saved_requests,
+            ..Default::default()
+        }
+    }
+
+    /// Send as one iteration of a data-driven run: `iteration_data` is
+    /// exposed to scripts as `bro.iterationData` and resolved ahead of every
+    /// other variable tier.
+    pub fn with_iteration_data(
+        mut self,
+        iteration_data: HashMap<String, serde_json::Value>,
+    ) -> Self {
+        self.iteration_data = iteration_data;
+        self
+    }
+
+    /// Send as a request of a group: the folder's variables and headers
+    /// apply, and its scripts run around the request's own. The request's
+    /// auth is expected to be resolved already.
+    pub fn with_folder(mut self, folder: FolderSettings) -> Self {
+        self.folder = folder;
+        self
+    }
+
+    /// Send as a request of a collection with default headers and scripts:
+    /// its headers apply under the folder's, and its scripts run around the
+    /// folder's and the request's own.
+    pub fn with_collection(mut self, collection: CollectionDefaults) -> Self {
+        self.collection = collection;
+        self
+    }
+
+    /// Send cookies from `jar` and store the cookies responses set in it.
+    /// Without a jar, cookies aren't kept between requests.
+    pub fn with_cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.cookie_jar = Some(jar);
+        self
+    }
+
+    /// Report the bytes received of the response body to `progress`
+    pub fn with_progress(mut self, progress: ResponseProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Run OAuth2 grants that need the user, sending what they should do to
+    /// `prompts`. Without it, only tokens fetched earlier are used for them.
+    pub fn with_oauth2_prompts(mut self, prompts: smol::channel::Sender<OAuth2Prompt>) -> Self {
+        self.oauth2_prompts = Some(prompts);
+        self
+    }
+}
+
+/// Performs `bro.sendRequest`/`bro.runRequest` for the scripts of one
+/// request, through the shared client and with the variables that request
+/// was sent with.
+pub(super) struct ScriptRequests<'a> {
+    client: &'a HttpClientService,
+    variables: Option<HashMap<String, String>>,
+    secrets: Option<HashMap<String, String>>,
+    collection_vars: Option<HashMap<String, String>>,
+    /// The calling request's store: saved requests see its current vars and
+    /// write theirs back into it.
+    store: VariableStore,
+    scope: RequestScope,
+}
+
+impl<'a> ScriptRequests<'a> {
+    pub(super) fn new(
+        client: &'a HttpClientService,
+        variables: Option<HashMap<String, String>>,
+        secrets: Option<HashMap<String, String>>,
+        collection_vars: Option<HashMap<String, String>>,
+        store: VariableStore,
+        scope: RequestScope,
+    ) -> Self {
+        Self {
+            client,
+            variables,
+            secrets,
+            collection_vars,
+            store,
+            scope,
+        }
+    }
+
+    /// Where the calling request reports the download of its body
+    pub(super) fn progress(&self) -> Option<ResponseProgress> {
+        self.scope.progress.clone()
+    }
+
+    /// Where the calling request asks the user to sign in for OAuth2
+    pub(super) fn oauth2_prompts(&self) -> Option<smol::channel::Sender<OAuth2Prompt>> {
+        self.scope.oauth2_prompts.clone()
+    }
+
+    /// The post-response scripts of the calling request's folder and then
+    /// its collection
+    pub(super) fn inherited_post_response_scripts(&self) -> impl Iterator<Item = &str> {
+        [
+            self.scope.folder.post_response_script.as_deref(),
+            self.scope.collection.post_response_script.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        url: String,
+        headers: HashMap<String, String>,
+        body: Option<String>,
+    ) -> Result<ScriptResponse, String> {
+        let request = RequestData {
+            method: HttpMethod::parse(method),
+            url,
+            headers: headers
+                .into_iter()
+                .map(|(key, value)| KeyValuePair {
+                    key,
+                    value,
+                    enabled: true,
+                })
+                .collect(),
+            body: body.unwrap_or_default(),
+            ..Default::default()
+        };
+
+        check_method(&request.method).map_err(|e| e.to_string())?;
+        let start_time = std::time::Instant::now();
+        let cookie_jar = self.scope.cookie_jar.as_ref();
+        let (request_builder, request_headers) = self
+            .client
+            .build_request_builder(&request, None, cookie_jar);
+        let response = self
+            .client
+            .execute_request(request_builder, cookie_jar, &request.settings)
+            .await
+            .map_err(|e| e.to_string())?;
+        // The folder's and the collection's scripts belong to the calling
+        // request, not to the requests its scripts send
+        let requests = ScriptRequests::new(
+            self.client,
+            self.variables.clone(),
+            self.secrets.clone(),
+            self.collection_vars.clone(),
+            self.store.clone(),
+            RequestScope {
+                folder: FolderSettings::default(),
+                collection: CollectionDefaults::default(),
+                ..self.scope.clone()
+            },
+        );
+        let (response, _, _) = self
+            .client
+            .process_response(
+                response,
+                request,
+                request_headers,
+                start_time,
+                VariableStore::new(),
+                &requests,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(ScriptResponse::from(&response))
+    }
+
+    async fn run(&self, name: &str) -> Result<ScriptResponse, String> {
+        if self.scope.depth >= MAX_RUN_DEPTH {
+            return Err(format!(
+                "Requests run with bro.runRequest may nest at most {} deep",
+                MAX_RUN_DEPTH
+            ));
+        }
+        let (request, folder) = self
+            .scope
+            .saved_requests
+            .get(name)
+            .ok_or_else(|| format!("No saved request named '{}'", name))?;
+
+        // Env vars the calling script set so far apply to the saved request.
+        let variables = self.variables.clone().map(|mut variables| {
+            variables.extend(self.store.get_dirty_env_vars());
+            variables
+        });
+        // The saved request's body isn't the caller's download, and it is
+        // sent with its own folder
+        let scope = RequestScope {
+            depth: self.scope.depth + 1,
+            progress: None,
+            folder,
+            ..self.scope.clone()
+        };
+
+        let (response, store, _) = self
+            .client
+            .send_request_internal(
+                request,
+                variables,
+                self.secrets.clone(),
+                Some(self.store.get_all_vars()),
+                self.collection_vars.clone(),
+                scope,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Hand the saved request's variable changes back to the caller.
+        for (name, value) in store.get_all_vars() {
+            self.store.set_var(&name, value);
+        }
+        for (name, value) in store.get_dirty_env_vars() {
+            self.store.set_env_var_str(&name, &value);
+        }
+
+        Ok(ScriptResponse::from(&response))
+    }
+}
+
+impl ScriptRequestHandler for ScriptRequests<'_> {
+    fn handle(&self, request: ScriptRequest) -> ScriptRequestFuture<'_> {
+        Box::pin(async move {
+            match request {
+                ScriptRequest::Send {
+                    method,
+                    url,
+                    headers,
+                    body,
+                } => self.send(&method, url, headers, body).await,
+                ScriptRequest::Run { name } => self.run(&name).await,
+            }
+        })
+    }
+
+    fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.scope.cookie_jar.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn token_server() -> MockServer {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "token": "t1" })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .and(header("Authorization", "Bearer t1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("OK"))
+            .mount(&mock_server)
+            .await;
+        mock_server
+    }
+
+    #[tokio::test]
+    async fn send_request_from_pre_request_script() {
+        let mock_server = token_server().await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: format!("{}/api", mock_server.uri()),
+            pre_request_script: Some(format!(
+                "const r = await bro.sendRequest({{ method: 'POST', url: '{}/token' }});\n\
+                 req.setHeader('Authorization', 'Bearer ' + r.body.token);",
+                mock_server.uri()
+            )),
+            ..Default::default()
+        };
+
+        let (response, _, _) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default(),
+            )
+            .await
+            .expect("request should succeed");
+        assert_eq!(response.status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn run_request_resolves_variables_and_shares_runtime_vars() {
+        let mock_server = token_server().await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+        let login = RequestData {
+            name: "login".to_string(),
+            method: HttpMethod::Post,
+            url: "{{baseUrl}}/token".to_string(),
+            post_response_script: Some("bro.setVar('token', res.body.token);".to_string()),
+            ..Default::default()
+        };
+        let saved_requests = SavedRequests {
+            requests: HashMap::from([("auth/login".to_string(), login)]),
+            collection_auth: None,
+            folders: HashMap::new(),
+        };
+
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: "{{baseUrl}}/api".to_string(),
+            pre_request_script: Some(
+                "await bro.runRequest('auth/login');\n\
+                 req.setHeader('Authorization', 'Bearer ' + bro.getVar('token'));"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let variables = HashMap::from([("baseUrl".to_string(), mock_server.uri())]);
+
+        let (response, store, _) = client
+            .send_request(
+                request_data,
+                Some(variables),
+                Some(HashMap::new()),
+                None,
+                None,
+                RequestScope::new(saved_requests),
+            )
+            .await
+            .expect("request should succeed");
+        assert_eq!(response.status_code, Some(200));
+        assert_eq!(store.get_var("token"), Some(json!("t1")));
+    }
+
+    #[tokio::test]
+    async fn response_cookies_are_sent_with_later_requests() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/login"))
+            .respond_with(
+                ResponseTemplate::new(204).insert_header("Set-Cookie", "session=abc; Path=/"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/me"))
+            .and(header("Cookie", "theme=dark; session=abc"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+        let jar = CookieJar::default();
+
+        for (method, path, status) in [
+            (HttpMethod::Post, "/login", 204),
+            (HttpMethod::Get, "/me", 200),
+        ] {
+            let request_data = RequestData {
+                method,
+                url: format!("{}{}", mock_server.uri(), path),
+                headers: vec![KeyValuePair {
+                    key: "Cookie".to_string(),
+                    value: "theme=dark".to_string(),
+                    enabled: true,
+                }],
+                ..Default::default()
+            };
+            let (response, _, _) = client
+                .send_request(
+                    request_data,
+                    None,
+                    None,
+                    None,
+                    None,
+                    RequestScope::default().with_cookie_jar(jar.clone()),
+                )
+                .await
+                .expect("request should succeed");
+            assert_eq!(response.status_code, Some(status));
+        }
+        assert!(jar.take_changed());
+    }
+
+    #[tokio::test]
+    async fn folder_vars_headers_and_scripts_apply() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/users/me"))
+            .and(header("X-Service", "users"))
+            .and(header("X-Trace", "request"))
+            .and(header("X-Step", "folder"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+        let pair = |key: &str, value: &str| KeyValuePair {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        };
+        let folder = FolderSettings {
+            vars: vec![
+                pair("baseUrl", &mock_server.uri()),
+                pair("service", "users"),
+            ],
+            headers: vec![pair("X-Service", "{{service}}"), pair("X-Trace", "folder")],
+            pre_request_script: Some("req.setHeader('X-Step', 'folder');".to_string()),
+            post_response_script: Some(
+                "bro.setVar('order', bro.getVar('order') + ',folder');\n\
+                 test('folder ran', () => expect(bro.getFolderVar('service')).to.equal('users'));"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: "{{baseUrl}}/{{service}}/me".to_string(),
+            headers: vec![pair("x-trace", "request")],
+            post_response_script: Some("bro.setVar('order', 'request');".to_string()),
+            ..Default::default()
+        };
+
+        let (response, store, tests) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default().with_folder(folder),
+            )
+            .await
+            .expect("request should succeed");
+        assert_eq!(response.status_code, Some(200));
+        // The request's header wins over the folder's of the same name
+        let traces: Vec<&str> = response
+            .request_headers
+            .iter()
+            .filter(|h| h.key.eq_ignore_ascii_case("X-Trace"))
+            .map(|h| h.value.as_str())
+            .collect();
+        assert_eq!(traces, ["request"]);
+        assert_eq!(store.get_var("order"), Some(json!("request,folder")));
+        assert_eq!(tests.len(), 1);
+        assert!(tests[0].passed());
+    }
+
+    #[tokio::test]
+    async fn collection_headers_and_scripts_wrap_the_folder() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/orders"))
+            .and(header("X-Tenant", "acme"))
+            .and(header("Accept", "application/json"))
+            .and(header("X-Signed", "collection,folder"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let client = HttpClientService::new(30).expect("failed to create HTTP client");
+
+        let pair = |key: &str, value: &str| KeyValuePair {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        };
+        let collection = CollectionDefaults {
+            headers: vec![pair("X-Tenant", "acme"), pair("Accept", "text/plain")],
+            pre_request_script: Some("bro.setVar('signed', 'collection');".to_string()),
+            post_response_script: Some(
+                "bro.setVar('order', bro.getVar('order') + ',collection');".to_string(),
+            ),
+        };
+        let folder = FolderSettings {
+            headers: vec![pair("Accept", "application/json")],
+            pre_request_script: Some(
+                "req.setHeader('X-Signed', bro.getVar('signed') + ',folder');".to_string(),
+            ),
+            post_response_script: Some(
+                "bro.setVar('order', bro.getVar('order') + ',folder');".to_string(),
+            ),
+            ..Default::default()
+        };
+        let request_data = RequestData {
+            method: HttpMethod::Get,
+            url: format!("{}/orders", mock_server.uri()),
+            post_response_script: Some("bro.setVar('order', 'request');".to_string()),
+            ..Default::default()
+        };
+
+        let (response, store, _) = client
+            .send_request(
+                request_data,
+                None,
+                None,
+                None,
+                None,
+                RequestScope::default()
+                    .with_folder(folder)
+                    .with_collection(collection),
+            )
+            .await
+            .expect("request should succeed");
+        assert_eq!(response.status_code, Some(200));
+        // The folder's header wins over the collection's of the same name
+        let accepts: Vec<&str> = response
+            .request_headers
+            .iter()
+            .filter(|h| h.key.eq_ignore_ascii_case("Accept"))
+            .map(|h| h.value.as_str())
+            .collect();
+        assert_eq!(accepts, ["application/json"]);
+        assert_eq!(
+            store.get_var("order"),
+            Some(json!("request,folder,collection"))
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Parsing `text/event-stream` bodies into server-sent events as their
+//! chunks arrive, following the WHATWG event stream format.
+
+use std::time::{Duration, Instant};
+
+use super::HttpError;
+use super::body::{self, ResponseProgress};
+use crate::domain::ServerEvent;
+
+/// Whether a Content-Type is that of an event stream
+pub(super) fn is_event_stream(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("text/event-stream"))
+}
+
+/// Turns the chunks of an event stream into events. Lines may be split
+/// across chunks; an event is complete at the blank line ending it.
+#[derive(Debug, Default)]
+pub(super) struct EventStreamParser {
+    /// Bytes of the line not yet ended
+    line: Vec<u8>,
+    /// The previous chunk ended in `\r`, so a leading `\n` belongs to it
+    after_cr: bool,
+    data: Option<String>,
+    event: Option<String>,
+    last_id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl EventStreamParser {
+    /// Parse `chunk`, returning the events it completes. `received_after`
+    /// is stamped on them.
+    pub(super) fn push(&mut self, chunk: &[u8], received_after: Duration) -> Vec<ServerEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            let after_cr = std::mem::take(&mut self.after_cr);
+            match byte {
+                b'\n' if after_cr => {}
+                b'\n' | b'\r' => {
+                    self.after_cr = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&line, received_after) {
+                        events.push(event);
+                    }
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &[u8], received_after: Duration) -> Option<ServerEvent> {
+        if line.is_empty() {
+            return self.dispatch(received_after);
+        }
+        let line = String::from_utf8_lossy(line);
+        // Lines starting with a colon are comments, e.g. keep-alives
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_ref(), ""),
+        };
+        match field {
+            "data" => {
+                let data = self.data.get_or_insert_with(String::new);
+                if !data.is_empty() {
+                    data.push('\n');
+                }
+                data.push_str(value);
+            }
+            "event" => self.event = Some(value.to_string()),
+            // An id with a NUL is ignored, as in browsers
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(retry) = value.parse() {
+                    self.retry = Some(retry);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Complete the event gathered since the last blank line. Without data
+    /// lines there is no event, only the id is remembered.
+    fn dispatch(&mut self, received_after: Duration) -> Option<ServerEvent> {
+        let event = self.event.take();
+        let data = self.data.take()?;
+        Some(ServerEvent {
+            id: self.last_id.clone(),
+            event: event
+                .filter(|event| !event.is_empty())
+                .unwrap_or_else(|| "message".to_string()),
+            data,
+            retry: self.retry,
+            received_after,
+        })
+    }
+}
+
+/// A read event stream
+#[derive(Debug, Default)]
+pub(super) struct EventStream {
+    /// The raw stream, up to the memory limit
+    pub(super) body: Vec<u8>,
+    pub(super) events: Vec<ServerEvent>,
+}
+
+/// Read an event stream until the server ends it, calling `on_event` for
+/// each event as it arrives. Failing `on_event` ends the stream.
+pub(super) async fn read_event_stream(
+    mut response: reqwest::Response,
+    memory_limit: u64,
+    progress: Option<&ResponseProgress>,
+    mut on_event: impl AsyncFnMut(&ServerEvent) -> Result<(), HttpError>,
+) -> Result<EventStream, HttpError> {
+    if let Some(progress) = progress {
+        progress.start(response.content_length());
+    }
+
+    let started = Instant::now();
+    let mut parser = EventStreamParser::default();
+    let mut stream = EventStream::default();
+    while let Some(chunk) = async_compat::Compat::new(response.chunk())
+        .await
+        .map_err(body::read_error)?
+    {
+        if let Some(progress) = progress {
+            progress.add(chunk.len());
+        }
+        let room = usize::try_from(memory_limit)
+            .unwrap_or(usize::MAX)
+            .saturating_sub(stream.body.len());
+        stream
+            .body
+            .extend_from_slice(&chunk[..chunk.len().min(room)]);
+
+        for event in parser.push(&chunk, started.elapsed()) {
+            on_event(&event).await?;
+            if let Some(progress) = progress {
+                progress.push_event(event.clone());
+            }
+            stream.events.push(event);
+        }
+    }
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&str]) -> Vec<ServerEvent> {
+        let mut parser = EventStreamParser::default();
+        chunks
+            .iter()
+            .flat_map(|chunk| parser.push(chunk.as_bytes(), Duration::ZERO))
+            .collect()
+    }
+
+    #[test]
+    fn test_events_across_chunks() {
+        let events = parse(&[
+            ": keep-alive\n\nid: 1\nevent: upd",
+            "ate\ndata: {\"a\":1}\ndata:second line\n",
+            "\nretry: 3000\ndata: plain\r\n\r\n",
+        ]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+        assert_eq!(events[0].event, "update");
+        assert_eq!(events[0].data, "{\"a\":1}\nsecond line");
+        // The id carries over, the event type doesn't
+        assert_eq!(events[1].id.as_deref(), Some("1"));
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].data, "plain");
+        assert_eq!(events[1].retry, Some(3000));
+    }
+
+    #[test]
+    fn test_incomplete_and_empty_events_are_not_dispatched() {
+        assert!(parse(&["event: ping\n\n", "data: never ended\n"]).is_empty());
+        // A \r\n split across chunks is one line ending
+        assert_eq!(parse(&["data: x\r", "\n\r\n"]).len(), 1);
+        assert!(is_event_stream("text/event-stream; charset=utf-8"));
+        assert!(!is_event_stream("text/plain"));
+    }
+}
@@ -0,0 +1,290 @@
+//! Timing of the connection phases reqwest doesn't report: the host name
+//! lookup, the TCP connect and the TLS handshake. The resolver and connector
+//! layer record into the recorder of the request being polled, so requests
+//! sharing a client don't mix up their timings.
+//!
+//! The TCP connect and the TLS handshake run in one connector future, so the
+//! split between them is an estimate. When it can't be trusted they are
+//! reported together as the connect.
+
+use std::future::Future;
+use std::net::ToSocketAddrs as _;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Connection phases of one request. All stay `None` when it reused an open
+/// connection.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct ConnectTimings {
+    pub(super) dns: Option<Duration>,
+    /// TCP connect, after the host name lookup. Includes the TLS handshake
+    /// when `tls` is `None` for HTTPS.
+    pub(super) connect: Option<Duration>,
+    /// Estimated TLS handshake once the TCP connection is open. `None` for
+    /// plain HTTP and when it couldn't be told from the TCP connect.
+    pub(super) tls: Option<Duration>,
+    /// A host name lookup is in flight
+    resolving: bool,
+    /// Addresses the last lookup found
+    addresses: usize,
+}
+
+type Recorder = Arc<Mutex<ConnectTimings>>;
+
+tokio::task_local! {
+    static RECORDER: Recorder;
+}
+
+/// Run `future` (sending a request) and return the connection phases it went
+/// through
+pub(super) async fn recorded<F: Future>(future: F) -> (F::Output, ConnectTimings) {
+    let recorder = Recorder::default();
+    let output = RECORDER.scope(recorder.clone(), future).await;
+    let timings = *recorder.lock().unwrap_or_else(|e| e.into_inner());
+    (output, timings)
+}
+
+fn record(update: impl FnOnce(&mut ConnectTimings)) {
+    // Connections opened in the background, outside any request, go unrecorded
+    let _ = RECORDER.try_with(|recorder| {
+        update(&mut recorder.lock().unwrap_or_else(|e| e.into_inner()));
+    });
+}
+
+fn read<T: Default>(get: impl FnOnce(&ConnectTimings) -> T) -> T {
+    RECORDER
+        .try_with(|recorder| get(&recorder.lock().unwrap_or_else(|e| e.into_inner())))
+        .unwrap_or_default()
+}
+
+fn add(phase: &mut Option<Duration>, elapsed: Duration) {
+    *phase = Some(phase.unwrap_or_default() + elapsed);
+}
+
+/// Resolves host names like reqwest's default resolver, timing each lookup
+pub(super) struct TimingResolver;
+
+impl reqwest::dns::Resolve for TimingResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let start = Instant::now();
+            record(|timings| timings.resolving = true);
+            let addrs = smol::unblock(move || {
+                (host.as_str(), 0)
+                    .to_socket_addrs()
+                    .map(|addrs| addrs.collect::<Vec<_>>())
+            })
+            .await;
+            record(|timings| {
+                add(&mut timings.dns, start.elapsed());
+                timings.resolving = false;
+                timings.addresses = addrs.as_ref().map_or(0, Vec::len);
+            });
+            let addrs: reqwest::dns::Addrs = Box::new(addrs?.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Times the connector, estimating where the TCP connect ends and the TLS
+/// handshake starts. reqwest runs both in one future, so the boundary is
+/// taken from its wake-ups: once the lookup is done the future waits on the
+/// TCP connect, and the next wake-up is taken as the connection opening.
+///
+/// That only holds for a single address reached directly. Through a proxy
+/// the tunnel is set up in between, and with several addresses the connector
+/// races or retries them, waking more than once before the connection opens.
+/// There the TCP connect and the handshake are reported together.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TimingLayer {
+    /// Connections may go through a proxy
+    pub(super) proxied: bool,
+}
+
+impl<S> tower::Layer<S> for TimingLayer {
+    type Service = TimingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimingService {
+            inner,
+            proxied: self.proxied,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TimingService<S> {
+    inner: S,
+    proxied: bool,
+}
+
+impl<S, R> tower::Service<R> for TimingService<S>
+where
+    S: tower::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let connecting = self.inner.call(request);
+        let proxied = self.proxied;
+        Box::pin(async move {
+            record(|timings| timings.addresses = 0);
+            let mut phases = ConnectPhases::default();
+            let mut connecting = std::pin::pin!(connecting);
+            let connection = std::future::poll_fn(|cx| {
+                let polled = Instant::now();
+                let poll = connecting.as_mut().poll(cx);
+                phases.polled(polled, poll.is_pending());
+                poll
+            })
+            .await;
+            phases.finish(!proxied && read(|timings| timings.addresses) <= 1);
+            connection
+        })
+    }
+}
+
+/// Where the connector future is, judged from its polls
+#[derive(Debug, Default)]
+struct ConnectPhases {
+    /// When the TCP connect started: the end of the poll that found no
+    /// lookup in flight
+    tcp_started: Option<Instant>,
+    /// When the connection is taken to have opened: the poll after the one
+    /// starting it
+    tcp_opened: Option<Instant>,
+}
+
+impl ConnectPhases {
+    fn polled(&mut self, polled: Instant, pending: bool) {
+        if !pending {
+            return;
+        }
+        match (self.tcp_started, self.tcp_opened) {
+            (None, _) => {
+                if !read(|timings| timings.resolving) {
+                    self.tcp_started = Some(Instant::now());
+                }
+            }
+            (Some(_), None) => self.tcp_opened = Some(polled),
+            (Some(_), Some(_)) => {}
+        }
+    }
+
+    /// Record the phases, splitting off the handshake only if `split`
+    fn finish(self, split: bool) {
+        let now = Instant::now();
+        let (connect, tls) = match (self.tcp_started, self.tcp_opened) {
+            (Some(started), Some(opened)) if split => (opened - started, Some(now - opened)),
+            (Some(started), _) => (now - started, None),
+            // Connected on the first poll
+            (None, _) => (Duration::ZERO, None),
+        };
+        record(|timings| {
+            add(&mut timings.connect, connect);
+            if let Some(tls) = tls {
+                add(&mut timings.tls, tls);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_phases_are_recorded_per_request() {
+        let (_, timings) = recorded(async {
+            record(|timings| add(&mut timings.dns, Duration::from_millis(2)));
+            record(|timings| add(&mut timings.dns, Duration::from_millis(3)));
+        })
+        .await;
+        assert_eq!(timings.dns, Some(Duration::from_millis(5)));
+        assert_eq!(timings.connect, None);
+
+        // Outside a request nothing is recorded, and nothing fails
+        record(|timings| add(&mut timings.connect, Duration::from_millis(1)));
+        let (_, timings) = recorded(async {}).await;
+        assert_eq!(timings.connect, None);
+    }
+
+    /// A connector looking up the host, then connecting and shaking hands.
+    /// With two addresses the first one fails after 10ms.
+    struct SlowConnector {
+        addresses: usize,
+    }
+
+    impl tower::Service<()> for SlowConnector {
+        type Response = ();
+        type Error = ();
+        type Future = Pin<Box<dyn Future<Output = Result<(), ()>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: ()) -> Self::Future {
+            let addresses = self.addresses;
+            Box::pin(async move {
+                record(|timings| timings.resolving = true);
+                smol::Timer::after(Duration::from_millis(5)).await;
+                record(|timings| {
+                    add(&mut timings.dns, Duration::from_millis(5));
+                    timings.resolving = false;
+                    timings.addresses = addresses;
+                });
+                if addresses > 1 {
+                    smol::Timer::after(Duration::from_millis(10)).await;
+                }
+                smol::Timer::after(Duration::from_millis(20)).await;
+                smol::Timer::after(Duration::from_millis(40)).await;
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_connect_is_told_from_the_tls_handshake() {
+        use tower::{Layer as _, Service as _};
+
+        let mut connector = TimingLayer { proxied: false }.layer(SlowConnector { addresses: 1 });
+        let (connection, timings) = recorded(connector.call(())).await;
+        assert!(connection.is_ok());
+        assert_eq!(timings.dns, Some(Duration::from_millis(5)));
+        let connect = timings.connect.expect("connect time");
+        let tls = timings.tls.expect("handshake time");
+        assert!(connect >= Duration::from_millis(20), "{:?}", connect);
+        assert!(connect < Duration::from_millis(40), "{:?}", connect);
+        assert!(tls >= Duration::from_millis(40), "{:?}", tls);
+    }
+
+    #[tokio::test]
+    async fn test_connect_and_handshake_are_one_phase_when_tcp_wakes_twice() {
+        use tower::{Layer as _, Service as _};
+
+        let mut connector = TimingLayer { proxied: false }.layer(SlowConnector { addresses: 2 });
+        let (connection, timings) = recorded(connector.call(())).await;
+        assert!(connection.is_ok());
+        assert_eq!(timings.tls, None);
+        let connect = timings.connect.expect("connect time");
+        assert!(connect >= Duration::from_millis(70), "{:?}", connect);
+
+        // Through a proxy the tunnel comes between the two
+        let mut connector = TimingLayer { proxied: true }.layer(SlowConnector { addresses: 1 });
+        let (_, timings) = recorded(connector.call(())).await;
+        assert_eq!(timings.tls, None);
+        let connect = timings.connect.expect("connect time");
+        assert!(connect >= Duration::from_millis(60), "{:?}", connect);
+    }
+}
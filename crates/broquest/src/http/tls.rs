@@ -0,0 +1,215 @@
+//! Collection TLS options: extra CA certificates, client certificates per
+//! host and skipping certificate verification.
+
+use anyhow::{Context as _, Result};
+use std::path::Path;
+
+use crate::collections::{ClientCertificate, TlsSettings};
+use crate::environments::placeholder_names;
+
+/// The reqwest clients of a collection: one per client certificate, plus one
+/// for the hosts without a certificate. All share the proxy and trust
+/// settings.
+#[derive(Clone)]
+pub(super) struct ClientSet {
+    default: reqwest::Client,
+    /// Host patterns and the client presenting their certificate
+    by_host: Vec<(String, reqwest::Client)>,
+}
+
+impl ClientSet {
+    pub(super) fn new(default: reqwest::Client) -> Self {
+        Self {
+            default,
+            by_host: Vec::new(),
+        }
+    }
+
+    /// Build the clients for `tls`, starting each from `builder` (timeout,
+    /// proxy). Relative paths are resolved against `collection_dir`.
+    pub(super) fn build(
+        builder: impl Fn() -> Result<reqwest::ClientBuilder>,
+        tls: Option<&TlsSettings>,
+        collection_dir: &Path,
+    ) -> Result<Self> {
+        let Some(tls) = tls else {
+            return Ok(Self::new(builder()?.build()?));
+        };
+
+        let mut ca_certificates = Vec::new();
+        for path in &tls.ca_certificates {
+            let pem = read_file(collection_dir, path)?;
+            let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA certificate '{}'", path))?;
+            ca_certificates.extend(certificates);
+        }
+
+        let with_trust = || -> Result<reqwest::ClientBuilder> {
+            let mut builder = builder()?;
+            for certificate in &ca_certificates {
+                builder = builder.add_root_certificate(certificate.clone());
+            }
+            if tls.insecure {
+                builder = builder
+                    .danger_accept_invalid_certs(true)
+                    .danger_accept_invalid_hostnames(true);
+            }
+            Ok(builder)
+        };
+
+        let mut by_host = Vec::new();
+        for certificate in &tls.client_certificates {
+            // A `{{variable}}` passphrase is resolved from the environment
+            // when a request is sent, which builds the clients again
+            if certificate
+                .passphrase
+                .as_deref()
+                .is_some_and(|passphrase| !placeholder_names(passphrase).is_empty())
+            {
+                continue;
+            }
+            let identity = load_identity(certificate, collection_dir)?;
+            let client = with_trust()?
+                .identity(identity)
+                .build()
+                .with_context(|| format!("Invalid client certificate '{}'", certificate.cert))?;
+            by_host.push((certificate.host.trim().to_ascii_lowercase(), client));
+        }
+
+        Ok(Self {
+            default: with_trust()?.build()?,
+            by_host,
+        })
+    }
+
+    /// The client presenting the certificate for `url`'s host, if any
+    pub(super) fn for_url(&self, url: &str) -> &reqwest::Client {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase));
+        host.and_then(|host| {
+            self.by_host
+                .iter()
+                .find(|(pattern, _)| host_matches(pattern, &host))
+        })
+        .map(|(_, client)| client)
+        .unwrap_or(&self.default)
+    }
+}
+
+/// TLS options of a gRPC channel to `host`: the system roots, the
+/// collection's CA certificates and the client certificate for the host.
+/// Client certificates have to be PEM files here, and certificates are always
+/// verified.
+pub(super) fn grpc_tls_config(
+    tls: Option<&TlsSettings>,
+    collection_dir: &Path,
+    host: &str,
+) -> Result<tonic::transport::ClientTlsConfig> {
+    let mut config = tonic::transport::ClientTlsConfig::new().with_native_roots();
+    let Some(tls) = tls else {
+        return Ok(config);
+    };
+
+    for path in &tls.ca_certificates {
+        let pem = read_file(collection_dir, path)?;
+        config = config.ca_certificate(tonic::transport::Certificate::from_pem(pem));
+    }
+    if tls.insecure {
+        tracing::warn!("Certificates of gRPC servers are verified despite the insecure setting");
+    }
+
+    let certificate = tls
+        .client_certificates
+        .iter()
+        .find(|certificate| host_matches(&certificate.host.trim().to_ascii_lowercase(), host));
+    if let Some(certificate) = certificate {
+        let extension = Path::new(&certificate.cert)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        if matches!(extension.as_deref(), Some("p12" | "pfx")) {
+            anyhow::bail!(
+                "Client certificate '{}' is a PKCS#12 archive, gRPC calls need PEM files",
+                certificate.cert
+            );
+        }
+        let cert = read_file(collection_dir, &certificate.cert)?;
+        let key = match &certificate.key {
+            Some(key) => read_file(collection_dir, key)?,
+            None => cert.clone(),
+        };
+        config = config.identity(tonic::transport::Identity::from_pem(cert, key));
+    }
+    Ok(config)
+}
+
+fn read_file(collection_dir: &Path, path: &str) -> Result<Vec<u8>> {
+    let full_path = collection_dir.join(path);
+    std::fs::read(&full_path).with_context(|| format!("Failed to read '{}'", full_path.display()))
+}
+
+fn load_identity(
+    certificate: &ClientCertificate,
+    collection_dir: &Path,
+) -> Result<reqwest::Identity> {
+    let cert = read_file(collection_dir, &certificate.cert)?;
+    let extension = Path::new(&certificate.cert)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+
+    let identity = if matches!(extension.as_deref(), Some("p12" | "pfx")) {
+        let passphrase = certificate.passphrase.as_deref().unwrap_or_default();
+        reqwest::Identity::from_pkcs12_der(&cert, passphrase)
+    } else {
+        // Without a key file the key is expected next to the certificate
+        let key = match &certificate.key {
+            Some(key) => read_file(collection_dir, key)?,
+            None => cert.clone(),
+        };
+        reqwest::Identity::from_pkcs8_pem(&cert, &key)
+    };
+    identity.with_context(|| format!("Invalid client certificate '{}'", certificate.cert))
+}
+
+/// Whether `host` matches a pattern: an exact host name, `*.example.com` for
+/// its subdomains, or `*` for every host.
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.len() > domain.len() + 1 && host.ends_with(&format!(".{}", domain)),
+        None => pattern == "*" || pattern == host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_patterns() {
+        assert!(host_matches("api.internal", "api.internal"));
+        assert!(!host_matches("api.internal", "web.internal"));
+        assert!(host_matches("*.internal", "api.internal"));
+        assert!(host_matches("*.internal", "a.b.internal"));
+        assert!(!host_matches("*.internal", "internal"));
+        assert!(!host_matches("*.internal", "notinternal"));
+        assert!(host_matches("*", "example.com"));
+    }
+
+    #[test]
+    fn test_missing_certificate_files_are_reported() {
+        let tls = TlsSettings {
+            ca_certificates: vec!["certs/missing-ca.pem".to_string()],
+            ..Default::default()
+        };
+        let error = ClientSet::build(
+            || Ok(reqwest::Client::builder()),
+            Some(&tls),
+            Path::new("/nonexistent-collection"),
+        )
+        .err()
+        .expect("missing CA file");
+        assert!(format!("{:#}", error).contains("missing-ca.pem"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Request bodies streamed from a file on disk, so large uploads aren't held
+//! in memory, and multipart bodies whose file parts are streamed the same way.
+
+use futures_util::{TryStreamExt as _, stream};
+use reqwest::multipart::{Form, Part};
+use std::path::{Path, PathBuf};
+
+use super::HttpError;
+use crate::domain::{MultipartKind, MultipartPart};
+
+/// Where a body file is: relative paths are in the collection's folder
+pub(super) fn resolve_path(file: &str, collection_dir: &Path) -> PathBuf {
+    collection_dir.join(file)
+}
+
+/// Content type of a body file, guessed from its extension
+pub fn file_content_type(path: &Path) -> String {
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+/// Size of a body file, failing before anything is sent when it can't be read
+pub(super) fn file_size(path: &Path) -> Result<u64, HttpError> {
+    let metadata = std::fs::metadata(path).map_err(|e| {
+        HttpError::new(
+            "Body file not found",
+            format!("Can't read {}: {}", path.display(), e),
+        )
+    })?;
+    if !metadata.is_file() {
+        return Err(HttpError::new(
+            "Body file not found",
+            format!("{} is not a file", path.display()),
+        ));
+    }
+    Ok(metadata.len())
+}
+
+/// A body reading the file as it's sent. The file is opened again each time
+/// the body is built, e.g. for the second request of digest auth.
+pub(super) fn file_body(path: PathBuf) -> reqwest::Body {
+    let chunks = stream::once(tokio::fs::File::open(path))
+        .map_ok(tokio_util::io::ReaderStream::new)
+        .try_flatten();
+    reqwest::Body::wrap_stream(chunks)
+}
+
+/// The parts of a multipart body that are sent: enabled and named
+fn sent_parts(parts: &[MultipartPart]) -> impl Iterator<Item = &MultipartPart> {
+    parts
+        .iter()
+        .filter(|part| part.enabled && !part.name.is_empty())
+}
+
+/// Check the files of a multipart body can be read and its parts' types are
+/// valid, before anything is sent
+pub(super) fn check_multipart(
+    parts: &[MultipartPart],
+    collection_dir: &Path,
+) -> Result<(), HttpError> {
+    for part in sent_parts(parts) {
+        if part.kind == MultipartKind::File {
+            file_size(&resolve_path(&part.value, collection_dir))?;
+        }
+        if !part.content_type.is_empty() && part.content_type.parse::<mime_guess::Mime>().is_err() {
+            return Err(HttpError::new(
+                "Invalid multipart part",
+                format!(
+                    "'{}' is not a valid Content-Type for part '{}'",
+                    part.content_type, part.name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A `multipart/form-data` body of the parts sent. A file part is streamed
+/// from disk and typed by its extension unless it sets a Content-Type.
+pub(super) fn multipart_form(parts: &[MultipartPart], collection_dir: &Path) -> Form {
+    sent_parts(parts).fold(Form::new(), |form, part| {
+        form.part(part.name.clone(), multipart_part(part, collection_dir))
+    })
+}
+
+fn multipart_part(part: &MultipartPart, collection_dir: &Path) -> Part {
+    let content_type = match part.kind {
+        MultipartKind::File if part.content_type.is_empty() => {
+            file_content_type(&resolve_path(&part.value, collection_dir))
+        }
+        _ => part.content_type.clone(),
+    };
+    if content_type.is_empty() {
+        return untyped_part(part, collection_dir);
+    }
+    untyped_part(part, collection_dir)
+        .mime_str(&content_type)
+        .unwrap_or_else(|e| {
+            tracing::error!(
+                "Invalid Content-Type '{}' of part '{}': {}",
+                content_type,
+                part.name,
+                e
+            );
+            untyped_part(part, collection_dir)
+        })
+}
+
+/// A part with its contents and file name
+fn untyped_part(part: &MultipartPart, collection_dir: &Path) -> Part {
+    let new_part = match part.kind {
+        MultipartKind::Text => Part::text(part.value.clone()),
+        MultipartKind::File => {
+            let path = resolve_path(&part.value, collection_dir);
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "file".to_string());
+            // With every part's length known the body isn't sent chunked
+            let new_part = match file_size(&path) {
+                Ok(size) => Part::stream_with_length(file_body(path), size),
+                Err(_) => Part::stream(file_body(path)),
+            };
+            new_part.file_name(file_name)
+        }
+    };
+    if part.filename.is_empty() {
+        new_part
+    } else {
+        new_part.file_name(part.filename.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_content_type() {
+        assert_eq!(file_content_type(Path::new("logo.png")), "image/png");
+        assert_eq!(
+            file_content_type(Path::new("backup.zip")),
+            "application/zip"
+        );
+        assert_eq!(
+            file_content_type(Path::new("data.unknown-ext")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let collection = Path::new("/collections/storage");
+        assert_eq!(
+            resolve_path("fixtures/logo.png", collection),
+            Path::new("/collections/storage/fixtures/logo.png")
+        );
+        assert_eq!(
+            resolve_path("/tmp/logo.png", collection),
+            Path::new("/tmp/logo.png")
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let error = file_size(Path::new("/does/not/exist.bin")).unwrap_err();
+        assert_eq!(error.summary, "Body file not found");
+    }
+
+    #[test]
+    fn test_check_multipart() {
+        let part = |kind, value: &str, content_type: &str| MultipartPart {
+            name: "part".to_string(),
+            kind,
+            value: value.to_string(),
+            content_type: content_type.to_string(),
+            enabled: true,
+            ..Default::default()
+        };
+        let collection = Path::new("/does/not/exist");
+
+        assert!(check_multipart(&[part(MultipartKind::Text, "hi", "")], collection).is_ok());
+        let error = check_multipart(&[part(MultipartKind::File, "a.png", "")], collection)
+            .expect_err("missing file");
+        assert_eq!(error.summary, "Body file not found");
+        let error = check_multipart(&[part(MultipartKind::Text, "hi", "not a type")], collection)
+            .expect_err("invalid type");
+        assert_eq!(error.summary, "Invalid multipart part");
+
+        // Disabled parts aren't sent, so aren't checked
+        let disabled = MultipartPart {
+            enabled: false,
+            ..part(MultipartKind::File, "a.png", "")
+        };
+        assert!(check_multipart(&[disabled], collection).is_ok());
+    }
+}
@@ -0,0 +1,311 @@
+//! WebSocket connections. The opening handshake is sent with the
+//! collection's HTTP client, so proxies, TLS settings and auth apply to it
+//! like to any request; the upgraded connection then carries the frames.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use futures_util::{SinkExt as _, StreamExt as _};
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::client::generate_key;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Role};
+
+use super::HttpError;
+use super::client::header_pairs;
+use crate::domain::{FrameDirection, FrameKind, KeyValuePair, ResponseData, WebSocketFrame};
+use crate::environments::EnvironmentResolver;
+
+/// Variable tiers a request was resolved with, for resolving the `{{}}`
+/// placeholders of the messages sent after it
+#[derive(Debug, Clone, Default)]
+pub(super) struct MessageVariables {
+    pub(super) runtime: HashMap<String, String>,
+    pub(super) collection: HashMap<String, String>,
+    pub(super) variables: HashMap<String, String>,
+    pub(super) secrets: HashMap<String, String>,
+}
+
+enum Command {
+    Send(String),
+    Close,
+}
+
+/// Handle on an open WebSocket connection, shared by its clones
+#[derive(Clone)]
+pub struct WebSocketSession {
+    commands: smol::channel::Sender<Command>,
+    frames: Arc<Mutex<Vec<WebSocketFrame>>>,
+    variables: Arc<MessageVariables>,
+}
+
+impl WebSocketSession {
+    /// Send a text message once its `{{}}` placeholders are resolved
+    pub fn send(&self, text: &str) -> Result<(), HttpError> {
+        let vars = &self.variables;
+        let text = EnvironmentResolver::new().resolve_string(
+            text,
+            &vars.runtime,
+            &vars.collection,
+            &vars.variables,
+            &vars.secrets,
+        );
+        self.commands
+            .try_send(Command::Send(text))
+            .map_err(|_| HttpError::new("WebSocket closed", "The connection is closed"))
+    }
+
+    /// Start the closing handshake
+    pub fn close(&self) {
+        let _ = self.commands.try_send(Command::Close);
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.commands.is_closed()
+    }
+
+    /// Frames sent and received so far, in order
+    pub fn frames(&self) -> Vec<WebSocketFrame> {
+        self.frames.lock().map(|f| f.clone()).unwrap_or_default()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.lock().map(|f| f.len()).unwrap_or_default()
+    }
+
+    fn record(&self, direction: FrameDirection, kind: FrameKind, data: String) {
+        if let Ok(mut frames) = self.frames.lock() {
+            frames.push(WebSocketFrame {
+                direction,
+                kind,
+                data,
+                at: chrono::Local::now(),
+            });
+        }
+    }
+}
+
+/// An open connection: the handshake response and the socket, which
+/// [`Self::run`] reads and writes until it closes
+pub struct WebSocketConnection {
+    pub session: WebSocketSession,
+    /// The handshake request and response
+    pub handshake: ResponseData,
+    socket: WebSocketStream<reqwest::Upgraded>,
+    commands: smol::channel::Receiver<Command>,
+}
+
+enum Next {
+    Command(Option<Command>),
+    Frame(Option<Result<Message, tokio_tungstenite::tungstenite::Error>>),
+}
+
+impl WebSocketConnection {
+    /// Send the session's messages and log every frame until either side
+    /// closes the connection. Needs a tokio runtime like the rest of the
+    /// client.
+    pub async fn run(self) {
+        let Self {
+            session,
+            mut socket,
+            commands,
+            ..
+        } = self;
+        let mut closing = false;
+
+        loop {
+            let next = if closing {
+                Next::Frame(socket.next().await)
+            } else {
+                smol::future::or(async { Next::Command(commands.recv().await.ok()) }, async {
+                    Next::Frame(socket.next().await)
+                })
+                .await
+            };
+
+            match next {
+                Next::Command(Some(Command::Send(text))) => {
+                    session.record(FrameDirection::Sent, FrameKind::Text, text.clone());
+                    if let Err(e) = socket.send(Message::Text(text)).await {
+                        session.record(FrameDirection::Sent, FrameKind::Error, e.to_string());
+                        break;
+                    }
+                }
+                Next::Command(Some(Command::Close)) | Next::Command(None) => {
+                    // Every handle is gone or the user closed it: wait for the
+                    // server to acknowledge the close frame.
+                    closing = true;
+                    let frame = CloseFrame {
+                        code: CloseCode::Normal,
+                        reason: "".into(),
+                    };
+                    session.record(
+                        FrameDirection::Sent,
+                        FrameKind::Close,
+                        describe_close(&frame),
+                    );
+                    if socket.send(Message::Close(Some(frame))).await.is_err() {
+                        break;
+                    }
+                }
+                Next::Frame(Some(Ok(message))) => {
+                    // Pings and the server's close are answered by the socket
+                    if let Some((kind, data)) = describe(&message) {
+                        session.record(FrameDirection::Received, kind, data);
+                    }
+                }
+                Next::Frame(Some(Err(e))) => {
+                    session.record(FrameDirection::Received, FrameKind::Error, e.to_string());
+                    break;
+                }
+                Next::Frame(None) => break,
+            }
+        }
+
+        // Dropping the receiver marks the session closed
+        drop(commands);
+    }
+}
+
+/// The `http(s)` URL the opening handshake of a `ws(s)` URL is sent to
+pub(super) fn handshake_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Send the opening handshake and upgrade the connection
+pub(super) async fn connect(
+    request: reqwest::RequestBuilder,
+    request_headers: Vec<KeyValuePair>,
+    url: String,
+    variables: MessageVariables,
+) -> Result<WebSocketConnection, HttpError> {
+    let key = generate_key();
+    let start = Instant::now();
+    let response = request
+        .version(reqwest::Version::HTTP_11)
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", &key)
+        .send()
+        .await
+        .map_err(|e| HttpError::from_reqwest_error(&e))?;
+
+    let status = response.status();
+    let handshake = ResponseData {
+        status_code: Some(status.as_u16()),
+        status_text: status.canonical_reason().map(str::to_string),
+        latency: Some(start.elapsed()),
+        headers: header_pairs(response.headers()),
+        request_headers,
+        url: Some(url),
+        ..Default::default()
+    };
+
+    if status != reqwest::StatusCode::SWITCHING_PROTOCOLS {
+        let body = response.text().await.unwrap_or_default();
+        return Err(HttpError::new(
+            "WebSocket handshake failed",
+            format!(
+                "Expected 101 Switching Protocols, got {}\n\n{}",
+                status, body
+            ),
+        ));
+    }
+    let accept = response
+        .headers()
+        .get("sec-websocket-accept")
+        .and_then(|value| value.to_str().ok());
+    if accept != Some(derive_accept_key(key.as_bytes()).as_str()) {
+        return Err(HttpError::new(
+            "WebSocket handshake failed",
+            "The server's Sec-WebSocket-Accept doesn't match the key sent",
+        ));
+    }
+
+    let upgraded = response
+        .upgrade()
+        .await
+        .map_err(|e| HttpError::new("WebSocket handshake failed", e.to_string()))?;
+    let socket = WebSocketStream::from_raw_socket(upgraded, Role::Client, None).await;
+    let (sender, commands) = smol::channel::unbounded();
+
+    Ok(WebSocketConnection {
+        session: WebSocketSession {
+            commands: sender,
+            frames: Arc::default(),
+            variables: Arc::new(variables),
+        },
+        handshake,
+        socket,
+        commands,
+    })
+}
+
+fn describe(message: &Message) -> Option<(FrameKind, String)> {
+    Some(match message {
+        Message::Text(text) => (FrameKind::Text, text.to_string()),
+        Message::Binary(bytes) => (
+            FrameKind::Binary,
+            format!("[Binary data: {} bytes]", bytes.len()),
+        ),
+        Message::Ping(bytes) => (FrameKind::Ping, String::from_utf8_lossy(bytes).into_owned()),
+        Message::Pong(bytes) => (FrameKind::Pong, String::from_utf8_lossy(bytes).into_owned()),
+        Message::Close(frame) => (
+            FrameKind::Close,
+            frame.as_ref().map(describe_close).unwrap_or_default(),
+        ),
+        Message::Frame(_) => return None,
+    })
+}
+
+fn describe_close(frame: &CloseFrame) -> String {
+    if frame.reason.is_empty() {
+        u16::from(frame.code).to_string()
+    } else {
+        format!("{} {}", u16::from(frame.code), frame.reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_url() {
+        assert_eq!(
+            handshake_url("ws://localhost:8080/ws"),
+            "http://localhost:8080/ws"
+        );
+        assert_eq!(
+            handshake_url("wss://example.com/feed?x=1"),
+            "https://example.com/feed?x=1"
+        );
+        assert_eq!(handshake_url("https://example.com"), "https://example.com");
+    }
+
+    #[test]
+    fn test_describe_binary_and_close() {
+        let (kind, data) = describe(&Message::Binary(vec![0; 12])).unwrap();
+        assert_eq!(kind, FrameKind::Binary);
+        assert_eq!(data, "[Binary data: 12 bytes]");
+
+        let frame = CloseFrame {
+            code: CloseCode::Away,
+            reason: "bye".into(),
+        };
+        assert_eq!(
+            describe(&Message::Close(Some(frame))),
+            Some((FrameKind::Close, "1001 bye".to_string()))
+        );
+    }
+}
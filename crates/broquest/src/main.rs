@@ -10,13 +10,16 @@ mod app_events;
 mod app_settings;
 mod assets;
 mod collections;
+mod cookies;
 mod domain;
 mod environments;
+mod graphql;
 mod highlighting;
 mod history;
 mod http;
 mod requests;
 mod result_ext;
+mod runner;
 mod scripting;
 mod settings;
 mod themes_manager;
@@ -35,6 +38,16 @@ use settings::Settings;
 use themes_manager::ThemesManager;
 
 fn main() {
+    // `broquest run ...` executes a collection headlessly, without GPUI.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("run") {
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .init();
+        std::process::exit(runner::run_cli(&args[2..]));
+    }
+
     tracing_subscriber::fmt::init();
 
     let app = application()
@@ -100,6 +113,25 @@ fn main() {
 
         let mut app_settings = Settings::from_key_values(&kv_settings);
 
+        // The proxy password is kept in the keychain. Earlier versions stored
+        // it in the settings table: move it over.
+        match kv_settings
+            .iter()
+            .find(|(k, v)| k == "connection.proxy_password" && !v.is_empty())
+        {
+            Some((key, password)) => {
+                settings::write_proxy_password(password, cx);
+                if let Err(e) = smol::block_on(db.save_setting(key, "")) {
+                    tracing::warn!("Failed to clear the stored proxy password: {}", e);
+                }
+                app_settings.connection.proxy.password = password.clone();
+            }
+            None => {
+                app_settings.connection.proxy.password =
+                    settings::read_proxy_password(cx).unwrap_or_default();
+            }
+        }
+
         // Migrate old user_settings.theme if new settings table has no appearance.theme
         if app_settings.appearance.theme == Settings::default().appearance.theme
             && let Some(ref old_settings) = user_settings
@@ -127,6 +159,16 @@ fn main() {
             tracing::error!("Failed to watch themes directory: {}", err);
         }
 
+        // Load the cookie jars of every collection and environment
+        let stored_cookies = match smol::block_on(async { db.load_all_cookies().await }) {
+            Ok(cookies) => cookies,
+            Err(e) => {
+                tracing::warn!("Failed to load cookies: {}", e);
+                vec![]
+            }
+        };
+        cx.set_global(cookies::CookieJars::new(stored_cookies));
+
         cx.set_global(db);
 
         // Initialize global CollectionManager as an entity-backed global.
@@ -142,12 +184,20 @@ fn main() {
         CollectionManager::set_global(collection_manager, cx);
 
         // Initialize HTTP client
-        let timeout = AppSettings::global(cx)
-            .settings
-            .connection
-            .request_timeout_seconds;
-        match http::HttpClientService::new(timeout) {
-            Ok(http_client) => cx.set_global(http_client),
+        let connection = AppSettings::global(cx).settings.connection.clone();
+        let timeout = connection.request_timeout_seconds;
+        let http_client =
+            http::HttpClientService::with_proxy(timeout, connection.proxy).or_else(|e| {
+                // Don't lock the user out of the settings over a bad proxy URL
+                tracing::error!("Ignoring invalid proxy settings: {:#}", e);
+                http::HttpClientService::new(timeout)
+            });
+        match http_client {
+            Ok(mut http_client) => {
+                http_client.set_redirects(connection.redirects);
+                http_client.set_response_memory_limit_mb(connection.response_memory_limit_mb);
+                cx.set_global(http_client)
+            }
             Err(e) => {
                 tracing::error!("Failed to initialize HTTP client: {}", e);
                 return;
@@ -3,13 +3,15 @@ use gpui::{
     div, prelude::*, px,
 };
 use gpui_component::{
-    ActiveTheme, h_flex,
+    ActiveTheme, Sizable, h_flex,
     input::{Input, InputEvent, InputState},
     scroll::ScrollableElement,
-    select::{Select, SelectItem, SelectState},
+    select::{Select, SelectEvent, SelectItem, SelectState},
+    switch::Switch,
     v_flex,
 };
 
+use super::key_value_editor::{KeyValueConfig, KeyValueEditor, KeyValueEvent};
 use crate::domain::{
     AuthType, BasicAuth, DigestAuth, JwtAuth, KeyAuth, OAuth2Auth, OAuth2GrantType,
 };
@@ -96,6 +98,18 @@ impl SelectItem for AuthTypeOption {
     }
 }
 
+impl SelectItem for OAuth2GrantType {
+    type Value = OAuth2GrantType;
+
+    fn title(&self) -> SharedString {
+        self.name().into()
+    }
+
+    fn value(&self) -> &Self::Value {
+        self
+    }
+}
+
 pub struct AuthEditor {
     auth_type_options: Vec<AuthTypeOption>,
     auth_type_select: Entity<SelectState<Vec<AuthTypeOption>>>,
@@ -107,6 +121,14 @@ pub struct AuthEditor {
     client_secret_input: Entity<InputState>,
     token_url_input: Entity<InputState>,
     scope_input: Entity<InputState>,
+    grant_type_select: Entity<SelectState<Vec<OAuth2GrantType>>>,
+    authorize_url_input: Entity<InputState>,
+    redirect_url_input: Entity<InputState>,
+    device_authorization_url_input: Entity<InputState>,
+    audience_input: Entity<InputState>,
+    resource_input: Entity<InputState>,
+    pkce: bool,
+    extra_params_editor: Entity<KeyValueEditor>,
     // JWT inputs
     jwt_login_url_input: Entity<InputState>,
     jwt_username_field_input: Entity<InputState>,
@@ -173,6 +195,41 @@ impl AuthEditor {
 
         let scope_input = cx.new(|cx| InputState::new(window, cx).placeholder("Scope (optional)"));
 
+        let grant_type_select = cx.new(|cx| {
+            SelectState::new(
+                OAuth2GrantType::all(),
+                Some(gpui_component::IndexPath::default().row(0)),
+                window,
+                cx,
+            )
+        });
+
+        let authorize_url_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Authorization URL"));
+
+        let redirect_url_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Redirect URL (optional, e.g. http://localhost:8080)")
+        });
+
+        let device_authorization_url_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Device authorization URL"));
+
+        let audience_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Audience (optional)"));
+
+        let resource_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Resource (optional)"));
+
+        let extra_params_editor = cx.new(|cx| {
+            KeyValueEditor::new(
+                KeyValueConfig::new("oauth2-params", "Parameter name", "Parameter value")
+                    .embedded(),
+                window,
+                cx,
+            )
+        });
+
         // JWT inputs
         let jwt_login_url_input = cx.new(|cx| InputState::new(window, cx).placeholder("Login URL"));
 
@@ -229,6 +286,24 @@ impl AuthEditor {
         subscriptions.push(subscribe_to_input(&client_secret_input, cx));
         subscriptions.push(subscribe_to_input(&token_url_input, cx));
         subscriptions.push(subscribe_to_input(&scope_input, cx));
+        subscriptions.push(subscribe_to_input(&authorize_url_input, cx));
+        subscriptions.push(subscribe_to_input(&redirect_url_input, cx));
+        subscriptions.push(subscribe_to_input(&device_authorization_url_input, cx));
+        subscriptions.push(subscribe_to_input(&audience_input, cx));
+        subscriptions.push(subscribe_to_input(&resource_input, cx));
+        subscriptions.push(cx.subscribe(
+            &grant_type_select,
+            |_this, _state, _event: &SelectEvent<Vec<OAuth2GrantType>>, cx| {
+                cx.emit(AuthEditorEvent::AuthChanged);
+                cx.notify();
+            },
+        ));
+        subscriptions.push(cx.subscribe(
+            &extra_params_editor,
+            |_this, _editor, _event: &KeyValueEvent, cx| {
+                cx.emit(AuthEditorEvent::AuthChanged);
+            },
+        ));
         subscriptions.push(subscribe_to_input(&jwt_login_url_input, cx));
         subscriptions.push(subscribe_to_input(&jwt_username_field_input, cx));
         subscriptions.push(subscribe_to_input(&jwt_username_input, cx));
@@ -249,6 +324,14 @@ impl AuthEditor {
             client_secret_input,
             token_url_input,
             scope_input,
+            grant_type_select,
+            authorize_url_input,
+            redirect_url_input,
+            device_authorization_url_input,
+            audience_input,
+            resource_input,
+            pkce: true,
+            extra_params_editor,
             jwt_login_url_input,
             jwt_username_field_input,
             jwt_username_input,
@@ -322,6 +405,41 @@ impl AuthEditor {
                 self.scope_input.update(cx, |state, cx| {
                     state.set_value(oauth.scope.clone().unwrap_or_default(), window, cx);
                 });
+                let grant_index = OAuth2GrantType::all()
+                    .iter()
+                    .position(|g| g == &oauth.grant_type)
+                    .unwrap_or_default();
+                self.grant_type_select.update(cx, |state, cx| {
+                    state.set_selected_index(
+                        Some(gpui_component::IndexPath::default().row(grant_index)),
+                        window,
+                        cx,
+                    );
+                });
+                for (input, value) in [
+                    (&self.authorize_url_input, &oauth.authorize_url),
+                    (&self.redirect_url_input, &oauth.redirect_url),
+                    (
+                        &self.device_authorization_url_input,
+                        &oauth.device_authorization_url,
+                    ),
+                    (&self.audience_input, &oauth.audience),
+                    (&self.resource_input, &oauth.resource),
+                ] {
+                    input.update(cx, |state, cx| {
+                        state.set_value(value.clone().unwrap_or_default(), window, cx);
+                    });
+                }
+                self.username_input.update(cx, |state, cx| {
+                    state.set_value(oauth.username.clone(), window, cx);
+                });
+                self.password_input.update(cx, |state, cx| {
+                    state.set_value(oauth.password.clone(), window, cx);
+                });
+                self.pkce = oauth.pkce;
+                self.extra_params_editor.update(cx, |editor, cx| {
+                    editor.set_pairs(&oauth.extra_params, window, cx);
+                });
             }
             AuthType::Jwt(jwt) => {
                 self.jwt_login_url_input.update(cx, |state, cx| {
@@ -383,16 +501,25 @@ impl AuthEditor {
                 value: self.value_input.read(cx).value().to_string(),
             }),
             AuthTypeOption::OAuth2 => AuthType::OAuth2(OAuth2Auth {
-                grant_type: OAuth2GrantType::ClientCredentials,
+                grant_type: self
+                    .grant_type_select
+                    .read(cx)
+                    .selected_value()
+                    .cloned()
+                    .unwrap_or_default(),
                 client_id: self.client_id_input.read(cx).value().to_string(),
                 client_secret: self.client_secret_input.read(cx).value().to_string(),
                 token_url: self.token_url_input.read(cx).value().to_string(),
-                scope: {
-                    let scope = self.scope_input.read(cx).value().to_string();
-                    if scope.is_empty() { None } else { Some(scope) }
-                },
-                authorize_url: None,
-                redirect_url: None,
+                scope: optional_value(&self.scope_input, cx),
+                authorize_url: optional_value(&self.authorize_url_input, cx),
+                redirect_url: optional_value(&self.redirect_url_input, cx),
+                username: self.username_input.read(cx).value().to_string(),
+                password: self.password_input.read(cx).value().to_string(),
+                pkce: self.pkce,
+                device_authorization_url: optional_value(&self.device_authorization_url_input, cx),
+                audience: optional_value(&self.audience_input, cx),
+                resource: optional_value(&self.resource_input, cx),
+                extra_params: self.extra_params_editor.read(cx).get_pairs(cx),
                 access_token: None,
                 refresh_token: None,
                 expires_at: None,
@@ -462,13 +589,107 @@ impl AuthEditor {
     }
 
     fn render_oauth2(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let grant_type = self
+            .grant_type_select
+            .read(cx)
+            .selected_value()
+            .cloned()
+            .unwrap_or_default();
+        let in_browser = matches!(
+            grant_type,
+            OAuth2GrantType::AuthorizationCode | OAuth2GrantType::Implicit
+        );
+        // The implicit grant takes its token from the redirect
+        let implicit = grant_type == OAuth2GrantType::Implicit;
+
         v_flex()
             .gap_3()
             .p_4()
+            .child(
+                h_flex()
+                    .gap_3()
+                    .items_center()
+                    .child(
+                        div()
+                            .w(px(140.))
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child("Grant Type"),
+                    )
+                    .child(
+                        div()
+                            .w(px(240.))
+                            .child(Select::new(&self.grant_type_select)),
+                    ),
+            )
+            .when(in_browser, |this| {
+                this.child(self.render_labeled_input(
+                    "Authorization URL",
+                    &self.authorize_url_input,
+                    cx,
+                ))
+                .child(self.render_labeled_input(
+                    "Redirect URL",
+                    &self.redirect_url_input,
+                    cx,
+                ))
+            })
+            .when(grant_type == OAuth2GrantType::DeviceCode, |this| {
+                this.child(self.render_labeled_input(
+                    "Device Auth URL",
+                    &self.device_authorization_url_input,
+                    cx,
+                ))
+            })
+            .when(!implicit, |this| {
+                this.child(self.render_labeled_input("Token URL", &self.token_url_input, cx))
+            })
             .child(self.render_labeled_input("Client ID", &self.client_id_input, cx))
-            .child(self.render_labeled_input("Client Secret", &self.client_secret_input, cx))
-            .child(self.render_labeled_input("Token URL", &self.token_url_input, cx))
+            .when(!implicit, |this| {
+                this.child(self.render_labeled_input(
+                    "Client Secret",
+                    &self.client_secret_input,
+                    cx,
+                ))
+            })
+            .when(grant_type == OAuth2GrantType::Password, |this| {
+                this.child(self.render_labeled_input("Username", &self.username_input, cx))
+                    .child(self.render_labeled_input("Password", &self.password_input, cx))
+            })
             .child(self.render_labeled_input("Scope", &self.scope_input, cx))
+            .child(self.render_labeled_input("Audience", &self.audience_input, cx))
+            .child(self.render_labeled_input("Resource", &self.resource_input, cx))
+            .when(grant_type == OAuth2GrantType::AuthorizationCode, |this| {
+                this.child(
+                    h_flex()
+                        .gap_3()
+                        .items_center()
+                        .child(
+                            div()
+                                .w(px(140.))
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child("PKCE"),
+                        )
+                        .child(
+                            Switch::new("oauth2-pkce")
+                                .small()
+                                .checked(self.pkce)
+                                .on_click(cx.listener(|this, checked, _window, cx| {
+                                    this.pkce = *checked;
+                                    cx.emit(AuthEditorEvent::AuthChanged);
+                                    cx.notify();
+                                })),
+                        ),
+                )
+            })
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("Extra Token Parameters"),
+            )
+            .child(self.extra_params_editor.clone())
     }
 
     fn render_jwt(&self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -537,6 +758,12 @@ impl AuthEditor {
     }
 }
 
+/// The value of an optional field, `None` when it's left empty
+fn optional_value(input: &Entity<InputState>, cx: &App) -> Option<String> {
+    let value = input.read(cx).value().to_string();
+    if value.is_empty() { None } else { Some(value) }
+}
+
 impl Render for AuthEditor {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let selected_type = self
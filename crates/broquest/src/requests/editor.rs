@@ -2,8 +2,8 @@ use crate::ui::resizable::{ResizableState, h_resizable, resizable_panel, v_resiz
 use gpui::{
     App, AppContext, Context, Entity, EventEmitter, FocusHandle, Focusable, ImageSource,
     InteractiveElement as _, IntoElement, KeyBinding, ObjectFit, ParentElement as _, Render,
-    SharedString, StyleRefinement, Styled as _, StyledImage as _, Subscription, Task, Window,
-    actions, div, img, prelude::FluentBuilder, px,
+    SharedString, StatefulInteractiveElement as _, StyleRefinement, Styled as _, StyledImage as _,
+    Subscription, Task, Window, actions, div, img, prelude::FluentBuilder, px, relative,
 };
 use gpui_component::{
     ActiveTheme, Icon, IndexPath, Sizable, StyledExt, WindowExt,
@@ -15,32 +15,65 @@ use gpui_component::{
     scroll::ScrollableElement,
     select::{Select, SelectEvent, SelectItem, SelectState},
     tab::{Tab, TabBar},
+    tooltip::Tooltip,
     v_flex,
 };
 use jsonpath_rust::JsonPath;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use super::auth_editor::{AuthEditor, AuthEditorEvent};
+use super::file_body_editor::{FileBodyEditor, FileBodyEvent};
+use super::graphql_editor::{GraphQLEditor, GraphQLEditorEvent};
+use super::grpc_editor::{GrpcEditor, GrpcEditorEvent, GrpcServicesCache};
 use super::key_value_editor::{KeyValueConfig, KeyValueEditor, KeyValueEvent};
+use super::message_composer::{MessageComposer, MessageComposerEvent};
+use super::multipart_editor::{MultipartEditor, MultipartEvent};
+use super::settings_editor::{RequestSettingsEditor, RequestSettingsEvent};
+use crate::app_events::AppEvent;
 use crate::app_settings::AppSettings;
-use crate::domain::{AuthType, ContentType, HttpMethod, KeyValuePair, RequestData, ResponseData};
-use crate::http::ResponseFormat;
+use crate::cookies::CookieJars;
+use crate::domain::{
+    AuthType, CollectionDefaults, ContentType, FolderSettings, FrameDirection, FrameKind,
+    GraphQLRequest, HttpMethod, KeyValuePair, RequestData, RequestKind, RequestSettings,
+    ResponseData, ServerEvent,
+};
+use crate::environments::{
+    EnvironmentResolver, PlaceholderStatus, VariableReport, VariableSource, VariableTiers,
+};
+use crate::graphql::{INTROSPECTION_QUERY, Schema, SchemaCache};
 use crate::http::curl;
+use crate::http::oauth2::{OAuth2Cancel, OAuth2Prompt};
+use crate::http::{
+    HttpError, RequestScope, ResponseFormat, ResponseProgress, SavedRequests, WebSocketSession,
+};
 use crate::result_ext::ResultExt;
-use crate::scripting::{ScriptEditor, ScriptEditorEvent};
+use crate::scripting::{ScriptEditor, ScriptEditorEvent, TestResult};
 use crate::settings::EditorLayout;
 use crate::ui::icon::IconName;
 use crate::ui::tab_badge::TabBadge;
-use crate::{app_events::AppEvent, environments::EnvironmentResolver};
 use crate::{
-    collections::{CollectionManager, EnvironmentToml},
+    collections::{CollectionManager, CollectionManagerEvent, EnvironmentToml},
     http::HttpClientService,
 };
 use std::time::Duration;
 
 const CONTEXT: &str = "request_editor";
 
+/// Methods of the method select: the standard ones, then an entry for a
+/// custom method named in an input beside it
+const METHOD_OPTIONS: [HttpMethod; 8] = [
+    HttpMethod::Get,
+    HttpMethod::Post,
+    HttpMethod::Put,
+    HttpMethod::Delete,
+    HttpMethod::Patch,
+    HttpMethod::Head,
+    HttpMethod::Options,
+    HttpMethod::Custom(String::new()),
+];
+
 actions!(request_editor, [Save, Send]);
 
 /// Basic URL encoding function
@@ -114,16 +147,25 @@ pub enum RequestTab {
     Path,
     Query,
     Body,
+    /// Message composer of WebSocket requests, in place of the body
+    Messages,
     Headers,
     Auth,
     Scripts,
     Vars,
+    Settings,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResponseTab {
     Response,
     Raw,
+    Tests,
+    Redirects,
+    Timings,
+    Events,
+    /// Frame log of a WebSocket connection
+    Messages,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -136,13 +178,23 @@ pub struct RequestEditor {
     request_data: RequestData,
     original_request_data: Option<RequestData>,
     response_data: ResponseData,
+    /// `test()` outcomes from the last response's post-response script.
+    test_results: Vec<TestResult>,
     active_tab: RequestTab,
     active_response_tab: ResponseTab,
     is_loading: bool,
     current_request_task: Option<Task<Result<(), anyhow::Error>>>,
+    /// Download of the response body of the request in flight
+    response_progress: Option<ResponseProgress>,
+    _progress_task: Option<Task<()>>,
+    /// Connection of a WebSocket request, open or the last one closed
+    websocket: Option<WebSocketSession>,
     collection_path: Option<String>,
     group_path: Option<String>,
     method_select: Entity<SelectState<Vec<HttpMethod>>>,
+    /// Name of a custom method, e.g. PURGE
+    custom_method_input: Entity<InputState>,
+    kind_select: Entity<SelectState<Vec<RequestKind>>>,
     environment_select: Entity<SelectState<Vec<EnvironmentOption>>>,
     content_type_select: Entity<SelectState<Vec<ContentType>>>,
     name_input: Entity<InputState>,
@@ -153,13 +205,26 @@ pub struct RequestEditor {
     path_param_editor: Entity<KeyValueEditor>,
     query_param_editor: Entity<KeyValueEditor>,
     header_editor: Entity<KeyValueEditor>,
-    form_editor: Entity<KeyValueEditor>,
+    /// Parts of a multipart body
+    multipart_editor: Entity<MultipartEditor>,
+    /// File sent as the body
+    file_body_editor: Entity<FileBodyEditor>,
     auth_editor: Entity<AuthEditor>,
     script_editor: Entity<ScriptEditor>,
     /// Editable request-level variables (Bruno `runtime.variables`).
     request_vars_editor: Entity<KeyValueEditor>,
+    /// Request-level overrides of the connection settings
+    settings_editor: Entity<RequestSettingsEditor>,
+    /// Messages a WebSocket request sends
+    message_composer: Entity<MessageComposer>,
+    /// Query and variables of a GraphQL body
+    graphql_editor: Entity<GraphQLEditor>,
+    /// Method and `.proto` files of a gRPC request
+    grpc_editor: Entity<GrpcEditor>,
     /// Read-only variable inspector scoped to the owning collection.
     vars_view: Entity<crate::collections::VarsView>,
+    /// Where the request's `{{name}}` placeholders resolve from
+    variable_report: VariableReport,
     _subscriptions: Vec<Subscription>,
     _updating_url_from_params: bool,
     _was_dirty: bool,
@@ -186,13 +251,28 @@ impl RequestEditor {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let method_select = cx.new(|cx| {
             SelectState::new(
-                HttpMethod::ALL.to_vec(),
+                METHOD_OPTIONS.to_vec(),
                 Some(IndexPath::default().row(0)), // Select GET by default
                 window,
                 cx,
             )
         });
 
+        let custom_method_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("METHOD")
+                .multi_line(false)
+        });
+
+        let kind_select = cx.new(|cx| {
+            SelectState::new(
+                RequestKind::ALL.to_vec(),
+                Some(IndexPath::default().row(0)),
+                window,
+                cx,
+            )
+        });
+
         let environment_select = cx.new(|cx| {
             SelectState::new(
                 vec![EnvironmentOption::None],     // Start with just None option
@@ -275,6 +355,14 @@ impl RequestEditor {
             )
         });
 
+        let settings_editor = cx.new(|cx| RequestSettingsEditor::new(window, cx));
+
+        let message_composer = cx.new(|cx| MessageComposer::new(window, cx));
+
+        let graphql_editor = cx.new(|cx| GraphQLEditor::new(window, cx));
+
+        let grpc_editor = cx.new(|cx| GrpcEditor::new(window, cx));
+
         let header_editor = cx.new(|cx| {
             KeyValueEditor::new(
                 KeyValueConfig::new("headers", "Header name", "Header value"),
@@ -283,14 +371,9 @@ impl RequestEditor {
             )
         });
 
-        let form_editor = cx.new(|cx| {
-            KeyValueEditor::new(
-                KeyValueConfig::new("form-fields", "Field name", "Field value or @/path/to/file")
-                    .with_file_picker(),
-                window,
-                cx,
-            )
-        });
+        let multipart_editor = cx.new(|cx| MultipartEditor::new(window, cx));
+
+        let file_body_editor = cx.new(|_cx| FileBodyEditor::default());
 
         let script_editor = cx.new(|cx| ScriptEditor::new(window, cx));
 
@@ -329,18 +412,42 @@ impl RequestEditor {
             },
         ));
 
+        // Collection and runtime variables may change under an open request
+        subscriptions.push(cx.subscribe(
+            &CollectionManager::global(cx),
+            |this, _manager, event: &CollectionManagerEvent, cx| {
+                let relevant = match event {
+                    CollectionManagerEvent::CollectionsChanged => true,
+                    CollectionManagerEvent::EnvironmentsChanged { collection_path }
+                    | CollectionManagerEvent::RequestsChanged { collection_path }
+                    | CollectionManagerEvent::RuntimeVarsChanged { collection_path } => {
+                        this.collection_path.as_deref() == Some(collection_path.as_ref())
+                    }
+                };
+                if relevant {
+                    this.refresh_variable_report(cx);
+                }
+            },
+        ));
+
         Self {
             _focus_handle: cx.focus_handle(),
             request_data: RequestData::default(),
             original_request_data: None,
             response_data: ResponseData::default(),
+            test_results: Vec::new(),
             active_tab: RequestTab::Query,
             active_response_tab: ResponseTab::Response,
             is_loading: false,
             current_request_task: None,
+            response_progress: None,
+            _progress_task: None,
+            websocket: None,
             collection_path: None,
             group_path: None,
             method_select,
+            custom_method_input,
+            kind_select,
             environment_select,
             content_type_select,
             name_input,
@@ -351,11 +458,17 @@ impl RequestEditor {
             path_param_editor,
             query_param_editor,
             header_editor,
-            form_editor,
+            multipart_editor,
+            file_body_editor,
             auth_editor,
             script_editor,
             request_vars_editor,
+            settings_editor,
+            message_composer,
+            graphql_editor,
+            grpc_editor,
             vars_view,
+            variable_report: VariableReport::default(),
             _subscriptions: subscriptions,
             _updating_url_from_params: false,
             _was_dirty: false,
@@ -376,11 +489,21 @@ impl RequestEditor {
 
     pub fn set_collection_path(&mut self, collection_path: Option<String>, cx: &mut Context<Self>) {
         self.collection_path = collection_path.clone();
+        self.grpc_editor.update(cx, |editor, _cx| {
+            editor.set_collection_path(collection_path.clone());
+        });
+        self.file_body_editor.update(cx, |editor, _cx| {
+            editor.set_collection_path(collection_path.clone());
+        });
+        self.multipart_editor.update(cx, |editor, _cx| {
+            editor.set_collection_path(collection_path.clone());
+        });
         // Scope the read-only Vars view to this collection so it shows the right
         // collection + runtime variables.
         self.vars_view.update(cx, |view, cx| {
             view.set_collection(collection_path, cx);
         });
+        self.refresh_variable_report(cx);
     }
 
     pub fn set_group_path(&mut self, group_path: Option<String>) {
@@ -403,6 +526,12 @@ impl RequestEditor {
         self.script_editor.update(cx, |editor, cx| {
             editor.apply_editor_settings(window, cx);
         });
+        self.message_composer.update(cx, |composer, cx| {
+            composer.apply_editor_settings(window, cx);
+        });
+        self.graphql_editor.update(cx, |editor, cx| {
+            editor.apply_editor_settings(window, cx);
+        });
         if self.layout != settings.layout {
             self.layout = settings.layout;
             self.request_response_state = cx.new(|_cx| ResizableState::default());
@@ -420,14 +549,19 @@ impl RequestEditor {
         self._was_dirty = false; // Reset dirty state when loading saved data
 
         // Update method selector
-        self.method_select.update(cx, |state, cx| {
-            if let Some(index) = HttpMethod::ALL
-                .iter()
-                .position(|&method| method == data.method)
-            {
-                state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
-            }
+        self.set_method(&data.method, window, cx);
+
+        let kind_index = RequestKind::ALL
+            .iter()
+            .position(|&kind| kind == data.kind)
+            .unwrap_or(0);
+        self.kind_select.update(cx, |state, cx| {
+            state.set_selected_index(Some(IndexPath::default().row(kind_index)), window, cx);
         });
+        self.message_composer.update(cx, |composer, cx| {
+            composer.set_templates(&data.messages, window, cx);
+        });
+        self.sync_tab_with_kind(data.kind);
 
         // Update URL input
         self.url_input.update(cx, |state, cx| {
@@ -449,6 +583,21 @@ impl RequestEditor {
         self.body_input.update(cx, |state, cx| {
             state.set_value(data.body.clone(), window, cx);
         });
+        self.graphql_editor.update(cx, |editor, cx| {
+            editor.set_graphql(&data.graphql.clone().unwrap_or_default(), window, cx);
+        });
+        self.grpc_editor.update(cx, |editor, cx| {
+            editor.set_grpc(&data.grpc.clone().unwrap_or_default(), window, cx);
+        });
+        self.file_body_editor.update(cx, |editor, cx| {
+            editor.set_file(data.body_file.clone(), cx);
+        });
+        self.multipart_editor.update(cx, |editor, cx| {
+            editor.set_parts(data.multipart.as_deref().unwrap_or_default(), window, cx);
+        });
+        if data.kind == RequestKind::Grpc {
+            self.refresh_grpc_services(window, cx);
+        }
 
         // Update query parameters
         self.query_param_editor.update(cx, |editor, cx| {
@@ -460,18 +609,29 @@ impl RequestEditor {
             editor.set_pairs(&data.vars, window, cx);
         });
 
+        self.settings_editor.update(cx, |editor, cx| {
+            editor.set_settings(&data.settings, window, cx);
+        });
+
         // Update headers
         self.header_editor.update(cx, |editor, cx| {
             editor.set_pairs(&data.headers, window, cx);
         });
 
         // Update content type selector based on Content-Type header
-        let content_type = data
-            .headers
-            .iter()
-            .find(|h| h.key.to_lowercase() == "content-type" && h.enabled)
-            .map(|h| ContentType::from_header(&h.value))
-            .unwrap_or(ContentType::Json);
+        let content_type = if data.graphql.is_some() {
+            ContentType::GraphQL
+        } else if data.body_file.is_some() {
+            ContentType::File
+        } else if data.multipart.is_some() {
+            ContentType::Form
+        } else {
+            data.headers
+                .iter()
+                .find(|h| h.key.to_lowercase() == "content-type" && h.enabled)
+                .map(|h| ContentType::from_header(&h.value))
+                .unwrap_or(ContentType::Json)
+        };
         let content_type_index = ContentType::ALL
             .iter()
             .position(|ct| *ct == content_type)
@@ -491,6 +651,7 @@ impl RequestEditor {
             editor.set_scripts(
                 data.pre_request_script.as_deref(),
                 data.post_response_script.as_deref(),
+                data.event_script.as_deref(),
                 window,
                 cx,
             );
@@ -501,6 +662,7 @@ impl RequestEditor {
             editor.set_auth(&data.auth, window, cx);
         });
 
+        self.refresh_variable_report(cx);
         cx.notify();
     }
 
@@ -535,6 +697,7 @@ impl RequestEditor {
             state.set_selected_index(Some(IndexPath::default().row(selected_row)), window, cx);
         });
 
+        self.refresh_variable_report(cx);
         cx.notify();
     }
 
@@ -608,9 +771,9 @@ impl RequestEditor {
         let mut data = self.request_data.clone();
 
         // Update method from select
-        if let Some(selected_method) = self.method_select.read(cx).selected_value() {
-            data.method = *selected_method;
-        }
+        data.method = self.selected_method(cx);
+        data.kind = self.selected_kind(cx);
+        data.messages = self.message_composer.read(cx).get_templates();
 
         // Update URL from input and strip query parameters
         let raw_url = self.url_input.read(cx).value().to_string();
@@ -647,38 +810,51 @@ impl RequestEditor {
             .header_editor
             .read_with(cx, |editor, cx| editor.get_pairs(cx));
 
-        let (pre_request_script, post_response_script) =
+        let (pre_request_script, post_response_script, event_script) =
             self.script_editor.read_with(cx, |editor, cx| {
                 (
                     editor.get_pre_request_script(cx),
                     editor.get_post_response_script(cx),
+                    editor.get_event_script(cx),
                 )
             });
 
         // Update body from input
         data.body = self.body_input.read(cx).value().to_string();
+        data.graphql = None;
+        data.grpc = None;
+        data.body_file = None;
+        data.multipart = None;
+
+        // A gRPC message is JSON, sent to the method picked in its editor
+        if data.kind == RequestKind::Grpc {
+            data.method = HttpMethod::Post;
+            data.grpc = Some(self.grpc_editor.read(cx).get_grpc());
+        }
 
-        // If Form content type is selected, convert form editor data to body string
-        // This ensures form data is properly saved and variables can be resolved
-        if let Some(selected_content_type) = self.content_type_select.read(cx).selected_value()
-            && selected_content_type == &ContentType::Form
+        // A GraphQL body is the operation's JSON, kept in step with its editors
+        if data.kind != RequestKind::Grpc
+            && let Some(ContentType::GraphQL) = self.content_type_select.read(cx).selected_value()
         {
-            let form_data = self.form_editor.read(cx).get_pairs(cx);
-            let form_body = form_data
-                .iter()
-                .filter(|field| field.enabled && !field.key.is_empty())
-                .map(|field| {
-                    if field.value.starts_with('@') {
-                        // File reference - keep as-is
-                        format!("{}={}", field.key, field.value)
-                    } else {
-                        // Regular form field - URL encode
-                        format!("{}={}", url_encode(&field.key), url_encode(&field.value))
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("&");
-            data.body = form_body;
+            let graphql = self.graphql_editor.read(cx).get_graphql(cx);
+            data.body = graphql.body();
+            data.graphql = Some(graphql);
+        }
+
+        // A file body is streamed from disk in place of the body text
+        if data.kind != RequestKind::Grpc
+            && let Some(ContentType::File) = self.content_type_select.read(cx).selected_value()
+        {
+            data.body = String::new();
+            data.body_file = self.file_body_editor.read(cx).get_file();
+        }
+
+        // A multipart body is sent as its parts in place of the body text
+        if data.kind != RequestKind::Grpc
+            && let Some(ContentType::Form) = self.content_type_select.read(cx).selected_value()
+        {
+            data.body = String::new();
+            data.multipart = Some(self.multipart_editor.read(cx).get_parts(cx));
         }
 
         let vars = self
@@ -690,11 +866,18 @@ impl RequestEditor {
         data.headers = headers;
         data.pre_request_script = pre_request_script;
         data.post_response_script = post_response_script;
+        data.event_script = event_script;
         data.vars = vars;
+        data.settings = self.settings_editor.read(cx).get_settings(cx);
 
         // Set Content-Type header from the dropdown only if the user hasn't set one
-        // in the headers editor.
-        if let Some(selected_content_type) = self.content_type_select.read(cx).selected_value() {
+        // in the headers editor. WebSocket requests have no body to describe,
+        // a file's type is guessed from its extension when it's sent, and a
+        // multipart body's type names the boundary picked when it's sent.
+        if data.kind == RequestKind::Http
+            && let Some(selected_content_type) = self.content_type_select.read(cx).selected_value()
+            && !matches!(selected_content_type, ContentType::File | ContentType::Form)
+        {
             let has_content_type = data
                 .headers
                 .iter()
@@ -724,9 +907,12 @@ impl RequestEditor {
 
         // Compare scalar fields
         if original.name != current.name
+            || original.kind != current.kind
             || original.method != current.method
             || original.url != current.url
             || original.body != current.body
+            || original.body_file != current.body_file
+            || original.multipart != current.multipart
         {
             return true;
         }
@@ -747,6 +933,10 @@ impl RequestEditor {
             _ => return true,
         }
 
+        if original.event_script != current.event_script {
+            return true;
+        }
+
         // Compare vectors using order-independent comparison
         if !KeyValuePair::vec_equals(&original.path_params, &current.path_params) {
             return true;
@@ -765,6 +955,22 @@ impl RequestEditor {
             return true;
         }
 
+        if original.settings != current.settings {
+            return true;
+        }
+
+        if original.messages != current.messages {
+            return true;
+        }
+
+        if original.graphql != current.graphql {
+            return true;
+        }
+
+        if original.grpc != current.grpc {
+            return true;
+        }
+
         false
     }
 
@@ -772,6 +978,7 @@ impl RequestEditor {
     /// synchronously from the child-editor/input subscriptions, which already
     /// fire after their value has settled.
     fn recompute_dirty(&mut self, cx: &mut Context<Self>) {
+        self.refresh_variable_report(cx);
         let is_now_dirty = self.is_dirty(cx);
         if is_now_dirty != self._was_dirty {
             self._was_dirty = is_now_dirty;
@@ -789,8 +996,12 @@ impl RequestEditor {
         };
 
         if let Some(content_type) = content_type {
-            // Update body input syntax highlighting
-            let language = content_type.language();
+            // Update body input syntax highlighting; gRPC messages are JSON
+            let language = if self.selected_kind(cx) == RequestKind::Grpc {
+                ContentType::Json.language()
+            } else {
+                content_type.language()
+            };
             self.body_input.update(cx, |input_state, cx| {
                 input_state.set_highlighter(language, cx);
                 cx.notify();
@@ -798,9 +1009,223 @@ impl RequestEditor {
 
             // Update request data to include proper Content-Type header
             self.request_data = self.get_request_data(cx);
+
+            if content_type == ContentType::GraphQL {
+                self.refresh_graphql_schema(cx);
+            }
         }
     }
 
+    /// Show the schema fetched for the request's URL, if any
+    fn refresh_graphql_schema(&mut self, cx: &mut Context<Self>) {
+        let url = self.url_input.read(cx).value().to_string();
+        let schema = SchemaCache::get(cx, self.collection_path.as_deref(), &url);
+        self.graphql_editor.update(cx, |editor, cx| {
+            editor.set_schema(schema, cx);
+        });
+    }
+
+    /// Fetch the schema of the request's URL by introspection, sent like the
+    /// request itself so its auth, headers and pre-request script apply
+    fn fetch_graphql_schema(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let url = self.url_input.read(cx).value().to_string();
+        let mut request_data = self.get_request_data(cx);
+        request_data.url = self
+            .path_param_editor
+            .read(cx)
+            .replace_path_parameters(&request_data.url, cx);
+        request_data.method = HttpMethod::Post;
+        let graphql = GraphQLRequest {
+            query: INTROSPECTION_QUERY.to_string(),
+            variables: String::new(),
+        };
+        request_data.body = graphql.body();
+        request_data.graphql = Some(graphql);
+        request_data.post_response_script = None;
+        request_data.event_script = None;
+        self.resolve_inherited_auth(&mut request_data, cx);
+
+        let (variables, secrets) = self.load_environment(cx);
+        let (runtime_vars, collection_vars, saved_requests) = self.collection_variables(cx);
+        let scope = RequestScope::new(saved_requests)
+            .with_folder(self.folder_settings(cx))
+            .with_collection(self.collection_defaults(cx))
+            .with_oauth2_prompts(Self::oauth2_prompts(window, cx));
+        let scope = match &self.collection_path {
+            Some(path) => {
+                let environment = self.get_selected_environment(cx).map(|env| env.name);
+                scope.with_cookie_jar(CookieJars::jar(cx, path, environment.as_deref()))
+            }
+            None => scope,
+        };
+        let http_client = match self.http_client(cx) {
+            Ok(client) => client,
+            Err(e) => {
+                window.push_notification(
+                    (NotificationType::Error, SharedString::from(e.to_string())),
+                    cx,
+                );
+                return;
+            }
+        };
+
+        self.graphql_editor
+            .update(cx, |editor, cx| editor.set_fetching(true, cx));
+        let collection_path = self.collection_path.clone();
+        let graphql_editor = self.graphql_editor.clone();
+        cx.spawn_in(window, async move |_this, window| {
+            let result = async_compat::Compat::new(http_client.send_request(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            ))
+            .await
+            .map_err(|e| anyhow::anyhow!(e.summary))
+            .and_then(|(response, _, _)| Schema::from_introspection(&response.body));
+
+            window
+                .update(|window, cx| {
+                    let schema = match result {
+                        Ok(schema) => {
+                            let schema = Arc::new(schema);
+                            SchemaCache::insert(
+                                cx,
+                                collection_path.as_deref(),
+                                &url,
+                                schema.clone(),
+                            );
+                            Some(schema)
+                        }
+                        Err(e) => {
+                            window.push_notification(
+                                (
+                                    NotificationType::Error,
+                                    SharedString::from(format!("Failed to fetch the schema: {e}")),
+                                ),
+                                cx,
+                            );
+                            None
+                        }
+                    };
+                    graphql_editor.update(cx, |editor, cx| {
+                        editor.set_fetching(false, cx);
+                        if schema.is_some() {
+                            editor.set_schema(schema, cx);
+                        }
+                    });
+                })
+                .log_err()
+                .ok();
+        })
+        .detach();
+    }
+
+    /// Show the services loaded for the request's URL and `.proto` files, if any
+    fn refresh_grpc_services(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let url = self.url_input.read(cx).value().to_string();
+        let grpc = self.grpc_editor.read(cx).get_grpc();
+        let services = GrpcServicesCache::get(cx, self.collection_path.as_deref(), &url, &grpc);
+        self.grpc_editor.update(cx, |editor, cx| {
+            editor.set_services(services, window, cx);
+        });
+    }
+
+    /// Load the services of a gRPC request from its `.proto` files, or by
+    /// reflection with the request's auth, metadata and pre-request script
+    fn load_grpc_services(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let url = self.url_input.read(cx).value().to_string();
+        let mut request_data = self.get_request_data(cx);
+        request_data.url = self
+            .path_param_editor
+            .read(cx)
+            .replace_path_parameters(&request_data.url, cx);
+        request_data.post_response_script = None;
+        request_data.event_script = None;
+        self.resolve_inherited_auth(&mut request_data, cx);
+        let grpc = request_data.grpc.clone().unwrap_or_default();
+
+        let (variables, secrets) = self.load_environment(cx);
+        let (runtime_vars, collection_vars, saved_requests) = self.collection_variables(cx);
+        let scope = RequestScope::new(saved_requests)
+            .with_folder(self.folder_settings(cx))
+            .with_collection(self.collection_defaults(cx))
+            .with_oauth2_prompts(Self::oauth2_prompts(window, cx));
+        let scope = match &self.collection_path {
+            Some(path) => {
+                let environment = self.get_selected_environment(cx).map(|env| env.name);
+                scope.with_cookie_jar(CookieJars::jar(cx, path, environment.as_deref()))
+            }
+            None => scope,
+        };
+        let http_client = match self.http_client(cx) {
+            Ok(client) => client,
+            Err(e) => {
+                window.push_notification(
+                    (NotificationType::Error, SharedString::from(e.to_string())),
+                    cx,
+                );
+                return;
+            }
+        };
+
+        self.grpc_editor
+            .update(cx, |editor, cx| editor.set_loading(true, cx));
+        let collection_path = self.collection_path.clone();
+        let grpc_editor = self.grpc_editor.clone();
+        cx.spawn_in(window, async move |_this, window| {
+            let result = async_compat::Compat::new(http_client.grpc_services(
+                request_data,
+                variables,
+                secrets,
+                runtime_vars,
+                collection_vars,
+                scope,
+            ))
+            .await;
+
+            window
+                .update(|window, cx| {
+                    let services = match result {
+                        Ok(services) => {
+                            GrpcServicesCache::insert(
+                                cx,
+                                collection_path.as_deref(),
+                                &url,
+                                &grpc,
+                                services.clone(),
+                            );
+                            Some(services)
+                        }
+                        Err(e) => {
+                            window.push_notification(
+                                (
+                                    NotificationType::Error,
+                                    SharedString::from(format!(
+                                        "Failed to load the services: {}",
+                                        e.summary
+                                    )),
+                                ),
+                                cx,
+                            );
+                            None
+                        }
+                    };
+                    grpc_editor.update(cx, |editor, cx| {
+                        editor.set_loading(false, cx);
+                        if services.is_some() {
+                            editor.set_services(services, window, cx);
+                        }
+                    });
+                })
+                .log_err()
+                .ok();
+        })
+        .detach();
+    }
+
     fn on_environment_change(&mut self, cx: &mut Context<Self>) {
         // Get the selected environment
         let selected_env = self.get_selected_environment(cx);
@@ -811,18 +1236,561 @@ impl RequestEditor {
             tracing::info!("Environment changed to: None");
         }
 
+        self.refresh_variable_report(cx);
+        cx.notify();
+    }
+
+    fn selected_kind(&self, cx: &App) -> RequestKind {
+        self.kind_select
+            .read(cx)
+            .selected_value()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// WebSocket requests compose messages where HTTP and gRPC requests have
+    /// a body
+    fn sync_tab_with_kind(&mut self, kind: RequestKind) {
+        self.active_tab = match (kind, self.active_tab) {
+            (RequestKind::WebSocket, RequestTab::Body) => RequestTab::Messages,
+            (RequestKind::Http | RequestKind::Grpc, RequestTab::Messages) => RequestTab::Body,
+            (_, tab) => tab,
+        };
+        if kind != RequestKind::WebSocket && self.active_response_tab == ResponseTab::Messages {
+            self.active_response_tab = ResponseTab::Response;
+        }
+    }
+
+    fn on_kind_change(&mut self, cx: &mut Context<Self>) {
+        let kind = self.selected_kind(cx);
+        self.sync_tab_with_kind(kind);
+        self.on_content_type_change(cx);
+        self.recompute_dirty(cx);
         cx.notify();
     }
 
+    fn is_websocket_open(&self) -> bool {
+        self.websocket
+            .as_ref()
+            .is_some_and(|session| session.is_open())
+    }
+
     fn cancel_request(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        // Dropping the task aborts the in-flight request.
+        // An open connection is closed with a handshake; the task ends once
+        // the server acknowledges it.
+        if let Some(session) = self.websocket.as_ref().filter(|session| session.is_open()) {
+            session.close();
+            return;
+        }
+
+        // Dropping the task aborts the in-flight request, including a body
+        // being downloaded (and removes its temporary file).
         self.current_request_task.take();
+        self._progress_task.take();
         self.is_loading = false;
 
-        window.push_notification((NotificationType::Info, "Request cancelled"), cx);
+        let progress = self.response_progress.take();
+        let message = match progress.as_ref().map(|p| p.received()) {
+            Some(received) if received > 0 => format!(
+                "Request cancelled after receiving {}",
+                format_bytes(received)
+            ),
+            _ => "Request cancelled".to_string(),
+        };
+
+        // Cancelling is how an event stream usually ends, so keep its events
+        if let Some(events) = progress
+            .map(|progress| progress.events())
+            .filter(|events| !events.is_empty())
+        {
+            self.set_response_data(ResponseData {
+                events,
+                ..Default::default()
+            });
+            self.test_results.clear();
+            for input in [&self.response_input, &self.raw_response_input] {
+                input.update(cx, |input, cx| input.set_value("", window, cx));
+            }
+        }
+        window.push_notification((NotificationType::Info, SharedString::from(message)), cx);
+        cx.notify();
+    }
+
+    /// Show `response_data`. The file the previous body was saved to goes
+    /// away with the previous response.
+    fn set_response_data(&mut self, response_data: ResponseData) {
+        self.response_data = response_data;
+        self.response_progress = None;
+        self._progress_task = None;
+    }
+
+    /// Refresh the download indicator while the request is in flight
+    fn watch_progress(&mut self, progress: ResponseProgress, cx: &mut Context<Self>) {
+        self.response_progress = Some(progress.clone());
+        self._progress_task = Some(cx.spawn(async move |this, cx| {
+            let mut showed_events = false;
+            loop {
+                cx.background_executor()
+                    .timer(Duration::from_millis(250))
+                    .await;
+                let loading = this
+                    .update(cx, |this, cx| {
+                        // Show the event log once an event stream delivers
+                        if !showed_events && progress.event_count() > 0 {
+                            showed_events = true;
+                            this.active_response_tab = ResponseTab::Events;
+                        }
+                        cx.notify();
+                        this.is_loading
+                    })
+                    .unwrap_or(false);
+                if !loading {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// Show the handshake of an opened WebSocket connection and follow its
+    /// frames
+    fn show_websocket(
+        &mut self,
+        handshake: ResponseData,
+        session: WebSocketSession,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let raw_content = handshake.format_raw_response();
+        self.set_response_data(handshake);
+        self.websocket = Some(session);
+        self.test_results.clear();
+        self.response_format = ResponseFormat::Unknown;
+        self.original_response_body = None;
+        self.response_image = None;
+        self.active_response_tab = ResponseTab::Messages;
+        self.response_input.update(cx, |input, cx| {
+            input.set_value("", window, cx);
+        });
+        self.raw_response_input.update(cx, |input, cx| {
+            input.set_value(raw_content, window, cx);
+        });
+        self.message_composer.update(cx, |composer, cx| {
+            composer.set_connected(true, cx);
+        });
+
+        // Refresh the frame log while the connection is open
+        self._progress_task = Some(cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor()
+                    .timer(Duration::from_millis(250))
+                    .await;
+                let open = this
+                    .update(cx, |this, cx| {
+                        cx.notify();
+                        this.is_loading
+                    })
+                    .unwrap_or(false);
+                if !open {
+                    break;
+                }
+            }
+        }));
         cx.notify();
     }
 
+    /// Show why a request failed in place of its response
+    fn show_request_error(
+        &mut self,
+        error: HttpError,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut response_data = ResponseData {
+            status_text: Some("Error".to_string()),
+            size: Some(error.details.len()),
+            body: error.details.clone(),
+            ..Default::default()
+        };
+        // Keep the events a stream delivered before it failed
+        if let Some(progress) = &self.response_progress {
+            response_data.events = progress.events();
+        }
+        self.set_response_data(response_data);
+        self.test_results = error.tests.clone();
+        self.is_loading = false;
+        self.response_image = None;
+
+        self.response_input.update(cx, |input_state, cx| {
+            input_state.set_value(&error.details, window, cx);
+            cx.notify();
+        });
+
+        window.push_notification(
+            (NotificationType::Error, SharedString::from(error.summary)),
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// Copy a response body that was saved to a temporary file to a path
+    /// the user picks
+    fn save_response_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(source) = self.response_data.body_file.clone() else {
+            return;
+        };
+        let suggested_name = self
+            .response_data
+            .url
+            .as_deref()
+            .and_then(|url| url::Url::parse(url).ok())
+            .and_then(|url| {
+                url.path_segments()
+                    .and_then(|mut segments| segments.next_back().map(str::to_string))
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "response".to_string());
+        let directory = dirs::download_dir().unwrap_or_else(std::env::temp_dir);
+        let path_future = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+
+        cx.spawn_in(window, async move |_, window| {
+            match path_future.await {
+                Ok(Ok(Some(target))) => {
+                    // The guard keeps the file until it's copied
+                    let copied = smol::unblock(move || std::fs::copy(source.path(), &target)).await;
+                    if let Err(e) = copied {
+                        window
+                            .update(|window, cx| {
+                                window.push_notification(
+                                    (
+                                        NotificationType::Error,
+                                        SharedString::from(format!(
+                                            "Failed to save response: {}",
+                                            e
+                                        )),
+                                    ),
+                                    cx,
+                                );
+                            })
+                            .log_err()
+                            .ok();
+                    }
+                }
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => tracing::error!("Failed to select file: {}", e),
+                Err(e) => tracing::error!("Failed to open file dialog: {}", e),
+            }
+            Some(())
+        })
+        .detach();
+    }
+
+    /// Variables and secrets of the selected environment
+    fn load_environment(
+        &self,
+        cx: &Context<Self>,
+    ) -> (
+        Option<HashMap<String, String>>,
+        Option<HashMap<String, String>>,
+    ) {
+        let Some(selected_env) = self.get_selected_environment(cx) else {
+            return (None, None);
+        };
+        let env_resolver = EnvironmentResolver::new();
+        let env_name = selected_env.name.clone();
+        let collection_manager = CollectionManager::global(cx);
+        let collection_manager = collection_manager.read(cx);
+
+        if let Some(ref collection_path) = self.collection_path
+            && let Some(collection) = collection_manager.get_collection_by_path(collection_path)
+        {
+            match env_resolver.load_environment_data(
+                &collection.data.name,
+                &env_name,
+                &[selected_env],
+                cx,
+            ) {
+                Ok((vars, secs)) => {
+                    tracing::info!(
+                        "Loaded {} variables and {} secrets for environment '{}'",
+                        vars.len(),
+                        secs.len(),
+                        env_name
+                    );
+                    (Some(vars), Some(secs))
+                }
+                Err(e) => {
+                    tracing::error!("Failed to load environment data: {}", e);
+                    (None, None)
+                }
+            }
+        } else {
+            (None, None)
+        }
+    }
+
+    /// Replace an inherited auth with the folder's, or the collection's
+    fn resolve_inherited_auth(&self, request_data: &mut RequestData, cx: &Context<Self>) {
+        if matches!(request_data.auth, AuthType::Inherit) {
+            let collection_auth = self
+                .collection_path
+                .as_ref()
+                .and_then(|path| {
+                    CollectionManager::global(cx)
+                        .read(cx)
+                        .get_collection_by_path(path)
+                })
+                .and_then(|collection| collection.toml.collection.auth.clone());
+            request_data.auth = self
+                .folder_settings(cx)
+                .inherited_auth(collection_auth.as_ref());
+        }
+    }
+
+    /// Settings of the group this request is saved in, if any
+    fn folder_settings(&self, cx: &Context<Self>) -> FolderSettings {
+        let (Some(path), Some(group_path)) = (&self.collection_path, &self.group_path) else {
+            return FolderSettings::default();
+        };
+        CollectionManager::global(cx)
+            .read(cx)
+            .get_collection_by_path(path)
+            .and_then(|info| info.groups.values().find(|group| &group.path == group_path))
+            .map(|group| group.folder.clone())
+            .unwrap_or_default()
+    }
+
+    /// Headers and scripts of the collection this request is saved in, if any
+    fn collection_defaults(&self, cx: &Context<Self>) -> CollectionDefaults {
+        self.collection_path
+            .as_ref()
+            .and_then(|path| {
+                CollectionManager::global(cx)
+                    .read(cx)
+                    .get_collection_by_path(path)
+            })
+            .map(|info| info.toml.collection.defaults())
+            .unwrap_or_default()
+    }
+
+    /// Where OAuth2 grants of a request ask the user to sign in: the
+    /// authorization page opens in the browser, and device codes are shown
+    /// in a dialog with their verification page opened
+    fn oauth2_prompts(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> smol::channel::Sender<OAuth2Prompt> {
+        let (sender, receiver) = smol::channel::unbounded();
+        cx.spawn_in(window, async move |_this, window| {
+            // The device code dialog on screen, until it's closed
+            let mut device_code: Option<OAuth2Cancel> = None;
+            while let Ok(prompt) = receiver.recv().await {
+                window
+                    .update(|window, cx| match prompt {
+                        OAuth2Prompt::OpenUrl(url) => cx.open_url(&url),
+                        OAuth2Prompt::DeviceCode {
+                            user_code,
+                            verification_uri,
+                            verification_uri_complete,
+                            cancel,
+                        } => {
+                            cx.open_url(
+                                verification_uri_complete
+                                    .as_deref()
+                                    .unwrap_or(&verification_uri),
+                            );
+                            Self::open_device_code_dialog(
+                                user_code,
+                                verification_uri,
+                                cancel.clone(),
+                                window,
+                                cx,
+                            );
+                            device_code = Some(cancel);
+                        }
+                        OAuth2Prompt::Done => {
+                            // A cancelled dialog has closed already
+                            if let Some(cancel) = device_code.take()
+                                && !cancel.is_cancelled()
+                            {
+                                window.close_dialog(cx);
+                            }
+                        }
+                    })
+                    .log_err()
+                    .ok();
+            }
+        })
+        .detach();
+        sender
+    }
+
+    /// Show the user code of a device code grant while it waits for the code
+    /// to be entered. Closing the dialog stops the wait.
+    fn open_device_code_dialog(
+        user_code: String,
+        verification_uri: String,
+        cancel: OAuth2Cancel,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        window.open_dialog(cx, move |dialog, _window, cx| {
+            let on_close = cancel.clone();
+            let on_cancel = cancel.clone();
+            let code = user_code.clone();
+            dialog
+                .title("Sign in on another device")
+                .on_close(move |_, _, _| on_close.cancel())
+                .child(
+                    v_flex()
+                        .gap_3()
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!("Enter this code at {}", verification_uri)),
+                        )
+                        .child(div().text_2xl().font_semibold().child(user_code.clone()))
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .justify_end()
+                                .child(
+                                    Button::new("copy-device-code")
+                                        .icon(IconName::Copy)
+                                        .label("Copy Code")
+                                        .on_click(move |_, _, cx| {
+                                            cx.write_to_clipboard(gpui::ClipboardItem::new_string(
+                                                code.clone(),
+                                            ));
+                                        }),
+                                )
+                                .child(Button::new("cancel-device-code").label("Cancel").on_click(
+                                    move |_, window, cx| {
+                                        on_cancel.cancel();
+                                        window.close_dialog(cx);
+                                    },
+                                )),
+                        ),
+                )
+        });
+    }
+
+    /// The collection's runtime and declared variables, and its saved requests
+    fn collection_variables(
+        &self,
+        cx: &Context<Self>,
+    ) -> (
+        Option<HashMap<String, serde_json::Value>>,
+        Option<HashMap<String, String>>,
+        SavedRequests,
+    ) {
+        let Some(path) = &self.collection_path else {
+            return (None, None, SavedRequests::default());
+        };
+        let manager = CollectionManager::global(cx);
+        let Some(info) = manager.read(cx).get_collection_by_path(path) else {
+            return (None, None, SavedRequests::default());
+        };
+        let cv: HashMap<String, String> = info
+            .toml
+            .collection
+            .vars
+            .iter()
+            .filter(|v| v.enabled && !v.key.is_empty())
+            .map(|v| (v.key.clone(), v.value.clone()))
+            .collect();
+        (
+            Some(info.runtime_vars.clone()),
+            Some(cv),
+            SavedRequests::from_collection(info),
+        )
+    }
+
+    /// Work out where the request's placeholders resolve from, as sending it
+    /// would. Secrets aren't read from the keychain, only named.
+    fn refresh_variable_report(&mut self, cx: &mut Context<Self>) {
+        let mut request_data = self.get_request_data(cx);
+        self.resolve_inherited_auth(&mut request_data, cx);
+        let folder = self.folder_settings(cx);
+        folder.apply_headers(&mut request_data);
+        self.collection_defaults(cx)
+            .apply_headers(&mut request_data);
+
+        let mut tiers = VariableTiers {
+            request: request_data
+                .vars
+                .iter()
+                .filter(|v| v.enabled && !v.key.is_empty())
+                .map(|v| (v.key.clone(), v.value.clone()))
+                .collect(),
+            folder: folder
+                .vars
+                .iter()
+                .filter(|v| v.enabled && !v.key.is_empty())
+                .map(|v| (v.key.clone(), v.value.clone()))
+                .collect(),
+            ..Default::default()
+        };
+        let (runtime_vars, collection_vars, _) = self.collection_variables(cx);
+        tiers.runtime = runtime_vars
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                (name, value)
+            })
+            .collect();
+        tiers.collection = collection_vars.unwrap_or_default();
+        if let Some(environment) = self.get_selected_environment(cx) {
+            for (name, variable) in environment.variables {
+                if variable.temporary {
+                    continue;
+                }
+                if variable.secret {
+                    tiers.secrets.insert(name, String::new());
+                } else {
+                    tiers.environment.insert(name, variable.value);
+                }
+            }
+        }
+
+        let report = EnvironmentResolver::new().inspect_request(&request_data, &tiers);
+        if report != self.variable_report {
+            self.variable_report = report;
+            cx.notify();
+        }
+    }
+
+    /// HTTP client with the collection's proxy, TLS and HTTP version overrides
+    fn http_client(&self, cx: &Context<Self>) -> Result<HttpClientService, HttpError> {
+        let collection_connection = self.collection_path.as_ref().and_then(|path| {
+            CollectionManager::global(cx)
+                .read(cx)
+                .get_collection_by_path(path)
+                .map(|info| {
+                    (
+                        info.toml.collection.proxy.clone(),
+                        info.toml.collection.tls.clone(),
+                        info.toml.collection.http_version,
+                        info.data.path.clone(),
+                    )
+                })
+        });
+        match &collection_connection {
+            Some((proxy, tls, http_version, path)) => HttpClientService::global(cx).for_collection(
+                proxy.as_ref(),
+                tls.as_ref(),
+                *http_version,
+                Path::new(path),
+            ),
+            None => Ok(HttpClientService::global(cx).clone()),
+        }
+    }
+
     pub fn send_request(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.is_loading {
             return;
@@ -833,30 +1801,7 @@ impl RequestEditor {
         cx.notify();
 
         // Get the current request data
-        let mut request_data = self.get_request_data(cx);
-
-        // Check if Form Data content type is selected and update form data
-        if let Some(selected_content_type) = self.content_type_select.read(cx).selected_value()
-            && selected_content_type == &ContentType::Form
-        {
-            let form_data = self.form_editor.read(cx).get_pairs(cx);
-            // Convert form data to body format (key=value pairs, files as @path)
-            let form_body = form_data
-                .iter()
-                .filter(|field| field.enabled && !field.key.is_empty())
-                .map(|field| {
-                    if field.value.starts_with('@') {
-                        // File reference
-                        format!("{}={}", field.key, field.value)
-                    } else {
-                        // Regular form field
-                        format!("{}={}", url_encode(&field.key), url_encode(&field.value))
-                    }
-                })
-                .collect::<Vec<_>>()
-                .join("&");
-            request_data.body = form_body;
-        }
+        let request_data = self.get_request_data(cx);
 
         // Perform path parameter replacement on the URL
         let final_url = self
@@ -876,83 +1821,109 @@ impl RequestEditor {
         final_request_data.url = final_url;
 
         // Load environment variables and secrets in the main thread
-        let (variables, secrets) = if let Some(selected_env) = self.get_selected_environment(cx) {
-            let env_resolver = EnvironmentResolver::new();
-            let env_name = selected_env.name.clone();
-            let selected_env_clone = selected_env.clone();
-            let collection_manager = CollectionManager::global(cx);
-            let collection_manager = collection_manager.read(cx);
-
-            if let Some(ref collection_path) = self.collection_path
-                && let Some(collection) = collection_manager.get_collection_by_path(collection_path)
-            {
-                match env_resolver.load_environment_data(
-                    &collection.data.name,
-                    &env_name,
-                    &[selected_env_clone],
-                    cx,
-                ) {
-                    Ok((vars, secs)) => {
-                        tracing::info!(
-                            "Loaded {} variables and {} secrets for environment '{}'",
-                            vars.len(),
-                            secs.len(),
-                            env_name
-                        );
-                        (Some(vars), Some(secs))
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to load environment data: {}", e);
-                        (None, None)
-                    }
-                }
-            } else {
-                (None, None)
-            }
-        } else {
-            (None, None)
-        };
+        let (variables, secrets) = self.load_environment(cx);
 
         // Resolve inherited auth from collection
-        if matches!(final_request_data.auth, crate::domain::AuthType::Inherit) {
-            final_request_data.auth = self
-                .collection_path
-                .as_ref()
-                .and_then(|path| {
-                    CollectionManager::global(cx)
-                        .read(cx)
-                        .get_collection_by_path(path)
-                })
-                .and_then(|collection| collection.toml.collection.auth.clone())
-                .unwrap_or_default();
-        }
+        self.resolve_inherited_auth(&mut final_request_data, cx);
 
         // Gather the collection's session runtime vars and declared collection
         // vars so they can be seeded into the request's VariableStore and
-        // participate in {{}} resolution (precedence: runtime > collection).
-        let (runtime_vars_for_request, collection_vars_for_request) =
-            if let Some(path) = &self.collection_path {
-                let manager = CollectionManager::global(cx);
-                let collection = manager.read(cx).get_collection_by_path(path);
-                if let Some(info) = collection {
-                    let cv: HashMap<String, String> = info
-                        .toml
-                        .collection
-                        .vars
-                        .iter()
-                        .filter(|v| v.enabled && !v.key.is_empty())
-                        .map(|v| (v.key.clone(), v.value.clone()))
-                        .collect();
-                    (Some(info.runtime_vars.clone()), Some(cv))
-                } else {
-                    (None, None)
+        // participate in {{}} resolution (precedence: runtime > collection),
+        // plus its saved requests for bro.runRequest.
+        let (runtime_vars_for_request, collection_vars_for_request, saved_requests) =
+            self.collection_variables(cx);
+
+        // Requests of a collection share its cookie jar for the environment
+        let cookie_jar = self.collection_path.clone().map(|path| {
+            let environment = self.get_selected_environment(cx).map(|env| env.name);
+            let jar = CookieJars::jar(cx, &path, environment.as_deref());
+            (path, environment, jar)
+        });
+        let progress = ResponseProgress::default();
+        let scope = RequestScope::new(saved_requests)
+            .with_folder(self.folder_settings(cx))
+            .with_collection(self.collection_defaults(cx))
+            .with_oauth2_prompts(Self::oauth2_prompts(window, cx))
+            .with_progress(progress.clone());
+        let scope = match &cookie_jar {
+            Some((_, _, jar)) => scope.with_cookie_jar(jar.clone()),
+            None => scope,
+        };
+        self.watch_progress(progress, cx);
+
+        // Get the HTTP client after updating UI to avoid borrow issues. A
+        // collection may override the proxy and TLS options.
+        let http_client = self.http_client(cx);
+        let http_client = match http_client {
+            Ok(client) => client,
+            Err(e) => {
+                self.is_loading = false;
+                window.push_notification(
+                    (NotificationType::Error, SharedString::from(e.to_string())),
+                    cx,
+                );
+                cx.notify();
+                return;
+            }
+        };
+        if final_request_data.kind == RequestKind::WebSocket {
+            let editor_entity = cx.entity().clone();
+            let task = cx.spawn_in(window, async move |_this, window| {
+                let result = async_compat::Compat::new(http_client.connect_websocket(
+                    final_request_data,
+                    variables,
+                    secrets,
+                    runtime_vars_for_request,
+                    collection_vars_for_request,
+                    scope,
+                ))
+                .await;
+
+                // Scripts may have changed cookies even if the handshake failed
+                if let Some((path, environment, jar)) = &cookie_jar {
+                    window
+                        .update(|_, cx| CookieJars::persist(cx, path, environment.as_deref(), jar))
+                        .log_err()
+                        .ok();
                 }
-            } else {
-                (None, None)
-            };
 
-        // Get the HTTP client after updating UI to avoid borrow issues
-        let http_client = HttpClientService::global(cx);
+                match result {
+                    Ok(connection) => {
+                        let session = connection.session.clone();
+                        let handshake = connection.handshake.clone();
+                        window.update(|window, cx| {
+                            editor_entity.update(cx, |request_editor, cx| {
+                                request_editor.show_websocket(handshake, session, window, cx);
+                            });
+                        })?;
+
+                        // Pump frames until either side closes the connection
+                        async_compat::Compat::new(connection.run()).await;
+
+                        window.update(|_, cx| {
+                            editor_entity.update(cx, |request_editor, cx| {
+                                request_editor.is_loading = false;
+                                request_editor.message_composer.update(cx, |composer, cx| {
+                                    composer.set_connected(false, cx);
+                                });
+                                cx.notify();
+                            });
+                        })?;
+                    }
+                    Err(error) => {
+                        window.update(|window, cx| {
+                            editor_entity.update(cx, |request_editor, cx| {
+                                request_editor.show_request_error(error, window, cx);
+                            });
+                        })?;
+                    }
+                }
+                Ok::<(), anyhow::Error>(())
+            });
+            self.current_request_task = Some(task);
+            return;
+        }
+
         // Capture the manager entity so the async task can write back dirty env
         // vars and runtime vars after the request completes.
         let collection_manager = CollectionManager::global(cx);
@@ -972,19 +1943,30 @@ impl RequestEditor {
 
         // Execute request using async-compat and GPUI's spawn
         let request_data_clone1 = final_request_data.clone();
-        let http_client_clone = http_client.clone();
+        let http_client_clone = http_client;
 
         let task = cx.spawn_in(window, async move |_this, window| {
-            match async_compat::Compat::new(
+            let result = async_compat::Compat::new(
                 http_client_clone.send_request(
                     request_data_clone1,
                     variables,
                     secrets,
                     runtime_vars_for_request,
                     collection_vars_for_request,
+                    scope,
                 )
-            ).await {
-                Ok((response_data, variable_store)) => {
+            ).await;
+
+            // Scripts may have changed cookies even if the request failed
+            if let Some((path, environment, jar)) = &cookie_jar {
+                window
+                    .update(|_, cx| CookieJars::persist(cx, path, environment.as_deref(), jar))
+                    .log_err()
+                    .ok();
+            }
+
+            match result {
+                Ok((response_data, variable_store, test_results)) => {
                     // Check for dirty environment variables
                     let dirty_vars = variable_store.get_dirty_env_vars();
                     if !dirty_vars.is_empty() {
@@ -1059,7 +2041,8 @@ impl RequestEditor {
 
                         // Update the RequestEditor's response_data for status bar and reset loading state
                         editor_entity.update(cx, |request_editor, cx| {
-                            request_editor.response_data = response_data.clone();
+                            request_editor.set_response_data(response_data.clone());
+                            request_editor.test_results = test_results;
                             request_editor.is_loading = false;
                             request_editor.response_format = format;
                             request_editor.original_response_body = Some(formatted_content.clone());
@@ -1090,41 +2073,10 @@ impl RequestEditor {
                     })?;
                 }
                 Err(error) => {
-                    let http_error = error;
-
-                    let error_summary = SharedString::from(http_error.summary.clone());
-
                     window.update(|window, cx| {
-                        // Create error response
-                        let response_data = ResponseData {
-                            status_code: None,
-                            status_text: Some("Error".to_string()),
-                            latency: None,
-                            size: Some(http_error.details.len()),
-                            headers: vec![],
-                            request_headers: vec![],
-                            body: http_error.details.clone(),
-                            body_bytes: None,
-                            url: None,
-                        };
-
-                        // Update the RequestEditor's response_data for status bar and reset loading state
                         editor_entity.update(cx, |request_editor, cx| {
-                            request_editor.response_data = response_data.clone();
-                            request_editor.is_loading = false;
-                            request_editor.response_image = None;
-                            cx.notify();
+                            request_editor.show_request_error(error, window, cx);
                         });
-
-                        response_input.update(cx, |input_state, cx| {
-                            input_state.set_value(&response_data.body, window, cx);
-                            cx.notify();
-                        });
-
-                        window.push_notification(
-                            (NotificationType::Error, error_summary.clone()),
-                             cx,
-                        );
                     })?;
                 }
             }
@@ -1208,11 +2160,7 @@ impl RequestEditor {
         };
 
         // Set method
-        if let Some(index) = HttpMethod::ALL.iter().position(|&m| m == parsed.method) {
-            self.method_select.update(cx, |state, cx| {
-                state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
-            });
-        }
+        self.set_method(&parsed.method, window, cx);
 
         // Set URL (strip query params so they go to the query editor)
         let (base_url, url_query) = Self::strip_query_params_from_url(&parsed.url);
@@ -1231,6 +2179,42 @@ impl RequestEditor {
                 state.set_value(&body, window, cx);
             });
         }
+        if let Some(file) = parsed.body_file {
+            self.file_body_editor.update(cx, |editor, cx| {
+                editor.set_file(Some(file), cx);
+            });
+            if let Some(index) = ContentType::ALL
+                .iter()
+                .position(|ct| *ct == ContentType::File)
+            {
+                self.content_type_select.update(cx, |state, cx| {
+                    state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
+                });
+            }
+        }
+        if let Some(parts) = parsed.multipart {
+            self.multipart_editor.update(cx, |editor, cx| {
+                editor.set_parts(&parts, window, cx);
+            });
+            if let Some(index) = ContentType::ALL
+                .iter()
+                .position(|ct| *ct == ContentType::Form)
+            {
+                self.content_type_select.update(cx, |state, cx| {
+                    state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
+                });
+            }
+        }
+
+        if let Some(http_version) = parsed.http_version {
+            self.settings_editor.update(cx, |editor, cx| {
+                let settings = RequestSettings {
+                    http_version: Some(http_version),
+                    ..editor.get_settings(cx)
+                };
+                editor.set_settings(&settings, window, cx);
+            });
+        }
 
         // Handle basic auth from -u flag
         if let Some((user, pass)) = parsed.basic_auth {
@@ -1260,17 +2244,7 @@ impl RequestEditor {
             });
         }
 
-        if parsed.is_multipart {
-            window.push_notification(
-                (
-                    NotificationType::Warning,
-                    "Multipart cURL imported with limitations: form fields kept as raw body",
-                ),
-                cx,
-            );
-        } else {
-            window.push_notification((NotificationType::Success, "Imported from cURL"), cx);
-        }
+        window.push_notification((NotificationType::Success, "Imported from cURL"), cx);
         true
     }
 
@@ -1279,9 +2253,25 @@ impl RequestEditor {
             .method_select
             .read(cx)
             .selected_value()
-            .unwrap_or(&HttpMethod::Get);
+            .cloned()
+            .unwrap_or(HttpMethod::Get);
 
         let method_color = selected_method.get_color(cx);
+        let is_custom_method = matches!(selected_method, HttpMethod::Custom(_));
+        let kind = self.selected_kind(cx);
+        let is_websocket = kind == RequestKind::WebSocket;
+        let connected = self.is_websocket_open();
+        let url_variables = variables_tooltip(&self.variable_report.url);
+        let unresolved = self.variable_report.unresolved();
+        let unresolved_count = unresolved.len();
+        let unresolved_tooltip = SharedString::from(format!(
+            "Not resolved, sent as written: {}",
+            unresolved
+                .iter()
+                .map(|name| format!("{{{{{}}}}}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
 
         h_flex()
             .gap_3()
@@ -1293,11 +2283,28 @@ impl RequestEditor {
                     .gap_3()
                     .child(
                         div()
-                            .w(px(120.))
+                            .w(px(90.))
                             .font_bold()
                             .font_family(cx.theme().mono_font_family.clone())
-                            .child(Select::new(&self.method_select).text_color(method_color)),
+                            .child(Select::new(&self.kind_select)),
                     )
+                    .when(kind == RequestKind::Http, |this| {
+                        this.child(
+                            div()
+                                .w(px(120.))
+                                .font_bold()
+                                .font_family(cx.theme().mono_font_family.clone())
+                                .child(Select::new(&self.method_select).text_color(method_color)),
+                        )
+                        .when(is_custom_method, |this| {
+                            this.child(
+                                div()
+                                    .w(px(110.))
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .child(Input::new(&self.custom_method_input)),
+                            )
+                        })
+                    })
                     .child(
                         div()
                             .flex_auto()
@@ -1311,12 +2318,20 @@ impl RequestEditor {
                     .refine_style(&self.url_bar_style_refinement)
                     .min_w(px(300.))
                     .child(
-                        div().flex_1().child(
-                            Input::new(&self.url_input)
-                                .cleanable(true)
-                                .font_family(cx.theme().mono_font_family.clone())
-                                .text_sm(),
-                        ),
+                        div()
+                            .id("url-variables")
+                            .flex_1()
+                            .when_some(url_variables, |this, text| {
+                                this.tooltip(move |window, cx| {
+                                    Tooltip::new(text.clone()).build(window, cx)
+                                })
+                            })
+                            .child(
+                                Input::new(&self.url_input)
+                                    .cleanable(true)
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .text_sm(),
+                            ),
                     )
                     .child(
                         // A single mouse-down handler toggles between send and
@@ -1337,21 +2352,58 @@ impl RequestEditor {
                             .child(
                                 Button::new("send-request")
                                     .primary()
-                                    .icon(IconName::Send)
-                                    .loading(self.is_loading)
+                                    .when(is_websocket, |button| {
+                                        button.label(if connected {
+                                            "Disconnect"
+                                        } else {
+                                            "Connect"
+                                        })
+                                    })
+                                    .icon(if connected {
+                                        IconName::Close
+                                    } else {
+                                        IconName::Send
+                                    })
+                                    .loading(self.is_loading && !connected)
                                     .loading_icon(IconName::LoaderCircle),
-                            ),
+                            )
+                            .relative()
+                            .when(unresolved_count > 0, |this| {
+                                this.child(
+                                    div()
+                                        .id("unresolved-variables")
+                                        .absolute()
+                                        .top(px(-6.))
+                                        .right(px(-6.))
+                                        .flex()
+                                        .items_center()
+                                        .justify_center()
+                                        .size(px(16.))
+                                        .rounded_full()
+                                        .bg(cx.theme().yellow)
+                                        .text_color(gpui::black())
+                                        .text_size(px(10.))
+                                        .font_bold()
+                                        .child(unresolved_count.to_string())
+                                        .tooltip(move |window, cx| {
+                                            Tooltip::new(unresolved_tooltip.clone())
+                                                .build(window, cx)
+                                        }),
+                                )
+                            }),
                     )
-                    .child(
-                        Button::new("copy-curl")
-                            .ghost()
-                            .compact()
-                            .icon(IconName::Copy)
-                            .tooltip("Copy as cURL")
-                            .on_click(cx.listener(|this, _, window, cx| {
-                                this.copy_as_curl(window, cx);
-                            })),
-                    ),
+                    .when(kind != RequestKind::Grpc, |this| {
+                        this.child(
+                            Button::new("copy-curl")
+                                .ghost()
+                                .compact()
+                                .icon(IconName::Copy)
+                                .tooltip("Copy as cURL")
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.copy_as_curl(window, cx);
+                                })),
+                        )
+                    }),
             )
     }
 
@@ -1367,6 +2419,10 @@ impl RequestEditor {
         let vars_count = self
             .request_vars_editor
             .read_with(cx, |editor, cx| editor.count(cx));
+        let settings_count = self.settings_editor.read(cx).count(cx);
+        let kind = self.selected_kind(cx);
+        let is_websocket = kind == RequestKind::WebSocket;
+        let template_count = self.message_composer.read(cx).get_templates().len();
 
         // The segmented trough is full-bleed, so inset it with a wrapper.
         div().p(px(6.)).min_w_0().child(
@@ -1374,22 +2430,25 @@ impl RequestEditor {
                 .segmented()
                 .selected_index(match self.active_tab {
                     RequestTab::Query => 0,
-                    RequestTab::Body => 1,
+                    RequestTab::Body | RequestTab::Messages => 1,
                     RequestTab::Headers => 2,
                     RequestTab::Auth => 3,
                     RequestTab::Path => 4,
                     RequestTab::Vars => 5,
                     RequestTab::Scripts => 6,
+                    RequestTab::Settings => 7,
                 })
-                .on_click(cx.listener(|this, &index, _, cx| {
+                .on_click(cx.listener(move |this, &index, _, cx| {
                     this.active_tab = match index {
                         0 => RequestTab::Query,
+                        1 if is_websocket => RequestTab::Messages,
                         1 => RequestTab::Body,
                         2 => RequestTab::Headers,
                         3 => RequestTab::Auth,
                         4 => RequestTab::Path,
                         5 => RequestTab::Vars,
                         6 => RequestTab::Scripts,
+                        7 => RequestTab::Settings,
                         _ => RequestTab::Query,
                     };
                     cx.notify();
@@ -1397,11 +2456,21 @@ impl RequestEditor {
                 .child(Tab::new().label("Query").when(query_count > 0, |tab| {
                     tab.pr_2().suffix(TabBadge::new().count(query_count))
                 }))
-                .child(
+                .child(if is_websocket {
                     Tab::new()
-                        .label("Body")
-                        .when(has_body, |tab| tab.pr_2().suffix(TabBadge::new().count(1))),
-                )
+                        .label("Messages")
+                        .when(template_count > 0, |tab| {
+                            tab.pr_2().suffix(TabBadge::new().count(template_count))
+                        })
+                } else {
+                    Tab::new()
+                        .label(if kind == RequestKind::Grpc {
+                            "Message"
+                        } else {
+                            "Body"
+                        })
+                        .when(has_body, |tab| tab.pr_2().suffix(TabBadge::new().count(1)))
+                })
                 .child(Tab::new().label("Headers").when(headers_count > 0, |tab| {
                     tab.pr_2().suffix(TabBadge::new().count(headers_count))
                 }))
@@ -1418,7 +2487,14 @@ impl RequestEditor {
                 }))
                 .child(Tab::new().label("Scripts").when(scripts_count > 0, |tab| {
                     tab.pr_2().suffix(TabBadge::new().count(scripts_count))
-                })),
+                }))
+                .child(
+                    Tab::new()
+                        .label("Settings")
+                        .when(settings_count > 0, |tab| {
+                            tab.pr_2().suffix(TabBadge::new().count(settings_count))
+                        }),
+                ),
         )
     }
 
@@ -1426,6 +2502,19 @@ impl RequestEditor {
         match self.active_tab {
             RequestTab::Path => div().size_full().child(self.path_param_editor.clone()),
             RequestTab::Query => div().size_full().child(self.query_param_editor.clone()),
+            RequestTab::Body if self.selected_kind(cx) == RequestKind::Grpc => {
+                div().size_full().child(
+                    v_flex().h_full().child(self.grpc_editor.clone()).child(
+                        Input::new(&self.body_input)
+                            .font_family(cx.theme().mono_font_family.clone())
+                            .text_size(px(12.))
+                            .h_full()
+                            .bordered(false)
+                            .rounded_none()
+                            .py_3(),
+                    ),
+                )
+            }
             RequestTab::Body => {
                 let selected_content_type =
                     self.content_type_select.read(cx).selected_value().copied();
@@ -1455,7 +2544,16 @@ impl RequestEditor {
                         .child(match selected_content_type {
                             Some(ContentType::Form) => div()
                                 .size_full()
-                                .child(self.form_editor.clone())
+                                .child(self.multipart_editor.clone())
+                                .into_any_element(),
+                            Some(ContentType::GraphQL) => div()
+                                .flex_1()
+                                .min_h_0()
+                                .child(self.graphql_editor.clone())
+                                .into_any_element(),
+                            Some(ContentType::File) => div()
+                                .size_full()
+                                .child(self.file_body_editor.clone())
                                 .into_any_element(),
                             _ => Input::new(&self.body_input)
                                 .font_family(cx.theme().mono_font_family.clone())
@@ -1468,9 +2566,55 @@ impl RequestEditor {
                         }),
                 )
             }
-            RequestTab::Headers => div().size_full().child(self.header_editor.clone()),
+            RequestTab::Messages => div().size_full().child(self.message_composer.clone()),
+            RequestTab::Headers => div().size_full().child(
+                v_flex()
+                    .h_full()
+                    .when_some(
+                        variables_tooltip(&self.variable_report.headers),
+                        |this, text| {
+                            let unresolved = self
+                                .variable_report
+                                .headers
+                                .iter()
+                                .filter(|status| status.source.is_none())
+                                .count();
+                            let count = self.variable_report.headers.len();
+                            this.child(
+                                h_flex()
+                                    .id("header-variables")
+                                    .px_3()
+                                    .py_1()
+                                    .gap_1()
+                                    .items_center()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .border_b_1()
+                                    .border_color(cx.theme().border)
+                                    .child(Icon::new(IconName::Braces).small())
+                                    .child(format!(
+                                        "{} variable{}",
+                                        count,
+                                        if count == 1 { "" } else { "s" }
+                                    ))
+                                    .when(unresolved > 0, |this| {
+                                        this.child(
+                                            div()
+                                                .text_color(cx.theme().yellow)
+                                                .child(format!("• {} unresolved", unresolved)),
+                                        )
+                                    })
+                                    .tooltip(move |window, cx| {
+                                        Tooltip::new(text.clone()).build(window, cx)
+                                    }),
+                            )
+                        },
+                    )
+                    .child(div().flex_1().min_h_0().child(self.header_editor.clone())),
+            ),
             RequestTab::Auth => div().size_full().child(self.auth_editor.clone()),
             RequestTab::Scripts => div().size_full().child(self.script_editor.clone()),
+            RequestTab::Settings => div().size_full().child(self.settings_editor.clone()),
             RequestTab::Vars => div().size_full().child(
                 // A single scroll region (like the other editors): editable
                 // request variables on top, then the read-only inherited
@@ -1493,6 +2637,16 @@ impl RequestEditor {
     }
 
     fn render_status_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        // A gRPC call's outcome is its status, green only when OK
+        let grpc_status = self.response_data.grpc.as_ref().map(|status| {
+            let text = if status.message.is_empty() {
+                format!("{} {}", status.code, status.name())
+            } else {
+                format!("{} {}: {}", status.code, status.name(), status.message)
+            };
+            (status.is_ok(), text)
+        });
+
         let status_text = if let Some(status) = self.response_data.status_code {
             let text = format!(
                 "{} {}",
@@ -1511,15 +2665,35 @@ impl RequestEditor {
         };
 
         let size_text = if let Some(size) = self.response_data.size {
-            if size >= 1024 {
-                format!(" {:.1}KB", size as f64 / 1024.0)
-            } else {
-                format!(" {}B", size)
-            }
+            format!(" {}", format_bytes(size as u64))
         } else {
             String::new()
         };
 
+        // Protocol the response came over, e.g. HTTP/2.0
+        let version_text = self
+            .response_data
+            .http_version
+            .as_ref()
+            .map(|version| format!(" • {}", version));
+
+        // Bytes received so far of a body being downloaded
+        let progress_text = self
+            .response_progress
+            .as_ref()
+            .filter(|progress| self.is_loading && progress.received() > 0)
+            .map(|progress| {
+                let received = match progress.expected() {
+                    Some(expected) => format!(
+                        "{} of {}",
+                        format_bytes(progress.received()),
+                        format_bytes(expected)
+                    ),
+                    None => format_bytes(progress.received()),
+                };
+                format!("{} • {}/s", received, format_bytes(progress.rate() as u64))
+            });
+
         h_flex()
             .justify_between()
             .p_3()
@@ -1546,10 +2720,18 @@ impl RequestEditor {
                         };
                         this.child(div().text_color(text_color).child(text))
                     })
+                    .when_some(grpc_status, |this, (ok, text)| {
+                        let text_color = if ok { cx.theme().green } else { cx.theme().red };
+                        this.child(div().text_color(text_color).child(text))
+                    })
                     .when(!latency_text.is_empty(), |this| {
                         this.child(div().child(format!(" • {}", latency_text)))
                     })
-                    .child(size_text),
+                    .child(size_text)
+                    .when_some(version_text, |this, text| this.child(div().child(text)))
+                    .when_some(progress_text, |this, text| {
+                        this.child(div().text_color(cx.theme().blue).child(text))
+                    }),
             )
             .child(div().flex_1())
             .child(
@@ -1584,6 +2766,14 @@ impl RequestEditor {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
+        let event_count = self.live_events().len();
+        let is_websocket = self.selected_kind(cx) == RequestKind::WebSocket;
+        let frame_count = self
+            .websocket
+            .as_ref()
+            .map(|session| session.frame_count())
+            .unwrap_or_default();
+
         div()
             .flex_1()
             .h_full()
@@ -1605,24 +2795,72 @@ impl RequestEditor {
                                 .selected_index(match self.active_response_tab {
                                     ResponseTab::Response => 0,
                                     ResponseTab::Raw => 1,
+                                    ResponseTab::Tests => 2,
+                                    ResponseTab::Redirects => 3,
+                                    ResponseTab::Timings => 4,
+                                    ResponseTab::Events => 5,
+                                    ResponseTab::Messages => 6,
                                 })
                                 .on_click(cx.listener(|this, &index, _, cx| {
                                     this.active_response_tab = match index {
                                         0 => ResponseTab::Response,
                                         1 => ResponseTab::Raw,
+                                        2 => ResponseTab::Tests,
+                                        3 => ResponseTab::Redirects,
+                                        4 => ResponseTab::Timings,
+                                        5 => ResponseTab::Events,
+                                        6 => ResponseTab::Messages,
                                         _ => ResponseTab::Response,
                                     };
                                     cx.notify();
                                 }))
                                 .child(Tab::new().label("Response"))
-                                .child(Tab::new().label("Raw")),
+                                .child(Tab::new().label("Raw"))
+                                .child(Tab::new().label("Tests").when(
+                                    !self.test_results.is_empty(),
+                                    |tab| {
+                                        tab.pr_2().suffix(
+                                            TabBadge::new().count(self.test_results.len()),
+                                        )
+                                    },
+                                ))
+                                .child(Tab::new().label("Redirects").when(
+                                    !self.response_data.redirects.is_empty(),
+                                    |tab| {
+                                        tab.pr_2().suffix(
+                                            TabBadge::new()
+                                                .count(self.response_data.redirects.len()),
+                                        )
+                                    },
+                                ))
+                                .child(Tab::new().label("Timings"))
+                                .child(Tab::new().label("Events").when(
+                                    event_count > 0,
+                                    |tab| tab.pr_2().suffix(TabBadge::new().count(event_count)),
+                                ))
+                                .when(is_websocket, |tabs| {
+                                    tabs.child(Tab::new().label("Messages").when(
+                                        frame_count > 0,
+                                        |tab| {
+                                            tab.pr_2()
+                                                .suffix(TabBadge::new().count(frame_count))
+                                        },
+                                    ))
+                                }),
                         ),
                     )
                     .child(
                         // Response content
                         div().flex_1().child(match self.active_response_tab {
                             ResponseTab::Response => {
-                                if self.response_format == ResponseFormat::Pdf {
+                                if let Some(size) = self
+                                    .response_data
+                                    .body_file
+                                    .as_ref()
+                                    .and(self.response_data.size)
+                                {
+                                    self.render_saved_body(size, cx).into_any_element()
+                                } else if self.response_format == ResponseFormat::Pdf {
                                     div()
                                         .h_full()
                                         .flex()
@@ -1728,11 +2966,446 @@ impl RequestEditor {
                                         .cleanable(true),
                                 )
                                 .into_any_element(),
+                            ResponseTab::Tests => {
+                                self.render_test_results(cx).into_any_element()
+                            }
+                            ResponseTab::Redirects => {
+                                self.render_redirects(cx).into_any_element()
+                            }
+                            ResponseTab::Timings => self.render_timings(cx).into_any_element(),
+                            ResponseTab::Events => self.render_events(cx).into_any_element(),
+                            ResponseTab::Messages => self.render_frames(cx).into_any_element(),
                         }),
                     ),
             )
     }
 
+    /// A body over the memory limit, kept in a temporary file instead of the
+    /// response view
+    fn render_saved_body(&self, size: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .h_full()
+            .gap_3()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!(
+                        "The {} response body is too large to show.",
+                        format_bytes(size as u64)
+                    )),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("open-response-file")
+                            .label("Open")
+                            .icon(IconName::ExternalLink)
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                if let Some(file) = &this.response_data.body_file {
+                                    cx.open_with_system(file.path());
+                                }
+                            })),
+                    )
+                    .child(
+                        Button::new("save-response-file")
+                            .label("Save As...")
+                            .primary()
+                            .icon(IconName::Save)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.save_response_file(window, cx);
+                            })),
+                    ),
+            )
+    }
+
+    fn render_test_results(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.test_results.is_empty() {
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("No tests ran. Add test() calls to the post-response script.");
+        }
+
+        let failed = self.test_results.iter().filter(|t| !t.passed()).count();
+        let passed = self.test_results.len() - failed;
+
+        div().size_full().child(
+            v_flex()
+                .h_full()
+                .overflow_y_scrollbar()
+                .child(
+                    h_flex()
+                        .px_3()
+                        .py_2()
+                        .gap_3()
+                        .text_sm()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .child(
+                            div()
+                                .text_color(cx.theme().green)
+                                .child(format!("{} passed", passed)),
+                        )
+                        .when(failed > 0, |this| {
+                            this.child(
+                                div()
+                                    .text_color(cx.theme().red)
+                                    .child(format!("{} failed", failed)),
+                            )
+                        }),
+                )
+                .children(self.test_results.iter().map(|test| {
+                    let (icon, color) = if test.passed() {
+                        (IconName::CircleCheck, cx.theme().green)
+                    } else {
+                        (IconName::CircleX, cx.theme().red)
+                    };
+
+                    v_flex()
+                        .px_3()
+                        .py_2()
+                        .gap_1()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .items_center()
+                                .text_sm()
+                                .child(div().text_color(color).child(Icon::new(icon)))
+                                .child(div().flex_1().child(test.name.clone()))
+                                .child(
+                                    div()
+                                        .font_family(cx.theme().mono_font_family.clone())
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(format!("{:.0}ms", test.duration_ms)),
+                                ),
+                        )
+                        .when_some(test.error.clone(), |this, error| {
+                            this.child(
+                                div()
+                                    .pl_6()
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .text_xs()
+                                    .text_color(cx.theme().red)
+                                    .child(error),
+                            )
+                        })
+                })),
+        )
+    }
+
+    fn render_redirects(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.response_data.redirects.is_empty() {
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("The response was not redirected.");
+        }
+
+        div()
+            .size_full()
+            .child(v_flex().h_full().overflow_y_scrollbar().children(
+                self.response_data.redirects.iter().map(|hop| {
+                    let status = match &hop.status_text {
+                        Some(text) => format!("{} {}", hop.status_code, text),
+                        None => hop.status_code.to_string(),
+                    };
+
+                    v_flex()
+                        .px_3()
+                        .py_2()
+                        .gap_1()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .items_center()
+                                .text_sm()
+                                .child(div().text_color(cx.theme().blue).child(status))
+                                .child(div().flex_1().truncate().child(hop.url.clone())),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(Icon::new(IconName::ArrowRight))
+                                .child(div().truncate().child(hop.location.clone())),
+                        )
+                        .children(hop.headers.iter().map(|header| {
+                            div()
+                                .pl_6()
+                                .font_family(cx.theme().mono_font_family.clone())
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!("{}: {}", header.key, header.value))
+                        }))
+                }),
+            ))
+    }
+
+    /// Events of the stream in flight, or of the last response
+    fn live_events(&self) -> Vec<ServerEvent> {
+        match &self.response_progress {
+            Some(progress) if self.is_loading => progress.events(),
+            _ => self.response_data.events.clone(),
+        }
+    }
+
+    fn render_events(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let events = self.live_events();
+        if events.is_empty() {
+            let message = if self.is_loading {
+                "Waiting for events…"
+            } else {
+                "The response was not an event stream."
+            };
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child(message);
+        }
+
+        div()
+            .size_full()
+            .child(
+                v_flex()
+                    .h_full()
+                    .overflow_y_scrollbar()
+                    .children(events.into_iter().map(|event| {
+                        v_flex()
+                            .px_3()
+                            .py_2()
+                            .gap_1()
+                            .border_b_1()
+                            .border_color(cx.theme().border)
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .items_center()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(
+                                        div()
+                                            .text_color(cx.theme().blue)
+                                            .child(event.event.clone()),
+                                    )
+                                    .when_some(event.id.clone(), |this, id| {
+                                        this.child(format!("id {}", id))
+                                    })
+                                    .when_some(event.retry, |this, retry| {
+                                        this.child(format!("retry {}ms", retry))
+                                    })
+                                    .child(div().flex_1())
+                                    .child(format!("+{}ms", event.received_after.as_millis())),
+                            )
+                            .child(
+                                div()
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .text_sm()
+                                    .child(event.data.clone()),
+                            )
+                    })),
+            )
+    }
+
+    /// Frames of the WebSocket connection, oldest first
+    fn render_frames(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let frames = self
+            .websocket
+            .as_ref()
+            .map(|session| session.frames())
+            .unwrap_or_default();
+        if frames.is_empty() {
+            let message = if self.websocket.is_some() {
+                "No messages yet."
+            } else {
+                "Connect to see the messages of the connection."
+            };
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child(message);
+        }
+
+        div()
+            .size_full()
+            .child(
+                v_flex()
+                    .h_full()
+                    .overflow_y_scrollbar()
+                    .children(frames.into_iter().map(|frame| {
+                        let (icon, color) = match (frame.kind, frame.direction) {
+                            (FrameKind::Error, _) => (IconName::CircleX, cx.theme().red),
+                            (_, FrameDirection::Sent) => (IconName::ArrowUp, cx.theme().blue),
+                            (_, FrameDirection::Received) => {
+                                (IconName::ArrowDown, cx.theme().green)
+                            }
+                        };
+                        let label = match frame.kind {
+                            FrameKind::Text => None,
+                            FrameKind::Binary => Some("binary"),
+                            FrameKind::Ping => Some("ping"),
+                            FrameKind::Pong => Some("pong"),
+                            FrameKind::Close => Some("close"),
+                            FrameKind::Error => Some("error"),
+                        };
+
+                        h_flex()
+                            .px_3()
+                            .py_2()
+                            .gap_3()
+                            .items_start()
+                            .border_b_1()
+                            .border_color(cx.theme().border)
+                            .child(div().text_color(color).child(Icon::new(icon)))
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .text_sm()
+                                    .when_some(label, |this, label| {
+                                        this.text_color(cx.theme().muted_foreground)
+                                            .child(format!("[{}] ", label))
+                                    })
+                                    .child(frame.data),
+                            )
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(frame.at.format("%H:%M:%S%.3f").to_string()),
+                            )
+                    })),
+            )
+    }
+
+    /// The phases of the last request as a waterfall: each bar starts where
+    /// the previous phase ended
+    fn render_timings(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(latency) = self.response_data.latency else {
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("Send the request to see where its time goes.");
+        };
+
+        let timings = &self.response_data.timings;
+        let mut phases = vec![
+            (
+                "Prepare",
+                Some(timings.prepare),
+                cx.theme().muted_foreground,
+            ),
+            ("Redirects", Some(timings.redirect), cx.theme().blue),
+            ("DNS lookup", timings.dns, cx.theme().cyan),
+            ("Connect (TCP)", timings.connect, cx.theme().yellow),
+            ("TLS handshake", timings.tls, cx.theme().red),
+            ("Waiting (TTFB)", Some(timings.ttfb), cx.theme().green),
+            ("Download", Some(timings.download), cx.theme().magenta),
+        ];
+        // A new plain HTTP connection has no handshake, and when the handshake
+        // couldn't be told apart it is part of the connect
+        if timings.tls.is_none() && timings.connect.is_some() {
+            phases.retain(|(label, _, _)| *label != "TLS handshake");
+            phases[3].0 = "Connect";
+        }
+        let total = latency.as_secs_f32().max(f32::EPSILON);
+        let mut offset = 0.0;
+
+        div().size_full().child(
+            v_flex()
+                .h_full()
+                .overflow_y_scrollbar()
+                .children(phases.into_iter().map(|(label, duration, color)| {
+                    let start = offset;
+                    let width = duration.unwrap_or_default().as_secs_f32() / total;
+                    offset = (offset + width).min(1.0);
+                    let text = match duration {
+                        Some(duration) => format!("{:.1}ms", duration.as_secs_f64() * 1000.0),
+                        // No new connection was opened
+                        None => "reused".to_string(),
+                    };
+
+                    h_flex()
+                        .px_3()
+                        .py_2()
+                        .gap_3()
+                        .items_center()
+                        .text_sm()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .child(div().w(px(160.)).child(label))
+                        .child(
+                            div().flex_1().h(px(8.)).relative().child(
+                                div()
+                                    .absolute()
+                                    .top_0()
+                                    .h_full()
+                                    .left(relative(start))
+                                    .w(relative(width.min(1.0 - start)))
+                                    .min_w(px(1.))
+                                    .rounded_sm()
+                                    .bg(color),
+                            ),
+                        )
+                        .child(
+                            div()
+                                .w(px(80.))
+                                .text_right()
+                                .font_family(cx.theme().mono_font_family.clone())
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(text),
+                        )
+                }))
+                .child(
+                    h_flex()
+                        .px_3()
+                        .py_2()
+                        .gap_3()
+                        .text_sm()
+                        .child(div().w(px(160.)).child("Total"))
+                        .child(div().flex_1())
+                        .child(
+                            div()
+                                .w(px(80.))
+                                .text_right()
+                                .font_family(cx.theme().mono_font_family.clone())
+                                .text_xs()
+                                .child(format!("{:.1}ms", latency.as_secs_f64() * 1000.0)),
+                        ),
+                ),
+        )
+    }
+
     /// Set up two-way binding between URL input and query parameter editor
     pub fn setup_url_query_binding(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         // Set up subscription for URL input changes
@@ -1812,6 +3485,16 @@ impl RequestEditor {
 
                     // Schedule dirty state check after URL change
                     this.recompute_dirty(cx);
+
+                    // A schema belongs to the URL it was fetched from
+                    if let Some(ContentType::GraphQL) =
+                        this.content_type_select.read(cx).selected_value()
+                    {
+                        this.refresh_graphql_schema(cx);
+                    }
+                    if this.selected_kind(cx) == RequestKind::Grpc {
+                        this.refresh_grpc_services(window, cx);
+                    }
                 }
             }
         });
@@ -1859,6 +3542,73 @@ impl RequestEditor {
         );
         self._subscriptions.push(method_subscription);
 
+        let custom_method_subscription = cx.subscribe(
+            &self.custom_method_input,
+            |this, _input, event: &InputEvent, cx| {
+                if let InputEvent::Change = event {
+                    this.recompute_dirty(cx);
+                }
+            },
+        );
+        self._subscriptions.push(custom_method_subscription);
+
+        let kind_subscription = cx.subscribe(
+            &self.kind_select,
+            |this, _state, _event: &SelectEvent<Vec<RequestKind>>, cx| {
+                this.on_kind_change(cx);
+            },
+        );
+        self._subscriptions.push(kind_subscription);
+
+        let composer_subscription = cx.subscribe_in(&self.message_composer, window, {
+            move |this: &mut Self, _composer, event: &MessageComposerEvent, window, cx| {
+                match event {
+                    MessageComposerEvent::Send(message) => {
+                        if let Some(session) = &this.websocket
+                            && let Err(e) = session.send(message)
+                        {
+                            window.push_notification(
+                                (NotificationType::Error, SharedString::from(e.summary)),
+                                cx,
+                            );
+                        }
+                    }
+                    MessageComposerEvent::TemplatesChanged => this.recompute_dirty(cx),
+                }
+                cx.notify();
+            }
+        });
+        self._subscriptions.push(composer_subscription);
+
+        let graphql_subscription = cx.subscribe_in(&self.graphql_editor, window, {
+            move |this: &mut Self, _editor, event: &GraphQLEditorEvent, window, cx| match event {
+                GraphQLEditorEvent::Changed => {
+                    this.recompute_dirty(cx);
+                    cx.notify();
+                }
+                GraphQLEditorEvent::FetchSchema => this.fetch_graphql_schema(window, cx),
+            }
+        });
+        self._subscriptions.push(graphql_subscription);
+
+        let grpc_subscription = cx.subscribe_in(&self.grpc_editor, window, {
+            move |this: &mut Self, _editor, event: &GrpcEditorEvent, window, cx| match event {
+                GrpcEditorEvent::Changed => {
+                    this.recompute_dirty(cx);
+                    cx.notify();
+                }
+                GrpcEditorEvent::LoadServices => this.load_grpc_services(window, cx),
+                GrpcEditorEvent::UseSample(message) => {
+                    this.body_input.update(cx, |state, cx| {
+                        state.set_value(message.clone(), window, cx);
+                    });
+                    this.recompute_dirty(cx);
+                    cx.notify();
+                }
+            }
+        });
+        self._subscriptions.push(grpc_subscription);
+
         // Set up subscriptions for all editors to track dirty state changes
         // Subscribe to path parameter editor changes
         let path_param_subscription = cx.subscribe(
@@ -1890,6 +3640,15 @@ impl RequestEditor {
         );
         self._subscriptions.push(script_subscription);
 
+        let settings_subscription = cx.subscribe(
+            &self.settings_editor,
+            |this, _editor, _: &RequestSettingsEvent, cx| {
+                this.recompute_dirty(cx);
+                cx.notify();
+            },
+        );
+        self._subscriptions.push(settings_subscription);
+
         // Subscribe to auth editor changes
         let auth_subscription = cx.subscribe(
             &self.auth_editor,
@@ -1900,13 +3659,23 @@ impl RequestEditor {
         );
         self._subscriptions.push(auth_subscription);
 
-        // Subscribe to form editor changes
-        let form_subscription =
-            cx.subscribe(&self.form_editor, |this, _editor, _: &KeyValueEvent, cx| {
+        let file_body_subscription = cx.subscribe(
+            &self.file_body_editor,
+            |this, _editor, _: &FileBodyEvent, cx| {
                 this.recompute_dirty(cx);
                 cx.notify();
-            });
-        self._subscriptions.push(form_subscription);
+            },
+        );
+        self._subscriptions.push(file_body_subscription);
+
+        let multipart_subscription = cx.subscribe(
+            &self.multipart_editor,
+            |this, _editor, _: &MultipartEvent, cx| {
+                this.recompute_dirty(cx);
+                cx.notify();
+            },
+        );
+        self._subscriptions.push(multipart_subscription);
 
         // Subscribe to body input changes
         let body_subscription =
@@ -2038,6 +3807,44 @@ impl RequestEditor {
         &self.method_select
     }
 
+    pub fn custom_method_input(&self) -> &Entity<InputState> {
+        &self.custom_method_input
+    }
+
+    /// The method selected, or the custom method named
+    pub fn selected_method(&self, cx: &App) -> HttpMethod {
+        match self.method_select.read(cx).selected_value() {
+            Some(HttpMethod::Custom(_)) => {
+                HttpMethod::parse(&self.custom_method_input.read(cx).value())
+            }
+            Some(method) => method.clone(),
+            None => HttpMethod::Get,
+        }
+    }
+
+    /// Select `method`, naming a custom one in the custom method input
+    fn set_method(&mut self, method: &HttpMethod, window: &mut Window, cx: &mut Context<Self>) {
+        let index = METHOD_OPTIONS
+            .iter()
+            .position(|option| option == method)
+            .unwrap_or(METHOD_OPTIONS.len() - 1);
+        self.method_select.update(cx, |state, cx| {
+            state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
+        });
+        let custom_name = match method {
+            HttpMethod::Custom(name) => name.clone(),
+            _ => String::new(),
+        };
+        self.custom_method_input.update(cx, |state, cx| {
+            state.set_value(custom_name, window, cx);
+        });
+    }
+
+    /// Get the kind select entity for external subscriptions
+    pub fn kind_select(&self) -> &Entity<SelectState<Vec<RequestKind>>> {
+        &self.kind_select
+    }
+
     /// Get the name input entity for external subscriptions
     pub fn name_input(&self) -> &Entity<InputState> {
         &self.name_input
@@ -2063,18 +3870,16 @@ impl RequestEditor {
         let selected_content_type = self.content_type_select.read(cx).selected_value().copied();
 
         match selected_content_type {
-            Some(ContentType::Form) => {
-                // Check if form editor has any non-empty fields
-                self.form_editor.read(cx).get_pairs(cx).iter().any(|field| {
-                    field.enabled && (!field.key.is_empty() || !field.value.is_empty())
-                })
-            }
-            Some(ContentType::UrlEncoded) => {
-                // Check if form editor has any non-empty fields
-                self.form_editor.read(cx).get_pairs(cx).iter().any(|field| {
-                    field.enabled && (!field.key.is_empty() || !field.value.is_empty())
-                })
+            _ if self.selected_kind(cx) == RequestKind::Grpc => {
+                !self.body_input.read(cx).value().trim().is_empty()
             }
+            Some(ContentType::File) => self.file_body_editor.read(cx).get_file().is_some(),
+            Some(ContentType::Form) => self
+                .multipart_editor
+                .read(cx)
+                .get_parts(cx)
+                .iter()
+                .any(|part| part.enabled),
             _ => {
                 // For other content types, check if body has content
                 !self.body_input.read(cx).value().trim().is_empty()
@@ -2107,18 +3912,12 @@ impl RequestEditor {
         let script_editor = self.script_editor.read(cx);
         let pre_request = script_editor.get_pre_request_script(cx);
         let post_response = script_editor.get_post_response_script(cx);
+        let event = script_editor.get_event_script(cx);
 
-        let count = if pre_request.is_some_and(|s| !s.trim().is_empty()) {
-            1
-        } else {
-            0
-        };
-
-        if post_response.is_some_and(|s| !s.trim().is_empty()) {
-            count + 1
-        } else {
-            count
-        }
+        [pre_request, post_response, event]
+            .into_iter()
+            .filter(|script| script.as_ref().is_some_and(|s| !s.trim().is_empty()))
+            .count()
     }
 
     fn has_auth_configured(&self, cx: &App) -> bool {
@@ -2315,6 +4114,46 @@ impl Render for RequestEditor {
     }
 }
 
+/// One line per placeholder: its value and where it comes from, or that it
+/// isn't resolved. `None` without placeholders.
+fn variables_tooltip(statuses: &[PlaceholderStatus]) -> Option<SharedString> {
+    if statuses.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = statuses
+        .iter()
+        .map(|status| {
+            let placeholder = format!("{{{{{}}}}}", status.name);
+            match (status.source, &status.value) {
+                (None, _) => format!("{} — not resolved, sent as written", placeholder),
+                (Some(VariableSource::Dynamic), _) => {
+                    format!("{} — generated when sent", placeholder)
+                }
+                (Some(source), Some(value)) => {
+                    format!("{} = {} ({})", placeholder, value, source.label())
+                }
+                (Some(source), None) => format!("{} = •••••• ({})", placeholder, source.label()),
+            }
+        })
+        .collect();
+    Some(lines.join("\n").into())
+}
+
+/// A byte count in B, KB, MB or GB
+pub(super) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
+
 impl ResponseData {
     pub fn format_raw_response(&self) -> String {
         let mut raw_output = String::new();
@@ -2339,6 +4178,16 @@ impl ResponseData {
             let status_text = self.status_text.as_deref().unwrap_or("Unknown");
             raw_output.push_str(&format!("\n{} {}\n", status_code, status_text));
         }
+        if let Some(status) = &self.grpc {
+            raw_output.push_str(&format!(
+                "\ngrpc-status: {} {}\n",
+                status.code,
+                status.name()
+            ));
+            if !status.message.is_empty() {
+                raw_output.push_str(&format!("grpc-message: {}\n", status.message));
+            }
+        }
 
         // Add response headers section
         if !self.headers.is_empty() {
@@ -2360,6 +4209,14 @@ impl ResponseData {
             raw_output.push_str(&self.body);
         }
 
+        // gRPC trailers follow the messages
+        if let Some(status) = self.grpc.as_ref().filter(|s| !s.trailers.is_empty()) {
+            raw_output.push_str("\n\n--- Trailers ---\n");
+            for trailer in &status.trailers {
+                raw_output.push_str(&format!("{}: {}\n", trailer.key, trailer.value));
+            }
+        }
+
         raw_output
     }
 }
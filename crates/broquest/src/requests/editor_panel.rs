@@ -19,7 +19,9 @@ use crate::app_settings::AppSettings;
 use crate::collections::{
     CollectionEditor, CollectionManager, CollectionManagerEvent, CollectionToml, GroupEditor,
 };
-use crate::domain::{HttpMethod, RequestData};
+use crate::cookies::CookiesView;
+use crate::domain::{HttpMethod, RequestData, RequestKind};
+use crate::runner::RunnerView;
 use crate::settings::SettingsView;
 use crate::ui::icon::IconName;
 
@@ -31,6 +33,8 @@ pub enum TabType {
     Request(RequestTab),
     Collection(CollectionTab),
     Group(GroupTab),
+    Runner(RunnerTab),
+    Cookies(CookiesTab),
     Settings(SettingsTab),
 }
 
@@ -39,6 +43,7 @@ pub struct RequestTab {
     pub id: usize,
     pub title: String,
     pub method: HttpMethod,
+    pub kind: RequestKind,
     pub collection_name: String,
     pub request_editor: Entity<RequestEditor>,
     #[allow(dead_code)]
@@ -64,6 +69,18 @@ pub struct GroupTab {
     pub collection_path: String, // Link to the collection this belongs to
 }
 
+pub struct RunnerTab {
+    pub id: usize,
+    pub title: String,
+    pub runner_view: Entity<RunnerView>,
+}
+
+pub struct CookiesTab {
+    pub id: usize,
+    pub title: String,
+    pub cookies_view: Entity<CookiesView>,
+}
+
 pub struct SettingsTab {
     pub settings_view: Entity<SettingsView>,
 }
@@ -197,6 +214,8 @@ impl EditorPanel {
                 TabType::Request(t) => t.id,
                 TabType::Collection(t) => t.id,
                 TabType::Group(t) => t.id,
+                TabType::Runner(t) => t.id,
+                TabType::Cookies(t) => t.id,
                 TabType::Settings(_) => 0,
             })
             .max()
@@ -252,6 +271,7 @@ impl EditorPanel {
             id: tab_id,
             title: request_data.name,
             method: request_data.method,
+            kind: request_data.kind,
             collection_name: collection_suffix.clone(),
             request_editor: request_editor.clone(),
             collection_path: collection_path.clone(),
@@ -293,40 +313,56 @@ impl EditorPanel {
         self._subscriptions.push(name_subscription);
 
         let method_select = request_editor.read(cx).method_select().clone();
-        let method_select_for_closure = method_select.clone();
+        let request_editor_for_closure = request_editor.clone();
         let method_subscription = cx.subscribe_in(&method_select, window, {
             move |editor_panel: &mut Self,
                   _select_state,
                   _event: &SelectEvent<Vec<HttpMethod>>,
                   _window,
                   cx| {
-                // Find the request tab that corresponds to this request editor
+                editor_panel.refresh_tab_method(&request_editor_for_closure, cx);
+            }
+        });
+        self._subscriptions.push(method_subscription);
+
+        // A custom method is named in its own input
+        let custom_method_input = request_editor.read(cx).custom_method_input().clone();
+        let request_editor_for_closure = request_editor.clone();
+        let custom_method_subscription = cx.subscribe_in(&custom_method_input, window, {
+            move |editor_panel: &mut Self, _input_state, event: &InputEvent, _window, cx| {
+                if let InputEvent::Change = event {
+                    editor_panel.refresh_tab_method(&request_editor_for_closure, cx);
+                }
+            }
+        });
+        self._subscriptions.push(custom_method_subscription);
+
+        let kind_select = request_editor.read(cx).kind_select().clone();
+        let kind_subscription = cx.subscribe_in(&kind_select, window, {
+            move |editor_panel: &mut Self,
+                  select_state,
+                  _event: &SelectEvent<Vec<RequestKind>>,
+                  _window,
+                  cx| {
+                let current_kind = select_state
+                    .read(cx)
+                    .selected_value()
+                    .copied()
+                    .unwrap_or_default();
                 for tab in editor_panel.tabs.iter_mut() {
-                    if let TabType::Request(request_tab) = tab {
-                        // Check if this is the same request editor by comparing the method_select entity
-                        if request_tab.request_editor.read(cx).method_select().clone()
-                            == method_select_for_closure
-                        {
-                            let current_method = request_tab
-                                .request_editor
-                                .read(cx)
-                                .method_select()
-                                .read(cx)
-                                .selected_value()
-                                .copied()
-                                .unwrap_or(HttpMethod::Get);
-
-                            if request_tab.method != current_method {
-                                request_tab.method = current_method;
-                                cx.notify();
-                                break;
-                            }
+                    if let TabType::Request(request_tab) = tab
+                        && request_tab.request_editor.read(cx).kind_select() == select_state
+                    {
+                        if request_tab.kind != current_kind {
+                            request_tab.kind = current_kind;
+                            cx.notify();
                         }
+                        break;
                     }
                 }
             }
         });
-        self._subscriptions.push(method_subscription);
+        self._subscriptions.push(kind_subscription);
 
         // Subscribe to dirty state changes from request editor
         let request_editor_for_closure = request_editor.clone();
@@ -355,6 +391,26 @@ impl EditorPanel {
         cx.notify();
     }
 
+    /// Show the method selected in `request_editor` on its tab
+    fn refresh_tab_method(
+        &mut self,
+        request_editor: &Entity<RequestEditor>,
+        cx: &mut Context<Self>,
+    ) {
+        let current_method = request_editor.read(cx).selected_method(cx);
+        for tab in self.tabs.iter_mut() {
+            if let TabType::Request(request_tab) = tab
+                && request_tab.request_editor == *request_editor
+            {
+                if request_tab.method != current_method {
+                    request_tab.method = current_method;
+                    cx.notify();
+                }
+                break;
+            }
+        }
+    }
+
     /// Load environments from global CollectionManager and set them on a request editor
     fn load_environments_for_request(
         &mut self,
@@ -532,6 +588,65 @@ impl EditorPanel {
         cx.notify();
     }
 
+    /// Create and add a collection runner tab for a collection or one group
+    pub fn create_and_add_runner_tab(
+        &mut self,
+        collection_path: String,
+        group_name: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let tab_id = self.next_tab_id();
+
+        let target = group_name.clone().unwrap_or_else(|| {
+            CollectionManager::global(cx)
+                .read(cx)
+                .get_collection_by_path(&collection_path)
+                .map(|info| info.data.name.clone())
+                .unwrap_or_else(|| "Collection".to_string())
+        });
+
+        let runner_view = cx.new(|cx| RunnerView::new(window, cx, collection_path, group_name));
+
+        self.tabs.push(TabType::Runner(RunnerTab {
+            id: tab_id,
+            title: format!("Run {}", target),
+            runner_view,
+        }));
+        self.active_tab_ix = self.tabs.len() - 1;
+        self.scroll_tabbar_to_active();
+
+        cx.notify();
+    }
+
+    /// Create and add a tab listing the cookie jar of a collection
+    pub fn create_and_add_cookies_tab(
+        &mut self,
+        collection_path: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let tab_id = self.next_tab_id();
+
+        let collection_name = CollectionManager::global(cx)
+            .read(cx)
+            .get_collection_by_path(&collection_path)
+            .map(|info| info.data.name.clone())
+            .unwrap_or_else(|| "Collection".to_string());
+
+        let cookies_view = cx.new(|cx| CookiesView::new(window, cx, collection_path));
+
+        self.tabs.push(TabType::Cookies(CookiesTab {
+            id: tab_id,
+            title: format!("Cookies ({})", collection_name),
+            cookies_view,
+        }));
+        self.active_tab_ix = self.tabs.len() - 1;
+        self.scroll_tabbar_to_active();
+
+        cx.notify();
+    }
+
     /// Add or focus the Settings tab (singleton)
     pub fn add_settings_tab(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
         // Check if settings tab already exists
@@ -615,7 +730,8 @@ impl Render for EditorPanel {
                                 let tab_index = ix;
                                 // Segmented tabs keep the surface colour when
                                 // selected, so the method colours read normally.
-                                let method_color = request_tab.method.get_color(cx);
+                                let method_color =
+                                    request_tab.kind.badge_color_fn(&request_tab.method)(cx);
                                 let muted_color = cx.theme().muted_foreground;
 
                                 Tab::new()
@@ -640,7 +756,7 @@ impl Render for EditorPanel {
                                                 )
                                             })
                                             .text_color(method_color)
-                                            .child(request_tab.method.as_str()),
+                                            .child(request_tab.kind.badge(&request_tab.method)),
                                     )
                                     .suffix(
                                         h_flex()
@@ -745,6 +861,72 @@ impl Render for EditorPanel {
                                             .into_any_element(),
                                     )
                             }
+                            TabType::Runner(runner_tab) => {
+                                let tab_index = ix;
+                                Tab::new()
+                                    .label(&runner_tab.title)
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(
+                                            move |_this,
+                                                  _event: &gpui::MouseDownEvent,
+                                                  _window,
+                                                  cx| {
+                                                cx.emit(AppEvent::TabChanged { tab_id: tab_index });
+                                            },
+                                        ),
+                                    )
+                                    .suffix(
+                                        h_flex()
+                                            .gap_2()
+                                            .items_center()
+                                            .child(
+                                                Button::new(("close-tab", ix))
+                                                    .ghost()
+                                                    .xsmall()
+                                                    .icon(IconName::Close)
+                                                    .on_click(cx.listener(
+                                                        move |this, _, _, cx| {
+                                                            this.close_tab(tab_index, cx);
+                                                        },
+                                                    )),
+                                            )
+                                            .into_any_element(),
+                                    )
+                            }
+                            TabType::Cookies(cookies_tab) => {
+                                let tab_index = ix;
+                                Tab::new()
+                                    .label(&cookies_tab.title)
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(
+                                            move |_this,
+                                                  _event: &gpui::MouseDownEvent,
+                                                  _window,
+                                                  cx| {
+                                                cx.emit(AppEvent::TabChanged { tab_id: tab_index });
+                                            },
+                                        ),
+                                    )
+                                    .suffix(
+                                        h_flex()
+                                            .gap_2()
+                                            .items_center()
+                                            .child(
+                                                Button::new(("close-tab", ix))
+                                                    .ghost()
+                                                    .xsmall()
+                                                    .icon(IconName::Close)
+                                                    .on_click(cx.listener(
+                                                        move |this, _, _, cx| {
+                                                            this.close_tab(tab_index, cx);
+                                                        },
+                                                    )),
+                                            )
+                                            .into_any_element(),
+                                    )
+                            }
                             TabType::Settings(_) => {
                                 let tab_index = ix;
                                 Tab::new()
@@ -815,6 +997,18 @@ impl Render for EditorPanel {
                                         .child(group_tab.group_editor.clone()),
                                 )
                             }
+                            TabType::Runner(runner_tab) => this.child(
+                                div()
+                                    .flex_1()
+                                    .h_full()
+                                    .child(runner_tab.runner_view.clone()),
+                            ),
+                            TabType::Cookies(cookies_tab) => this.child(
+                                div()
+                                    .flex_1()
+                                    .h_full()
+                                    .child(cookies_tab.cookies_view.clone()),
+                            ),
                             TabType::Settings(settings_tab) => this.child(
                                 div()
                                     .flex_1()
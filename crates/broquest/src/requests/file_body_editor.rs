@@ -0,0 +1,172 @@
+//! File sent as the body of a request, picked from disk
+
+use gpui::{Context, EventEmitter, SharedString, Window, div, prelude::*};
+use gpui_component::{
+    ActiveTheme, Icon, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex, v_flex,
+};
+use std::path::{Path, PathBuf};
+
+use super::editor::format_bytes;
+use crate::http::file_content_type;
+use crate::ui::icon::IconName;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileBodyEvent {
+    /// A file was picked or cleared
+    Changed,
+}
+
+#[derive(Default)]
+pub struct FileBodyEditor {
+    /// The file as saved: relative to the collection when inside it
+    file: Option<String>,
+    collection_path: Option<String>,
+}
+
+impl EventEmitter<FileBodyEvent> for FileBodyEditor {}
+
+impl FileBodyEditor {
+    pub fn set_collection_path(&mut self, collection_path: Option<String>) {
+        self.collection_path = collection_path;
+    }
+
+    pub fn get_file(&self) -> Option<String> {
+        self.file.clone()
+    }
+
+    pub fn set_file(&mut self, file: Option<String>, cx: &mut Context<Self>) {
+        self.file = file;
+        cx.notify();
+    }
+
+    /// Where the file is on disk
+    fn resolved_path(&self, file: &str) -> PathBuf {
+        match &self.collection_path {
+            Some(collection) => Path::new(collection).join(file),
+            None => PathBuf::from(file),
+        }
+    }
+
+    fn choose_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(gpui::PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Select file to send as the body".into()),
+        });
+
+        cx.spawn_in(window, async move |this, window| {
+            let path = paths.await.ok()?.ok()??.into_iter().next()?;
+            window
+                .update(|_, cx| {
+                    this.update(cx, |this, cx| {
+                        let file = this
+                            .collection_path
+                            .as_ref()
+                            .and_then(|collection| path.strip_prefix(collection).ok())
+                            .unwrap_or(&path)
+                            .to_string_lossy()
+                            .to_string();
+                        this.set_file(Some(file), cx);
+                        cx.emit(FileBodyEvent::Changed);
+                    })
+                })
+                .ok()?
+                .ok()
+        })
+        .detach();
+    }
+
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        self.set_file(None, cx);
+        cx.emit(FileBodyEvent::Changed);
+    }
+}
+
+impl Render for FileBodyEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let details = self.file.as_ref().map(|file| {
+            let path = self.resolved_path(file);
+            let size = std::fs::metadata(&path)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len());
+            (file.clone(), file_content_type(&path), size)
+        });
+
+        v_flex()
+            .size_full()
+            .p_3()
+            .gap_3()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("choose-body-file")
+                            .small()
+                            .icon(IconName::Folder)
+                            .label(if self.file.is_some() {
+                                "Choose another file"
+                            } else {
+                                "Choose file"
+                            })
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.choose_file(window, cx);
+                            })),
+                    )
+                    .when(self.file.is_some(), |this| {
+                        this.child(
+                            Button::new("clear-body-file")
+                                .small()
+                                .ghost()
+                                .icon(IconName::Close)
+                                .label("Clear")
+                                .on_click(cx.listener(|this, _, _, cx| this.clear(cx))),
+                        )
+                    }),
+            )
+            .child(match details {
+                Some((file, content_type, size)) => v_flex()
+                    .gap_1()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .items_center()
+                            .child(Icon::new(IconName::File).small())
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .child(file),
+                            )
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .when_some(size, |this, size| {
+                                        this.text_color(cx.theme().muted_foreground)
+                                            .child(format_bytes(size))
+                                    })
+                                    .when(size.is_none(), |this| {
+                                        this.text_color(cx.theme().red).child("Not found")
+                                    }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(SharedString::from(format!(
+                                "Sent as {content_type} unless a Content-Type header is set"
+                            ))),
+                    )
+                    .into_any_element(),
+                None => div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("No file selected. It's streamed from disk when the request is sent.")
+                    .into_any_element(),
+            })
+    }
+}
@@ -0,0 +1,268 @@
+//! Query and variables editors of a GraphQL request, completing and checking
+//! the query against the endpoint's schema, with a schema explorer beside.
+
+use gpui::{App, Context, Entity, EventEmitter, SharedString, Task, Window, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme, Disableable as _, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    highlighter::{Diagnostic, DiagnosticSeverity},
+    input::{Input, InputEvent, InputState, Position},
+    v_flex,
+};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app_settings::AppSettings;
+use crate::domain::GraphQLRequest;
+use crate::graphql::{GraphQLCompletionProvider, Schema, SchemaExplorer};
+use crate::result_ext::ResultExt;
+use crate::ui::icon::IconName;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphQLEditorEvent {
+    /// The query or variables changed
+    Changed,
+    /// Fetch the endpoint's schema by introspection
+    FetchSchema,
+}
+
+pub struct GraphQLEditor {
+    query_input: Entity<InputState>,
+    variables_input: Entity<InputState>,
+    explorer: Entity<SchemaExplorer>,
+    completion_provider: Rc<GraphQLCompletionProvider>,
+    schema: Option<Arc<Schema>>,
+    show_explorer: bool,
+    /// Whether an introspection request is in flight
+    fetching: bool,
+    _subscriptions: Vec<gpui::Subscription>,
+    _lint_task: Task<()>,
+}
+
+impl EventEmitter<GraphQLEditorEvent> for GraphQLEditor {}
+
+impl GraphQLEditor {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let editor_settings = AppSettings::global(cx).settings.editor.clone();
+        let completion_provider = GraphQLCompletionProvider::new();
+
+        let query_input = cx.new(|cx| {
+            let mut editor = InputState::new(window, cx)
+                .code_editor("graphql")
+                .folding(editor_settings.folding)
+                .show_whitespaces(editor_settings.show_whitespace)
+                .soft_wrap(editor_settings.soft_wrap);
+            editor.lsp.completion_provider = Some(completion_provider.clone());
+            editor
+        });
+        let variables_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .code_editor("json")
+                .folding(editor_settings.folding)
+                .show_whitespaces(editor_settings.show_whitespace)
+                .soft_wrap(editor_settings.soft_wrap)
+        });
+        let explorer = cx.new(|cx| SchemaExplorer::new(window, cx));
+
+        let subscriptions = vec![
+            cx.subscribe(&query_input, |this, _input, event: &InputEvent, cx| {
+                if let InputEvent::Change = event {
+                    this.lint(cx);
+                    cx.emit(GraphQLEditorEvent::Changed);
+                }
+            }),
+            cx.subscribe(&variables_input, |_this, _input, event: &InputEvent, cx| {
+                if let InputEvent::Change = event {
+                    cx.emit(GraphQLEditorEvent::Changed);
+                }
+            }),
+        ];
+
+        Self {
+            query_input,
+            variables_input,
+            explorer,
+            completion_provider,
+            schema: None,
+            show_explorer: false,
+            fetching: false,
+            _subscriptions: subscriptions,
+            _lint_task: Task::ready(()),
+        }
+    }
+
+    pub fn apply_editor_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let settings = AppSettings::global(cx).settings.editor.clone();
+        for input in [&self.query_input, &self.variables_input] {
+            input.update(cx, |state, cx| {
+                state.set_show_whitespaces(settings.show_whitespace, window, cx);
+                state.set_soft_wrap(settings.soft_wrap, window, cx);
+                state.set_folding(settings.folding, window, cx);
+            });
+        }
+    }
+
+    pub fn get_graphql(&self, cx: &App) -> GraphQLRequest {
+        GraphQLRequest {
+            query: self.query_input.read(cx).value().to_string(),
+            variables: self.variables_input.read(cx).value().to_string(),
+        }
+    }
+
+    pub fn set_graphql(
+        &mut self,
+        graphql: &GraphQLRequest,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.query_input.update(cx, |input, cx| {
+            input.set_value(graphql.query.clone(), window, cx);
+        });
+        self.variables_input.update(cx, |input, cx| {
+            input.set_value(graphql.variables.clone(), window, cx);
+        });
+    }
+
+    /// Use the schema of the request's endpoint, `None` while none is fetched
+    pub fn set_schema(&mut self, schema: Option<Arc<Schema>>, cx: &mut Context<Self>) {
+        self.completion_provider.set_schema(schema.clone());
+        self.explorer.update(cx, |explorer, cx| {
+            explorer.set_schema(schema.clone(), cx);
+        });
+        self.schema = schema;
+        self.lint(cx);
+        cx.notify();
+    }
+
+    pub fn set_fetching(&mut self, fetching: bool, cx: &mut Context<Self>) {
+        self.fetching = fetching;
+        cx.notify();
+    }
+
+    /// Mark the fields the schema doesn't have
+    fn lint(&mut self, cx: &mut Context<Self>) {
+        let input = self.query_input.clone();
+        let schema = self.schema.clone();
+
+        // Debounce: wait 500ms then check the query
+        self._lint_task = cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(Duration::from_millis(500))
+                .await;
+
+            let query = cx.read_entity(&input, |input, _cx| input.value().to_string());
+            let errors = match &schema {
+                Some(schema) => crate::graphql::validate(schema, &query),
+                None => Vec::new(),
+            };
+
+            let _ = this
+                .update(cx, |_this, cx| {
+                    input.update(cx, |input, _cx| {
+                        if let Some(diagnostics) = input.diagnostics_mut() {
+                            diagnostics.clear();
+                            for error in errors {
+                                diagnostics.push(
+                                    Diagnostic::new(
+                                        Position::new(error.line, error.column)
+                                            ..Position::new(error.line, error.column + error.len),
+                                        error.message,
+                                    )
+                                    .with_severity(DiagnosticSeverity::Error),
+                                );
+                            }
+                        }
+                    });
+                    cx.notify();
+                })
+                .log_err();
+        });
+    }
+
+    fn render_header(&self, title: &'static str, cx: &mut Context<Self>) -> gpui::Div {
+        h_flex()
+            .px_3()
+            .py_1()
+            .gap_2()
+            .items_center()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(title),
+            )
+    }
+}
+
+impl Render for GraphQLEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let editor = |input: &Entity<InputState>, cx: &mut Context<Self>| {
+            Input::new(input)
+                .font_family(cx.theme().mono_font_family.clone())
+                .text_size(px(12.))
+                .h_full()
+                .bordered(false)
+                .rounded_none()
+                .py_3()
+        };
+
+        h_flex()
+            .size_full()
+            .child(
+                v_flex()
+                    .flex_1()
+                    .h_full()
+                    .child(
+                        self.render_header("Query", cx)
+                            .child(
+                                Button::new("fetch-schema")
+                                    .xsmall()
+                                    .ghost()
+                                    .icon(IconName::Refresh)
+                                    .label(if self.schema.is_some() {
+                                        "Refresh schema"
+                                    } else {
+                                        "Fetch schema"
+                                    })
+                                    .loading(self.fetching)
+                                    .disabled(self.fetching)
+                                    .tooltip(SharedString::from(
+                                        "Fetch the schema from the URL by introspection",
+                                    ))
+                                    .on_click(cx.listener(|_this, _, _, cx| {
+                                        cx.emit(GraphQLEditorEvent::FetchSchema);
+                                    })),
+                            )
+                            .child(
+                                Button::new("toggle-schema-explorer")
+                                    .xsmall()
+                                    .ghost()
+                                    .icon(if self.show_explorer {
+                                        IconName::PanelRightClose
+                                    } else {
+                                        IconName::PanelRightOpen
+                                    })
+                                    .label("Schema")
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.show_explorer = !this.show_explorer;
+                                        cx.notify();
+                                    })),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_h_0()
+                            .child(editor(&self.query_input, cx)),
+                    )
+                    .child(self.render_header("Variables", cx).border_t_1())
+                    .child(div().h(px(140.)).child(editor(&self.variables_input, cx))),
+            )
+            .when(self.show_explorer, |this| this.child(self.explorer.clone()))
+    }
+}
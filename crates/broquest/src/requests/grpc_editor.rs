@@ -0,0 +1,380 @@
+//! Method and `.proto` files of a gRPC request, above its JSON message
+
+use gpui::{App, Context, Entity, EventEmitter, Global, SharedString, Window, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme, Disableable as _, IndexPath, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    select::{SearchableVec, Select, SelectEvent, SelectItem, SelectState},
+    v_flex,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::domain::GrpcRequest;
+use crate::http::GrpcServices;
+use crate::ui::icon::IconName;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrpcEditorEvent {
+    /// The method or the `.proto` files changed
+    Changed,
+    /// Load the services from the `.proto` files or by reflection
+    LoadServices,
+    /// Replace the message with a sample of the method's request
+    UseSample(String),
+}
+
+/// Services loaded per collection, URL and `.proto` files, so reopened
+/// requests list their methods without loading them again
+#[derive(Default)]
+pub struct GrpcServicesCache {
+    services: HashMap<(String, String, Vec<String>), GrpcServices>,
+}
+
+impl Global for GrpcServicesCache {}
+
+impl GrpcServicesCache {
+    pub fn get(
+        cx: &App,
+        collection_path: Option<&str>,
+        url: &str,
+        grpc: &GrpcRequest,
+    ) -> Option<GrpcServices> {
+        cx.try_global::<Self>()?
+            .services
+            .get(&Self::key(collection_path, url, grpc))
+            .cloned()
+    }
+
+    pub fn insert(
+        cx: &mut App,
+        collection_path: Option<&str>,
+        url: &str,
+        grpc: &GrpcRequest,
+        services: GrpcServices,
+    ) {
+        cx.default_global::<Self>()
+            .services
+            .insert(Self::key(collection_path, url, grpc), services);
+    }
+
+    fn key(
+        collection_path: Option<&str>,
+        url: &str,
+        grpc: &GrpcRequest,
+    ) -> (String, String, Vec<String>) {
+        (
+            collection_path.unwrap_or_default().to_string(),
+            url.trim().to_string(),
+            grpc.proto_files.clone(),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MethodOption(String);
+
+impl SelectItem for MethodOption {
+    type Value = String;
+
+    fn title(&self) -> SharedString {
+        SharedString::from(self.0.clone())
+    }
+
+    fn value(&self) -> &String {
+        &self.0
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        self.0.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+pub struct GrpcEditor {
+    method_select: Entity<SelectState<SearchableVec<MethodOption>>>,
+    /// Method of the request, kept while it isn't among the loaded ones
+    method: String,
+    proto_files: Vec<String>,
+    services: Option<GrpcServices>,
+    /// Picked `.proto` files inside it are saved relative to it
+    collection_path: Option<String>,
+    /// Whether services are being loaded
+    loading: bool,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl EventEmitter<GrpcEditorEvent> for GrpcEditor {}
+
+impl GrpcEditor {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let method_select = cx.new(|cx| {
+            SelectState::new(SearchableVec::new(Vec::new()), None, window, cx).searchable(true)
+        });
+        let subscriptions = vec![cx.subscribe(
+            &method_select,
+            |this, _select, event: &SelectEvent<SearchableVec<MethodOption>>, cx| {
+                let SelectEvent::Confirm(Some(method)) = event else {
+                    return;
+                };
+                if *method != this.method {
+                    this.method = method.clone();
+                    cx.emit(GrpcEditorEvent::Changed);
+                }
+            },
+        )];
+
+        Self {
+            method_select,
+            method: String::new(),
+            proto_files: Vec::new(),
+            services: None,
+            collection_path: None,
+            loading: false,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn set_collection_path(&mut self, collection_path: Option<String>) {
+        self.collection_path = collection_path;
+    }
+
+    pub fn get_grpc(&self) -> GrpcRequest {
+        GrpcRequest {
+            method: self.method.clone(),
+            proto_files: self.proto_files.clone(),
+        }
+    }
+
+    pub fn set_grpc(&mut self, grpc: &GrpcRequest, window: &mut Window, cx: &mut Context<Self>) {
+        self.method = grpc.method.clone();
+        self.proto_files = grpc.proto_files.clone();
+        self.services = None;
+        self.update_methods(window, cx);
+    }
+
+    /// Use the services loaded for the request, `None` while none are
+    pub fn set_services(
+        &mut self,
+        services: Option<GrpcServices>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.services = services;
+        // Start from the first method when there's none to keep
+        if let Some(services) = &self.services
+            && !services.methods().contains(&self.method)
+            && let Some(first) = services.methods().into_iter().next()
+        {
+            self.method = first;
+            cx.emit(GrpcEditorEvent::Changed);
+        }
+        self.update_methods(window, cx);
+    }
+
+    pub fn set_loading(&mut self, loading: bool, cx: &mut Context<Self>) {
+        self.loading = loading;
+        cx.notify();
+    }
+
+    fn update_methods(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let mut methods = self
+            .services
+            .as_ref()
+            .map(GrpcServices::methods)
+            .unwrap_or_default();
+        if !self.method.is_empty() && !methods.contains(&self.method) {
+            methods.insert(0, self.method.clone());
+        }
+        let selected = methods.iter().position(|method| *method == self.method);
+        let options = methods.into_iter().map(MethodOption).collect();
+        self.method_select.update(cx, |select, cx| {
+            select.set_items(SearchableVec::new(options), window, cx);
+            select.set_selected_index(
+                selected.map(|index| IndexPath::default().row(index)),
+                window,
+                cx,
+            );
+        });
+        cx.notify();
+    }
+
+    fn use_sample(&mut self, cx: &mut Context<Self>) {
+        if let Some(sample) = self
+            .services
+            .as_ref()
+            .and_then(|services| services.sample_request(&self.method))
+        {
+            cx.emit(GrpcEditorEvent::UseSample(sample));
+        }
+    }
+
+    fn add_proto_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(gpui::PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+            prompt: Some("Select .proto files".into()),
+        });
+
+        cx.spawn_in(window, async move |this, window| {
+            let paths = paths.await.ok()?.ok()??;
+            window
+                .update(|window, cx| {
+                    this.update(cx, |this, cx| {
+                        for path in paths {
+                            let file = this.collection_relative(&path);
+                            if !this.proto_files.contains(&file) {
+                                this.proto_files.push(file);
+                            }
+                        }
+                        this.services = None;
+                        this.update_methods(window, cx);
+                        cx.emit(GrpcEditorEvent::Changed);
+                    })
+                })
+                .ok()?
+                .ok()
+        })
+        .detach();
+    }
+
+    fn remove_proto_file(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if index < self.proto_files.len() {
+            self.proto_files.remove(index);
+            self.services = None;
+            self.update_methods(window, cx);
+            cx.emit(GrpcEditorEvent::Changed);
+        }
+    }
+
+    /// A picked file as saved: relative to the collection when inside it
+    fn collection_relative(&self, path: &Path) -> String {
+        self.collection_path
+            .as_ref()
+            .and_then(|collection| path.strip_prefix(collection).ok())
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+impl Render for GrpcEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let has_services = self.services.is_some();
+        let can_sample = self
+            .services
+            .as_ref()
+            .is_some_and(|services| services.methods().contains(&self.method));
+
+        v_flex()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .p_3()
+                    .gap_3()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child("Method"),
+                    )
+                    .child(
+                        div().flex_1().min_w(px(200.)).child(
+                            Select::new(&self.method_select)
+                                .small()
+                                .placeholder("Load services to pick a method"),
+                        ),
+                    )
+                    .child(
+                        Button::new("load-grpc-services")
+                            .small()
+                            .ghost()
+                            .icon(IconName::Refresh)
+                            .label(if has_services {
+                                "Reload services"
+                            } else {
+                                "Load services"
+                            })
+                            .loading(self.loading)
+                            .disabled(self.loading)
+                            .tooltip(SharedString::from(if self.proto_files.is_empty() {
+                                "Ask the server for its services by reflection"
+                            } else {
+                                "Read the services from the .proto files"
+                            }))
+                            .on_click(cx.listener(|_this, _, _, cx| {
+                                cx.emit(GrpcEditorEvent::LoadServices);
+                            })),
+                    )
+                    .child(
+                        Button::new("grpc-sample-message")
+                            .small()
+                            .ghost()
+                            .label("Sample message")
+                            .disabled(!can_sample)
+                            .tooltip(SharedString::from(
+                                "Replace the message with every field of the method's request",
+                            ))
+                            .on_click(cx.listener(|this, _, _, cx| this.use_sample(cx))),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .px_3()
+                    .pb_3()
+                    .gap_2()
+                    .items_center()
+                    .flex_wrap()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child("Proto files"),
+                    )
+                    .when(self.proto_files.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child("None, the server is asked by reflection"),
+                        )
+                    })
+                    .children(self.proto_files.iter().enumerate().map(|(index, file)| {
+                        h_flex()
+                            .pl_2()
+                            .gap_1()
+                            .items_center()
+                            .rounded(cx.theme().radius)
+                            .bg(cx.theme().secondary)
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .font_family(cx.theme().mono_font_family.clone())
+                                    .child(file.clone()),
+                            )
+                            .child(
+                                Button::new(("remove-proto-file", index))
+                                    .xsmall()
+                                    .ghost()
+                                    .icon(IconName::Close)
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.remove_proto_file(index, window, cx);
+                                    })),
+                            )
+                    }))
+                    .child(
+                        Button::new("add-proto-files")
+                            .xsmall()
+                            .ghost()
+                            .icon(IconName::Plus)
+                            .label("Add")
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.add_proto_files(window, cx);
+                            })),
+                    ),
+            )
+    }
+}
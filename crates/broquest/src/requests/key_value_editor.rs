@@ -1,10 +1,10 @@
 //! A generic key/value table editor.
 //!
-//! Request headers, query params, path params, form fields, and request
-//! variables are all edited as a list of `(key, value, enabled)` rows with the
-//! same add/remove/toggle/clear behavior. This component captures that shared
-//! shape; per-use differences (placeholders, the form file-picker column, the
-//! embedded natural-height layout) are expressed through [`KeyValueConfig`].
+//! Request headers, query params, path params, and request variables are all
+//! edited as a list of `(key, value, enabled)` rows with the same
+//! add/remove/toggle/clear behavior. This component captures that shared
+//! shape; per-use differences (placeholders, the embedded natural-height
+//! layout) are expressed through [`KeyValueConfig`].
 
 use gpui::{
     App, Context, Entity, EventEmitter, Focusable, SharedString, Window, div, prelude::*, px,
@@ -19,7 +19,6 @@ use gpui_component::{
 };
 
 use crate::domain::KeyValuePair;
-use crate::result_ext::ResultExt;
 use crate::ui::icon::IconName;
 
 /// Emitted whenever the set of rows or their contents changes.
@@ -38,9 +37,6 @@ pub struct KeyValueConfig {
     pub key_placeholder: SharedString,
     /// Placeholder for the value column input.
     pub value_placeholder: SharedString,
-    /// Show a per-row file-picker button that writes `@/path` into the value
-    /// (used by multipart form fields).
-    pub show_file_picker: bool,
     /// Render at natural height without an own scroll region, for embedding in a
     /// parent scroll area (used by the request Vars tab).
     pub embedded: bool,
@@ -57,17 +53,10 @@ impl KeyValueConfig {
             id_prefix: id_prefix.into(),
             key_placeholder: key_placeholder.into(),
             value_placeholder: value_placeholder.into(),
-            show_file_picker: false,
             embedded: false,
         }
     }
 
-    /// Enable the per-row file picker (multipart form fields).
-    pub fn with_file_picker(mut self) -> Self {
-        self.show_file_picker = true;
-        self
-    }
-
     /// Render at natural height for embedding in a parent scroll region.
     pub fn embedded(mut self) -> Self {
         self.embedded = true;
@@ -253,54 +242,6 @@ impl KeyValueEditor {
         cx.notify();
     }
 
-    /// Open a file picker and write the chosen path (prefixed with `@`) into the
-    /// row's value input.
-    fn select_file_for_row(&mut self, id: usize, window: &mut Window, cx: &mut Context<Self>) {
-        let Some(row) = self.rows.iter().find(|row| row.id == id) else {
-            return;
-        };
-        let value_input = row.value_input.clone();
-        let editor = cx.entity().downgrade();
-
-        let path_future = cx.prompt_for_paths(gpui::PathPromptOptions {
-            files: true,
-            directories: false,
-            multiple: false,
-            prompt: Some("Select file for form field".into()),
-        });
-
-        cx.spawn_in(window, async move |_, window| {
-            match path_future.await {
-                Ok(Ok(Some(paths))) => {
-                    if let Some(path) = paths.first()
-                        && let Some(path_str) = path.to_str()
-                    {
-                        let file_path = format!("@{}", path_str);
-                        window
-                            .update(|window, cx| {
-                                value_input.update(cx, |state, cx| {
-                                    state.set_value(file_path, window, cx);
-                                    cx.notify();
-                                });
-                                if let Some(editor) = editor.upgrade() {
-                                    editor.update(cx, |_this, cx| {
-                                        cx.emit(KeyValueEvent::Changed);
-                                    });
-                                }
-                            })
-                            .log_err()
-                            .ok();
-                    }
-                }
-                Ok(Ok(None)) => {}
-                Ok(Err(e)) => tracing::error!("Failed to select file: {}", e),
-                Err(e) => tracing::error!("Failed to open file dialog: {}", e),
-            }
-            Some(())
-        })
-        .detach();
-    }
-
     fn render_row(&self, row: &KeyValueRow, cx: &mut Context<Self>) -> impl IntoElement {
         h_flex()
             .gap_2()
@@ -340,21 +281,6 @@ impl KeyValueEditor {
                             .font_family(cx.theme().mono_font_family.clone()),
                     ),
             )
-            .when(self.config.show_file_picker, |this| {
-                this.child(
-                    Button::new(("file", row.id))
-                        .small()
-                        .ghost()
-                        .icon(IconName::File)
-                        .w(px(24.))
-                        .on_click(cx.listener({
-                            let id = row.id;
-                            move |this, _, window, cx| {
-                                this.select_file_for_row(id, window, cx);
-                            }
-                        })),
-                )
-            })
             .child(
                 Button::new(("enabled", row.id))
                     .small()
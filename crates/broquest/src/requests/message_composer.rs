@@ -0,0 +1,266 @@
+//! Composer of the messages a WebSocket request sends, with the request's
+//! saved message templates.
+
+use gpui::{Context, Entity, EventEmitter, SharedString, Window, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme, Disableable as _, Sizable, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    scroll::ScrollableElement,
+    v_flex,
+};
+
+use crate::app_settings::AppSettings;
+use crate::domain::MessageTemplate;
+use crate::ui::icon::IconName;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageComposerEvent {
+    /// Send the message on the open connection
+    Send(String),
+    /// The saved templates changed
+    TemplatesChanged,
+}
+
+pub struct MessageComposer {
+    message_input: Entity<InputState>,
+    name_input: Entity<InputState>,
+    templates: Vec<MessageTemplate>,
+    /// Whether a connection is open to send on
+    connected: bool,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl EventEmitter<MessageComposerEvent> for MessageComposer {}
+
+impl MessageComposer {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let editor_settings = AppSettings::global(cx).settings.editor.clone();
+        let message_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .code_editor("json")
+                .folding(editor_settings.folding)
+                .show_whitespaces(editor_settings.show_whitespace)
+                .soft_wrap(editor_settings.soft_wrap)
+        });
+        let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Template name"));
+
+        // Re-render so the Send button follows whether there is a message
+        let subscriptions =
+            vec![
+                cx.subscribe(&message_input, |_this, _input, event: &InputEvent, cx| {
+                    if let InputEvent::Change = event {
+                        cx.notify();
+                    }
+                }),
+            ];
+
+        Self {
+            message_input,
+            name_input,
+            templates: Vec::new(),
+            connected: false,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn apply_editor_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let settings = AppSettings::global(cx).settings.editor.clone();
+        self.message_input.update(cx, |state, cx| {
+            state.set_show_whitespaces(settings.show_whitespace, window, cx);
+            state.set_soft_wrap(settings.soft_wrap, window, cx);
+            state.set_folding(settings.folding, window, cx);
+        });
+    }
+
+    pub fn get_templates(&self) -> Vec<MessageTemplate> {
+        self.templates.clone()
+    }
+
+    pub fn set_templates(
+        &mut self,
+        templates: &[MessageTemplate],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.templates = templates.to_vec();
+        // Start from the first template, like a request starts from its body
+        let first = templates.first().cloned().unwrap_or_default();
+        self.load(&first, window, cx);
+    }
+
+    pub fn set_connected(&mut self, connected: bool, cx: &mut Context<Self>) {
+        if self.connected != connected {
+            self.connected = connected;
+            cx.notify();
+        }
+    }
+
+    fn load(&mut self, template: &MessageTemplate, window: &mut Window, cx: &mut Context<Self>) {
+        self.message_input.update(cx, |input, cx| {
+            input.set_value(template.body.clone(), window, cx);
+        });
+        self.name_input.update(cx, |input, cx| {
+            input.set_value(template.name.clone(), window, cx);
+        });
+        cx.notify();
+    }
+
+    fn send(&mut self, cx: &mut Context<Self>) {
+        let message = self.message_input.read(cx).value().to_string();
+        if self.connected && !message.is_empty() {
+            cx.emit(MessageComposerEvent::Send(message));
+        }
+    }
+
+    /// Save the message under the name typed, replacing a template of the
+    /// same name
+    fn save_template(&mut self, cx: &mut Context<Self>) {
+        let body = self.message_input.read(cx).value().to_string();
+        let name = self.name_input.read(cx).value().trim().to_string();
+        let name = if name.is_empty() {
+            format!("Message {}", self.templates.len() + 1)
+        } else {
+            name
+        };
+
+        match self.templates.iter_mut().find(|t| t.name == name) {
+            Some(template) => template.body = body,
+            None => self.templates.push(MessageTemplate { name, body }),
+        }
+        cx.emit(MessageComposerEvent::TemplatesChanged);
+        cx.notify();
+    }
+
+    fn delete_template(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.templates.len() {
+            self.templates.remove(index);
+            cx.emit(MessageComposerEvent::TemplatesChanged);
+            cx.notify();
+        }
+    }
+
+    fn render_templates(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let current_name = self.name_input.read(cx).value().to_string();
+
+        v_flex()
+            .w(px(200.))
+            .h_full()
+            .border_r_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .p_3()
+                    .text_sm()
+                    .font_semibold()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child("Templates"),
+            )
+            .when(self.templates.is_empty(), |this| {
+                this.child(
+                    div()
+                        .p_3()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Save a message to send it again later."),
+                )
+            })
+            .child(v_flex().flex_1().overflow_y_scrollbar().children(
+                self.templates.iter().enumerate().map(|(index, template)| {
+                    let selected = template.name == current_name;
+                    h_flex()
+                        .id(("message-template", index))
+                        .pl_3()
+                        .pr_1()
+                        .py_1()
+                        .gap_2()
+                        .items_center()
+                        .cursor_pointer()
+                        .when(selected, |this| this.bg(cx.theme().accent))
+                        .hover(|this| this.bg(cx.theme().accent))
+                        .on_click(cx.listener({
+                            let template = template.clone();
+                            move |this, _, window, cx| this.load(&template, window, cx)
+                        }))
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_sm()
+                                .truncate()
+                                .child(template.name.clone()),
+                        )
+                        .child(
+                            Button::new(("delete-message-template", index))
+                                .small()
+                                .ghost()
+                                .icon(IconName::Trash)
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    cx.stop_propagation();
+                                    this.delete_template(index, cx);
+                                })),
+                        )
+                }),
+            ))
+    }
+}
+
+impl Render for MessageComposer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let can_send = self.connected && !self.message_input.read(cx).value().is_empty();
+
+        h_flex().size_full().child(self.render_templates(cx)).child(
+            v_flex()
+                .flex_1()
+                .h_full()
+                .child(
+                    h_flex()
+                        .p_3()
+                        .gap_3()
+                        .items_center()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .child(
+                            div()
+                                .w(px(200.))
+                                .child(Input::new(&self.name_input).small()),
+                        )
+                        .child(
+                            Button::new("save-message-template")
+                                .small()
+                                .outline()
+                                .icon(IconName::Save)
+                                .label("Save as template")
+                                .on_click(cx.listener(|this, _, _, cx| this.save_template(cx))),
+                        )
+                        .child(div().flex_1())
+                        .child(
+                            Button::new("send-message")
+                                .small()
+                                .primary()
+                                .icon(IconName::Send)
+                                .label("Send")
+                                .disabled(!can_send)
+                                .tooltip(SharedString::from(if self.connected {
+                                    "Send the message"
+                                } else {
+                                    "Connect to send messages"
+                                }))
+                                .on_click(cx.listener(|this, _, _, cx| this.send(cx))),
+                        ),
+                )
+                .child(
+                    div().flex_1().child(
+                        Input::new(&self.message_input)
+                            .font_family(cx.theme().mono_font_family.clone())
+                            .text_size(px(12.))
+                            .h_full()
+                            .bordered(false)
+                            .rounded_none()
+                            .py_3(),
+                    ),
+                ),
+        )
+    }
+}
@@ -3,7 +3,13 @@
 mod auth_editor;
 mod editor;
 mod editor_panel;
+mod file_body_editor;
+mod graphql_editor;
+mod grpc_editor;
 mod key_value_editor;
+mod message_composer;
+mod multipart_editor;
+mod settings_editor;
 
 pub use auth_editor::AuthEditor;
 pub use editor::*;
@@ -0,0 +1,371 @@
+//! Table of the parts of a `multipart/form-data` body. Each part is text or a
+//! file picked from disk, with its own Content-Type and file name.
+
+use gpui::{
+    App, Context, Entity, EventEmitter, Focusable, SharedString, Window, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    scroll::ScrollableElement,
+    v_flex,
+};
+
+use crate::domain::{MultipartKind, MultipartPart};
+use crate::ui::icon::IconName;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultipartEvent {
+    /// A part was added, removed or edited
+    Changed,
+}
+
+struct PartRow {
+    id: usize,
+    kind: MultipartKind,
+    name_input: Entity<InputState>,
+    /// Text of a text part, or the path of a file part
+    value_input: Entity<InputState>,
+    content_type_input: Entity<InputState>,
+    filename_input: Entity<InputState>,
+    enabled: bool,
+}
+
+pub struct MultipartEditor {
+    rows: Vec<PartRow>,
+    next_id: usize,
+    /// Files inside the collection are saved relative to it
+    collection_path: Option<String>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl EventEmitter<MultipartEvent> for MultipartEditor {}
+
+/// The row left at the end to type a new part into
+fn empty_part() -> MultipartPart {
+    MultipartPart {
+        enabled: true,
+        ..Default::default()
+    }
+}
+
+impl MultipartEditor {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut editor = Self {
+            rows: Vec::new(),
+            next_id: 0,
+            collection_path: None,
+            _subscriptions: Vec::new(),
+        };
+        editor.add_row(&empty_part(), window, cx);
+        editor
+    }
+
+    pub fn set_collection_path(&mut self, collection_path: Option<String>) {
+        self.collection_path = collection_path;
+    }
+
+    /// Replace the rows with `parts`, keeping a trailing empty row
+    pub fn set_parts(
+        &mut self,
+        parts: &[MultipartPart],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.rows.clear();
+        for part in parts {
+            self.add_row(part, window, cx);
+        }
+        self.add_empty_row(window, cx);
+    }
+
+    /// The parts with a name
+    pub fn get_parts(&self, cx: &App) -> Vec<MultipartPart> {
+        self.rows
+            .iter()
+            .filter_map(|row| {
+                let name = row.name_input.read(cx).value().to_string();
+                if name.trim().is_empty() {
+                    return None;
+                }
+                Some(MultipartPart {
+                    name,
+                    kind: row.kind,
+                    value: row.value_input.read(cx).value().to_string(),
+                    content_type: row.content_type_input.read(cx).value().trim().to_string(),
+                    filename: row.filename_input.read(cx).value().trim().to_string(),
+                    enabled: row.enabled,
+                })
+            })
+            .collect()
+    }
+
+    fn add_row(&mut self, part: &MultipartPart, window: &mut Window, cx: &mut Context<Self>) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut input = |placeholder: &str, value: &str| {
+            cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder(placeholder.to_string())
+                    .default_value(value)
+            })
+        };
+        let name_input = input("Part name", &part.name);
+        let value_input = input("Value, or path of the file", &part.value);
+        let content_type_input = input("Content-Type", &part.content_type);
+        let filename_input = input("File name", &part.filename);
+
+        // Emit only when the change originates from user typing (the input is
+        // focused), to avoid feedback loops when rows are set programmatically.
+        for input in [
+            &name_input,
+            &value_input,
+            &content_type_input,
+            &filename_input,
+        ] {
+            let subscription = cx.subscribe_in(input, window, {
+                move |_this: &mut Self, input_state, event: &InputEvent, window, cx| {
+                    if let InputEvent::Change = event
+                        && input_state.read(cx).focus_handle(cx).is_focused(window)
+                    {
+                        cx.emit(MultipartEvent::Changed);
+                    }
+                }
+            });
+            self._subscriptions.push(subscription);
+        }
+
+        self.rows.push(PartRow {
+            id,
+            kind: part.kind,
+            name_input,
+            value_input,
+            content_type_input,
+            filename_input,
+            enabled: part.enabled,
+        });
+        cx.notify();
+    }
+
+    fn add_empty_row(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self
+            .rows
+            .last()
+            .is_none_or(|row| !row.name_input.read(cx).value().is_empty())
+        {
+            self.add_row(&empty_part(), window, cx);
+        }
+    }
+
+    fn remove_row(&mut self, id: usize, cx: &mut Context<Self>) {
+        self.rows.retain(|row| row.id != id);
+        cx.emit(MultipartEvent::Changed);
+        cx.notify();
+    }
+
+    fn toggle_row(&mut self, id: usize, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+            row.enabled = !row.enabled;
+            cx.emit(MultipartEvent::Changed);
+            cx.notify();
+        }
+    }
+
+    /// Switch a part between text and file
+    fn toggle_kind(&mut self, id: usize, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.iter_mut().find(|row| row.id == id) {
+            row.kind = match row.kind {
+                MultipartKind::Text => MultipartKind::File,
+                MultipartKind::File => MultipartKind::Text,
+            };
+            cx.emit(MultipartEvent::Changed);
+            cx.notify();
+        }
+    }
+
+    fn clear_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.rows.clear();
+        self.add_row(&empty_part(), window, cx);
+        cx.emit(MultipartEvent::Changed);
+    }
+
+    /// Pick the file of a part, saved relative to the collection when inside it
+    fn choose_file(&mut self, id: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(row) = self.rows.iter().find(|row| row.id == id) else {
+            return;
+        };
+        let value_input = row.value_input.clone();
+        let paths = cx.prompt_for_paths(gpui::PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Select file for the part".into()),
+        });
+
+        cx.spawn_in(window, async move |this, window| {
+            let path = paths.await.ok()?.ok()??.into_iter().next()?;
+            window
+                .update(|window, cx| {
+                    this.update(cx, |this, cx| {
+                        let file = this
+                            .collection_path
+                            .as_ref()
+                            .and_then(|collection| path.strip_prefix(collection).ok())
+                            .unwrap_or(&path)
+                            .to_string_lossy()
+                            .to_string();
+                        value_input.update(cx, |state, cx| {
+                            state.set_value(file, window, cx);
+                        });
+                        if let Some(row) = this.rows.iter_mut().find(|row| row.id == id) {
+                            row.kind = MultipartKind::File;
+                        }
+                        cx.emit(MultipartEvent::Changed);
+                        cx.notify();
+                    })
+                })
+                .ok()?
+                .ok()
+        })
+        .detach();
+    }
+
+    fn render_row(&self, row: &PartRow, cx: &mut Context<Self>) -> impl IntoElement {
+        let cell = |input: &Entity<InputState>, cx: &mut Context<Self>| {
+            div()
+                .border_r_1()
+                .border_color(cx.theme().border)
+                .pr_2()
+                .py_2()
+                .child(
+                    Input::new(input)
+                        .small()
+                        .appearance(false)
+                        .text_sm()
+                        .font_family(cx.theme().mono_font_family.clone()),
+                )
+        };
+        let is_file = row.kind == MultipartKind::File;
+
+        h_flex()
+            .gap_2()
+            .pl_2()
+            .pr_4()
+            .items_center()
+            .bg(cx.theme().table)
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(cell(&row.name_input, cx).flex_1())
+            .child(
+                Button::new(("part-kind", row.id))
+                    .small()
+                    .ghost()
+                    .w(px(48.))
+                    .label(if is_file { "File" } else { "Text" })
+                    .tooltip("Send the value as text or a file's contents")
+                    .on_click(cx.listener({
+                        let id = row.id;
+                        move |this, _, _, cx| this.toggle_kind(id, cx)
+                    })),
+            )
+            .child(cell(&row.value_input, cx).flex_1())
+            .child(
+                Button::new(("part-file", row.id))
+                    .small()
+                    .ghost()
+                    .icon(IconName::File)
+                    .w(px(24.))
+                    .tooltip("Choose file")
+                    .on_click(cx.listener({
+                        let id = row.id;
+                        move |this, _, window, cx| this.choose_file(id, window, cx)
+                    })),
+            )
+            .child(cell(&row.content_type_input, cx).w(px(140.)))
+            .child(cell(&row.filename_input, cx).w(px(120.)))
+            .child(
+                Button::new(("part-enabled", row.id))
+                    .small()
+                    .ghost()
+                    .text_color(if row.enabled {
+                        cx.theme().green
+                    } else {
+                        cx.theme().red
+                    })
+                    .w(px(24.))
+                    .label(if row.enabled { "✓" } else { "○" })
+                    .on_click(cx.listener({
+                        let id = row.id;
+                        move |this, _, _, cx| this.toggle_row(id, cx)
+                    })),
+            )
+            .child(
+                Button::new(("part-delete", row.id))
+                    .small()
+                    .ghost()
+                    .icon(IconName::Trash)
+                    .on_click(cx.listener({
+                        let id = row.id;
+                        move |this, _, _, cx| this.remove_row(id, cx)
+                    })),
+            )
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .gap_3()
+            .items_center()
+            .p_3()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(SharedString::from(
+                        "A file's Content-Type is guessed from its extension when empty",
+                    )),
+            )
+            .child(
+                Button::new("multipart-add")
+                    .small()
+                    .outline()
+                    .icon(IconName::Plus)
+                    .label("Add")
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.add_empty_row(window, cx);
+                        cx.emit(MultipartEvent::Changed);
+                    })),
+            )
+            .child(
+                Button::new("multipart-clear")
+                    .small()
+                    .outline()
+                    .icon(IconName::Trash)
+                    .label("Clear All")
+                    .on_click(cx.listener(|this, _, window, cx| this.clear_all(window, cx))),
+            )
+    }
+}
+
+impl Render for MultipartEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| div().child(self.render_row(row, cx)))
+            .collect::<Vec<_>>();
+
+        v_flex().h_full().child(self.render_header(cx)).child(
+            div()
+                .size_full()
+                .flex_1()
+                .min_h_0()
+                .child(v_flex().overflow_y_scrollbar().children(rows)),
+        )
+    }
+}
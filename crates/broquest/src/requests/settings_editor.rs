@@ -0,0 +1,322 @@
+use gpui::{
+    App, Context, Entity, EventEmitter, IntoElement, SharedString, Window, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme, IndexPath, Sizable as _, h_flex,
+    input::{Input, InputEvent, InputState},
+    select::{Select, SelectEvent, SelectItem, SelectState},
+    switch::Switch,
+    v_flex,
+};
+
+use crate::domain::{HttpVersion, RequestSettings};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestSettingsEvent {
+    Changed,
+}
+
+/// A boolean setting a request may leave to the app settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideOption {
+    Default,
+    On,
+    Off,
+}
+
+impl OverrideOption {
+    const ALL: [OverrideOption; 3] = [
+        OverrideOption::Default,
+        OverrideOption::On,
+        OverrideOption::Off,
+    ];
+
+    fn from_value(value: Option<bool>) -> Self {
+        match value {
+            None => OverrideOption::Default,
+            Some(true) => OverrideOption::On,
+            Some(false) => OverrideOption::Off,
+        }
+    }
+
+    fn value(self) -> Option<bool> {
+        match self {
+            OverrideOption::Default => None,
+            OverrideOption::On => Some(true),
+            OverrideOption::Off => Some(false),
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|o| *o == self).unwrap_or(0)
+    }
+}
+
+impl SelectItem for OverrideOption {
+    type Value = OverrideOption;
+
+    fn title(&self) -> SharedString {
+        match self {
+            OverrideOption::Default => "App default".into(),
+            OverrideOption::On => "Yes".into(),
+            OverrideOption::Off => "No".into(),
+        }
+    }
+
+    fn value(&self) -> &Self::Value {
+        self
+    }
+}
+
+/// The HTTP version of a request, or the collection's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HttpVersionOption(Option<HttpVersion>);
+
+impl HttpVersionOption {
+    const ALL: [HttpVersionOption; 4] = [
+        HttpVersionOption(None),
+        HttpVersionOption(Some(HttpVersion::Negotiate)),
+        HttpVersionOption(Some(HttpVersion::Http1)),
+        HttpVersionOption(Some(HttpVersion::Http2PriorKnowledge)),
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|o| *o == self).unwrap_or(0)
+    }
+}
+
+impl SelectItem for HttpVersionOption {
+    type Value = HttpVersionOption;
+
+    fn title(&self) -> SharedString {
+        match self.0 {
+            None => "Collection default".into(),
+            Some(version) => version.label().into(),
+        }
+    }
+
+    fn value(&self) -> &Self::Value {
+        self
+    }
+}
+
+/// Edits a request's overrides of the connection settings
+pub struct RequestSettingsEditor {
+    follow_redirects_select: Entity<SelectState<Vec<OverrideOption>>>,
+    max_redirects_input: Entity<InputState>,
+    keep_auth_select: Entity<SelectState<Vec<OverrideOption>>>,
+    http_version_select: Entity<SelectState<Vec<HttpVersionOption>>>,
+    event_stream: bool,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl EventEmitter<RequestSettingsEvent> for RequestSettingsEditor {}
+
+impl RequestSettingsEditor {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let new_select = |window: &mut Window, cx: &mut Context<Self>| {
+            cx.new(|cx| {
+                SelectState::new(
+                    OverrideOption::ALL.to_vec(),
+                    Some(IndexPath::default().row(0)),
+                    window,
+                    cx,
+                )
+            })
+        };
+        let follow_redirects_select = new_select(window, cx);
+        let keep_auth_select = new_select(window, cx);
+        let max_redirects_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("App default"));
+        let http_version_select = cx.new(|cx| {
+            SelectState::new(
+                HttpVersionOption::ALL.to_vec(),
+                Some(IndexPath::default().row(0)),
+                window,
+                cx,
+            )
+        });
+
+        let mut subscriptions = Vec::new();
+        for select in [&follow_redirects_select, &keep_auth_select] {
+            subscriptions.push(cx.subscribe(
+                select,
+                |_this, _select, _: &SelectEvent<Vec<OverrideOption>>, cx| {
+                    cx.emit(RequestSettingsEvent::Changed);
+                    cx.notify();
+                },
+            ));
+        }
+        subscriptions.push(cx.subscribe(
+            &http_version_select,
+            |_this, _select, _: &SelectEvent<Vec<HttpVersionOption>>, cx| {
+                cx.emit(RequestSettingsEvent::Changed);
+                cx.notify();
+            },
+        ));
+        subscriptions.push(cx.subscribe(
+            &max_redirects_input,
+            |_this, _input, event: &InputEvent, cx| {
+                if let InputEvent::Change = event {
+                    cx.emit(RequestSettingsEvent::Changed);
+                    cx.notify();
+                }
+            },
+        ));
+
+        Self {
+            follow_redirects_select,
+            max_redirects_input,
+            keep_auth_select,
+            http_version_select,
+            event_stream: false,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn get_settings(&self, cx: &App) -> RequestSettings {
+        let selected = |select: &Entity<SelectState<Vec<OverrideOption>>>| {
+            select
+                .read(cx)
+                .selected_value()
+                .copied()
+                .and_then(OverrideOption::value)
+        };
+
+        RequestSettings {
+            follow_redirects: selected(&self.follow_redirects_select),
+            // Anything but a number leaves the app setting in place
+            max_redirects: self
+                .max_redirects_input
+                .read(cx)
+                .value()
+                .trim()
+                .parse()
+                .ok(),
+            keep_auth_on_redirect: selected(&self.keep_auth_select),
+            event_stream: self.event_stream,
+            http_version: self
+                .http_version_select
+                .read(cx)
+                .selected_value()
+                .and_then(|option| option.0),
+        }
+    }
+
+    pub fn set_settings(
+        &mut self,
+        settings: &RequestSettings,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        for (select, value) in [
+            (&self.follow_redirects_select, settings.follow_redirects),
+            (&self.keep_auth_select, settings.keep_auth_on_redirect),
+        ] {
+            let index = OverrideOption::from_value(value).index();
+            select.update(cx, |state, cx| {
+                state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
+            });
+        }
+
+        let max_redirects = settings
+            .max_redirects
+            .map(|max| max.to_string())
+            .unwrap_or_default();
+        self.max_redirects_input.update(cx, |state, cx| {
+            state.set_value(max_redirects, window, cx);
+        });
+        self.event_stream = settings.event_stream;
+
+        let index = HttpVersionOption(settings.http_version).index();
+        self.http_version_select.update(cx, |state, cx| {
+            state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
+        });
+    }
+
+    /// Number of settings this request overrides
+    pub fn count(&self, cx: &App) -> usize {
+        let settings = self.get_settings(cx);
+        [
+            settings.follow_redirects.is_some(),
+            settings.max_redirects.is_some(),
+            settings.keep_auth_on_redirect.is_some(),
+            settings.event_stream,
+            settings.http_version.is_some(),
+        ]
+        .into_iter()
+        .filter(|set| *set)
+        .count()
+    }
+
+    fn render_row(
+        &self,
+        label: &'static str,
+        description: &'static str,
+        control: impl IntoElement,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        h_flex()
+            .gap_3()
+            .items_center()
+            .child(
+                v_flex()
+                    .w(px(240.))
+                    .child(div().text_sm().child(label))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(description),
+                    ),
+            )
+            .child(div().w(px(160.)).child(control))
+    }
+}
+
+impl Render for RequestSettingsEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_4()
+            .p_4()
+            .child(self.render_row(
+                "Follow redirects",
+                "Follow 3xx responses to their Location",
+                Select::new(&self.follow_redirects_select).small(),
+                cx,
+            ))
+            .child(self.render_row(
+                "Max redirects",
+                "Redirects followed at most",
+                Input::new(&self.max_redirects_input).small(),
+                cx,
+            ))
+            .child(self.render_row(
+                "Keep Authorization",
+                "Send it to other hosts when redirected",
+                Select::new(&self.keep_auth_select).small(),
+                cx,
+            ))
+            .child(self.render_row(
+                "HTTP version",
+                "Force HTTP/1.1, or HTTP/2 without negotiating",
+                Select::new(&self.http_version_select).small(),
+                cx,
+            ))
+            .child(
+                self.render_row(
+                    "Event stream",
+                    "Keep the connection open for server-sent events",
+                    Switch::new("event-stream")
+                        .small()
+                        .checked(self.event_stream)
+                        .on_click(cx.listener(|this, checked, _window, cx| {
+                            this.event_stream = *checked;
+                            cx.emit(RequestSettingsEvent::Changed);
+                            cx.notify();
+                        })),
+                    cx,
+                ),
+            )
+    }
+}
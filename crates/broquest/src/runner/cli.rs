@@ -0,0 +1,418 @@
+use anyhow::{Context as _, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::collections::{CollectionManager, EnvironmentToml};
+use crate::http::{HttpClientService, SavedRequests};
+use crate::settings::ConnectionSettings;
+
+use super::data::IterationData;
+use super::plan::RunPlan;
+use super::report::RunReport;
+use super::session::{CollectionRunner, RequestRunResult, RunContext};
+
+const USAGE: &str = "\
+Usage: broquest run <collection-dir> [options]
+
+Runs every request in a collection (or one group) in seq order and exits
+non-zero when a request fails or a test() in a post-response script fails.
+
+Options:
+  -e, --env <name>         Environment to resolve variables from
+  -g, --group <name>       Only run the requests in this group
+  -d, --data <file>        CSV or JSON data file; each row runs the requests
+                           once, with its columns as variables
+      --var <name=value>   Set or override a variable (repeatable). Secret
+                           environment variables must be supplied this way.
+      --timeout <seconds>  Request timeout (default: 300)
+      --junit <file>       Write a JUnit XML report
+      --json <file>        Write a JSON report
+      --archive            Also archive both reports in the collection's
+                           .runs directory
+  -h, --help               Show this help";
+
+/// Parsed `broquest run` arguments
+#[derive(Debug, Clone, PartialEq)]
+struct RunArgs {
+    collection_dir: PathBuf,
+    environment: Option<String>,
+    group: Option<String>,
+    data_path: Option<PathBuf>,
+    vars: Vec<(String, String)>,
+    timeout_seconds: u32,
+    junit_path: Option<PathBuf>,
+    json_path: Option<PathBuf>,
+    archive: bool,
+}
+
+impl RunArgs {
+    /// Parse the arguments that follow `run`. `Ok(None)` means help was
+    /// requested.
+    fn parse(args: &[String]) -> Result<Option<Self>> {
+        let mut collection_dir = None;
+        let mut environment = None;
+        let mut group = None;
+        let mut data_path = None;
+        let mut vars = Vec::new();
+        let mut timeout_seconds = ConnectionSettings::default().request_timeout_seconds;
+        let mut junit_path = None;
+        let mut json_path = None;
+        let mut archive = false;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value_for = |flag: &str| {
+                iter.next()
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("{} requires a value", flag))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-e" | "--env" => environment = Some(value_for(arg)?),
+                "-g" | "--group" => group = Some(value_for(arg)?),
+                "-d" | "--data" => data_path = Some(PathBuf::from(value_for(arg)?)),
+                "--var" => {
+                    let pair = value_for(arg)?;
+                    let (name, value) = pair.split_once('=').ok_or_else(|| {
+                        anyhow::anyhow!("--var expects name=value, got '{}'", pair)
+                    })?;
+                    vars.push((name.to_string(), value.to_string()));
+                }
+                "--timeout" => {
+                    let value = value_for(arg)?;
+                    timeout_seconds = value
+                        .parse()
+                        .with_context(|| format!("Invalid --timeout '{}'", value))?;
+                }
+                "--junit" => junit_path = Some(PathBuf::from(value_for(arg)?)),
+                "--json" => json_path = Some(PathBuf::from(value_for(arg)?)),
+                "--archive" => archive = true,
+                flag if flag.starts_with('-') => anyhow::bail!("Unknown option '{}'", flag),
+                path => {
+                    if collection_dir.is_some() {
+                        anyhow::bail!("Unexpected argument '{}'", path);
+                    }
+                    collection_dir = Some(PathBuf::from(path));
+                }
+            }
+        }
+
+        let collection_dir =
+            collection_dir.ok_or_else(|| anyhow::anyhow!("Missing collection directory"))?;
+
+        Ok(Some(Self {
+            collection_dir,
+            environment,
+            group,
+            data_path,
+            vars,
+            timeout_seconds,
+            junit_path,
+            json_path,
+            archive,
+        }))
+    }
+}
+
+/// Entry point for `broquest run`. Returns the process exit code: 0 when
+/// every request and test passed, 1 when anything failed, 2 on usage or
+/// setup errors.
+pub fn run_cli(args: &[String]) -> i32 {
+    let args = match RunArgs::parse(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return 0;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    match smol::block_on(async_compat::Compat::new(run(args))) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            2
+        }
+    }
+}
+
+/// Run the collection and print results. Returns whether everything passed.
+async fn run(args: RunArgs) -> Result<bool> {
+    let info = CollectionManager::load_detached(&args.collection_dir).with_context(|| {
+        format!(
+            "Failed to load collection from {}",
+            args.collection_dir.display()
+        )
+    })?;
+    let plan = RunPlan::for_collection(&info, args.group.as_deref())?;
+    let data = match &args.data_path {
+        Some(path) => Some(IterationData::load(path)?),
+        None => None,
+    };
+
+    let (mut variables, missing_secrets) = match &args.environment {
+        Some(name) => environment_variables(name, &info.toml.environments)?,
+        None => (HashMap::new(), Vec::new()),
+    };
+    for (name, value) in &args.vars {
+        variables.insert(name.clone(), value.clone());
+    }
+    for name in missing_secrets {
+        if !variables.contains_key(&name) {
+            eprintln!(
+                "warning: secret variable '{}' is not set; pass it with --var {}=...",
+                name, name
+            );
+        }
+    }
+
+    let context = RunContext {
+        variables,
+        secrets: HashMap::new(),
+        collection_vars: info
+            .toml
+            .collection
+            .vars
+            .iter()
+            .filter(|v| v.enabled && !v.key.is_empty())
+            .map(|v| (v.key.clone(), v.value.clone()))
+            .collect(),
+        collection_auth: info.toml.collection.auth.clone(),
+        folders: info
+            .groups
+            .iter()
+            .map(|(name, group)| (name.clone(), group.folder.clone()))
+            .collect(),
+        collection: info.toml.collection.defaults(),
+        saved_requests: SavedRequests::from_collection(&info),
+        // Cookies last for the run only.
+        cookie_jar: Default::default(),
+    };
+
+    if plan.is_empty() || data.as_ref().is_some_and(IterationData::is_empty) {
+        println!("No requests to run");
+        return Ok(true);
+    }
+
+    // The CLI doesn't read the app settings: use the collection's proxy, or
+    // the proxy environment variables.
+    let proxy = info.toml.collection.proxy.clone().unwrap_or_default();
+    let client = HttpClientService::with_proxy(args.timeout_seconds, proxy)?.for_collection(
+        None,
+        info.toml.collection.tls.as_ref(),
+        info.toml.collection.http_version,
+        Path::new(&info.data.path),
+    )?;
+    let mut runner = CollectionRunner::new(client, context);
+    let started_at = chrono::Utc::now();
+
+    println!(
+        "Running {} ({} request{})",
+        info.toml.collection.name,
+        plan.len(),
+        if plan.len() == 1 { "" } else { "s" }
+    );
+
+    let mut results = Vec::new();
+    match &data {
+        Some(data) => {
+            for (index, row) in data.rows.iter().enumerate() {
+                println!("\nIteration {}/{}", index + 1, data.len());
+                for item in &plan.items {
+                    let mut result = runner.run_item(item, Some(row)).await;
+                    result.iteration = Some(index + 1);
+                    print_result(&result);
+                    results.push(result);
+                }
+            }
+        }
+        None => {
+            for item in &plan.items {
+                let result = runner.run_item(item, None).await;
+                print_result(&result);
+                results.push(result);
+            }
+        }
+    }
+
+    print_summary(&results);
+
+    let report = RunReport::new(
+        info.toml.collection.name.clone(),
+        args.environment.clone(),
+        started_at,
+        &results,
+    );
+    if let Some(path) = &args.junit_path {
+        report.write_junit(path)?;
+        println!("JUnit report written to {}", path.display());
+    }
+    if let Some(path) = &args.json_path {
+        report.write_json(path)?;
+        println!("JSON report written to {}", path.display());
+    }
+    if args.archive {
+        let (json_path, junit_path) = report.archive(&args.collection_dir)?;
+        println!(
+            "Run archived to {} and {}",
+            json_path.display(),
+            junit_path.display()
+        );
+    }
+
+    Ok(results.iter().all(RequestRunResult::passed))
+}
+
+/// Non-secret variables of an environment, plus the names of its secret
+/// variables. Secrets live in the OS keychain, which is only reachable
+/// through GPUI, so headless runs take them from `--var` instead.
+fn environment_variables(
+    name: &str,
+    environments: &[EnvironmentToml],
+) -> Result<(HashMap<String, String>, Vec<String>)> {
+    let environment = environments
+        .iter()
+        .find(|e| e.name == name)
+        .ok_or_else(|| {
+            let available: Vec<&str> = environments.iter().map(|e| e.name.as_str()).collect();
+            anyhow::anyhow!(
+                "Environment '{}' not found (available: {})",
+                name,
+                available.join(", ")
+            )
+        })?;
+
+    let mut variables = HashMap::new();
+    let mut secrets = Vec::new();
+    for (key, var) in &environment.variables {
+        if var.temporary {
+            continue;
+        }
+        if var.secret {
+            secrets.push(key.clone());
+        } else {
+            variables.insert(key.clone(), var.value.clone());
+        }
+    }
+    secrets.sort();
+    Ok((variables, secrets))
+}
+
+fn print_result(result: &RequestRunResult) {
+    let mark = if result.passed() { "✓" } else { "✗" };
+    let name = match &result.group {
+        Some(group) => format!("{}/{}", group, result.name),
+        None => result.name.clone(),
+    };
+
+    match &result.response {
+        Some(response) => {
+            let status = response
+                .status_code
+                .map(|code| code.to_string())
+                .unwrap_or_default();
+            let latency = response
+                .latency
+                .map(|l| format!(" ({}ms)", l.as_millis()))
+                .unwrap_or_default();
+            println!(
+                "{} {} {} {} -> {}{}",
+                mark, name, result.method, result.url, status, latency
+            );
+        }
+        None => println!("{} {} {} {}", mark, name, result.method, result.url),
+    }
+
+    if let Some(error) = &result.error {
+        println!("    error: {}", error);
+    }
+    for test in &result.tests {
+        if test.passed() {
+            println!("    ✓ {}", test.name);
+        } else {
+            println!(
+                "    ✗ {}: {}",
+                test.name,
+                test.error.as_deref().unwrap_or("failed")
+            );
+        }
+    }
+}
+
+fn print_summary(results: &[RequestRunResult]) {
+    let failed_requests = results.iter().filter(|r| !r.passed()).count();
+    let total_tests: usize = results.iter().map(|r| r.tests.len()).sum();
+    let failed_tests: usize = results.iter().map(RequestRunResult::failed_tests).sum();
+
+    println!();
+    println!(
+        "Requests: {} passed, {} failed, {} total",
+        results.len() - failed_requests,
+        failed_requests,
+        results.len()
+    );
+    println!(
+        "Tests:    {} passed, {} failed, {} total",
+        total_tests - failed_tests,
+        failed_tests,
+        total_tests
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_run_arguments() {
+        let parsed = RunArgs::parse(&args(&[
+            "./api",
+            "--env",
+            "staging",
+            "-g",
+            "users",
+            "-d",
+            "rows.csv",
+            "--var",
+            "token=abc=def",
+            "--timeout",
+            "5",
+            "--junit",
+            "out/junit.xml",
+            "--archive",
+        ]))
+        .expect("valid args")
+        .expect("not help");
+
+        assert_eq!(parsed.collection_dir, PathBuf::from("./api"));
+        assert_eq!(parsed.environment.as_deref(), Some("staging"));
+        assert_eq!(parsed.group.as_deref(), Some("users"));
+        assert_eq!(parsed.data_path, Some(PathBuf::from("rows.csv")));
+        assert_eq!(
+            parsed.vars,
+            vec![("token".to_string(), "abc=def".to_string())]
+        );
+        assert_eq!(parsed.timeout_seconds, 5);
+        assert_eq!(parsed.junit_path, Some(PathBuf::from("out/junit.xml")));
+        assert_eq!(parsed.json_path, None);
+        assert!(parsed.archive);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(RunArgs::parse(&args(&[])).is_err());
+        assert!(RunArgs::parse(&args(&["./api", "--env"])).is_err());
+        assert!(RunArgs::parse(&args(&["./api", "--var", "novalue"])).is_err());
+        assert!(RunArgs::parse(&args(&["./api", "--bogus"])).is_err());
+        assert!(RunArgs::parse(&args(&["./a", "./b"])).is_err());
+        assert!(matches!(RunArgs::parse(&args(&["--help"])), Ok(None)));
+    }
+}
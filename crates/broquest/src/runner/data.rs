@@ -0,0 +1,236 @@
+use anyhow::{Context as _, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One iteration's worth of data: column name -> value
+pub type IterationRow = HashMap<String, Value>;
+
+/// Rows of a data file attached to a run. Each row drives one iteration; its
+/// columns become the highest-precedence `{{variables}}` and are exposed to
+/// scripts as `bro.iterationData`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IterationData {
+    pub rows: Vec<IterationRow>,
+}
+
+impl IterationData {
+    /// Load a `.csv` or `.json` file. Anything other than a `.json` extension
+    /// is read as CSV.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read data file {}", path.display()))?;
+        let is_json = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+        let data = if is_json {
+            Self::from_json(&content)
+        } else {
+            Self::from_csv(&content)
+        };
+        data.with_context(|| format!("Failed to parse data file {}", path.display()))
+    }
+
+    /// Parse a JSON array of objects.
+    pub fn from_json(content: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(content)?;
+        let Value::Array(items) = value else {
+            anyhow::bail!("Expected a JSON array of objects");
+        };
+
+        let rows = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| match item {
+                Value::Object(map) => Ok(map.into_iter().collect()),
+                _ => Err(anyhow::anyhow!("Row {} is not an object", index + 1)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rows })
+    }
+
+    /// Parse CSV with a header row. Quoted fields may contain commas, doubled
+    /// quotes and line breaks; blank lines are skipped.
+    pub fn from_csv(content: &str) -> Result<Self> {
+        let mut records = parse_csv(content.trim_start_matches('\u{feff}'))?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(Self::default());
+        };
+        let header: Vec<String> = header.into_iter().map(|h| h.trim().to_string()).collect();
+        // Rows are keyed by column name, so every column needs its own
+        for (index, name) in header.iter().enumerate() {
+            if name.is_empty() {
+                anyhow::bail!("Column {} of the header has no name", index + 1);
+            }
+            if header[..index].contains(name) {
+                anyhow::bail!("Column '{}' appears more than once in the header", name);
+            }
+        }
+
+        let rows = records
+            .enumerate()
+            .map(|(index, record)| {
+                if record.len() > header.len() {
+                    anyhow::bail!(
+                        "Row {} has {} columns, the header has {}",
+                        index + 1,
+                        record.len(),
+                        header.len()
+                    );
+                }
+                Ok(header
+                    .iter()
+                    .cloned()
+                    .zip(record.into_iter().map(Value::String))
+                    .collect())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rows })
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// Split CSV content into records of fields.
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    // A quoted field just closed, so only a separator may follow
+    let mut closed = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    in_quotes = false;
+                    closed = true;
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                closed = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                push_record(&mut records, std::mem::take(&mut record));
+                closed = false;
+            }
+            _ if closed => {
+                anyhow::bail!(
+                    "{} has '{}' after a closing quote",
+                    row_name(records.len()),
+                    c
+                );
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            '"' => anyhow::bail!(
+                "{} has a quote inside an unquoted field",
+                row_name(records.len())
+            ),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        anyhow::bail!("Unterminated quoted field");
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        push_record(&mut records, record);
+    }
+
+    Ok(records)
+}
+
+/// Name a record the way `IterationData::from_csv` counts rows: the header,
+/// then the data rows from 1
+fn row_name(index: usize) -> String {
+    match index {
+        0 => "The header".to_string(),
+        _ => format!("Row {}", index),
+    }
+}
+
+fn push_record(records: &mut Vec<Vec<String>>, record: Vec<String>) {
+    // A blank line parses as a single empty field.
+    if record.len() == 1 && record[0].is_empty() {
+        return;
+    }
+    records.push(record);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_csv_with_quotes_and_blank_lines() {
+        let data = IterationData::from_csv(
+            "id,name,note\r\n1,Ada,\"likes \"\"math\"\", chess\"\n\n2,Linus,\"multi\nline\"\n3,Grace",
+        )
+        .expect("valid csv");
+
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.rows[0]["id"], json!("1"));
+        assert_eq!(data.rows[0]["note"], json!("likes \"math\", chess"));
+        assert_eq!(data.rows[1]["note"], json!("multi\nline"));
+        // Missing trailing columns are simply absent.
+        assert_eq!(data.rows[2]["name"], json!("Grace"));
+        assert!(!data.rows[2].contains_key("note"));
+    }
+
+    #[test]
+    fn rejects_bad_csv() {
+        assert!(IterationData::from_csv("a,b\n1,2,3").is_err());
+        assert!(IterationData::from_csv("a\n\"open").is_err());
+        let error = IterationData::from_csv("a,b\n1,2\n\"a\"b,c").expect_err("text after quote");
+        assert!(error.to_string().contains("Row 2"), "{}", error);
+        let error = IterationData::from_csv("a,b\nx\"y\"z,c").expect_err("quote mid-field");
+        assert!(error.to_string().contains("Row 1"), "{}", error);
+        assert!(IterationData::from_csv("").expect("empty").is_empty());
+    }
+
+    #[test]
+    fn rejects_unnamed_and_duplicate_columns() {
+        let error = IterationData::from_csv("id,name,id\n1,Ada,2").expect_err("duplicate column");
+        assert!(error.to_string().contains("'id'"));
+        let error = IterationData::from_csv("id,,name\n1,2,Ada").expect_err("unnamed column");
+        assert!(error.to_string().contains("Column 2"));
+    }
+
+    #[test]
+    fn parses_json_array_of_objects() {
+        let data = IterationData::from_json(r#"[{"id": 1, "tags": ["a"]}, {"id": 2}]"#)
+            .expect("valid json");
+
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.rows[0]["id"], json!(1));
+        assert_eq!(data.rows[0]["tags"], json!(["a"]));
+        assert!(IterationData::from_json(r#"{"id": 1}"#).is_err());
+        assert!(IterationData::from_json("[1, 2]").is_err());
+    }
+}
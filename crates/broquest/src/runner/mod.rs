@@ -0,0 +1,16 @@
+//! Collection runner: sends a collection's requests in order, carrying runtime
+//! variables from one request to the next, and reports test outcomes.
+
+mod cli;
+mod data;
+mod plan;
+mod report;
+mod session;
+mod view;
+
+pub use cli::*;
+pub use data::*;
+pub use plan::*;
+pub use report::*;
+pub use session::*;
+pub use view::*;
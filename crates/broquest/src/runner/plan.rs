@@ -0,0 +1,198 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::collections::CollectionInfo;
+use crate::domain::{RequestData, RequestKind};
+
+/// A single request scheduled in a run, with the group it came from
+#[derive(Debug, Clone)]
+pub struct RunItem {
+    pub group: Option<String>,
+    pub request: RequestData,
+}
+
+/// The ordered list of requests a run will execute.
+///
+/// Requests at the collection root run first, then each group in name order.
+/// Within a group, requests are ordered by `seq`, falling back to their name
+/// for requests without one (or with the same one). WebSocket requests stay
+/// connected until closed, so runs leave them out.
+#[derive(Debug, Clone, Default)]
+pub struct RunPlan {
+    pub items: Vec<RunItem>,
+}
+
+impl RunPlan {
+    /// Build a plan for a whole collection, or for a single group when
+    /// `group` is given.
+    pub fn for_collection(info: &CollectionInfo, group: Option<&str>) -> Result<Self> {
+        let mut items = Vec::new();
+
+        if let Some(group_name) = group {
+            let group = info
+                .groups
+                .get(group_name)
+                .ok_or_else(|| anyhow::anyhow!("Group '{}' not found in collection", group_name))?;
+            items.extend(Self::ordered(&group.requests, Some(&group.name)));
+        } else {
+            items.extend(Self::ordered(&info.requests, None));
+
+            let mut group_names: Vec<&String> = info.groups.keys().collect();
+            group_names.sort();
+            for name in group_names {
+                let group = &info.groups[name];
+                items.extend(Self::ordered(&group.requests, Some(&group.name)));
+            }
+        }
+
+        Ok(Self { items })
+    }
+
+    /// Sort a `file_path -> RequestData` map into run order.
+    fn ordered(requests: &HashMap<String, RequestData>, group: Option<&str>) -> Vec<RunItem> {
+        let mut entries: Vec<(&String, &RequestData)> = requests
+            .iter()
+            .filter(|(_, request)| request.kind != RequestKind::WebSocket)
+            .collect();
+        entries.sort_by(|(path_a, a), (path_b, b)| {
+            a.seq
+                .unwrap_or(u32::MAX)
+                .cmp(&b.seq.unwrap_or(u32::MAX))
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| path_a.cmp(path_b))
+        });
+        entries
+            .into_iter()
+            .map(|(_, request)| RunItem {
+                group: group.map(str::to_string),
+                request: request.clone(),
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::GroupInfo;
+    use crate::domain::FolderSettings;
+
+    fn request(name: &str, seq: Option<u32>) -> RequestData {
+        RequestData {
+            name: name.to_string(),
+            seq,
+            ..Default::default()
+        }
+    }
+
+    fn requests(entries: &[(&str, Option<u32>)]) -> HashMap<String, RequestData> {
+        entries
+            .iter()
+            .map(|(name, seq)| (format!("/tmp/{}.toml", name), request(name, *seq)))
+            .collect()
+    }
+
+    fn names(plan: &RunPlan) -> Vec<&str> {
+        plan.items
+            .iter()
+            .map(|item| item.request.name.as_str())
+            .collect()
+    }
+
+    fn collection() -> CollectionInfo {
+        let now = chrono::Utc::now();
+        let mut info = CollectionInfo {
+            data: crate::app_database::CollectionData {
+                id: None,
+                name: "Plan".to_string(),
+                path: "/tmp/plan".to_string(),
+                position: 0,
+                format: String::new(),
+                created_at: now,
+                updated_at: now,
+            },
+            toml: crate::collections::create_empty_collection(),
+            requests: HashMap::new(),
+            groups: HashMap::new(),
+            format: Default::default(),
+            oc_source: None,
+            oc_items: HashMap::new(),
+            oc_groups: HashMap::new(),
+            oc_envs: HashMap::new(),
+            runtime_vars: HashMap::new(),
+        };
+
+        info.requests = requests(&[("root-b", Some(2)), ("root-a", Some(1))]);
+        info.groups.insert(
+            "users".to_string(),
+            GroupInfo {
+                name: "users".to_string(),
+                requests: requests(&[("delete", Some(3)), ("create", Some(1)), ("zzz", None)]),
+                path: "users".to_string(),
+                folder: FolderSettings::default(),
+            },
+        );
+        info.groups.insert(
+            "auth".to_string(),
+            GroupInfo {
+                name: "auth".to_string(),
+                requests: requests(&[("login", Some(1))]),
+                path: "auth".to_string(),
+                folder: FolderSettings::default(),
+            },
+        );
+        info
+    }
+
+    #[test]
+    fn whole_collection_runs_root_then_groups_by_seq() {
+        let plan = RunPlan::for_collection(&collection(), None).expect("plan");
+        assert_eq!(
+            names(&plan),
+            vec!["root-a", "root-b", "login", "create", "delete", "zzz"]
+        );
+        assert_eq!(plan.items[2].group.as_deref(), Some("auth"));
+        assert_eq!(plan.items[0].group, None);
+    }
+
+    #[test]
+    fn single_group_plan_and_unknown_group() {
+        let info = collection();
+        let plan = RunPlan::for_collection(&info, Some("users")).expect("plan");
+        assert_eq!(names(&plan), vec!["create", "delete", "zzz"]);
+
+        assert!(RunPlan::for_collection(&info, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn equal_seq_falls_back_to_name() {
+        let mut info = collection();
+        info.groups.clear();
+        info.requests = requests(&[("beta", Some(1)), ("alpha", Some(1))]);
+        let plan = RunPlan::for_collection(&info, None).expect("plan");
+        assert_eq!(names(&plan), vec!["alpha", "beta"]);
+    }
+
+    #[test]
+    fn websocket_requests_are_left_out() {
+        let mut info = collection();
+        info.groups.clear();
+        info.requests = requests(&[("http", Some(1)), ("socket", Some(2)), ("grpc", Some(3))]);
+        if let Some(socket) = info.requests.get_mut("/tmp/socket.toml") {
+            socket.kind = RequestKind::WebSocket;
+        }
+        if let Some(grpc) = info.requests.get_mut("/tmp/grpc.toml") {
+            grpc.kind = RequestKind::Grpc;
+        }
+        let plan = RunPlan::for_collection(&info, None).expect("plan");
+        assert_eq!(names(&plan), vec!["http", "grpc"]);
+    }
+}
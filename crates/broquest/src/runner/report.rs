@@ -0,0 +1,444 @@
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Utc};
+use quick_xml::escape::escape;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::scripting::TestResult;
+
+use super::session::RequestRunResult;
+
+/// Directory inside a collection where archived run reports are written.
+/// Hidden, so the collection loaders don't mistake it for a group.
+pub const RUNS_DIR: &str = ".runs";
+
+/// Serializable summary of a collection run, exported as JSON or JUnit XML
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub collection: String,
+    pub environment: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub summary: RunSummary,
+    pub requests: Vec<RequestReport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub requests: usize,
+    pub failed_requests: usize,
+    pub tests: usize,
+    pub failed_tests: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestReport {
+    pub name: String,
+    pub group: Option<String>,
+    pub iteration: Option<usize>,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub status_text: Option<String>,
+    pub latency_ms: Option<u64>,
+    pub passed: bool,
+    pub error: Option<String>,
+    pub tests: Vec<TestResult>,
+}
+
+impl RequestReport {
+    /// `group/name`, or just the name for requests at the collection root,
+    /// suffixed with the iteration in multi-iteration runs.
+    fn qualified_name(&self) -> String {
+        let name = match &self.group {
+            Some(group) => format!("{}/{}", group, self.name),
+            None => self.name.clone(),
+        };
+        match self.iteration {
+            Some(iteration) => format!("{} (iteration {})", name, iteration),
+            None => name,
+        }
+    }
+
+    fn seconds(&self) -> f64 {
+        self.latency_ms.unwrap_or(0) as f64 / 1000.0
+    }
+}
+
+impl From<&RequestRunResult> for RequestReport {
+    fn from(result: &RequestRunResult) -> Self {
+        let response = result.response.as_ref();
+        Self {
+            name: result.name.clone(),
+            group: result.group.clone(),
+            iteration: result.iteration,
+            method: result.method.clone(),
+            url: result.url.clone(),
+            status: response.and_then(|r| r.status_code),
+            status_text: response.and_then(|r| r.status_text.clone()),
+            latency_ms: response
+                .and_then(|r| r.latency)
+                .map(|l| l.as_millis() as u64),
+            passed: result.passed(),
+            error: result.error.clone(),
+            tests: result.tests.clone(),
+        }
+    }
+}
+
+impl RunReport {
+    pub fn new(
+        collection: impl Into<String>,
+        environment: Option<String>,
+        started_at: DateTime<Utc>,
+        results: &[RequestRunResult],
+    ) -> Self {
+        let requests: Vec<RequestReport> = results.iter().map(RequestReport::from).collect();
+        let summary = RunSummary {
+            requests: requests.len(),
+            failed_requests: requests.iter().filter(|r| !r.passed).count(),
+            tests: requests.iter().map(|r| r.tests.len()).sum(),
+            failed_tests: requests
+                .iter()
+                .map(|r| r.tests.iter().filter(|t| !t.passed()).count())
+                .sum(),
+        };
+        let duration_ms = (Utc::now() - started_at).num_milliseconds().max(0) as u64;
+
+        Self {
+            collection: collection.into(),
+            environment,
+            started_at,
+            duration_ms,
+            summary,
+            requests,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize run report")
+    }
+
+    /// Render as JUnit XML: one `<testsuite>` per request and one
+    /// `<testcase>` per `test()`. A request without tests becomes a single
+    /// test case so transport errors still show up as failures.
+    pub fn to_junit_xml(&self) -> String {
+        let errors = self.requests.iter().filter(|r| r.error.is_some()).count();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+            escape(&self.collection),
+            self.junit_case_count(),
+            self.summary.failed_tests,
+            errors,
+            self.duration_ms as f64 / 1000.0
+        );
+
+        for request in &self.requests {
+            let name = request.qualified_name();
+            let cases = request.tests.len()
+                + usize::from(request.error.is_some() || request.tests.is_empty());
+            let failures = request.tests.iter().filter(|t| !t.passed()).count();
+            let _ = writeln!(
+                xml,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\" timestamp=\"{}\">",
+                escape(&name),
+                cases,
+                failures,
+                usize::from(request.error.is_some()),
+                request.seconds(),
+                self.started_at.format("%Y-%m-%dT%H:%M:%S")
+            );
+            let _ = writeln!(xml, "    <properties>");
+            let _ = writeln!(
+                xml,
+                "      <property name=\"method\" value=\"{}\"/>",
+                escape(&request.method)
+            );
+            let _ = writeln!(
+                xml,
+                "      <property name=\"url\" value=\"{}\"/>",
+                escape(&request.url)
+            );
+            if let Some(status) = request.status {
+                let _ = writeln!(
+                    xml,
+                    "      <property name=\"status\" value=\"{}\"/>",
+                    status
+                );
+            }
+            let _ = writeln!(xml, "    </properties>");
+
+            if let Some(error) = &request.error {
+                let _ = writeln!(
+                    xml,
+                    "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">",
+                    escape(&name),
+                    escape(&request.name),
+                    request.seconds()
+                );
+                let _ = writeln!(
+                    xml,
+                    "      <error message=\"{}\">{}</error>",
+                    escape(error),
+                    escape(error)
+                );
+                let _ = writeln!(xml, "    </testcase>");
+            } else if request.tests.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"/>",
+                    escape(&name),
+                    escape(&request.name),
+                    request.seconds()
+                );
+            }
+
+            for test in &request.tests {
+                let time = test.duration_ms / 1000.0;
+                if test.passed() {
+                    let _ = writeln!(
+                        xml,
+                        "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"/>",
+                        escape(&name),
+                        escape(&test.name),
+                        time
+                    );
+                } else {
+                    let message = test.error.as_deref().unwrap_or("failed");
+                    let _ = writeln!(
+                        xml,
+                        "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\">",
+                        escape(&name),
+                        escape(&test.name),
+                        time
+                    );
+                    let _ = writeln!(
+                        xml,
+                        "      <failure message=\"{}\">{}</failure>",
+                        escape(message),
+                        escape(message)
+                    );
+                    let _ = writeln!(xml, "    </testcase>");
+                }
+            }
+
+            let _ = writeln!(xml, "  </testsuite>");
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// Total `<testcase>` elements: one per test, plus one for a request that
+    /// errored or has no tests.
+    fn junit_case_count(&self) -> usize {
+        self.requests
+            .iter()
+            .map(|r| {
+                if r.error.is_some() {
+                    r.tests.len() + 1
+                } else {
+                    r.tests.len().max(1)
+                }
+            })
+            .sum()
+    }
+
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        write_file(path, &self.to_json()?)
+    }
+
+    pub fn write_junit(&self, path: &Path) -> Result<()> {
+        write_file(path, &self.to_junit_xml())
+    }
+
+    /// Write both formats into the collection's [`RUNS_DIR`], named after
+    /// the run's start time. Runs that started in the same millisecond get a
+    /// numbered suffix rather than overwriting each other's reports. Returns
+    /// the paths written.
+    pub fn archive(&self, collection_dir: &Path) -> Result<(PathBuf, PathBuf)> {
+        let dir = collection_dir.join(RUNS_DIR);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create directory {:?}", dir))?;
+        let started = format!("run-{}", self.started_at.format("%Y%m%dT%H%M%S%3fZ"));
+        let json = self.to_json()?;
+        let junit = self.to_junit_xml();
+
+        for attempt in 1.. {
+            let stem = match attempt {
+                1 => started.clone(),
+                n => format!("{}-{}", started, n),
+            };
+            let json_path = dir.join(format!("{}.json", stem));
+            let junit_path = dir.join(format!("{}.xml", stem));
+            if !write_new_file(&json_path, &json)? {
+                continue;
+            }
+            if !write_new_file(&junit_path, &junit)? {
+                let _ = std::fs::remove_file(&json_path);
+                continue;
+            }
+            return Ok((json_path, junit_path));
+        }
+        unreachable!("archive names never run out")
+    }
+}
+
+/// Write a file that must not exist yet. Returns false, writing nothing, if
+/// it does.
+fn write_new_file(path: &Path, content: &str) -> Result<bool> {
+    use std::io::Write as _;
+
+    let mut file = match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(e).with_context(|| format!("Failed to write report {:?}", path)),
+    };
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write report {:?}", path))?;
+    Ok(true)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write report {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ResponseData;
+    use crate::scripting::TestStatus;
+    use std::time::Duration;
+
+    fn test_result(name: &str, error: Option<&str>) -> TestResult {
+        TestResult {
+            name: name.to_string(),
+            status: if error.is_some() {
+                TestStatus::Fail
+            } else {
+                TestStatus::Pass
+            },
+            error: error.map(str::to_string),
+            duration_ms: 2.0,
+        }
+    }
+
+    fn results() -> Vec<RequestRunResult> {
+        vec![
+            RequestRunResult {
+                name: "Get user".to_string(),
+                group: Some("users".to_string()),
+                iteration: None,
+                method: "GET".to_string(),
+                url: "https://api.example.com/users/1?a=1&b=2".to_string(),
+                response: Some(ResponseData {
+                    status_code: Some(200),
+                    status_text: Some("OK".to_string()),
+                    latency: Some(Duration::from_millis(120)),
+                    ..Default::default()
+                }),
+                tests: vec![
+                    test_result("status is 200", None),
+                    test_result("has <name>", Some("expected \"x\" to equal \"y\"")),
+                ],
+                error: None,
+            },
+            RequestRunResult {
+                name: "Health".to_string(),
+                group: None,
+                iteration: None,
+                method: "GET".to_string(),
+                url: "https://api.example.com/health".to_string(),
+                response: None,
+                tests: Vec::new(),
+                error: Some("Connection failed".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn json_report_carries_request_details_and_summary() {
+        let report = RunReport::new("Demo", Some("staging".to_string()), Utc::now(), &results());
+        let json: serde_json::Value =
+            serde_json::from_str(&report.to_json().expect("serialize")).expect("valid json");
+
+        assert_eq!(json["collection"], "Demo");
+        assert_eq!(json["environment"], "staging");
+        assert_eq!(json["summary"]["requests"], 2);
+        assert_eq!(json["summary"]["failedRequests"], 2);
+        assert_eq!(json["summary"]["tests"], 2);
+        assert_eq!(json["summary"]["failedTests"], 1);
+
+        let first = &json["requests"][0];
+        assert_eq!(first["name"], "Get user");
+        assert_eq!(first["url"], "https://api.example.com/users/1?a=1&b=2");
+        assert_eq!(first["status"], 200);
+        assert_eq!(first["latencyMs"], 120);
+        assert_eq!(first["tests"][1]["status"], "fail");
+        assert_eq!(json["requests"][1]["error"], "Connection failed");
+    }
+
+    #[test]
+    fn junit_report_escapes_and_counts_cases() {
+        let report = RunReport::new("Demo & Co", None, Utc::now(), &results());
+        let xml = report.to_junit_xml();
+
+        assert!(xml.contains(
+            "<testsuites name=\"Demo &amp; Co\" tests=\"3\" failures=\"1\" errors=\"1\""
+        ));
+        assert!(xml.contains("<testsuite name=\"users/Get user\" tests=\"2\" failures=\"1\" errors=\"0\" time=\"0.120\""));
+        assert!(xml.contains("value=\"https://api.example.com/users/1?a=1&amp;b=2\""));
+        assert!(xml.contains("name=\"has &lt;name&gt;\""));
+        assert!(
+            xml.contains("<failure message=\"expected &quot;x&quot; to equal &quot;y&quot;\">")
+        );
+        assert!(xml.contains("<error message=\"Connection failed\">Connection failed</error>"));
+    }
+
+    #[test]
+    fn reports_name_iterations() {
+        let mut results = results();
+        results[0].iteration = Some(2);
+        let report = RunReport::new("Demo", None, Utc::now(), &results);
+
+        assert_eq!(report.requests[0].iteration, Some(2));
+        assert!(
+            report
+                .to_junit_xml()
+                .contains("<testsuite name=\"users/Get user (iteration 2)\"")
+        );
+    }
+
+    #[test]
+    fn archived_runs_started_together_keep_both_reports() {
+        let dir = std::env::temp_dir().join(format!("broquest-runs-{}", std::process::id()));
+        let report = RunReport::new("Demo", None, Utc::now(), &results());
+
+        let first = report.archive(&dir).expect("first archive");
+        let second = report.archive(&dir).expect("second archive");
+        assert_ne!(first.0, second.0);
+        assert_ne!(first.1, second.1);
+        for path in [&first.0, &first.1, &second.0, &second.1] {
+            assert!(path.exists(), "{:?} should exist", path);
+        }
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use crate::cookies::CookieJar;
+use crate::domain::{AuthType, CollectionDefaults, FolderSettings, ResponseData};
+use crate::http::{HttpClientService, RequestScope, SavedRequests};
+use crate::scripting::TestResult;
+
+use super::data::IterationRow;
+use super::plan::RunItem;
+
+/// Variables and auth shared by every request in a run
+#[derive(Debug, Clone, Default)]
+pub struct RunContext {
+    pub variables: HashMap<String, String>,
+    pub secrets: HashMap<String, String>,
+    pub collection_vars: HashMap<String, String>,
+    /// Collection auth, used by requests whose auth is `Inherit` in groups
+    /// that inherit theirs too.
+    pub collection_auth: Option<AuthType>,
+    /// Settings of each group, keyed by group name.
+    pub folders: HashMap<String, FolderSettings>,
+    /// Headers and scripts of the collection.
+    pub collection: CollectionDefaults,
+    /// Requests scripts can run with `bro.runRequest`.
+    pub saved_requests: SavedRequests,
+    /// Jar the run's requests send cookies from and store cookies in.
+    pub cookie_jar: CookieJar,
+}
+
+/// Outcome of one request in a run
+#[derive(Debug, Clone)]
+pub struct RequestRunResult {
+    pub name: String,
+    pub group: Option<String>,
+    /// 1-based iteration, for runs that send the collection more than once.
+    pub iteration: Option<usize>,
+    pub method: String,
+    /// The URL after variable resolution, or the raw URL if the request
+    /// failed before it was sent.
+    pub url: String,
+    pub response: Option<ResponseData>,
+    pub tests: Vec<TestResult>,
+    /// Transport or script error that prevented a response.
+    pub error: Option<String>,
+}
+
+impl RequestRunResult {
+    /// A request passes when it produced a response and none of its tests
+    /// failed. HTTP error statuses alone don't fail a request.
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.tests.iter().all(TestResult::passed)
+    }
+
+    pub fn failed_tests(&self) -> usize {
+        self.tests.iter().filter(|t| !t.passed()).count()
+    }
+}
+
+/// Executes [`RunItem`]s one after another, carrying runtime vars (and any
+/// environment vars scripts set) from each request into the next.
+pub struct CollectionRunner {
+    client: HttpClientService,
+    context: RunContext,
+    runtime_vars: HashMap<String, serde_json::Value>,
+    /// Environment vars set by scripts since the last [`Self::take_env_updates`].
+    env_updates: HashMap<String, String>,
+}
+
+impl CollectionRunner {
+    pub fn new(client: HttpClientService, context: RunContext) -> Self {
+        Self {
+            client,
+            context,
+            runtime_vars: HashMap::new(),
+            env_updates: HashMap::new(),
+        }
+    }
+
+    /// Seed the run with existing runtime vars, e.g. the collection's
+    /// current session vars in the desktop app.
+    pub fn with_runtime_vars(mut self, runtime_vars: HashMap<String, serde_json::Value>) -> Self {
+        self.runtime_vars = runtime_vars;
+        self
+    }
+
+    pub fn runtime_vars(&self) -> &HashMap<String, serde_json::Value> {
+        &self.runtime_vars
+    }
+
+    /// Environment vars changed by scripts since the last call, so callers
+    /// can persist them.
+    pub fn take_env_updates(&mut self) -> HashMap<String, String> {
+        std::mem::take(&mut self.env_updates)
+    }
+
+    /// Send a single request and fold its variable changes into the run.
+    /// `iteration_data` is the current row when the run is data-driven.
+    pub async fn run_item(
+        &mut self,
+        item: &RunItem,
+        iteration_data: Option<&IterationRow>,
+    ) -> RequestRunResult {
+        let mut request = item.request.clone();
+        let folder = item
+            .group
+            .as_ref()
+            .and_then(|group| self.context.folders.get(group))
+            .cloned()
+            .unwrap_or_default();
+        if matches!(request.auth, AuthType::Inherit) {
+            request.auth = folder.inherited_auth(self.context.collection_auth.as_ref());
+        }
+
+        let name = request.name.clone();
+        let method = request.method.as_str().to_string();
+        let raw_url = request.url.clone();
+
+        let result = self
+            .client
+            .send_request(
+                request,
+                Some(self.context.variables.clone()),
+                Some(self.context.secrets.clone()),
+                Some(self.runtime_vars.clone()),
+                Some(self.context.collection_vars.clone()),
+                RequestScope::new(self.context.saved_requests.clone())
+                    .with_folder(folder)
+                    .with_collection(self.context.collection.clone())
+                    .with_iteration_data(iteration_data.cloned().unwrap_or_default())
+                    .with_cookie_jar(self.context.cookie_jar.clone()),
+            )
+            .await;
+
+        match result {
+            Ok((response, variable_store, tests)) => {
+                self.runtime_vars = variable_store.get_all_vars();
+                for (key, value) in variable_store.get_dirty_env_vars() {
+                    self.context.variables.insert(key.clone(), value.clone());
+                    self.env_updates.insert(key, value);
+                }
+
+                RequestRunResult {
+                    name,
+                    group: item.group.clone(),
+                    iteration: None,
+                    method,
+                    url: response.url.clone().unwrap_or(raw_url),
+                    response: Some(response),
+                    tests,
+                    error: None,
+                }
+            }
+            Err(error) => RequestRunResult {
+                name,
+                group: item.group.clone(),
+                iteration: None,
+                method,
+                url: raw_url,
+                response: None,
+                tests: error.tests,
+                error: Some(error.details),
+            },
+        }
+    }
+}
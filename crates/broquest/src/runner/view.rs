@@ -0,0 +1,686 @@
+use gpui::{
+    App, AppContext, Context, Entity, FocusHandle, Focusable, IntoElement, ParentElement, Render,
+    SharedString, Styled, Task, Window, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, IndexPath, Sizable as _, StyledExt, WindowExt,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    notification::NotificationType,
+    scroll::ScrollableElement,
+    select::{Select, SelectState},
+    switch::Switch,
+    v_flex,
+};
+use smol::Timer;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::collections::{CollectionManager, EnvironmentToml};
+use crate::cookies::CookieJars;
+use crate::environments::EnvironmentResolver;
+use crate::http::{HttpClientService, SavedRequests};
+use crate::requests::EnvironmentOption;
+use crate::ui::icon::IconName;
+
+use super::data::IterationData;
+use super::plan::RunPlan;
+use super::session::{CollectionRunner, RequestRunResult, RunContext};
+
+/// Desktop collection runner: sends every request of a collection (or one
+/// group) in order, repeatedly if asked (or once per row of an attached data
+/// file), and lists the results as they come in. Runtime vars are written back to the collection after each request so
+/// the next one (and the rest of the app) sees them.
+pub struct RunnerView {
+    collection_path: String,
+    group_name: Option<String>,
+    environment_select: Entity<SelectState<Vec<EnvironmentOption>>>,
+    iterations_input: Entity<InputState>,
+    delay_input: Entity<InputState>,
+    stop_on_failure: bool,
+    /// Attached CSV/JSON data file; when set, each row is one iteration.
+    data_file: Option<(PathBuf, IterationData)>,
+    results: Vec<RequestRunResult>,
+    /// `group/name` of the request in flight.
+    current: Option<String>,
+    /// Requests the run will send in total (plan length × iterations).
+    total: usize,
+    is_running: bool,
+    run_task: Option<Task<anyhow::Result<()>>>,
+    focus_handle: FocusHandle,
+}
+
+impl RunnerView {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        collection_path: String,
+        group_name: Option<String>,
+    ) -> Self {
+        let environments = CollectionManager::global(cx)
+            .read(cx)
+            .get_collection_environments(&collection_path)
+            .unwrap_or_default();
+        let mut env_options = vec![EnvironmentOption::None];
+        env_options.extend(environments.into_iter().map(EnvironmentOption::Environment));
+
+        let environment_select = cx
+            .new(|cx| SelectState::new(env_options, Some(IndexPath::default().row(0)), window, cx));
+        let iterations_input = cx.new(|cx| InputState::new(window, cx).default_value("1"));
+        let delay_input = cx.new(|cx| InputState::new(window, cx).default_value("0"));
+
+        Self {
+            collection_path,
+            group_name,
+            environment_select,
+            iterations_input,
+            delay_input,
+            stop_on_failure: false,
+            data_file: None,
+            results: Vec::new(),
+            current: None,
+            total: 0,
+            is_running: false,
+            run_task: None,
+            focus_handle: cx.focus_handle(),
+        }
+    }
+
+    fn selected_environment(&self, cx: &App) -> Option<EnvironmentToml> {
+        match self.environment_select.read(cx).selected_value() {
+            Some(EnvironmentOption::Environment(env)) => Some(env.clone()),
+            _ => None,
+        }
+    }
+
+    fn start_run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_running {
+            return;
+        }
+
+        let data = self.data_file.as_ref().map(|(_, data)| data.clone());
+        let iterations = match &data {
+            Some(data) => data.len(),
+            None => match self
+                .iterations_input
+                .read(cx)
+                .value()
+                .trim()
+                .parse::<usize>()
+            {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    window.push_notification(
+                        (
+                            NotificationType::Error,
+                            "Iterations must be a positive number",
+                        ),
+                        cx,
+                    );
+                    return;
+                }
+            },
+        };
+        let delay_ms = match self.delay_input.read(cx).value().trim().parse::<u64>() {
+            Ok(ms) => ms,
+            Err(_) => {
+                window.push_notification(
+                    (
+                        NotificationType::Error,
+                        "Delay must be a number of milliseconds",
+                    ),
+                    cx,
+                );
+                return;
+            }
+        };
+
+        let manager = CollectionManager::global(cx);
+        let Some(info) = manager
+            .read(cx)
+            .get_collection_by_path(&self.collection_path)
+            .cloned()
+        else {
+            window.push_notification((NotificationType::Error, "Collection not found"), cx);
+            return;
+        };
+
+        let plan = match RunPlan::for_collection(&info, self.group_name.as_deref()) {
+            Ok(plan) => plan,
+            Err(e) => {
+                window.push_notification(
+                    (NotificationType::Error, SharedString::from(e.to_string())),
+                    cx,
+                );
+                return;
+            }
+        };
+        if plan.is_empty() || iterations == 0 {
+            window.push_notification((NotificationType::Info, "No requests to run"), cx);
+            return;
+        }
+
+        let environment = self.selected_environment(cx);
+        let (variables, secrets) = match &environment {
+            Some(env) => match EnvironmentResolver::new().load_environment_data(
+                &info.data.name,
+                &env.name,
+                std::slice::from_ref(env),
+                cx,
+            ) {
+                Ok(data) => data,
+                Err(e) => {
+                    tracing::error!("Failed to load environment data: {}", e);
+                    (HashMap::new(), HashMap::new())
+                }
+            },
+            None => (HashMap::new(), HashMap::new()),
+        };
+
+        let environment_name = environment.map(|env| env.name);
+        let cookie_jar = CookieJars::jar(cx, &self.collection_path, environment_name.as_deref());
+        let context = RunContext {
+            variables,
+            secrets,
+            collection_vars: info
+                .toml
+                .collection
+                .vars
+                .iter()
+                .filter(|v| v.enabled && !v.key.is_empty())
+                .map(|v| (v.key.clone(), v.value.clone()))
+                .collect(),
+            collection_auth: info.toml.collection.auth.clone(),
+            folders: info
+                .groups
+                .iter()
+                .map(|(name, group)| (name.clone(), group.folder.clone()))
+                .collect(),
+            collection: info.toml.collection.defaults(),
+            saved_requests: SavedRequests::from_collection(&info),
+            cookie_jar: cookie_jar.clone(),
+        };
+        let http_client = match HttpClientService::global(cx).for_collection(
+            info.toml.collection.proxy.as_ref(),
+            info.toml.collection.tls.as_ref(),
+            info.toml.collection.http_version,
+            Path::new(&info.data.path),
+        ) {
+            Ok(client) => client,
+            Err(e) => {
+                window.push_notification(
+                    (NotificationType::Error, SharedString::from(e.to_string())),
+                    cx,
+                );
+                return;
+            }
+        };
+        let mut runner = CollectionRunner::new(http_client, context)
+            .with_runtime_vars(info.runtime_vars.clone());
+
+        self.results.clear();
+        self.current = None;
+        self.total = plan.len() * iterations;
+        self.is_running = true;
+        cx.notify();
+
+        let collection_path = self.collection_path.clone();
+        let stop_on_failure = self.stop_on_failure;
+        let delay = Duration::from_millis(delay_ms);
+
+        let task = cx.spawn_in(window, async move |this, window| {
+            let mut first = true;
+            'iterations: for iteration in 1..=iterations {
+                for item in &plan.items {
+                    if !first && !delay.is_zero() {
+                        Timer::after(delay).await;
+                    }
+                    first = false;
+
+                    let label = match &item.group {
+                        Some(group) => format!("{}/{}", group, item.request.name),
+                        None => item.request.name.clone(),
+                    };
+                    this.update(window, |view, cx| {
+                        view.current = Some(label);
+                        cx.notify();
+                    })?;
+
+                    let row = data.as_ref().map(|data| &data.rows[iteration - 1]);
+                    let mut result = async_compat::Compat::new(runner.run_item(item, row)).await;
+                    if iterations > 1 || row.is_some() {
+                        result.iteration = Some(iteration);
+                    }
+
+                    // Share runtime vars (and script-set env vars) with the
+                    // rest of the app, the same way a single send does.
+                    let runtime_vars = runner.runtime_vars().clone();
+                    if let Err(e) = manager.update(window, |manager, cx| {
+                        manager.update_runtime_vars(&collection_path, runtime_vars, cx)
+                    }) {
+                        tracing::error!("Failed to update runtime variables: {}", e);
+                    }
+                    let env_updates = runner.take_env_updates();
+                    if let Some(env_name) = &environment_name
+                        && !env_updates.is_empty()
+                        && let Err(e) = manager.update(window, |manager, cx| {
+                            manager.update_environment_variables(
+                                &collection_path,
+                                env_name,
+                                &env_updates,
+                                cx,
+                            )
+                        })
+                    {
+                        tracing::error!("Failed to update environment variables: {}", e);
+                    }
+                    window.update(|_, cx| {
+                        CookieJars::persist(
+                            cx,
+                            &collection_path,
+                            environment_name.as_deref(),
+                            &cookie_jar,
+                        )
+                    })?;
+
+                    let failed = !result.passed();
+                    this.update(window, |view, cx| {
+                        view.results.push(result);
+                        cx.notify();
+                    })?;
+
+                    if failed && stop_on_failure {
+                        break 'iterations;
+                    }
+                }
+            }
+
+            this.update(window, |view, cx| {
+                view.is_running = false;
+                view.current = None;
+                cx.notify();
+            })?;
+            Ok(())
+        });
+
+        self.run_task = Some(task);
+    }
+
+    fn browse_data_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let path = cx.prompt_for_paths(gpui::PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Select a CSV or JSON data file".into()),
+        });
+
+        cx.spawn_in(window, async move |this, window| {
+            let path = path.await.ok()?.ok()??.into_iter().next()?;
+            let loaded = IterationData::load(&path);
+            window
+                .update(|window, cx| match loaded {
+                    Ok(data) => {
+                        this.update(cx, |view, cx| {
+                            view.data_file = Some((path, data));
+                            cx.notify();
+                        })
+                        .ok();
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load data file: {:#}", e);
+                        window.push_notification(
+                            (
+                                NotificationType::Error,
+                                SharedString::from(format!("{:#}", e)),
+                            ),
+                            cx,
+                        );
+                    }
+                })
+                .ok();
+            Some(())
+        })
+        .detach();
+    }
+
+    fn stop_run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        // Dropping the task aborts the run, including the request in flight.
+        self.run_task.take();
+        self.is_running = false;
+        self.current = None;
+
+        window.push_notification((NotificationType::Info, "Run stopped"), cx);
+        cx.notify();
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let muted = cx.theme().muted_foreground;
+        let label = |text: &'static str| div().text_sm().text_color(muted).child(text);
+        let label_owned = |text: String| div().text_sm().text_color(muted).child(text);
+
+        h_flex()
+            .gap_3()
+            .p_3()
+            .items_center()
+            .flex_wrap()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .min_w(px(160.))
+                    .child(Select::new(&self.environment_select)),
+            )
+            .child(match &self.data_file {
+                Some((path, data)) => h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(Icon::new(IconName::Sheet).text_color(muted))
+                    .child(
+                        div().text_sm().child(
+                            path.file_name()
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_default(),
+                        ),
+                    )
+                    .child(label_owned(format!(
+                        "{} iteration{}",
+                        data.len(),
+                        if data.len() == 1 { "" } else { "s" }
+                    )))
+                    .child(
+                        Button::new("clear-data-file")
+                            .ghost()
+                            .xsmall()
+                            .icon(IconName::Close)
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.data_file = None;
+                                cx.notify();
+                            })),
+                    ),
+                None => {
+                    h_flex()
+                        .gap_2()
+                        .items_center()
+                        .child(label("Iterations"))
+                        .child(
+                            div()
+                                .w(px(64.))
+                                .child(Input::new(&self.iterations_input).small()),
+                        )
+                        .child(
+                            Button::new("browse-data-file")
+                                .outline()
+                                .small()
+                                .icon(IconName::Sheet)
+                                .label("Data file")
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.browse_data_file(window, cx)
+                                })),
+                        )
+                }
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .items_center()
+                    .child(label("Delay (ms)"))
+                    .child(
+                        div()
+                            .w(px(80.))
+                            .child(Input::new(&self.delay_input).small()),
+                    ),
+            )
+            .child(
+                Switch::new("stop-on-failure")
+                    .small()
+                    .label("Stop on failure")
+                    .checked(self.stop_on_failure)
+                    .on_click(cx.listener(|this, checked, _window, cx| {
+                        this.stop_on_failure = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(div().flex_1())
+            .child(if self.is_running {
+                Button::new("stop-run")
+                    .danger()
+                    .compact()
+                    .icon(IconName::Close)
+                    .label("Stop")
+                    .on_click(cx.listener(|this, _, window, cx| this.stop_run(window, cx)))
+            } else {
+                Button::new("start-run")
+                    .primary()
+                    .compact()
+                    .icon(IconName::Send)
+                    .label("Run")
+                    .on_click(cx.listener(|this, _, window, cx| this.start_run(window, cx)))
+            })
+    }
+
+    fn render_summary(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let failed = self.results.iter().filter(|r| !r.passed()).count();
+        let passed = self.results.len() - failed;
+        let tests: usize = self.results.iter().map(|r| r.tests.len()).sum();
+        let failed_tests: usize = self
+            .results
+            .iter()
+            .map(RequestRunResult::failed_tests)
+            .sum();
+
+        h_flex()
+            .px_3()
+            .py_2()
+            .gap_3()
+            .text_sm()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(div().text_color(cx.theme().muted_foreground).child(format!(
+                "{}/{} requests",
+                self.results.len(),
+                self.total
+            )))
+            .child(
+                div()
+                    .text_color(cx.theme().green)
+                    .child(format!("{} passed", passed)),
+            )
+            .when(failed > 0, |this| {
+                this.child(
+                    div()
+                        .text_color(cx.theme().red)
+                        .child(format!("{} failed", failed)),
+                )
+            })
+            .when(tests > 0, |this| {
+                this.child(div().text_color(cx.theme().muted_foreground).child(format!(
+                    "Tests: {}/{} passed",
+                    tests - failed_tests,
+                    tests
+                )))
+            })
+    }
+
+    fn render_result(&self, result: &RequestRunResult, cx: &mut Context<Self>) -> impl IntoElement {
+        let (icon, color) = if result.passed() {
+            (IconName::CircleCheck, cx.theme().green)
+        } else {
+            (IconName::CircleX, cx.theme().red)
+        };
+        let name = match &result.group {
+            Some(group) => format!("{}/{}", group, result.name),
+            None => result.name.clone(),
+        };
+        let status = result.response.as_ref().and_then(|r| r.status_code);
+        let status_color = status
+            .map(|code| match code {
+                100..=199 => cx.theme().blue,
+                200..=299 => cx.theme().green,
+                300..=399 => cx.theme().blue,
+                400..=499 => cx.theme().yellow,
+                500..=599 => cx.theme().red,
+                _ => cx.theme().muted_foreground,
+            })
+            .unwrap_or(cx.theme().muted_foreground);
+        let latency = result
+            .response
+            .as_ref()
+            .and_then(|r| r.latency)
+            .map(|l| format!("{}ms", l.as_millis()))
+            .unwrap_or_default();
+        let mono = cx.theme().mono_font_family.clone();
+
+        v_flex()
+            .px_3()
+            .py_2()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .gap_3()
+                    .items_center()
+                    .text_sm()
+                    .child(div().text_color(color).child(Icon::new(icon)))
+                    .when_some(result.iteration, |this, iteration| {
+                        this.child(
+                            div()
+                                .w(px(32.))
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!("#{}", iteration)),
+                        )
+                    })
+                    .child(
+                        div()
+                            .w(px(64.))
+                            .font_family(mono.clone())
+                            .font_bold()
+                            .child(result.method.clone()),
+                    )
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(div().text_ellipsis().child(name))
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_ellipsis()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(result.url.clone()),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .w(px(48.))
+                            .font_family(mono.clone())
+                            .text_color(status_color)
+                            .child(status.map(|s| s.to_string()).unwrap_or_default()),
+                    )
+                    .child(
+                        div()
+                            .w(px(72.))
+                            .font_family(mono.clone())
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(latency),
+                    )
+                    .child(
+                        div()
+                            .w(px(64.))
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .when(!result.tests.is_empty(), |this| {
+                                this.child(format!(
+                                    "{}/{} tests",
+                                    result.tests.len() - result.failed_tests(),
+                                    result.tests.len()
+                                ))
+                            }),
+                    ),
+            )
+            .when_some(result.error.clone(), |this, error| {
+                this.child(
+                    div()
+                        .pl_6()
+                        .font_family(mono.clone())
+                        .text_xs()
+                        .text_color(cx.theme().red)
+                        .child(error),
+                )
+            })
+            .children(result.tests.iter().filter(|t| !t.passed()).map(|test| {
+                div()
+                    .pl_6()
+                    .font_family(mono.clone())
+                    .text_xs()
+                    .text_color(cx.theme().red)
+                    .child(format!(
+                        "✗ {}: {}",
+                        test.name,
+                        test.error.as_deref().unwrap_or("failed")
+                    ))
+            }))
+    }
+}
+
+impl Focusable for RunnerView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for RunnerView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let body = if self.results.is_empty() && self.current.is_none() {
+            div()
+                .flex_1()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("Choose an environment and press Run to send every request in order.")
+                .into_any_element()
+        } else {
+            v_flex()
+                .flex_1()
+                .min_h_0()
+                .child(self.render_summary(cx))
+                .child(
+                    v_flex()
+                        .flex_1()
+                        .overflow_y_scrollbar()
+                        .children(
+                            self.results
+                                .iter()
+                                .map(|result| self.render_result(result, cx).into_any_element())
+                                .collect::<Vec<_>>(),
+                        )
+                        .when_some(self.current.clone(), |this, current| {
+                            this.child(
+                                h_flex()
+                                    .px_3()
+                                    .py_2()
+                                    .gap_3()
+                                    .items_center()
+                                    .text_sm()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(Icon::new(IconName::LoaderCircle))
+                                    .child(current),
+                            )
+                        }),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(self.render_toolbar(cx))
+            .child(body)
+    }
+}
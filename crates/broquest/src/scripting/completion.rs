@@ -14,6 +14,31 @@ pub enum ScriptContext {
     PreRequest,
     /// Post-response script context (has access to `res` and `bro`)
     PostResponse,
+    /// Event script context (has access to `event` and `bro`)
+    Event,
+}
+
+/// Completion item that replaces the text from `start` to `end` with its
+/// label
+pub(crate) fn completion_item(
+    label: &str,
+    kind: CompletionItemKind,
+    detail: impl Into<String>,
+    documentation: Option<String>,
+    start: lsp_types::Position,
+    end: lsp_types::Position,
+) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail: Some(detail.into()),
+        documentation: documentation.map(lsp_types::Documentation::String),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range: lsp_types::Range { start, end },
+            new_text: label.to_string(),
+        })),
+        ..Default::default()
+    }
 }
 
 /// Completion provider for script editors
@@ -220,6 +245,24 @@ impl ScriptCompletionProvider {
                 })),
                 ..Default::default()
             },
+            CompletionItem {
+                label: "timings".to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some("object".to_string()),
+                documentation: Some(lsp_types::Documentation::String(
+                    "Phase timings in milliseconds: prepare, redirect, dns, connect, ttfb, \
+                     download and total"
+                        .to_string(),
+                )),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: lsp_types::Range {
+                        start: start_pos,
+                        end: end_pos,
+                    },
+                    new_text: "timings".to_string(),
+                })),
+                ..Default::default()
+            },
             CompletionItem {
                 label: "size".to_string(),
                 kind: Some(CompletionItemKind::PROPERTY),
@@ -255,7 +298,50 @@ impl ScriptCompletionProvider {
         ]
     }
 
-    /// Get completion items for the `bro` object (both contexts)
+    /// Get completion items for the `event` object (event scripts only)
+    fn event_completions(
+        &self,
+        start_pos: lsp_types::Position,
+        end_pos: lsp_types::Position,
+    ) -> Vec<CompletionItem> {
+        [
+            (
+                "id",
+                "string | undefined",
+                "Event id, or the last one sent before",
+            ),
+            ("event", "string", "Event type, `message` when not given"),
+            (
+                "data",
+                "string",
+                "Event data, data lines joined by newlines",
+            ),
+            (
+                "retry",
+                "number | undefined",
+                "Reconnection time the server asked for, in milliseconds",
+            ),
+            (
+                "receivedAfter",
+                "number",
+                "Milliseconds since the response headers arrived",
+            ),
+        ]
+        .into_iter()
+        .map(|(label, detail, documentation)| {
+            completion_item(
+                label,
+                CompletionItemKind::PROPERTY,
+                detail,
+                Some(documentation.to_string()),
+                start_pos,
+                end_pos,
+            )
+        })
+        .collect()
+    }
+
+    /// Get completion items for the `bro` object (all contexts)
     fn bro_completions(
         &self,
         start_pos: lsp_types::Position,
@@ -294,6 +380,56 @@ impl ScriptCompletionProvider {
                 })),
                 ..Default::default()
             },
+            CompletionItem {
+                label: "sendRequest".to_string(),
+                kind: Some(CompletionItemKind::METHOD),
+                detail: Some(
+                    "(options: { method, url, headers, body }) => Promise<response>".to_string(),
+                ),
+                documentation: Some(lsp_types::Documentation::String(
+                    "Sends an HTTP request; await it for the response".to_string(),
+                )),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: lsp_types::Range {
+                        start: start_pos,
+                        end: end_pos,
+                    },
+                    new_text: "sendRequest()".to_string(),
+                })),
+                ..Default::default()
+            },
+            CompletionItem {
+                label: "runRequest".to_string(),
+                kind: Some(CompletionItemKind::METHOD),
+                detail: Some("(name: string) => Promise<response>".to_string()),
+                documentation: Some(lsp_types::Documentation::String(
+                    "Runs a saved request of the collection by \"group/name\"".to_string(),
+                )),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: lsp_types::Range {
+                        start: start_pos,
+                        end: end_pos,
+                    },
+                    new_text: "runRequest()".to_string(),
+                })),
+                ..Default::default()
+            },
+            CompletionItem {
+                label: "cookies".to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some("{ get, getAll, set, clear }".to_string()),
+                documentation: Some(lsp_types::Documentation::String(
+                    "Cookie jar of the collection and environment".to_string(),
+                )),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range: lsp_types::Range {
+                        start: start_pos,
+                        end: end_pos,
+                    },
+                    new_text: "cookies".to_string(),
+                })),
+                ..Default::default()
+            },
         ]
     }
 }
@@ -331,6 +467,8 @@ impl CompletionProvider for ScriptCompletionProvider {
             self.req_completions(start_pos, end_pos)
         } else if obj_name == "res" && self.context == ScriptContext::PostResponse {
             self.res_completions(start_pos, end_pos)
+        } else if obj_name == "event" && self.context == ScriptContext::Event {
+            self.event_completions(start_pos, end_pos)
         } else if obj_name == "bro" {
             self.bro_completions(start_pos, end_pos)
         } else {
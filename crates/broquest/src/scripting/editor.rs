@@ -27,6 +27,9 @@ pub enum ScriptEditorEvent {
 pub struct ScriptEditor {
     pre_request_input: Entity<InputState>,
     post_response_input: Entity<InputState>,
+    event_input: Entity<InputState>,
+    /// Folders have no event stream, so their editor hides the event section
+    show_event_script: bool,
     _subscriptions: Vec<Subscription>,
     _lint_task: Task<()>,
 }
@@ -59,6 +62,17 @@ impl ScriptEditor {
             editor
         });
 
+        let event_input = cx.new(|cx| {
+            let mut editor = InputState::new(window, cx)
+                .code_editor("javascript")
+                .folding(editor_settings.folding)
+                .show_whitespaces(editor_settings.show_whitespace)
+                .soft_wrap(editor_settings.soft_wrap);
+            editor.lsp.completion_provider =
+                Some(ScriptCompletionProvider::new(ScriptContext::Event));
+            editor
+        });
+
         // Set up subscriptions for script input change events
         let pre_subscription = cx.subscribe_in(&pre_request_input, window, {
             move |this: &mut Self,
@@ -90,14 +104,37 @@ impl ScriptEditor {
             }
         });
 
+        let event_subscription = cx.subscribe_in(&event_input, window, {
+            move |this: &mut Self,
+                  input_state: &Entity<InputState>,
+                  event: &InputEvent,
+                  window,
+                  cx| {
+                if let InputEvent::Change = event
+                    && input_state.read(cx).focus_handle(cx).is_focused(window)
+                {
+                    this.lint_script(input_state.clone(), ScriptContext::Event, cx);
+                    cx.emit(ScriptEditorEvent::ScriptChanged);
+                }
+            }
+        });
+
         Self {
             pre_request_input,
             post_response_input,
-            _subscriptions: vec![pre_subscription, post_subscription],
+            event_input,
+            show_event_script: true,
+            _subscriptions: vec![pre_subscription, post_subscription, event_subscription],
             _lint_task: Task::ready(()),
         }
     }
 
+    /// Only show the pre-request and post-response sections
+    pub fn without_event_script(mut self) -> Self {
+        self.show_event_script = false;
+        self
+    }
+
     fn lint_script(
         &mut self,
         input: Entity<InputState>,
@@ -149,7 +186,11 @@ impl ScriptEditor {
 
     pub fn apply_editor_settings(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let settings = AppSettings::global(cx).settings.editor.clone();
-        for input in [&self.pre_request_input, &self.post_response_input] {
+        for input in [
+            &self.pre_request_input,
+            &self.post_response_input,
+            &self.event_input,
+        ] {
             input.update(cx, |state, cx| {
                 state.set_show_whitespaces(settings.show_whitespace, window, cx);
                 state.set_soft_wrap(settings.soft_wrap, window, cx);
@@ -162,6 +203,7 @@ impl ScriptEditor {
         &mut self,
         pre_request_script: Option<&str>,
         post_response_script: Option<&str>,
+        event_script: Option<&str>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
@@ -180,6 +222,14 @@ impl ScriptEditor {
                 input.set_value(&script, window, cx);
             });
         }
+
+        // Set event script
+        if let Some(script) = event_script {
+            let script = script.to_string();
+            self.event_input.update(cx, |input, cx| {
+                input.set_value(&script, window, cx);
+            });
+        }
     }
 
     pub fn get_pre_request_script(&self, cx: &App) -> Option<String> {
@@ -200,6 +250,15 @@ impl ScriptEditor {
         }
     }
 
+    pub fn get_event_script(&self, cx: &App) -> Option<String> {
+        let script = self.event_input.read(cx).value();
+        if script.trim().is_empty() {
+            None
+        } else {
+            Some(script.to_string())
+        }
+    }
+
     fn render_script_section(
         &self,
         title: &str,
@@ -210,6 +269,7 @@ impl ScriptEditor {
         let button_id = match title {
             "Pre-request Script" => "clear-pre-request-script",
             "Post-response Script" => "clear-post-response-script",
+            "Event Script" => "clear-event-script",
             _ => "clear-script",
         };
 
@@ -271,5 +331,9 @@ impl Render for ScriptEditor {
                 &self.post_response_input,
                 cx,
             ))
+            .when(self.show_event_script, |this| {
+                this.child(div().h_px().bg(cx.theme().border))
+                    .child(self.render_script_section("Event Script", &self.event_input, cx))
+            })
     }
 }
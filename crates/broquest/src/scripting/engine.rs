@@ -1,8 +1,12 @@
 use super::completion::ScriptContext;
+use super::script_request::{RequestQueue, ScriptRequest, ScriptRequestHandler};
+use super::test_result::{ScriptFailure, TestResult};
 use super::variable_store::VariableStore;
-use crate::domain::{KeyValuePair, RequestData, ResponseData};
+use crate::cookies::{Cookie, CookieJar};
+use crate::domain::{KeyValuePair, RequestData, ResponseData, ServerEvent};
 use anyhow::Result;
 use rquickjs::{Context, Ctx, Error, Function, Object, Runtime};
+use std::time::Duration;
 use tracing::{debug, error};
 
 // LLRT modules for buffer, crypto, and URL support
@@ -78,17 +82,97 @@ const BRUNO_COMPAT_PRELUDE: &str = r#"
     };
     bro.hasRequestVar = function (n) { return bro.__hasRequestVar(n); };
 
-    // Folder vars are not modeled by broquest yet. No-op stubs so scripts don't throw.
-    bro.getFolderVar = function (_n) { return undefined; };
-    bro.hasFolderVar = function (_n) { return false; };
+    // Variables of the request's folder (read-only, declared on the group).
+    bro.getFolderVar = function (n) {
+      var s = bro.__getFolderVar(n);
+      return s === undefined ? undefined : JSON.parse(s);
+    };
+    bro.hasFolderVar = function (n) { return bro.__hasFolderVar(n); };
+
+    // Current row of a data-driven run; empty outside of one.
+    bro.iterationData = JSON.parse(bro.__getIterationData());
+
+    // Requests from scripts. The host performs queued requests once the
+    // script yields and settles each promise through __settleRequest.
+    var pendingRequests = {};
+    function queueRequest(spec, callback) {
+      var promise = new Promise(function (resolve, reject) {
+        pendingRequests[bro.__queueRequest(JSON.stringify(spec))] = { resolve: resolve, reject: reject };
+      });
+      if (typeof callback === 'function') {
+        promise.then(function (r) { callback(null, r); }, function (e) { callback(e, null); });
+      }
+      return promise;
+    }
+    G.__settleRequest = function (id, ok, payload) {
+      var pending = pendingRequests[id];
+      if (!pending) return;
+      delete pendingRequests[id];
+      if (!ok) { pending.reject(new Error(payload)); return; }
+      var r = JSON.parse(payload);
+      var ct = findKey(r.headers, 'content-type');
+      if (ct && /json/i.test(r.headers[ct])) {
+        try { r.body = JSON.parse(r.body); } catch (e) { /* leave as text */ }
+      }
+      r.data = r.body;
+      pending.resolve(r);
+    };
+
+    // bro.sendRequest({ method, url, headers, body }) -> Promise<response>.
+    // `data` is accepted for `body` (axios-style); objects are sent as JSON.
+    bro.sendRequest = function (opts, callback) {
+      opts = opts || {};
+      var headers = {};
+      for (var h in (opts.headers || {})) headers[h] = String(opts.headers[h]);
+      var body = opts.body !== undefined ? opts.body : opts.data;
+      if (body !== undefined && body !== null && typeof body !== 'string') {
+        if (!findKey(headers, 'content-type')) headers['Content-Type'] = 'application/json';
+        body = JSON.stringify(body);
+      }
+      return queueRequest({
+        kind: 'send',
+        method: String(opts.method || 'GET').toUpperCase(),
+        url: String(opts.url || ''),
+        headers: headers,
+        body: (body === undefined || body === null) ? null : body
+      }, callback);
+    };
+
+    // bro.runRequest('group/name') runs a saved request of the collection.
+    bro.runRequest = function (name, callback) {
+      return queueRequest({ kind: 'run', name: String(name) }, callback);
+    };
+
+    // Cookie jar of the collection and environment. The URL defaults to the
+    // request's; `expires` takes a Date or anything `new Date()` accepts.
+    function cookieUrl(url) {
+      return String(url || (typeof req !== 'undefined' && req.url) || '');
+    }
+    bro.cookies = {
+      get: function (name, url) { return bro.__cookieGet(cookieUrl(url), String(name)); },
+      getAll: function (url) { return JSON.parse(bro.__cookieGetAll(cookieUrl(url))); },
+      set: function (name, value, opts) {
+        opts = opts || {};
+        var attrs = {
+          domain: opts.domain, path: opts.path, maxAge: opts.maxAge,
+          secure: opts.secure, httpOnly: opts.httpOnly
+        };
+        if (opts.expires !== undefined) attrs.expires = Math.floor(new Date(opts.expires).getTime() / 1000);
+        bro.__cookieSet(cookieUrl(opts.url), String(name), String(value), JSON.stringify(attrs));
+      },
+      // Clears the cookies of `url`'s host, or the whole jar without a URL.
+      clear: function (url) { bro.__cookieClear(url ? String(url) : ''); }
+    };
 
     // Simple {{var}} interpolation (dynamic {{$...}} vars are left untouched).
-    // Precedence: runtime > request > collection > environment (matches Bruno).
+    // Precedence: iteration data > runtime > request > folder > collection > environment.
     bro.interpolate = function (str) {
       return String(str).replace(/\{\{\s*([^}]+?)\s*\}\}/g, function (m, name) {
         if (name[0] === '$') return m;
-        var v = bro.getVar(name);
+        var v = Object.prototype.hasOwnProperty.call(bro.iterationData, name) ? bro.iterationData[name] : undefined;
+        if (v === undefined || v === null) v = bro.getVar(name);
         if (v === undefined || v === null) v = bro.getRequestVar(name);
+        if (v === undefined || v === null) v = bro.getFolderVar(name);
         if (v === undefined || v === null) v = bro.getCollectionVar(name);
         if (v === undefined || v === null) v = bro.getEnvVar(name);
         return (v === undefined || v === null) ? m : v;
@@ -100,10 +184,14 @@ const BRUNO_COMPAT_PRELUDE: &str = r#"
 
   // Minimal test() + chai-style expect().
   G.__testResults = G.__testResults || [];
+  function now() {
+    return (typeof performance !== 'undefined' && performance.now) ? performance.now() : Date.now();
+  }
   G.test = function (name, fn) {
-    try { fn(); G.__testResults.push({ name: name, status: 'pass' }); }
+    var start = now();
+    try { fn(); G.__testResults.push({ name: String(name), status: 'pass', duration: now() - start }); }
     catch (e) {
-      G.__testResults.push({ name: name, status: 'fail', error: String(e && e.message || e) });
+      G.__testResults.push({ name: String(name), status: 'fail', error: String(e && e.message || e), duration: now() - start });
       if (typeof console !== 'undefined') console.error('Test failed: ' + name + ' - ' + (e && e.message || e));
     }
   };
@@ -157,6 +245,83 @@ const BRUNO_COMPAT_PRELUDE: &str = r#"
 })();
 "#;
 
+/// Opening of the async function user scripts are wrapped in, kept on the
+/// script's first line so reported line numbers stay accurate.
+const ASYNC_WRAPPER_PREFIX: &str = "(async function () {";
+
+/// Tells whether a script only compiles with `await` allowed. Constructing
+/// a function compiles its body without running it.
+const NEEDS_ASYNC_CHECK: &str = r#"
+(function (source) {
+  try { new Function(source); return false; } catch (e) {}
+  var AsyncFunction = Object.getPrototypeOf(async function () {}).constructor;
+  try { new AsyncFunction(source); return true; } catch (e) { return false; }
+})
+"#;
+
+/// Whether a script is run wrapped in an async function: only scripts that
+/// `await` at the top level are, as the parser tells. Others run at global
+/// scope, so their top-level declarations stay globals.
+fn needs_async(ctx: &Ctx, script: &str) -> bool {
+    ctx.eval::<Function, _>(NEEDS_ASYNC_CHECK)
+        .and_then(|check| check.call::<_, bool>((script,)))
+        .unwrap_or(false)
+}
+
+/// The source evaluated for a user script. Scripts that `await` are wrapped
+/// in an async function so they can `await` `bro.sendRequest`/`bro.runRequest`,
+/// as Bruno does; an uncaught error is then parked in `__scriptError` for the
+/// engine to report.
+fn script_source(script: &str, wrapped: bool) -> String {
+    if !wrapped {
+        return script.to_string();
+    }
+    format!(
+        "{}{}\n}})().catch(function (e) {{ globalThis.__scriptError = e; }});",
+        ASYNC_WRAPPER_PREFIX, script
+    )
+}
+
+/// Attributes scripts can pass to `bro.cookies.set`
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptCookieOptions {
+    domain: Option<String>,
+    path: Option<String>,
+    /// Unix timestamp (seconds).
+    expires: Option<i64>,
+    max_age: Option<i64>,
+    secure: Option<bool>,
+    http_only: Option<bool>,
+}
+
+/// Build the cookie `bro.cookies.set` stores: a host-only session cookie for
+/// `url` unless the options say otherwise
+fn script_cookie(url: &str, name: &str, value: &str, options: &str) -> Option<Cookie> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let options: ScriptCookieOptions = serde_json::from_str(options).unwrap_or_default();
+    let mut cookie = Cookie::new(&url, name, value)?;
+
+    if let Some(domain) = options.domain {
+        let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+        if !domain.is_empty() {
+            cookie.domain = domain;
+            cookie.host_only = false;
+        }
+    }
+    if let Some(path) = options.path.filter(|p| p.starts_with('/')) {
+        cookie.path = path;
+    }
+    cookie.expires = match options.max_age {
+        Some(seconds) => Some(crate::http::current_unix_timestamp() + seconds),
+        None => options.expires,
+    };
+    cookie.secure = options.secure.unwrap_or_default();
+    cookie.http_only = options.http_only.unwrap_or_default();
+
+    Some(cookie)
+}
+
 /// Represents a syntax error found in a script
 #[derive(Debug, Clone)]
 pub struct ScriptDiagnostic {
@@ -179,81 +344,197 @@ impl ScriptExecutionService {
         })
     }
 
-    /// Execute a pre-request script
-    pub fn execute_pre_request_script(
+    /// Execute a pre-request script. Requests it makes through
+    /// `bro.sendRequest`/`bro.runRequest` are performed by `requests`.
+    pub async fn execute_pre_request_script(
         &self,
         script: &str,
         request: &mut RequestData,
         variable_store: &VariableStore,
+        requests: &dyn ScriptRequestHandler,
     ) -> Result<()> {
         if script.trim().is_empty() {
             return Ok(());
         }
 
         let ctx = Context::full(&self.runtime)?;
+        let queue = RequestQueue::default();
+        let cookie_jar = requests.cookie_jar().cloned().unwrap_or_default();
         ctx.with(|ctx| {
             // Setup global objects
             self.setup_request_object(ctx.clone(), request)?;
-            self.setup_bro_object(ctx.clone(), variable_store)?;
+            self.setup_bro_object(ctx.clone(), variable_store, &queue, &cookie_jar)?;
             self.setup_nodejs_compatibility(ctx.clone())?;
             self.setup_bruno_compat(ctx.clone())?;
 
-            // Execute the script with better error handling
-            if let Err(e) = ctx.eval::<(), _>(script) {
-                // Try to catch the actual JavaScript error
-                let js_error = ctx.catch();
-                let detailed_error = format!("JavaScript Exception: {:?}", js_error);
-                error!("Caught JavaScript error: {}", detailed_error);
-
-                let error_msg = format!("Pre-request script failed: {} - {}", e, detailed_error);
-                error!("Script execution error: {:?}", e); // Debug format for more details
-                error!("Script content that failed: {}", script);
-                return Err(anyhow::anyhow!(error_msg));
-            }
+            self.eval_script(ctx, script, "Pre-request")
+        })?;
 
-            // Extract modifications from request object
-            self.extract_request_modifications(ctx, request)?;
+        if let Err(e) = self.drive_requests(&ctx, &queue, requests).await {
+            error!("Script content that failed: {}", script);
+            return Err(anyhow::anyhow!("Pre-request script failed: {}", e));
+        }
 
-            Ok(())
-        })
+        // Extract modifications from request object
+        ctx.with(|ctx| self.extract_request_modifications(ctx, request))
     }
 
-    /// Execute a post-response script
-    pub fn execute_post_response_script(
+    /// Execute a post-response script, returning the `test()` outcomes it
+    /// recorded. If the script throws, the outcomes recorded until then come
+    /// with the error.
+    pub async fn execute_post_response_script(
         &self,
         script: &str,
         request: &RequestData,
         response: &ResponseData,
         variable_store: &VariableStore,
-    ) -> Result<()> {
+        requests: &dyn ScriptRequestHandler,
+    ) -> std::result::Result<Vec<TestResult>, ScriptFailure> {
         if script.trim().is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let ctx = Context::full(&self.runtime)?;
+        let queue = RequestQueue::default();
+        let cookie_jar = requests.cookie_jar().cloned().unwrap_or_default();
         ctx.with(|ctx| {
             // Setup global objects
             self.setup_request_object(ctx.clone(), request)?;
             self.setup_response_object(ctx.clone(), response)?;
-            self.setup_bro_object(ctx.clone(), variable_store)?;
+            self.setup_bro_object(ctx.clone(), variable_store, &queue, &cookie_jar)?;
             self.setup_nodejs_compatibility(ctx.clone())?;
-            self.setup_bruno_compat(ctx.clone())?;
+            self.setup_bruno_compat(ctx.clone())
+        })?;
 
-            // Execute the script with better error handling
-            if let Err(e) = ctx.eval::<(), _>(script) {
-                // Try to catch the actual JavaScript error
-                let js_error = ctx.catch();
-                let detailed_error = format!("JavaScript Exception: {:?}", js_error);
-                error!("Caught JavaScript error: {}", detailed_error);
-
-                let error_msg = format!("Post-response script failed: {} - {}", e, detailed_error);
-                error!("Script execution error: {:?}", e); // Debug format for more details
-                error!("Script content that failed: {}", script);
-                return Err(anyhow::anyhow!(error_msg));
+        let mut outcome = ctx.with(|ctx| self.eval_script(ctx, script, "Post-response"));
+        if outcome.is_ok()
+            && let Err(e) = self.drive_requests(&ctx, &queue, requests).await
+        {
+            error!("Script content that failed: {}", script);
+            outcome = Err(anyhow::anyhow!("Post-response script failed: {}", e));
+        }
+
+        // Tests that ran before the script threw still count
+        let tests = ctx.with(|ctx| self.extract_test_results(ctx))?;
+        match outcome {
+            Ok(()) => Ok(tests),
+            Err(error) => Err(ScriptFailure { error, tests }),
+        }
+    }
+
+    /// Execute an event script for one server-sent event, returning the
+    /// `test()` outcomes it recorded
+    pub async fn execute_event_script(
+        &self,
+        script: &str,
+        request: &RequestData,
+        event: &ServerEvent,
+        variable_store: &VariableStore,
+        requests: &dyn ScriptRequestHandler,
+    ) -> std::result::Result<Vec<TestResult>, ScriptFailure> {
+        if script.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ctx = Context::full(&self.runtime)?;
+        let queue = RequestQueue::default();
+        let cookie_jar = requests.cookie_jar().cloned().unwrap_or_default();
+        ctx.with(|ctx| {
+            // Setup global objects
+            self.setup_request_object(ctx.clone(), request)?;
+            self.setup_event_object(ctx.clone(), event)?;
+            self.setup_bro_object(ctx.clone(), variable_store, &queue, &cookie_jar)?;
+            self.setup_nodejs_compatibility(ctx.clone())?;
+            self.setup_bruno_compat(ctx.clone())
+        })?;
+
+        let mut outcome = ctx.with(|ctx| self.eval_script(ctx, script, "Event"));
+        if outcome.is_ok()
+            && let Err(e) = self.drive_requests(&ctx, &queue, requests).await
+        {
+            error!("Script content that failed: {}", script);
+            outcome = Err(anyhow::anyhow!("Event script failed: {}", e));
+        }
+
+        // Tests that ran before the script threw still count
+        let tests = ctx.with(|ctx| self.extract_test_results(ctx))?;
+        match outcome {
+            Ok(()) => Ok(tests),
+            Err(error) => Err(ScriptFailure { error, tests }),
+        }
+    }
+
+    /// Start a user script (see [`script_source`]). For scripts wrapped in an
+    /// async function only errors raised while compiling surface here; errors
+    /// thrown while running are picked up by [`Self::drive_requests`].
+    fn eval_script(&self, ctx: Ctx, script: &str, kind: &str) -> Result<()> {
+        let wrapped = needs_async(&ctx, script);
+        if let Err(e) = ctx.eval::<(), _>(script_source(script, wrapped)) {
+            // Try to catch the actual JavaScript error
+            let js_error = ctx.catch();
+            let detailed_error = format!("JavaScript Exception: {:?}", js_error);
+            error!("Caught JavaScript error: {}", detailed_error);
+
+            let error_msg = format!("{} script failed: {} - {}", kind, e, detailed_error);
+            error!("Script execution error: {:?}", e); // Debug format for more details
+            error!("Script content that failed: {}", script);
+            return Err(anyhow::anyhow!(error_msg));
+        }
+        Ok(())
+    }
+
+    /// Run the script to completion: execute pending jobs, then perform the
+    /// requests it queued and settle their promises, until it neither has
+    /// work left nor waits on a request. Fails with the script's uncaught
+    /// error, if any.
+    async fn drive_requests(
+        &self,
+        ctx: &Context,
+        queue: &RequestQueue,
+        requests: &dyn ScriptRequestHandler,
+    ) -> Result<()> {
+        loop {
+            while self
+                .runtime
+                .execute_pending_job()
+                .map_err(|_| anyhow::anyhow!("Failed to run a pending script job"))?
+            {}
+
+            let queued = queue.take();
+            if queued.is_empty() {
+                break;
             }
 
-            Ok(())
-        })
+            for (id, request_json) in queued {
+                let outcome = match serde_json::from_str::<ScriptRequest>(&request_json) {
+                    Ok(request) => requests.handle(request).await,
+                    Err(e) => Err(format!("Invalid request: {}", e)),
+                };
+                ctx.with(|ctx| -> Result<()> {
+                    let settle: Function = ctx.globals().get("__settleRequest")?;
+                    match outcome {
+                        Ok(response) => {
+                            settle.call::<_, ()>((id, true, serde_json::to_string(&response)?))?
+                        }
+                        Err(message) => settle.call::<_, ()>((id, false, message))?,
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        let script_error = ctx.with(|ctx| {
+            ctx.eval::<Option<String>, _>(
+                "(function (e) { return e === undefined ? undefined : String(e && e.stack ? e + '\\n' + e.stack : e); })(globalThis.__scriptError)",
+            )
+        })?;
+        match script_error {
+            Some(message) => {
+                error!("Caught JavaScript error: {}", message);
+                Err(anyhow::anyhow!("JavaScript Exception: {}", message))
+            }
+            None => Ok(()),
+        }
     }
 
     /// Setup the request object for JavaScript access
@@ -299,7 +580,12 @@ impl ScriptExecutionService {
             res_obj.set("status", status_code)?;
         }
 
+        // Bodies over the memory limit are only described by `body`; the
+        // file they were saved to is `bodyFile`
         res_obj.set("body", response.body.clone())?;
+        if let Some(file) = &response.body_file {
+            res_obj.set("bodyFile", file.path().to_string_lossy().to_string())?;
+        }
 
         // Convert headers to JavaScript object
         let headers_obj = Object::new(ctx.clone())?;
@@ -326,22 +612,63 @@ impl ScriptExecutionService {
             res_obj.set("url", url.clone())?;
         }
 
+        // Status and trailers of a gRPC call
+        if let Some(grpc) = &response.grpc {
+            res_obj.set("grpcStatus", grpc.code)?;
+            res_obj.set("grpcMessage", grpc.message.clone())?;
+            let trailers_obj = Object::new(ctx.clone())?;
+            for trailer in &grpc.trailers {
+                trailers_obj.set(trailer.key.clone(), trailer.value.clone())?;
+            }
+            res_obj.set("trailers", trailers_obj)?;
+        }
+
+        // Phase timings in milliseconds; dns, connect and tls are 0 when an
+        // open connection was reused, tls also for plain HTTP
+        if let Some(latency) = response.latency {
+            let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+            let timings = &response.timings;
+            let timings_obj = Object::new(ctx.clone())?;
+            timings_obj.set("prepare", ms(timings.prepare))?;
+            timings_obj.set("redirect", ms(timings.redirect))?;
+            timings_obj.set("dns", ms(timings.dns.unwrap_or_default()))?;
+            timings_obj.set("connect", ms(timings.connect.unwrap_or_default()))?;
+            timings_obj.set("tls", ms(timings.tls.unwrap_or_default()))?;
+            timings_obj.set("ttfb", ms(timings.ttfb))?;
+            timings_obj.set("download", ms(timings.download))?;
+            timings_obj.set("total", ms(latency))?;
+            res_obj.set("timings", timings_obj)?;
+        }
+
         // Set the response object as global
         ctx.globals().set("res", res_obj)?;
 
-        // Parse response body as JSON if content-type is application/json
-        if let Some(_content_type_header) = response
-            .headers
-            .iter()
-            .find(|h| h.key.to_lowercase() == "content-type")
-            .filter(|h| h.value.to_lowercase().contains("application/json"))
-        {
+        // Parse response body as JSON if content-type is application/json, or
+        // the decoded messages of a gRPC call
+        let is_json = response.headers.iter().any(|h| {
+            h.key.to_lowercase() == "content-type"
+                && h.value.to_lowercase().contains("application/json")
+        });
+        if is_json || (response.grpc.is_some() && !response.body.is_empty()) {
             ctx.eval::<(), _>("res.body = JSON.parse(res.body);")?;
         }
 
         Ok(())
     }
 
+    /// Setup the event object of an event script for JavaScript access
+    fn setup_event_object(&self, ctx: Ctx, event: &ServerEvent) -> Result<()> {
+        let event_obj = Object::new(ctx.clone())?;
+        event_obj.set("id", event.id.clone())?;
+        event_obj.set("event", event.event.clone())?;
+        event_obj.set("data", event.data.clone())?;
+        event_obj.set("retry", event.retry.map(|retry| retry as f64))?;
+        event_obj.set("receivedAfter", event.received_after.as_secs_f64() * 1000.0)?;
+
+        ctx.globals().set("event", event_obj)?;
+        Ok(())
+    }
+
     /// Setup the `bro` object with variable management functions.
     ///
     /// This exposes the native primitives; the higher-level Bruno-compatible API
@@ -350,7 +677,13 @@ impl ScriptExecutionService {
     ///
     /// The Rust <-> JS boundary is kept string-based (values crossing as JSON
     /// strings) to avoid engine-specific value conversions.
-    fn setup_bro_object<'js>(&self, ctx: Ctx<'js>, variable_store: &VariableStore) -> Result<()> {
+    fn setup_bro_object<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        variable_store: &VariableStore,
+        requests: &RequestQueue,
+        cookie_jar: &CookieJar,
+    ) -> Result<()> {
         let bro_obj = Object::new(ctx.clone())?;
 
         // --- Environment variables ---
@@ -472,6 +805,96 @@ impl ScriptExecutionService {
             })?,
         )?;
 
+        // --- Folder variables (read-only; Bruno bru.getFolderVar) ---
+        let store = variable_store.clone();
+        bro_obj.set(
+            "__getFolderVar",
+            Function::new(ctx.clone(), move |name: String| -> Option<String> {
+                store.get_folder_var(&name).map(|v| v.to_string())
+            })?,
+        )?;
+
+        let store = variable_store.clone();
+        bro_obj.set(
+            "__hasFolderVar",
+            Function::new(ctx.clone(), move |name: String| -> bool {
+                store.has_folder_var(&name)
+            })?,
+        )?;
+
+        // --- Requests (bro.sendRequest/runRequest queue here; see drive_requests) ---
+        let queue = requests.clone();
+        bro_obj.set(
+            "__queueRequest",
+            Function::new(ctx.clone(), move |json: String| -> u32 { queue.push(json) })?,
+        )?;
+
+        // --- Cookies (bro.cookies; the options of __cookieSet cross as JSON) ---
+        let jar = cookie_jar.clone();
+        bro_obj.set(
+            "__cookieGet",
+            Function::new(
+                ctx.clone(),
+                move |url: String, name: String| -> Option<String> {
+                    jar.cookies_for(&url)
+                        .into_iter()
+                        .find(|c| c.name == name)
+                        .map(|c| c.value)
+                },
+            )?,
+        )?;
+
+        let jar = cookie_jar.clone();
+        bro_obj.set(
+            "__cookieGetAll",
+            Function::new(ctx.clone(), move |url: String| -> String {
+                // Most specific path first, so that cookie wins a name clash.
+                let mut cookies = serde_json::Map::new();
+                for cookie in jar.cookies_for(&url) {
+                    cookies
+                        .entry(cookie.name)
+                        .or_insert(serde_json::Value::String(cookie.value));
+                }
+                serde_json::Value::Object(cookies).to_string()
+            })?,
+        )?;
+
+        let jar = cookie_jar.clone();
+        bro_obj.set(
+            "__cookieSet",
+            Function::new(
+                ctx.clone(),
+                move |url: String, name: String, value: String, options: String| {
+                    match script_cookie(&url, &name, &value, &options) {
+                        Some(cookie) => jar.set(cookie),
+                        None => debug!("Ignoring bro.cookies.set for {} at '{}'", name, url),
+                    }
+                },
+            )?,
+        )?;
+
+        let jar = cookie_jar.clone();
+        bro_obj.set(
+            "__cookieClear",
+            Function::new(ctx.clone(), move |url: String| {
+                if url.is_empty() {
+                    jar.clear();
+                } else {
+                    jar.clear_for(&url);
+                }
+            })?,
+        )?;
+
+        // --- Iteration data (read-only; current row of a data-driven run) ---
+        let store = variable_store.clone();
+        bro_obj.set(
+            "__getIterationData",
+            Function::new(ctx.clone(), move || -> String {
+                serde_json::to_string(&store.get_iteration_data())
+                    .unwrap_or_else(|_| "{}".to_string())
+            })?,
+        )?;
+
         ctx.globals().set("bro", bro_obj)?;
 
         Ok(())
@@ -530,6 +953,12 @@ impl ScriptExecutionService {
         Ok(())
     }
 
+    /// Collect the `test()` outcomes the prelude accumulated in `__testResults`
+    fn extract_test_results(&self, ctx: Ctx) -> Result<Vec<TestResult>> {
+        let json: String = ctx.eval("JSON.stringify(globalThis.__testResults || [])")?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
     /// Check script syntax without executing it
     /// Returns Ok(()) if syntax is valid, Err(ScriptDiagnostic) if there's an error
     /// Syntax errors are reported as errors, ReferenceErrors as warnings
@@ -552,7 +981,7 @@ impl ScriptExecutionService {
             is_warning: false,
         })?;
 
-        ctx.with(|ctx| {
+        let wrapped = ctx.with(|ctx| {
             // Initialize LLRT modules for buffer, crypto, url support
             if let Err(e) = buffer::init(&ctx) {
                 error!("Failed to initialize buffer module: {}", e);
@@ -671,6 +1100,19 @@ impl ScriptExecutionService {
                     })?;
             }
 
+            // Set up stub event object only for event scripts
+            if context == ScriptContext::Event {
+                ctx.eval::<(), _>(
+                    "globalThis.event = { id: null, event: 'message', data: '', retry: null, receivedAfter: 0 };",
+                )
+                .map_err(|e| ScriptDiagnostic {
+                    line: 0,
+                    column: 0,
+                    message: format!("Failed to set event global: {}", e),
+                    is_warning: false,
+                })?;
+            }
+
             // Set up stub bro object
             let bro_obj = Object::new(ctx.clone()).map_err(|e| ScriptDiagnostic {
                 line: 0,
@@ -751,6 +1193,7 @@ impl ScriptExecutionService {
                 "hasCollectionVar",
                 "getRequestVar",
                 "getFolderVar",
+                "hasFolderVar",
             ] {
                 let f = if method == "setVar" {
                     stub_setter.clone()
@@ -766,6 +1209,20 @@ impl ScriptExecutionService {
                     is_warning: false,
                 })?;
             }
+            let stub_iteration_data = Object::new(ctx.clone()).map_err(|e| ScriptDiagnostic {
+                line: 0,
+                column: 0,
+                message: format!("Failed to create stub iterationData: {}", e),
+                is_warning: false,
+            })?;
+            bro_obj
+                .set("iterationData", stub_iteration_data)
+                .map_err(|e| ScriptDiagnostic {
+                    line: 0,
+                    column: 0,
+                    message: format!("Failed to set stub iterationData: {}", e),
+                    is_warning: false,
+                })?;
             ctx.globals()
                 .set("bro", bro_obj)
                 .map_err(|e| ScriptDiagnostic {
@@ -775,107 +1232,218 @@ impl ScriptExecutionService {
                     is_warning: false,
                 })?;
 
+            // Stub request helpers with promises that never settle, so code
+            // after an `await` on them is simply not reached, and cookies with
+            // no-ops.
+            ctx.eval::<(), _>(
+                "bro.sendRequest = bro.runRequest = function () { return new Promise(function () {}); };\n\
+                 bro.cookies = { get: function () {}, getAll: function () { return {}; }, set: function () {}, clear: function () {} };",
+            )
+            .map_err(|e| ScriptDiagnostic {
+                line: 0,
+                column: 0,
+                message: format!("Failed to set stub request helpers: {}", e),
+                is_warning: false,
+            })?;
+
             // Try to evaluate the script
-            if let Err(Error::Exception) = ctx.eval::<(), _>(script) {
+            let wrapped = needs_async(&ctx, script);
+            if let Err(Error::Exception) = ctx.eval::<(), _>(script_source(script, wrapped)) {
                 // Catch the exception to get details
                 let exception = ctx.catch();
 
                 // Log the full exception for debugging
                 debug!("Script evaluation error: {:?}", exception);
 
-                // Extract error message and position
-                let (message, line, column, is_warning) =
-                    if let Ok(exc_obj) = rquickjs::Exception::from_value(exception.clone()) {
-                        let msg = exc_obj
-                            .message()
-                            .unwrap_or_else(|| "Unknown error".to_string());
-
-                        // Get error name to distinguish SyntaxError from ReferenceError
-                        let error_name = exc_obj
-                            .get::<_, String>("name")
-                            .ok()
-                            .unwrap_or_else(|| "Unknown".to_string());
-
-                        debug!(
-                            "Error name: {}, message: {}, {:?}",
-                            error_name, msg, exc_obj
-                        );
-
-                        // ReferenceError is a warning (variable might exist at runtime)
-                        // SyntaxError and other errors are actual errors
-                        let is_warning = error_name == "ReferenceError";
-
-                        let mut line: u32 = 0;
-                        let mut col: u32 = 0;
-
-                        if let Some(stack) = exc_obj.stack() {
-                            // Parse stack trace format: "at <eval> (eval_script:1:4)"
-                            // or "at <filename>:<line>:<column>"
-                            for line_part in stack.lines() {
-                                let line_part = line_part.trim();
-                                if line_part.contains("at ") && line_part.contains(":") {
-                                    // Split by ":" and try to extract numbers
-                                    let parts: Vec<&str> = line_part.split(':').collect();
-                                    if parts.len() >= 2 {
-                                        let mut found = false;
-                                        // Try to parse the second-to-last part as line number
-                                        if let Some(line_str) = parts.get(parts.len() - 2) {
-                                            let line_str = line_str.trim();
-                                            if let Ok(parsed_line) = line_str.parse::<u32>()
-                                                && parsed_line > 0
-                                            {
-                                                line = parsed_line.saturating_sub(1);
-                                                found = true;
-                                            }
-                                        }
-                                        // Try to parse the last part as column number
-                                        // Strip non-digit characters (like trailing ")" or "\n")
-                                        if let Some(col_str) = parts.last() {
-                                            let col_str = col_str
-                                                .trim()
-                                                .trim_end_matches(|c: char| !c.is_ascii_digit());
-                                            if let Ok(parsed_col) = col_str.parse::<u32>()
-                                                && parsed_col > 0
-                                            {
-                                                col = parsed_col.saturating_sub(1);
-                                            }
-                                        }
-                                        if found {
-                                            break;
-                                        }
+                return Err(Self::diagnostic_from_exception(exception, wrapped));
+            }
+
+            Ok(wrapped)
+        })?;
+
+        // Errors thrown while running reject the async wrapper rather than
+        // failing the eval; run it to its first suspension and pick them up.
+        while let Ok(true) = runtime.execute_pending_job() {}
+
+        ctx.with(|ctx| {
+            let exception: rquickjs::Value =
+                ctx.globals()
+                    .get("__scriptError")
+                    .map_err(|e| ScriptDiagnostic {
+                        line: 0,
+                        column: 0,
+                        message: format!("Failed to read script error: {}", e),
+                        is_warning: false,
+                    })?;
+            if exception.is_undefined() {
+                return Ok(());
+            }
+
+            debug!("Script evaluation error: {:?}", exception);
+            Err(Self::diagnostic_from_exception(exception, wrapped))
+        })
+    }
+
+    /// Turn a thrown JS value into a diagnostic, with its position taken
+    /// from the stack trace
+    fn diagnostic_from_exception(exception: rquickjs::Value, wrapped: bool) -> ScriptDiagnostic {
+        // Extract error message and position
+        let (message, line, column, is_warning) =
+            if let Ok(exc_obj) = rquickjs::Exception::from_value(exception.clone()) {
+                let msg = exc_obj
+                    .message()
+                    .unwrap_or_else(|| "Unknown error".to_string());
+
+                // Get error name to distinguish SyntaxError from ReferenceError
+                let error_name = exc_obj
+                    .get::<_, String>("name")
+                    .ok()
+                    .unwrap_or_else(|| "Unknown".to_string());
+
+                debug!(
+                    "Error name: {}, message: {}, {:?}",
+                    error_name, msg, exc_obj
+                );
+
+                // ReferenceError is a warning (variable might exist at runtime)
+                // SyntaxError and other errors are actual errors
+                let is_warning = error_name == "ReferenceError";
+
+                let mut line: u32 = 0;
+                let mut col: u32 = 0;
+
+                if let Some(stack) = exc_obj.stack() {
+                    // Parse stack trace format: "at <eval> (eval_script:1:4)"
+                    // or "at <filename>:<line>:<column>"
+                    for line_part in stack.lines() {
+                        let line_part = line_part.trim();
+                        if line_part.contains("at ") && line_part.contains(":") {
+                            // Split by ":" and try to extract numbers
+                            let parts: Vec<&str> = line_part.split(':').collect();
+                            if parts.len() >= 2 {
+                                let mut found = false;
+                                // Try to parse the second-to-last part as line number
+                                if let Some(line_str) = parts.get(parts.len() - 2) {
+                                    let line_str = line_str.trim();
+                                    if let Ok(parsed_line) = line_str.parse::<u32>()
+                                        && parsed_line > 0
+                                    {
+                                        line = parsed_line.saturating_sub(1);
+                                        found = true;
                                     }
                                 }
+                                // Try to parse the last part as column number
+                                // Strip non-digit characters (like trailing ")" or "\n")
+                                if let Some(col_str) = parts.last() {
+                                    let col_str = col_str
+                                        .trim()
+                                        .trim_end_matches(|c: char| !c.is_ascii_digit());
+                                    if let Ok(parsed_col) = col_str.parse::<u32>()
+                                        && parsed_col > 0
+                                    {
+                                        col = parsed_col.saturating_sub(1);
+                                    }
+                                }
+                                if found {
+                                    break;
+                                }
                             }
                         }
+                    }
+                }
 
-                        (msg, line, col, is_warning)
-                    } else {
-                        (format!("{:?}", exception), 0, 0, false)
-                    };
-
-                return Err(ScriptDiagnostic {
-                    line,
-                    column,
-                    message,
-                    is_warning,
-                });
-            }
+                (msg, line, col, is_warning)
+            } else {
+                (format!("{:?}", exception), 0, 0, false)
+            };
 
-            Ok(())
-        })
+        // The async wrapper shares the script's first line.
+        let column = if line == 0 && wrapped {
+            column.saturating_sub(ASYNC_WRAPPER_PREFIX.len() as u32)
+        } else {
+            column
+        };
+
+        ScriptDiagnostic {
+            line,
+            column,
+            message,
+            is_warning,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::ResponseTimings;
+    use crate::scripting::{ScriptRequestFuture, ScriptResponse};
     use serde_json::{Value, json};
     use std::collections::HashMap;
+    use std::sync::Mutex;
 
     fn service() -> ScriptExecutionService {
         ScriptExecutionService::new().expect("create script service")
     }
 
+    /// Fails every script request; these tests stay offline unless they
+    /// pass their own handler.
+    struct NoRequests;
+
+    impl ScriptRequestHandler for NoRequests {
+        fn handle(&self, _request: ScriptRequest) -> ScriptRequestFuture<'_> {
+            Box::pin(async { Err("requests are not available in tests".to_string()) })
+        }
+    }
+
+    fn run_pre(
+        svc: &ScriptExecutionService,
+        script: &str,
+        request: &mut RequestData,
+        store: &VariableStore,
+    ) -> Result<()> {
+        smol::block_on(svc.execute_pre_request_script(script, request, store, &NoRequests))
+    }
+
+    fn run_post(
+        svc: &ScriptExecutionService,
+        script: &str,
+        request: &RequestData,
+        response: &ResponseData,
+        store: &VariableStore,
+    ) -> std::result::Result<Vec<TestResult>, ScriptFailure> {
+        smol::block_on(svc.execute_post_response_script(
+            script,
+            request,
+            response,
+            store,
+            &NoRequests,
+        ))
+    }
+
+    /// Answers every request with a JSON token, recording what was asked.
+    #[derive(Default)]
+    struct TokenRequests {
+        seen: Mutex<Vec<ScriptRequest>>,
+    }
+
+    impl ScriptRequestHandler for TokenRequests {
+        fn handle(&self, request: ScriptRequest) -> ScriptRequestFuture<'_> {
+            self.seen.lock().expect("lock").push(request);
+            Box::pin(async {
+                Ok(ScriptResponse {
+                    status: 200,
+                    headers: HashMap::from([(
+                        "Content-Type".to_string(),
+                        "application/json".to_string(),
+                    )]),
+                    body: r#"{"token":"t1"}"#.to_string(),
+                    ..Default::default()
+                })
+            })
+        }
+    }
+
     fn vars(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
         pairs
             .iter()
@@ -888,9 +1456,9 @@ mod tests {
         let svc = service();
         let mut request = RequestData::default();
         let store = VariableStore::new();
-        svc.execute_pre_request_script("   ", &mut request, &store)
+        run_pre(&svc, "   ", &mut request, &store)
             .expect("empty pre-request script should succeed");
-        svc.execute_post_response_script("", &request, &ResponseData::default(), &store)
+        run_post(&svc, "", &request, &ResponseData::default(), &store)
             .expect("empty post-response script should succeed");
     }
 
@@ -904,7 +1472,8 @@ mod tests {
         };
         let store = VariableStore::new();
 
-        svc.execute_pre_request_script(
+        run_pre(
+            &svc,
             r#"
             req.setUrl('http://new.example/path');
             req.setBody('hello world');
@@ -932,7 +1501,8 @@ mod tests {
         let mut request = RequestData::default();
         let store = VariableStore::new();
 
-        svc.execute_pre_request_script(
+        run_pre(
+            &svc,
             r#"
             bro.setVar('num', 42);
             bru.setVar('str', 'two');
@@ -957,7 +1527,8 @@ mod tests {
         store.set_collection_vars(vars(&[("cv", json!("cval"))]));
         store.set_request_vars(vars(&[("rv", json!("rval"))]));
 
-        svc.execute_pre_request_script(
+        run_pre(
+            &svc,
             r#"
             bro.setVar('gotC', bro.getCollectionVar('cv'));
             bro.setVar('gotR', bru.getRequestVar('rv'));
@@ -975,6 +1546,65 @@ mod tests {
         assert_eq!(store.get_var("missing"), Some(json!(true)));
     }
 
+    #[test]
+    fn folder_vars_sit_between_request_and_collection() {
+        let svc = service();
+        let mut request = RequestData::default();
+        let store = VariableStore::new();
+        store.set_collection_vars(vars(&[
+            ("baseUrl", json!("https://api")),
+            ("v", json!("c")),
+        ]));
+        store.set_folder_vars(vars(&[
+            ("baseUrl", json!("https://users")),
+            ("v", json!("f")),
+        ]));
+        store.set_request_vars(vars(&[("v", json!("r"))]));
+
+        run_pre(
+            &svc,
+            r#"
+            bro.setVar('gotF', bro.getFolderVar('baseUrl'));
+            bro.setVar('hasF', bru.hasFolderVar('baseUrl'));
+            bro.setVar('missing', bro.getFolderVar('nope') === undefined);
+            bro.setVar('interp', bro.interpolate('{{baseUrl}}/{{v}}'));
+            "#,
+            &mut request,
+            &store,
+        )
+        .expect("script should run");
+
+        assert_eq!(store.get_var("gotF"), Some(json!("https://users")));
+        assert_eq!(store.get_var("hasF"), Some(json!(true)));
+        assert_eq!(store.get_var("missing"), Some(json!(true)));
+        assert_eq!(store.get_var("interp"), Some(json!("https://users/r")));
+    }
+
+    #[test]
+    fn iteration_data_is_exposed_and_wins_interpolation() {
+        let svc = service();
+        let mut request = RequestData::default();
+        let store = VariableStore::new();
+        store.set_iteration_data(vars(&[("user", json!("ada")), ("n", json!(3))]));
+        store.set_var("user", json!("runtime"));
+
+        run_pre(
+            &svc,
+            r#"
+            bro.setVar('gotUser', bro.iterationData.user);
+            bro.setVar('gotN', bro.iterationData.n);
+            bro.setVar('interp', bro.interpolate('{{user}}'));
+            "#,
+            &mut request,
+            &store,
+        )
+        .expect("script should run");
+
+        assert_eq!(store.get_var("gotUser"), Some(json!("ada")));
+        assert_eq!(store.get_var("gotN"), Some(json!(3)));
+        assert_eq!(store.get_var("interp"), Some(json!("ada")));
+    }
+
     #[test]
     fn post_response_exposes_res_and_collects_passing_tests() {
         let svc = service();
@@ -991,7 +1621,8 @@ mod tests {
             ..Default::default()
         };
 
-        svc.execute_post_response_script(
+        run_post(
+            &svc,
             r#"
             test('status is 200', function () { expect(res.getStatus()).to.equal(200); });
             test('body parsed as json', function () { expect(res.body.ok).to.equal(true); });
@@ -1008,6 +1639,73 @@ mod tests {
         assert_eq!(store.get_var("failed"), Some(json!(0)));
     }
 
+    #[test]
+    fn post_response_exposes_timings() {
+        let svc = service();
+        let store = VariableStore::new();
+        let response = ResponseData {
+            status_code: Some(200),
+            latency: Some(Duration::from_millis(40)),
+            timings: ResponseTimings {
+                dns: Some(Duration::from_millis(5)),
+                ttfb: Duration::from_millis(30),
+                download: Duration::from_millis(5),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        run_post(
+            &svc,
+            r#"
+            bro.setVar('ttfb', res.timings.ttfb);
+            bro.setVar('connect', res.timings.connect);
+            bro.setVar('tls', res.timings.tls);
+            bro.setVar('total', res.timings.total);
+            "#,
+            &RequestData::default(),
+            &response,
+            &store,
+        )
+        .expect("script should run");
+
+        let ms = |name: &str| store.get_var(name).and_then(|value| value.as_f64());
+        assert_eq!(ms("ttfb"), Some(30.0));
+        assert_eq!(ms("connect"), Some(0.0));
+        assert_eq!(ms("tls"), Some(0.0));
+        assert_eq!(ms("total"), Some(40.0));
+    }
+
+    #[test]
+    fn event_script_sees_each_event() {
+        let svc = service();
+        let store = VariableStore::new();
+        let event = ServerEvent {
+            id: Some("7".to_string()),
+            event: "update".to_string(),
+            data: r#"{"count":3}"#.to_string(),
+            ..Default::default()
+        };
+
+        let results = smol::block_on(svc.execute_event_script(
+            r#"
+            bro.setVar('count', JSON.parse(event.data).count);
+            bro.setVar('id', event.id);
+            test('is an update', function () { expect(event.event).to.equal('update'); });
+            "#,
+            &RequestData::default(),
+            &event,
+            &store,
+            &NoRequests,
+        ))
+        .expect("script should run");
+
+        assert_eq!(store.get_var("count"), Some(json!(3)));
+        assert_eq!(store.get_var("id"), Some(json!("7")));
+        assert_eq!(results.len(), 1);
+        assert!(results[0].passed());
+    }
+
     #[test]
     fn failing_test_is_caught_and_recorded() {
         let svc = service();
@@ -1016,7 +1714,8 @@ mod tests {
 
         // A failing expect inside test() is caught; the script itself still
         // succeeds and the failure is recorded in __testResults.
-        svc.execute_post_response_script(
+        let results = run_post(
+            &svc,
             r#"
             test('this fails', function () { expect(1).to.equal(2); });
             bro.setVar('failed', __testResults.filter(function (t) { return t.status === 'fail'; }).length);
@@ -1028,6 +1727,33 @@ mod tests {
         .expect("script with a failing test should still succeed");
 
         assert_eq!(store.get_var("failed"), Some(json!(1)));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "this fails");
+        assert!(!results[0].passed());
+        assert_eq!(results[0].error.as_deref(), Some("expected to equal 2"));
+        assert!(results[0].duration_ms >= 0.0);
+    }
+
+    #[test]
+    fn tests_recorded_before_a_throw_are_kept() {
+        let svc = service();
+        let store = VariableStore::new();
+
+        let failure = run_post(
+            &svc,
+            r#"
+            test('runs first', function () { expect(1).to.equal(1); });
+            throw new Error('boom');
+            "#,
+            &RequestData::default(),
+            &ResponseData::default(),
+            &store,
+        )
+        .expect_err("a throwing script should fail");
+
+        assert_eq!(failure.tests.len(), 1);
+        assert_eq!(failure.tests[0].name, "runs first");
+        assert!(failure.tests[0].passed());
     }
 
     #[test]
@@ -1038,7 +1764,7 @@ mod tests {
 
         // Outside test(), a failed expectation throws and surfaces as a script
         // execution error.
-        let result = svc.execute_pre_request_script("expect(1).to.equal(2);", &mut request, &store);
+        let result = run_pre(&svc, "expect(1).to.equal(2);", &mut request, &store);
         assert!(result.is_err(), "a bare failing expect should error");
     }
 
@@ -1048,10 +1774,182 @@ mod tests {
         let mut request = RequestData::default();
         let store = VariableStore::new();
 
-        let result = svc.execute_pre_request_script("var x = ;", &mut request, &store);
+        let result = run_pre(&svc, "var x = ;", &mut request, &store);
         assert!(result.is_err(), "a syntax error should fail execution");
     }
 
+    #[test]
+    fn scripts_without_requests_run_at_global_scope() {
+        let svc = service();
+        let mut request = RequestData::default();
+        let store = VariableStore::new();
+
+        run_pre(
+            &svc,
+            "var answer = 42;\nfunction helper() { return answer; }\nbro.setVar('global', globalThis.helper());",
+            &mut request,
+            &store,
+        )
+        .expect("script should run");
+        assert_eq!(store.get_var("global"), Some(json!(42)));
+
+        // Mentioning await outside of code doesn't make a script async
+        run_pre(
+            &svc,
+            "// TODO await the token
+var awaitingApproval = 'await';
+function sign() { return awaitingApproval; }
+bro.setVar('commented', typeof globalThis.sign);",
+            &mut request,
+            &store,
+        )
+        .expect("script should run");
+        assert_eq!(store.get_var("commented"), Some(json!("function")));
+
+        // Scripts that await are wrapped in an async function
+        run_pre(
+            &svc,
+            "var wrapped = 1;\nawait Promise.resolve();\nbro.setVar('wrapped', typeof globalThis.wrapped);",
+            &mut request,
+            &store,
+        )
+        .expect("script should run");
+        assert_eq!(store.get_var("wrapped"), Some(json!("undefined")));
+    }
+
+    #[test]
+    fn script_requests_resolve_and_the_script_continues_after_await() {
+        let svc = service();
+        let mut request = RequestData::default();
+        let store = VariableStore::new();
+        let requests = TokenRequests::default();
+
+        smol::block_on(svc.execute_pre_request_script(
+            r#"
+            const res = await bro.sendRequest({ method: 'post', url: 'http://auth.test/token', data: { user: 'ada' } });
+            req.setHeader('Authorization', 'Bearer ' + res.body.token);
+            bru.runRequest('auth/login', function (err, r) { bro.setVar('callbackStatus', r.status); });
+            "#,
+            &mut request,
+            &store,
+            &requests,
+        ))
+        .expect("script should run");
+
+        assert!(
+            request
+                .headers
+                .iter()
+                .any(|h| h.key == "Authorization" && h.value == "Bearer t1"),
+            "headers set after the await should be extracted"
+        );
+        assert_eq!(store.get_var("callbackStatus"), Some(json!(200)));
+
+        let seen = requests.seen.lock().expect("lock");
+        assert_eq!(
+            seen[0],
+            ScriptRequest::Send {
+                method: "POST".to_string(),
+                url: "http://auth.test/token".to_string(),
+                headers: HashMap::from([(
+                    "Content-Type".to_string(),
+                    "application/json".to_string()
+                )]),
+                body: Some(r#"{"user":"ada"}"#.to_string()),
+            }
+        );
+        assert_eq!(
+            seen[1],
+            ScriptRequest::Run {
+                name: "auth/login".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn failed_script_request_rejects_its_promise() {
+        let svc = service();
+        let mut request = RequestData::default();
+        let store = VariableStore::new();
+
+        let result = run_pre(
+            &svc,
+            "await bro.sendRequest({ url: 'http://down.test' });",
+            &mut request,
+            &store,
+        );
+        assert!(
+            result.is_err(),
+            "an uncaught rejection should fail the script"
+        );
+
+        run_pre(
+            &svc,
+            "try { await bro.runRequest('missing'); } catch (e) { bro.setVar('error', e.message); }",
+            &mut request,
+            &store,
+        )
+        .expect("a caught rejection should not fail the script");
+        assert_eq!(
+            store.get_var("error"),
+            Some(json!("requests are not available in tests"))
+        );
+    }
+
+    /// Gives scripts a cookie jar and no requests.
+    struct JarRequests(CookieJar);
+
+    impl ScriptRequestHandler for JarRequests {
+        fn handle(&self, request: ScriptRequest) -> ScriptRequestFuture<'_> {
+            NoRequests.handle(request)
+        }
+
+        fn cookie_jar(&self) -> Option<&CookieJar> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn scripts_read_and_write_the_cookie_jar() {
+        let svc = service();
+        let jar = CookieJar::default();
+        jar.store_response_cookies("https://api.example.com/login", ["session=abc; Path=/"]);
+        let mut request = RequestData {
+            url: "https://api.example.com/users".to_string(),
+            ..Default::default()
+        };
+        let store = VariableStore::new();
+
+        smol::block_on(svc.execute_pre_request_script(
+            "bro.setVar('session', bro.cookies.get('session'));\n\
+             bro.cookies.set('theme', 'dark', { domain: 'example.com', maxAge: 60 });\n\
+             bro.cookies.set('other', '1', { url: 'https://other.test/' });\n\
+             bro.setVar('all', bro.cookies.getAll('https://www.example.com/'));\n\
+             bro.cookies.clear('https://other.test/');",
+            &mut request,
+            &store,
+            &JarRequests(jar.clone()),
+        ))
+        .expect("script should run");
+
+        assert_eq!(store.get_var("session"), Some(json!("abc")));
+        assert_eq!(store.get_var("all"), Some(json!({ "theme": "dark" })));
+        assert_eq!(
+            jar.header_for("https://api.example.com/").as_deref(),
+            Some("session=abc; theme=dark")
+        );
+        assert_eq!(jar.header_for("https://other.test/"), None);
+
+        smol::block_on(svc.execute_pre_request_script(
+            "bro.cookies.clear();",
+            &mut request,
+            &store,
+            &JarRequests(jar.clone()),
+        ))
+        .expect("script should run");
+        assert!(jar.cookies().is_empty());
+    }
+
     #[test]
     fn check_syntax_distinguishes_valid_and_invalid_scripts() {
         assert!(
@@ -1061,5 +1959,18 @@ mod tests {
         assert!(
             ScriptExecutionService::check_syntax("var x = ;", ScriptContext::PreRequest).is_err()
         );
+        assert!(
+            ScriptExecutionService::check_syntax(
+                "const r = await bro.sendRequest({ url: 'http://x' });\nr.status;",
+                ScriptContext::PreRequest
+            )
+            .is_ok()
+        );
+
+        let unknown =
+            ScriptExecutionService::check_syntax("  missingThing();", ScriptContext::PreRequest)
+                .expect_err("unknown names should be reported");
+        assert!(unknown.is_warning);
+        assert_eq!(unknown.line, 0);
     }
 }
@@ -3,8 +3,13 @@
 mod completion;
 mod editor;
 mod engine;
+mod script_request;
+mod test_result;
 mod variable_store;
 
+pub(crate) use completion::completion_item;
 pub use editor::*;
 pub use engine::*;
+pub use script_request::*;
+pub use test_result::*;
 pub use variable_store::*;
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use crate::cookies::CookieJar;
+use crate::domain::ResponseData;
+
+/// A request issued by a script through `bro.sendRequest` or `bro.runRequest`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ScriptRequest {
+    /// An ad-hoc request, sent as given (no `{{variable}}` resolution)
+    Send {
+        method: String,
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        body: Option<String>,
+    },
+    /// A saved request of the collection, by `group/name` (or just `name` for
+    /// requests at the collection root)
+    Run { name: String },
+}
+
+/// The response a script request resolves with
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+    pub response_time: u64,
+    pub url: String,
+}
+
+impl From<&ResponseData> for ScriptResponse {
+    fn from(response: &ResponseData) -> Self {
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for header in response.headers.iter().filter(|h| h.enabled) {
+            headers
+                .entry(header.key.clone())
+                .and_modify(|value| {
+                    value.push_str(", ");
+                    value.push_str(&header.value);
+                })
+                .or_insert_with(|| header.value.clone());
+        }
+
+        Self {
+            status: response.status_code.unwrap_or_default(),
+            status_text: response.status_text.clone().unwrap_or_default(),
+            headers,
+            body: response.body.clone(),
+            response_time: response
+                .latency
+                .map(|l| l.as_millis() as u64)
+                .unwrap_or_default(),
+            url: response.url.clone().unwrap_or_default(),
+        }
+    }
+}
+
+pub type ScriptRequestFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<ScriptResponse, String>> + 'a>>;
+
+/// Performs the requests a script issues. The error is the message the
+/// script's promise rejects with.
+pub trait ScriptRequestHandler {
+    fn handle(&self, request: ScriptRequest) -> ScriptRequestFuture<'_>;
+
+    /// The jar `bro.cookies` reads and writes; scripts get a throwaway jar
+    /// when there is none.
+    fn cookie_jar(&self) -> Option<&CookieJar> {
+        None
+    }
+}
+
+/// Requests queued by a running script (as JSON), waiting for the engine to
+/// perform them and settle their promises.
+#[derive(Clone, Default)]
+pub(super) struct RequestQueue {
+    inner: Arc<Mutex<QueueState>>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_id: u32,
+    pending: Vec<(u32, String)>,
+}
+
+impl RequestQueue {
+    /// Queue a request and return the id its promise is settled under.
+    pub(super) fn push(&self, request_json: String) -> u32 {
+        let Ok(mut state) = self.inner.lock() else {
+            return 0;
+        };
+        state.next_id += 1;
+        let id = state.next_id;
+        state.pending.push((id, request_json));
+        id
+    }
+
+    pub(super) fn take(&self) -> Vec<(u32, String)> {
+        self.inner
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.pending))
+            .unwrap_or_default()
+    }
+}
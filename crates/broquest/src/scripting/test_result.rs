@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a single `test()` block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Pass,
+    Fail,
+}
+
+/// A `test()` recorded by a post-response script. Deserialized straight from
+/// the prelude's `__testResults` entries.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TestResult {
+    pub name: String,
+    pub status: TestStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Wall-clock time spent in the test body, in milliseconds.
+    #[serde(default, rename = "duration")]
+    pub duration_ms: f64,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.status == TestStatus::Pass
+    }
+}
+
+/// A script that threw, with the `test()` outcomes it recorded before it did
+#[derive(Debug)]
+pub struct ScriptFailure {
+    pub error: anyhow::Error,
+    pub tests: Vec<TestResult>,
+}
+
+impl From<anyhow::Error> for ScriptFailure {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            error,
+            tests: Vec::new(),
+        }
+    }
+}
+
+impl From<rquickjs::Error> for ScriptFailure {
+    fn from(error: rquickjs::Error) -> Self {
+        anyhow::Error::from(error).into()
+    }
+}
+
+impl std::fmt::Display for ScriptFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
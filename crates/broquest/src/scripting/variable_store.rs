@@ -21,9 +21,16 @@ struct VariableStoreData {
     /// Collection-level variables (Bruno `bru.getCollectionVar`): declared in
     /// the collection, read-only from scripts, resolvable via `{{name}}`.
     collection_vars: HashMap<String, Value>,
+    /// Variables of the request's folder (Bruno `bru.getFolderVar`): read-only
+    /// from scripts, resolvable via `{{name}}` between request and collection
+    /// variables.
+    folder_vars: HashMap<String, Value>,
     /// Request-level variables (Bruno `bru.getRequestVar`): declared on the
     /// request, read-only from scripts, resolvable via `{{name}}`.
     request_vars: HashMap<String, Value>,
+    /// Current row of a data-driven run (`bro.iterationData`): read-only,
+    /// highest precedence in `{{name}}` resolution.
+    iteration_data: HashMap<String, Value>,
 }
 
 impl VariableStore {
@@ -165,6 +172,30 @@ impl VariableStore {
             .unwrap_or(false)
     }
 
+    /// Seed the read-only folder variable bucket (Bruno `bru.getFolderVar`).
+    /// Replaces any prior folder vars. Called once per request before scripts run.
+    pub fn set_folder_vars(&self, vars: HashMap<String, Value>) {
+        if let Ok(mut data) = self.data.lock() {
+            data.folder_vars = vars;
+        }
+    }
+
+    /// Get a variable of the request's folder (Bruno `bru.getFolderVar`).
+    pub fn get_folder_var(&self, name: &str) -> Option<Value> {
+        self.data
+            .lock()
+            .ok()
+            .and_then(|data| data.folder_vars.get(name).cloned())
+    }
+
+    /// Check whether the request's folder has a variable.
+    pub fn has_folder_var(&self, name: &str) -> bool {
+        self.data
+            .lock()
+            .map(|data| data.folder_vars.contains_key(name))
+            .unwrap_or(false)
+    }
+
     /// Seed the read-only request variable bucket (Bruno `bru.getRequestVar`).
     /// Replaces any prior request vars. Called once per request before scripts run.
     pub fn set_request_vars(&self, vars: HashMap<String, Value>) {
@@ -189,6 +220,21 @@ impl VariableStore {
             .unwrap_or(false)
     }
 
+    /// Seed the current iteration's data row. Replaces any prior row.
+    pub fn set_iteration_data(&self, data: HashMap<String, Value>) {
+        if let Ok(mut data_store) = self.data.lock() {
+            data_store.iteration_data = data;
+        }
+    }
+
+    /// Get the current iteration's data row as a HashMap copy.
+    pub fn get_iteration_data(&self) -> HashMap<String, Value> {
+        self.data
+            .lock()
+            .map(|data| data.iteration_data.clone())
+            .unwrap_or_default()
+    }
+
     /// Initialize the variable store with environment data (not marked as dirty)
     pub fn initialize_with_env(
         &self,
@@ -3,6 +3,8 @@ mod view;
 pub use view::SettingsView;
 
 use crate::app_settings::AppSettings;
+use crate::domain::RequestSettings;
+use crate::environments::is_variable_reference;
 use gpui::{App, SharedString};
 use gpui_component::Theme;
 use serde::{Deserialize, Serialize};
@@ -39,6 +41,40 @@ impl Settings {
                 "connection.request_timeout_seconds" => {
                     settings.connection.request_timeout_seconds = value.parse().unwrap_or(300);
                 }
+                "connection.response_memory_limit_mb" => {
+                    settings.connection.response_memory_limit_mb = value.parse().unwrap_or(32);
+                }
+                "connection.follow_redirects" => {
+                    settings.connection.redirects.follow = value.parse().unwrap_or(true);
+                }
+                "connection.max_redirects" => {
+                    settings.connection.redirects.max_redirects = value.parse().unwrap_or(10);
+                }
+                "connection.redirect_keep_auth" => {
+                    settings.connection.redirects.keep_auth_across_hosts =
+                        value.parse().unwrap_or(false);
+                }
+                "connection.proxy_enabled" => {
+                    settings.connection.proxy.enabled = value.parse().unwrap_or(false);
+                }
+                "connection.proxy_http_url" => {
+                    settings.connection.proxy.http_url = value.clone();
+                }
+                "connection.proxy_https_url" => {
+                    settings.connection.proxy.https_url = value.clone();
+                }
+                "connection.proxy_socks_url" => {
+                    settings.connection.proxy.socks_url = value.clone();
+                }
+                "connection.proxy_username" => {
+                    settings.connection.proxy.username = value.clone();
+                }
+                "connection.proxy_no_proxy" => {
+                    settings.connection.proxy.no_proxy = value.clone();
+                }
+                "connection.proxy_use_env" => {
+                    settings.connection.proxy.use_env = value.parse().unwrap_or(true);
+                }
                 "editor.show_whitespace" => {
                     settings.editor.show_whitespace = value.parse().unwrap_or(false);
                 }
@@ -84,16 +120,135 @@ impl Default for GeneralSettings {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionSettings {
     pub request_timeout_seconds: u32,
+    /// Response bodies larger than this are saved to a temporary file
+    pub response_memory_limit_mb: u32,
+    pub redirects: RedirectSettings,
+    pub proxy: ProxySettings,
 }
 
 impl Default for ConnectionSettings {
     fn default() -> Self {
         Self {
             request_timeout_seconds: 300,
+            response_memory_limit_mb: 32,
+            redirects: RedirectSettings::default(),
+            proxy: ProxySettings::default(),
+        }
+    }
+}
+
+/// How redirect responses are followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedirectSettings {
+    pub follow: bool,
+    /// Redirects followed at most; the last redirect response is returned
+    /// when the limit is reached
+    pub max_redirects: u32,
+    /// Keep the Authorization header when a redirect leaves the host
+    pub keep_auth_across_hosts: bool,
+}
+
+impl Default for RedirectSettings {
+    fn default() -> Self {
+        Self {
+            follow: true,
+            max_redirects: 10,
+            keep_auth_across_hosts: false,
+        }
+    }
+}
+
+impl RedirectSettings {
+    /// These settings with a request's overrides applied
+    pub fn with_overrides(self, overrides: &RequestSettings) -> Self {
+        Self {
+            follow: overrides.follow_redirects.unwrap_or(self.follow),
+            max_redirects: overrides.max_redirects.unwrap_or(self.max_redirects),
+            keep_auth_across_hosts: overrides
+                .keep_auth_on_redirect
+                .unwrap_or(self.keep_auth_across_hosts),
+        }
+    }
+}
+
+/// Proxy configuration. Set app-wide in the connection settings and
+/// overridable per collection with `[collection.proxy]` in collection.toml.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    /// Route requests through the proxies below
+    pub enabled: bool,
+    /// Proxy for `http://` URLs, e.g. `http://proxy.local:3128`
+    pub http_url: String,
+    /// Proxy for `https://` URLs
+    pub https_url: String,
+    /// `socks5://host:port` proxy for anything the HTTP(S) proxies don't cover
+    pub socks_url: String,
+    pub username: String,
+    /// App-wide, kept in the keychain (see [`read_proxy_password`]). In a
+    /// collection, a `{{variable}}` reference resolved from the environment's
+    /// variables and secrets; a literal password is never written to
+    /// collection.toml.
+    #[serde(skip_serializing_if = "is_literal_password")]
+    pub password: String,
+    /// Comma-separated hosts, `.domains` and CIDR ranges reached directly
+    pub no_proxy: String,
+    /// Honor `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` when no
+    /// proxy is enabled
+    pub use_env: bool,
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            http_url: String::new(),
+            https_url: String::new(),
+            socks_url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            no_proxy: String::new(),
+            use_env: true,
         }
     }
 }
 
+fn is_literal_password(password: &str) -> bool {
+    !is_variable_reference(password)
+}
+
+/// Keychain entry of the app-wide proxy password
+const PROXY_PASSWORD_CREDENTIAL: &str = "broquest://settings/proxy_password";
+
+/// Read the app-wide proxy password from secure storage
+pub fn read_proxy_password(cx: &App) -> Option<String> {
+    let read_task = cx.read_credentials(PROXY_PASSWORD_CREDENTIAL);
+    match smol::block_on(read_task) {
+        Ok(Some((_, bytes))) => String::from_utf8(bytes).ok(),
+        Ok(None) => None,
+        Err(e) => {
+            tracing::error!("Failed to read the proxy password: {}", e);
+            None
+        }
+    }
+}
+
+/// Write the app-wide proxy password to secure storage, or delete it when
+/// empty
+pub fn write_proxy_password(password: &str, cx: &App) {
+    let task = if password.is_empty() {
+        cx.delete_credentials(PROXY_PASSWORD_CREDENTIAL)
+    } else {
+        cx.write_credentials(PROXY_PASSWORD_CREDENTIAL, "proxy", password.as_bytes())
+    };
+    cx.spawn(async move |_| {
+        if let Err(e) = task.await {
+            tracing::error!("Failed to store the proxy password: {}", e);
+        }
+    })
+    .detach();
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EditorSettings {
     pub show_whitespace: bool,
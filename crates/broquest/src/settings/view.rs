@@ -1,15 +1,17 @@
 use crate::app_database::AppDatabase;
 use crate::app_settings::AppSettings;
 use crate::http::HttpClientService;
-use crate::settings::{EditorLayout, Settings};
+use crate::settings::{self, EditorLayout, ProxySettings, Settings};
 use gpui::{
     App, AppContext, Context, Entity, FocusHandle, Focusable, InteractiveElement, IntoElement,
     ParentElement, Render, SharedString, Styled, Window, div, px, rems,
 };
 use gpui_component::ThemeRegistry;
 use gpui_component::{
-    ActiveTheme, Sizable, Theme,
+    ActiveTheme, Sizable, Theme, WindowExt,
     group_box::GroupBoxVariant,
+    input::{Input, InputEvent, InputState},
+    notification::NotificationType,
     select::{SearchableVec, Select, SelectEvent, SelectItem, SelectState},
     setting::{
         NumberFieldOptions, SettingField, SettingGroup, SettingItem, SettingPage,
@@ -46,9 +48,46 @@ pub struct SettingsView {
     save_tasks: std::collections::HashMap<String, gpui::Task<()>>,
     ui_font_select: Option<Entity<SelectState<SearchableVec<FontOption>>>>,
     mono_font_select: Option<Entity<SelectState<SearchableVec<FontOption>>>>,
+    /// Text inputs of the proxy settings, keyed by setting name
+    proxy_inputs: std::collections::HashMap<&'static str, Entity<InputState>>,
+    /// Debounced rebuild of the HTTP client after proxy edits
+    proxy_apply_task: Option<gpui::Task<()>>,
     _subscriptions: Vec<gpui::Subscription>,
 }
 
+/// Proxy settings edited as text: setting name, placeholder, masked
+const PROXY_TEXT_FIELDS: [(&str, &str, bool); 6] = [
+    ("proxy_http_url", "http://proxy.local:3128", false),
+    ("proxy_https_url", "http://proxy.local:3128", false),
+    ("proxy_socks_url", "socks5://127.0.0.1:1080", false),
+    ("proxy_username", "", false),
+    ("proxy_password", "", true),
+    ("proxy_no_proxy", "localhost, .internal, 10.0.0.0/8", false),
+];
+
+fn proxy_text_field<'a>(proxy: &'a mut ProxySettings, name: &str) -> Option<&'a mut String> {
+    match name {
+        "proxy_http_url" => Some(&mut proxy.http_url),
+        "proxy_https_url" => Some(&mut proxy.https_url),
+        "proxy_socks_url" => Some(&mut proxy.socks_url),
+        "proxy_username" => Some(&mut proxy.username),
+        "proxy_password" => Some(&mut proxy.password),
+        "proxy_no_proxy" => Some(&mut proxy.no_proxy),
+        _ => None,
+    }
+}
+
+fn apply_redirect_settings(cx: &mut App) {
+    let redirects = AppSettings::global(cx).settings.connection.redirects;
+    HttpClientService::global_mut(cx).set_redirects(redirects);
+}
+
+/// Rebuild the global HTTP client with the current proxy settings
+fn apply_proxy_settings(cx: &mut App) -> anyhow::Result<()> {
+    let proxy = AppSettings::global(cx).settings.connection.proxy.clone();
+    HttpClientService::global_mut(cx).set_proxy(proxy)
+}
+
 impl SettingsView {
     pub fn new(cx: &mut Context<Self>) -> Self {
         Self {
@@ -56,10 +95,90 @@ impl SettingsView {
             save_tasks: std::collections::HashMap::new(),
             ui_font_select: None,
             mono_font_select: None,
+            proxy_inputs: std::collections::HashMap::new(),
+            proxy_apply_task: None,
             _subscriptions: Vec::new(),
         }
     }
 
+    fn ensure_proxy_inputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.proxy_inputs.is_empty() {
+            return;
+        }
+
+        let mut proxy = AppSettings::global(cx).settings.connection.proxy.clone();
+        for (name, placeholder, masked) in PROXY_TEXT_FIELDS {
+            let value = proxy_text_field(&mut proxy, name)
+                .map(|value| value.clone())
+                .unwrap_or_default();
+            let input = cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder(placeholder)
+                    .masked(masked)
+                    .default_value(value)
+            });
+            let subscription = cx.subscribe_in(
+                &input,
+                window,
+                move |this: &mut Self,
+                      input: &Entity<InputState>,
+                      event: &InputEvent,
+                      window,
+                      cx| {
+                    if let InputEvent::Change = event {
+                        let value = input.read(cx).value().to_string();
+                        this.on_proxy_input_changed(name, value, window, cx);
+                    }
+                },
+            );
+            self.proxy_inputs.insert(name, input);
+            self._subscriptions.push(subscription);
+        }
+    }
+
+    fn on_proxy_input_changed(
+        &mut self,
+        name: &'static str,
+        value: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let proxy = &mut AppSettings::global_mut(cx).settings.connection.proxy;
+        let Some(field) = proxy_text_field(proxy, name) else {
+            return;
+        };
+        if *field == value {
+            return;
+        }
+        *field = value.clone();
+        if name == "proxy_password" {
+            self.save_proxy_password_debounced(value, cx);
+        } else {
+            self.save_setting_debounced(format!("connection.{}", name), value, cx);
+        }
+
+        // Rebuild the client once typing settles; report URLs it rejects.
+        self.proxy_apply_task = Some(cx.spawn_in(window, async move |_, window| {
+            window
+                .background_executor()
+                .timer(Duration::from_millis(500))
+                .await;
+            window
+                .update(|window, cx| {
+                    if let Err(e) = apply_proxy_settings(cx) {
+                        window.push_notification(
+                            (
+                                NotificationType::Error,
+                                SharedString::from(format!("{:#}", e)),
+                            ),
+                            cx,
+                        );
+                    }
+                })
+                .ok();
+        }));
+    }
+
     fn ensure_font_selects(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.ui_font_select.is_some() {
             return;
@@ -168,6 +287,22 @@ impl SettingsView {
         self.save_tasks.insert(key, task);
     }
 
+    /// Save the proxy password to the keychain with debouncing
+    fn save_proxy_password_debounced(&mut self, password: String, cx: &mut Context<Self>) {
+        let key = "connection.proxy_password".to_string();
+        self.save_tasks.remove(&key);
+
+        let task = cx.spawn(async move |_, cx| {
+            cx.background_executor()
+                .timer(Duration::from_millis(500))
+                .await;
+            cx.update(|cx| settings::write_proxy_password(&password, cx))
+                .ok();
+        });
+
+        self.save_tasks.insert(key, task);
+    }
+
     /// Get the settings pages for the UI
     fn setting_pages(&mut self, cx: &mut Context<Self>) -> Vec<SettingPage> {
         let default_settings = Settings::default();
@@ -357,7 +492,212 @@ impl SettingsView {
                         .default_value(default_settings.connection.request_timeout_seconds as f64),
                     )
                     .description("Timeout in seconds for HTTP requests (5-600 seconds)."),
+                    SettingItem::new(
+                        "Response Memory Limit",
+                        SettingField::number_input(
+                            NumberFieldOptions {
+                                min: 1.0,
+                                max: 1024.0,
+                                step: 8.0,
+                            },
+                            move |cx: &App| {
+                                AppSettings::global(cx)
+                                    .settings
+                                    .connection
+                                    .response_memory_limit_mb as f64
+                            },
+                            {
+                                let view_handle = view_handle.clone();
+                                move |val: f64, cx: &mut App| {
+                                    AppSettings::global_mut(cx)
+                                        .settings
+                                        .connection
+                                        .response_memory_limit_mb = val as u32;
+
+                                    HttpClientService::global_mut(cx)
+                                        .set_response_memory_limit_mb(val as u32);
+
+                                    let key = "connection.response_memory_limit_mb".to_string();
+                                    let value = (val as u32).to_string();
+                                    if let Some(view) = view_handle.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.save_setting_debounced(key, value, cx);
+                                        });
+                                    }
+                                }
+                            },
+                        )
+                        .default_value(default_settings.connection.response_memory_limit_mb as f64),
+                    )
+                    .description(
+                        "Response bodies over this many MB are saved to a temporary file.",
+                    ),
+                    SettingItem::new(
+                        "Follow Redirects",
+                        SettingField::switch(
+                            move |cx: &App| {
+                                AppSettings::global(cx).settings.connection.redirects.follow
+                            },
+                            {
+                                let view_handle = view_handle.clone();
+                                move |val: bool, cx: &mut App| {
+                                    AppSettings::global_mut(cx)
+                                        .settings
+                                        .connection
+                                        .redirects
+                                        .follow = val;
+                                    apply_redirect_settings(cx);
+
+                                    let key = "connection.follow_redirects".to_string();
+                                    if let Some(view) = view_handle.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.save_setting_debounced(key, val.to_string(), cx);
+                                        });
+                                    }
+                                }
+                            },
+                        )
+                        .default_value(default_settings.connection.redirects.follow),
+                    )
+                    .description("Follow redirect responses. Requests can override this."),
+                    SettingItem::new(
+                        "Max Redirects",
+                        SettingField::number_input(
+                            NumberFieldOptions {
+                                min: 0.0,
+                                max: 50.0,
+                                step: 1.0,
+                            },
+                            move |cx: &App| {
+                                AppSettings::global(cx)
+                                    .settings
+                                    .connection
+                                    .redirects
+                                    .max_redirects as f64
+                            },
+                            {
+                                let view_handle = view_handle.clone();
+                                move |val: f64, cx: &mut App| {
+                                    AppSettings::global_mut(cx)
+                                        .settings
+                                        .connection
+                                        .redirects
+                                        .max_redirects = val as u32;
+                                    apply_redirect_settings(cx);
+
+                                    let key = "connection.max_redirects".to_string();
+                                    let value = (val as u32).to_string();
+                                    if let Some(view) = view_handle.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.save_setting_debounced(key, value, cx);
+                                        });
+                                    }
+                                }
+                            },
+                        )
+                        .default_value(default_settings.connection.redirects.max_redirects as f64),
+                    )
+                    .description("Redirects followed at most before the redirect is returned."),
+                    SettingItem::new(
+                        "Keep Authorization Across Hosts",
+                        SettingField::switch(
+                            move |cx: &App| {
+                                AppSettings::global(cx)
+                                    .settings
+                                    .connection
+                                    .redirects
+                                    .keep_auth_across_hosts
+                            },
+                            {
+                                let view_handle = view_handle.clone();
+                                move |val: bool, cx: &mut App| {
+                                    AppSettings::global_mut(cx)
+                                        .settings
+                                        .connection
+                                        .redirects
+                                        .keep_auth_across_hosts = val;
+                                    apply_redirect_settings(cx);
+
+                                    let key = "connection.redirect_keep_auth".to_string();
+                                    if let Some(view) = view_handle.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.save_setting_debounced(key, val.to_string(), cx);
+                                        });
+                                    }
+                                }
+                            },
+                        )
+                        .default_value(
+                            default_settings.connection.redirects.keep_auth_across_hosts,
+                        ),
+                    )
+                    .description("Send the Authorization header to the host a redirect points to."),
                 ]),
+                SettingGroup::new().title("Proxy").items(
+                    [SettingItem::new(
+                        "Use Proxy",
+                        SettingField::switch(
+                            move |cx: &App| {
+                                AppSettings::global(cx).settings.connection.proxy.enabled
+                            },
+                            {
+                                let view_handle = view_handle.clone();
+                                move |val: bool, cx: &mut App| {
+                                    AppSettings::global_mut(cx)
+                                        .settings
+                                        .connection
+                                        .proxy
+                                        .enabled = val;
+                                    if let Err(e) = apply_proxy_settings(cx) {
+                                        tracing::error!("Failed to apply proxy settings: {:#}", e);
+                                    }
+
+                                    let key = "connection.proxy_enabled".to_string();
+                                    if let Some(view) = view_handle.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.save_setting_debounced(key, val.to_string(), cx);
+                                        });
+                                    }
+                                }
+                            },
+                        )
+                        .default_value(default_settings.connection.proxy.enabled),
+                    )
+                    .description("Send requests through the proxies below.")]
+                    .into_iter()
+                    .chain(self.proxy_setting_items())
+                    .chain([SettingItem::new(
+                        "Use Environment Proxy",
+                        SettingField::switch(
+                            move |cx: &App| {
+                                AppSettings::global(cx).settings.connection.proxy.use_env
+                            },
+                            {
+                                let view_handle = view_handle.clone();
+                                move |val: bool, cx: &mut App| {
+                                    AppSettings::global_mut(cx)
+                                        .settings
+                                        .connection
+                                        .proxy
+                                        .use_env = val;
+                                    if let Err(e) = apply_proxy_settings(cx) {
+                                        tracing::error!("Failed to apply proxy settings: {:#}", e);
+                                    }
+
+                                    let key = "connection.proxy_use_env".to_string();
+                                    if let Some(view) = view_handle.upgrade() {
+                                        view.update(cx, |view, cx| {
+                                            view.save_setting_debounced(key, val.to_string(), cx);
+                                        });
+                                    }
+                                }
+                            },
+                        )
+                        .default_value(default_settings.connection.proxy.use_env),
+                    )
+                    .description("Without a proxy above, honor HTTP_PROXY and NO_PROXY.")])
+                    .collect(),
+                ),
             ]),
             // Appearance Settings Page
             SettingPage::new("Appearance").resettable(true).groups(vec![
@@ -449,6 +789,53 @@ impl SettingsView {
             ]),
         ]
     }
+
+    /// Setting items for the proxy text inputs
+    fn proxy_setting_items(&self) -> Vec<SettingItem> {
+        [
+            ("HTTP Proxy", "proxy_http_url", "Proxy for http:// URLs."),
+            ("HTTPS Proxy", "proxy_https_url", "Proxy for https:// URLs."),
+            (
+                "SOCKS5 Proxy",
+                "proxy_socks_url",
+                "Used for requests the HTTP(S) proxies don't cover.",
+            ),
+            (
+                "Proxy Username",
+                "proxy_username",
+                "Sent to every proxy above.",
+            ),
+            (
+                "Proxy Password",
+                "proxy_password",
+                "Stored in the system keychain.",
+            ),
+            (
+                "No Proxy",
+                "proxy_no_proxy",
+                "Comma-separated hosts, .domains and IP ranges reached directly.",
+            ),
+        ]
+        .into_iter()
+        .map(|(title, name, description)| {
+            let input = self.proxy_inputs.get(name).cloned();
+            SettingItem::new(
+                title,
+                SettingField::render(move |options, _window, _cx| {
+                    if let Some(state) = &input {
+                        Input::new(state)
+                            .with_size(options.size)
+                            .w(px(240.))
+                            .into_any_element()
+                    } else {
+                        gpui::div().into_any_element()
+                    }
+                }),
+            )
+            .description(description)
+        })
+        .collect()
+    }
 }
 
 impl Focusable for SettingsView {
@@ -460,6 +847,7 @@ impl Focusable for SettingsView {
 impl Render for SettingsView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         self.ensure_font_selects(window, cx);
+        self.ensure_proxy_inputs(window, cx);
 
         div().track_focus(&self.focus_handle).size_full().child(
             GpuiSettings::new("broquest-settings")
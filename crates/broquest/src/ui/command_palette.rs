@@ -540,17 +540,12 @@ fn add_collection_requests(commands: &mut Vec<CommandItem>, cx: &App) {
 
     for collection in collections {
         let mut push_request = |request: &crate::domain::RequestData, group: Option<&str>| {
-            let method = crate::domain::HttpMethod::ALL
-                .iter()
-                .find(|m| m.as_str() == request.method.as_str())
-                .copied()
-                .unwrap_or(crate::domain::HttpMethod::Get);
             let group_label = match group {
                 Some(g) => format!("{} / {}", collection.data.name, g),
                 None => collection.data.name.clone(),
             };
             commands.push(CommandItem {
-                label: format!("{} {}", method.as_str(), request.name),
+                label: format!("{} {}", request.method.as_str(), request.name),
                 group: group_label,
                 action_type: CommandType::OpenRequest {
                     collection_path: collection.data.path.clone(),